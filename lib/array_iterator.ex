@@ -0,0 +1,33 @@
+defmodule RustyJson.ArrayIterator do
+  @moduledoc """
+  Handle for lazy, batched iteration over a JSON array.
+
+  Created by `RustyJson.iterator_new/2` and advanced with
+  `RustyJson.iterator_next/2`, or consumed as a `Stream` via
+  `RustyJson.stream_array/2`. The handle references the input binary without
+  copying it and remembers where the previous batch stopped, so each call
+  decodes only the requested number of elements.
+
+      iterator = RustyJson.iterator_new(~s({"data":{"items":[1,2,3]}}), path: ["data", "items"])
+      {:cont, [1, 2]} = RustyJson.iterator_next(iterator, 2)
+      {:done, [3]} = RustyJson.iterator_next(iterator, 2)
+
+  Bytes are validated as they are reached: an error late in the array surfaces
+  from the batch that reaches it. When a `:path` is given, bytes after the
+  target array are not read, so a key repeated along the path resolves to its
+  first occurrence whatever `:duplicate_keys` says. After an error the iterator is unusable and every
+  further call returns the same error.
+  """
+
+  @typedoc "Opaque array iterator handle."
+  @type t :: %__MODULE__{
+          resource: reference(),
+          data: binary(),
+          keys: RustyJson.keys(),
+          keys_fn: (String.t() -> term()) | nil,
+          dirty_threshold: non_neg_integer()
+        }
+
+  @enforce_keys [:resource, :data]
+  defstruct [:resource, :data, keys: :strings, keys_fn: nil, dirty_threshold: 0]
+end
@@ -8,6 +8,30 @@ defmodule RustyJson.EncodeError do
   ## Fields
 
   * `:message` - Human-readable error description
+  * `:reason` - A stable atom naming the kind of error, see below
+  * `:path` - Keys and indices leading from the encoded term to the value that
+    failed, e.g. `[:users, 42, :name]`. Map keys appear as written, list, tuple
+    and `MapSet` positions as 0-based integers. For a bad map key the path
+    ends at the map.
+  * `:value` - A truncated `inspect/2` of the value (or map key) that failed
+
+  `:reason`, `:path` and `:value` are filled in for failures in the native
+  encoder. Errors raised while running `RustyJson.Encoder` implementations
+  only have a `:message`, and `:path` is `[]`.
+
+  ## Reasons
+
+  | Reason | Cause |
+  |--------|-------|
+  | `:invalid_utf8` | A string or map key that is not valid UTF-8 |
+  | `:non_finite_float` | NaN or an infinity |
+  | `:unsupported_type` | A PID, reference, function or port |
+  | `:invalid_key` | A map key that is not an atom, string or integer |
+  | `:duplicate_key` | Two keys that write the same JSON key under `maps: :strict` |
+  | `:depth_exceeded` | More than `:max_depth` levels of nesting |
+  | `:invalid_value` | A malformed `RustyJson.OrderedObject`, fragment or stream item |
+  | `:finished` | A push to a streaming encoder after `encoder_finish/1` |
+  | `:io` | Writing or compressing the output failed |
 
   ## Common Causes
 
@@ -15,7 +39,7 @@ defmodule RustyJson.EncodeError do
   |-------|-------|
   | `"Failed to decode binary"` | Binary is not valid UTF-8 |
   | `"Non-finite float"` | Float is NaN or Infinity |
-  | `"Nesting depth exceeds maximum"` | More than 128 levels of nesting |
+  | `"Nesting depth exceeds maximum of N"` | More than `:max_depth` (default 128) levels of nesting |
   | `"Unsupported term type"` | Term type cannot be encoded (e.g., PID, Reference) |
 
   ## Examples
@@ -26,6 +50,10 @@ defmodule RustyJson.EncodeError do
       iex> RustyJson.encode!(:math.log(-1))
       ** (RustyJson.EncodeError) Non-finite float
 
+      iex> {:error, error} = RustyJson.encode(%{users: [%{name: "ok"}, %{name: <<0xFF>>}]})
+      iex> {error.reason, error.path, error.value}
+      {:invalid_utf8, [:users, 1, :name], "<<255>>"}
+
   ## Handling Errors
 
   Use `RustyJson.encode/2` to get `{:error, reason}` instead of raising:
@@ -42,10 +70,47 @@ defmodule RustyJson.EncodeError do
   """
   @type t :: %__MODULE__{
           message: String.t(),
+          reason: reason() | nil,
+          path: [term()],
+          value: String.t() | nil,
           __exception__: true
         }
 
-  defexception [:message]
+  @typedoc """
+  Why the native encoder failed.
+  """
+  @type reason ::
+          :invalid_utf8
+          | :non_finite_float
+          | :unsupported_type
+          | :invalid_key
+          | :duplicate_key
+          | :depth_exceeded
+          | :invalid_value
+          | :finished
+          | :io
+
+  defexception [:message, :reason, :value, path: []]
+
+  @value_limit 80
+
+  @doc false
+  @spec from_nif(reason(), String.t(), [term()], term()) :: t()
+  def from_nif(reason, message, path, value) do
+    %__MODULE__{message: message, reason: reason, path: path, value: render_value(value)}
+  end
+
+  defp render_value(nil), do: nil
+
+  defp render_value(value) do
+    rendered = inspect(value, limit: 10, printable_limit: @value_limit)
+
+    if String.length(rendered) > @value_limit do
+      String.slice(rendered, 0, @value_limit) <> "..."
+    else
+      rendered
+    end
+  end
 
   @doc """
   Creates an `EncodeError` from a tagged error reason.
@@ -96,6 +161,42 @@ defmodule RustyJson.DecodeError do
   * `:data` - The original input data that failed to decode
   * `:position` - The byte position in the input where the error occurred
   * `:token` - A short snippet of input around the error position
+  * `:line` and `:column` - 1-based line and column of `:position`; columns count
+    characters, not bytes
+  * `:path` - JSONPath of the value being parsed, e.g. `"$.orders[17].items[2].price"`
+  * `:snippet` - The line of input around the error with a `^` under it
+  * `:code` - A stable atom naming the kind of error, see below
+  * `:expected` - The tokens that would have been accepted at `:position`, e.g.
+    `[",", "]"]`; empty when the error is not about syntax
+  * `:violations` - For `:schema_violation`, every value in the document that broke
+    a typed `:schema` field, as maps with `:message`, `:position` and `:path`. The
+    other fields describe the first one.
+
+  `:line`, `:column` and `:snippet` need `:data`, and `:path` is only known for
+  `RustyJson.decode/2` and its variants; otherwise they are `nil`.
+
+  ## Error Codes
+
+  | Code | Cause |
+  |------|-------|
+  | `:unexpected_token` | A character that cannot appear here; see `:expected` |
+  | `:unexpected_end` | The input ends inside a value |
+  | `:trailing_characters` | Extra content after the value |
+  | `:unterminated_string` | A string without a closing quote |
+  | `:unterminated_comment` | A `/*` comment without `*/` (`relaxed:` only) |
+  | `:invalid_number` | A malformed number |
+  | `:invalid_escape` | A malformed escape sequence in a string |
+  | `:lone_surrogate` | A `\\uXXXX` surrogate escape without its pair |
+  | `:invalid_utf8` | Invalid UTF-8 in a string |
+  | `:control_character` | An unescaped control character in a string |
+  | `:depth_exceeded` | Nesting deeper than `:max_depth` |
+  | `:limit_exceeded` | A size limit such as `:max_bytes` or a decode quota |
+  | `:duplicate_key` | A repeated key under `duplicate_keys: :error` |
+  | `:invalid_key` | A key that cannot become an atom under `keys: :atoms` / `:atoms!` |
+  | `:not_found` | A `get_pointer/3` path that matches nothing |
+  | `:schema_violation` | Values that break typed `:schema` fields; see `:violations` |
+  | `:unknown_discriminator` | A missing or unknown discriminator under a `:schema` union with `unknown: :error` |
+  | `:unknown` | A term the decoder could not build, or misuse of a finished stream decoder |
 
   ## Common Causes
 
@@ -105,7 +206,7 @@ defmodule RustyJson.DecodeError do
   | `"Expected string key at position N"` | Object key is not a quoted string |
   | `"Unexpected end of input"` | JSON is truncated |
   | `"Unexpected trailing characters"` | Extra content after valid JSON |
-  | `"Nesting depth exceeds maximum"` | More than 128 levels of nesting |
+  | `"Nesting depth exceeds maximum of N"` | More than `:max_depth` (default 128) levels of nesting |
 
   ## Examples
 
@@ -136,10 +237,123 @@ defmodule RustyJson.DecodeError do
           data: String.t() | nil,
           position: non_neg_integer() | nil,
           token: String.t() | nil,
+          line: pos_integer() | nil,
+          column: pos_integer() | nil,
+          path: String.t() | nil,
+          snippet: String.t() | nil,
+          code: atom() | nil,
+          expected: [String.t()] | nil,
+          violations: [violation()] | nil,
           __exception__: true
         }
 
-  defexception [:message, :data, :position, :token]
+  @typedoc """
+  A value that broke a typed `:schema` field.
+  """
+  @type violation :: %{message: String.t(), position: non_neg_integer(), path: String.t()}
+
+  @typedoc false
+  @type nif_error :: {atom(), [String.t()], String.t(), non_neg_integer()}
+
+  defexception [
+    :message,
+    :data,
+    :position,
+    :token,
+    :line,
+    :column,
+    :path,
+    :snippet,
+    :code,
+    :expected,
+    :violations
+  ]
+
+  # Characters of the error line shown on each side of the caret.
+  @snippet_radius 30
+
+  @doc false
+  # Builds the error for a NIF failure `{code, expected, message, position}`
+  # on `data`, with the error `path` when the NIF reports one.
+  @spec from_nif(nif_error(), binary() | nil, list() | nil) :: t()
+  def from_nif({code, expected, message, position}, data, path \\ nil) do
+    error = %__MODULE__{
+      message: "#{message} at position #{position}",
+      data: data,
+      position: position,
+      path: path && render_path(path),
+      code: code,
+      expected: expected
+    }
+
+    if is_binary(data) and position <= byte_size(data) do
+      {line, column, snippet} = locate(data, position)
+      %{error | token: token(data, position), line: line, column: column, snippet: snippet}
+    else
+      error
+    end
+  end
+
+  @doc false
+  # Builds the error for a document rejected for its `violations`, each
+  # `{message, position, path}`. `message`, `position` and `path` are those
+  # of the first.
+  @spec from_nif(nif_error(), binary() | nil, list(), list()) :: t()
+  def from_nif({_code, _expected, message, _position} = nif_error, data, path, violations) do
+    violations =
+      Enum.map(violations, fn {message, position, path} ->
+        %{message: message, position: position, path: render_path(path)}
+      end)
+
+    details = Enum.map_join(violations, "; ", &"#{&1.path}: #{&1.message}")
+    error = from_nif(nif_error, data, path)
+    %{error | message: "#{message}: #{details}", violations: violations}
+  end
+
+  defp token(data, position) when position < byte_size(data) do
+    binary_part(data, position, min(byte_size(data) - position, 10))
+  end
+
+  defp token(_data, _position), do: nil
+
+  defp render_path(path), do: "$" <> Enum.map_join(path, &path_segment/1)
+
+  defp path_segment(index) when is_integer(index), do: "[#{index}]"
+
+  defp path_segment(key) do
+    if key =~ ~r/^[A-Za-z_$][A-Za-z0-9_$]*$/, do: ".#{key}", else: "[#{inspect(key)}]"
+  end
+
+  # Line, column and caret snippet for a byte position.
+  defp locate(data, position) do
+    before = binary_part(data, 0, position)
+    newlines = :binary.matches(before, "\n")
+    line_start = if newlines == [], do: 0, else: elem(List.last(newlines), 0) + 1
+
+    line_end =
+      case :binary.match(data, "\n", scope: {position, byte_size(data) - position}) do
+        {newline, _} -> newline
+        :nomatch -> byte_size(data)
+      end
+
+    offset = position - line_start
+    column = length(String.codepoints(binary_part(before, line_start, offset)))
+
+    chars =
+      data
+      |> binary_part(line_start, line_end - line_start)
+      |> String.trim_trailing("\r")
+      |> String.codepoints()
+
+    from = max(column - @snippet_radius, 0)
+    shown = chars |> Enum.slice(from, 2 * @snippet_radius) |> Enum.map_join(&blank_tab/1)
+    caret = String.duplicate(" ", column - from) <> "^"
+
+    {length(newlines) + 1, column + 1, shown <> "\n" <> caret}
+  end
+
+  defp blank_tab("\t"), do: " "
+  defp blank_tab(char), do: char
 
   @impl true
   @spec exception(String.t() | keyword() | map()) :: t()
@@ -60,6 +60,9 @@ defmodule RustyJson do
   | `RustyJson.Sigil` | `~j`/`~J` sigils for JSON literals |
   | `RustyJson.OrderedObject` | Order-preserving JSON object (for `objects: :ordered_objects`) |
   | `RustyJson.Decoder` | JSON decoding module (Jason.Decoder compatible) |
+  | `RustyJson.StreamDecoder` | Incremental decoder handle (see `decoder_new/1`) |
+  | `RustyJson.ArrayIterator` | Lazy array iterator handle (see `iterator_new/2`) |
+  | `RustyJson.StreamEncoder` | Streaming encoder handle (see `encoder_new/1`) |
   | `RustyJson.DecodeError` | Decoding error exception |
   | `RustyJson.EncodeError` | Encoding error exception |
 
@@ -113,9 +116,9 @@ defmodule RustyJson do
   3. **Use compression**: For large payloads over the network, `compress: :gzip`
      reduces output size 5-10x.
 
-  4. **Avoid `keys: :atoms` with untrusted input**: `keys: :atoms` uses
-     `String.to_atom/1`, which can exhaust the atom table. Use `keys: :atoms!`
-     (which uses `String.to_existing_atom/1`) or `keys: :strings` (default) instead.
+  4. **Avoid `keys: :atoms` with untrusted input**: `keys: :atoms` creates atoms,
+     which can exhaust the atom table. Use `keys: :atoms!` (existing atoms only),
+     cap new atoms with `:max_new_atoms`, or keep `keys: :strings` (default).
 
   5. **Use key interning for bulk data**: When decoding arrays of objects with
      the same schema (API responses, database results, webhooks), use `keys: :intern`
@@ -183,8 +186,8 @@ defmodule RustyJson do
   Options for decoding JSON object keys.
 
   - `:strings` - Keep keys as strings (default, safe)
-  - `:atoms` - Convert to atoms using `String.to_atom/1` (unsafe with untrusted input)
-  - `:atoms!` - Convert to existing atoms using `String.to_existing_atom/1` (safe, raises if missing)
+  - `:atoms` - Convert to atoms, creating them if needed (unsafe with untrusted input)
+  - `:atoms!` - Convert to existing atoms only (safe, fails with the key's position if missing)
   - `:copy` - Copy key binaries (same as `:strings` in RustyJson since NIFs always copy)
   - `:intern` - Cache repeated keys during parsing (~30% faster for arrays of objects).
     The cache is capped at 4096 unique keys — beyond this, new keys are allocated normally.
@@ -216,6 +219,7 @@ defmodule RustyJson do
   - `:lean` - Skip special struct handling. Default: `false`
   - `:maps` - Key uniqueness mode. `:naive` (default) allows duplicate serialized keys,
     `:strict` raises on duplicate keys (e.g. atom `:a` and string `"a"` in the same map).
+  - `:max_depth` - Maximum nesting depth, from 1 to 256. Default: 128
   """
   @type encode_opt ::
           {:pretty, boolean() | pos_integer() | keyword()}
@@ -225,54 +229,177 @@ defmodule RustyJson do
           | {:lean, boolean()}
           | {:maps, :naive | :strict}
           | {:sort_keys, boolean()}
+          | {:max_depth, pos_integer()}
           | {:scheduler, :auto | :normal | :dirty}
 
   @typedoc """
   Options for `decode/2` and `decode!/2`.
 
   - `:keys` - How to handle object keys (see `t:keys/0`). Default: `:strings`
-  - `:strings` - How to handle decoded strings. `:copy` or `:reference`. Both produce copies (RustyJson always copies). Default: `:reference`
+  - `:max_new_atoms` - Most atoms `keys: :atoms` may create per call. 0 (default) is unlimited.
+  - `:key_case` - Rewrite keys as `:snake_case`, `:camel_case` or `:kebab_case` while
+    decoding. Default: `nil` (keys as written).
+  - `:integer_keys` - Decode keys that are canonical integers, such as `"42"`, to integers.
+    Default: `false`.
+  - `:strings` - Whether long strings may reference the input. `:reference` (default),
+    `:copy` or `:auto`; see `decode/2`
+  - `:subbinary_threshold` - Shortest string, in bytes, that `:reference` and `:auto` turn
+    into a sub-binary of the input. Default: 64
   - `:objects` - How to decode JSON objects. `:maps` (default) or `:ordered_objects`
   - `:floats` - How to decode JSON floats. `:native` (default) or `:decimals`
+  - `:numbers` - How to decode all JSON numbers. `:native` (default) follows `:floats`;
+    `:raw`, `:strings` and `:decimals` override it
+  - `:datetimes` - Decode ISO 8601 strings to `Date`, `Time`, `NaiveDateTime` and
+    `DateTime` structs. `true` checks every string value; a list of keys checks only
+    the values of those members. Default: `false`
+  - `:schema` - Decode objects straight into structs and check typed fields
+    (see `RustyJson.Schema`). Default: `nil`
   - `:decoding_integer_digit_limit` - Maximum digits in integer part. 0 disables.
     Default: 1024, or the value of `Application.compile_env(:rustyjson, :decoding_integer_digit_limit)`
   - `:max_bytes` - Maximum input size in bytes. 0 means unlimited (default).
     The check is performed using `IO.iodata_length/1` *before* converting to binary,
     avoiding the memory spike from allocating the full binary.
   - `:duplicate_keys` - How to handle duplicate object keys. `:last` (default) uses
-    last-wins semantics. `:error` rejects objects with duplicate keys.
+    last-wins semantics. `:first` keeps the first value. `:collect` merges the values
+    of a repeated key into a list, in input order; keys that appear once keep their
+    plain value. `:error` rejects the object with a `DecodeError` naming the key, at
+    the position of its second occurrence. With `objects: :ordered_objects`, `:last`
+    keeps every pair and the other policies dedupe the pairs like maps do.
     **Performance note**: `:error` adds per-key overhead from HashSet tracking.
     Use only when strict validation is needed.
   - `:validate_strings` - Whether to validate that decoded strings contain valid UTF-8.
     Default: `true`. When `true`, rejects strings with invalid UTF-8 byte sequences.
     Set to `false` to skip validation for maximum throughput on trusted input.
+  - `:invalid_unicode` - How to handle lone surrogate escapes (`"\\ud800"`) and, when
+    `:validate_strings` is `true`, invalid UTF-8. `:error` (default) rejects the document.
+    `:replace` writes U+FFFD in place of each lone surrogate and each invalid byte
+    sequence; `decode_with_stats/2` reports how many. `:keep` leaves invalid bytes as
+    they are and writes lone surrogates in their WTF-8 form, so the result may not be a
+    valid `String.t()`.
+  - `:max_depth` - Maximum nesting depth of arrays and objects, from 1 to 256.
+    Default: 128. Lower it for untrusted input.
+  - `:max_string_bytes` - Maximum raw length of a string or key in bytes. 0 (default)
+    disables this and the quotas below.
+  - `:max_array_elements` - Maximum elements in one array.
+  - `:max_object_keys` - Maximum members in one object.
+  - `:max_total_values` - Maximum values in the whole document, the root included.
+  - `:max_fraction_digits` - Maximum digits after the decimal point of a number.
+  - `:max_exponent_digits` - Maximum digits in the exponent of a number.
+  - `:only` - Key paths to keep, e.g. `[["data", "*", "id"]]`. Each path is a list of
+    object keys (strings or atoms); `"*"` matches any key and any array element. Members
+    off every path are skipped in the byte stream without being decoded, and the value at
+    the end of a path is kept whole. Paths are relative to each decoded value.
+  - `:except` - Key paths to drop, e.g. `[["*", "debug"]]`. Same path syntax as `:only`;
+    the values at the ends of the paths are skipped and everything else is decoded.
+    Cannot be combined with `:only`.
+  - `:relaxed` - Accept JSON5-style syntax (`decode/2` and `decode!/2` only). `true` enables
+    every extension; a list enables some of them: `:comments` (`//` and `/* */`),
+    `:trailing_commas`, `:single_quotes`, `:unquoted_keys` (ASCII identifiers),
+    `:hex_numbers` (`0x1F`), `:leading_plus` and `:non_finite` (`NaN`, `Infinity`,
+    `-Infinity`). Default: `false`. Strict decoding is unaffected when off.
+  - `:non_finite` - What `NaN`, `Infinity` and `-Infinity` decode to under
+    `relaxed: [:non_finite]`: `:atoms` (default) for `:nan`, `:infinity` and `:neg_infinity`,
+    `nil`, or a keyword list overriding some of those atoms, e.g. `[nan: nil]`.
   - `:dirty_threshold` - Byte size threshold for auto-dispatching to dirty CPU scheduler.
     When input size >= this threshold, decode runs on a dirty scheduler to avoid blocking
     normal BEAM schedulers. Default: 102400 (100KB). Set to 0 to disable.
   """
   @type decode_opt ::
           {:keys, keys()}
-          | {:strings, :copy | :reference}
+          | {:max_new_atoms, non_neg_integer()}
+          | {:key_case, :snake_case | :camel_case | :kebab_case | nil}
+          | {:integer_keys, boolean()}
+          | {:strings, :copy | :reference | :auto}
+          | {:subbinary_threshold, non_neg_integer()}
           | {:objects, :maps | :ordered_objects}
           | {:floats, :native | :decimals}
+          | {:numbers, :native | :raw | :strings | :decimals}
+          | {:datetimes, boolean() | [String.t() | atom()]}
+          | {:schema, RustyJson.Schema.t() | RustyJson.Schema.definition() | nil}
           | {:decoding_integer_digit_limit, non_neg_integer()}
           | {:max_bytes, non_neg_integer()}
-          | {:duplicate_keys, :last | :error}
+          | {:duplicate_keys, :last | :first | :collect | :error}
           | {:validate_strings, boolean()}
+          | {:invalid_unicode, :error | :replace | :keep}
+          | {:max_depth, pos_integer()}
+          | {:max_string_bytes, non_neg_integer()}
+          | {:max_array_elements, non_neg_integer()}
+          | {:max_object_keys, non_neg_integer()}
+          | {:max_total_values, non_neg_integer()}
+          | {:max_fraction_digits, non_neg_integer()}
+          | {:max_exponent_digits, non_neg_integer()}
+          | {:only, [[String.t() | atom()]]}
+          | {:except, [[String.t() | atom()]]}
+          | {:relaxed, boolean() | [relaxed_extension()]}
+          | {:non_finite, :atoms | nil | [{:nan | :infinity | :neg_infinity, atom()}]}
           | {:dirty_threshold, non_neg_integer()}
 
+  @typedoc """
+  A JSON5-style syntax extension accepted by `relaxed:` decoding.
+  """
+  @type relaxed_extension ::
+          :comments
+          | :trailing_commas
+          | :single_quotes
+          | :unquoted_keys
+          | :hex_numbers
+          | :leading_plus
+          | :non_finite
+
+  @typedoc """
+  What `get_pointer/3` returns for the value at a pointer: the decoded
+  `:value`, its JSON `:type`, an array's `:length`, or an object's `:key_count`.
+  """
+  @type pointer_return :: :value | :type | :length | :key_count
+
+  @typedoc """
+  Summary returned by `validate/2` for valid input: the root value's JSON
+  `:type`, the input size in `:bytes`, the deepest container nesting in
+  `:max_depth` (0 for a scalar), and the number of values in `:value_count`
+  (containers included, object keys excluded).
+  """
+  @typedoc """
+  Statistics returned by `decode_with_stats/2`.
+
+  `:replacements` counts the U+FFFD characters written by
+  `invalid_unicode: :replace`.
+  """
+  @type decode_stats :: %{replacements: non_neg_integer()}
+
+  @type validation_summary :: %{
+          type: :object | :array | :string | :number | :boolean | :null,
+          bytes: non_neg_integer(),
+          max_depth: non_neg_integer(),
+          value_count: pos_integer()
+        }
+
   @default_dirty_threshold_bytes Application.compile_env(
                                    :rustyjson,
                                    :dirty_threshold_bytes,
                                    102_400
                                  )
 
+  @default_stream_chunk_size 65_536
+
+  @default_stream_batch_size 1000
+
+  # Nesting depth limits. The ceilings match `MAX_DEPTH_LIMIT` in
+  # direct_decode.rs and direct_json.rs, which are sized to the native stack
+  # available to dirty CPU schedulers.
+  @default_max_depth 128
+  @max_decode_depth 256
+  @max_encode_depth 256
+
   @default_integer_digit_limit Application.compile_env(
                                  :rustyjson,
                                  :decoding_integer_digit_limit,
                                  1024
                                )
 
+  # Strings of at least this many bytes may be sub-binaries of the input;
+  # SUBBINARY_THRESHOLD in direct_decode.rs.
+  @default_subbinary_threshold 64
+
   # ============================================================================
   # Decode fast-path defaults
   # ============================================================================
@@ -292,12 +419,31 @@ defmodule RustyJson do
   #   | Key in @default_decode_nif_opts | Default option value          | Derived from                         |
   #   |---------------------------------|-------------------------------|--------------------------------------|
   #   | `:intern_keys`                  | `false`                       | `keys: :strings` (not `:intern`)     |
+  #   | `:strings`                      | `:reference`                  | `strings: :reference`                |
+  #   | `:subbinary_threshold`          | `@default_subbinary_threshold`| `subbinary_threshold: 64`            |
   #   | `:floats_decimals`              | `false`                       | `floats: :native` (not `:decimals`)  |
+  #   | `:numbers`                      | `:native`                     | `numbers: :native`                   |
   #   | `:ordered_objects`              | `false`                       | `objects: :maps` (not `:ordered_objects`) |
   #   | `:integer_digit_limit`          | `@default_integer_digit_limit`| `decoding_integer_digit_limit:` default |
   #   | `:max_bytes`                    | `0`                           | `max_bytes: 0` (unlimited)           |
-  #   | `:reject_duplicate_keys`        | `false`                       | `duplicate_keys: :last` (not `:error`) |
+  #   | `:duplicate_keys`               | `:last`                       | `duplicate_keys: :last`              |
   #   | `:validate_strings`             | `true`                        | `validate_strings: true`             |
+  #   | `:invalid_unicode`              | `:error`                      | `invalid_unicode: :error`            |
+  #   | `:max_depth`                    | `@default_max_depth`          | `max_depth: 128`                     |
+  #   | `:max_string_bytes`             | `0`                           | `max_string_bytes: 0` (unlimited)    |
+  #   | `:max_array_elements`           | `0`                           | `max_array_elements: 0` (unlimited)  |
+  #   | `:max_object_keys`              | `0`                           | `max_object_keys: 0` (unlimited)     |
+  #   | `:max_total_values`             | `0`                           | `max_total_values: 0` (unlimited)    |
+  #   | `:max_fraction_digits`          | `0`                           | `max_fraction_digits: 0` (unlimited) |
+  #   | `:max_exponent_digits`          | `0`                           | `max_exponent_digits: 0` (unlimited) |
+  #   | `:atom_keys`                    | `nil`                         | `keys: :strings` (not `:atoms`/`:atoms!`) |
+  #   | `:max_new_atoms`                | `0`                           | `max_new_atoms: 0` (unlimited)       |
+  #   | `:key_case`                     | `nil`                         | `key_case: nil` (keys as written)    |
+  #   | `:integer_keys`                 | `false`                       | `integer_keys: false`                |
+  #   | `:projection`                   | `nil`                         | no `only:` / `except:` paths         |
+  #   | `:relaxed`                      | `nil`                         | `relaxed: false`                     |
+  #   | `:datetimes`                    | `false`                       | `datetimes: false`                   |
+  #   | `:schema`                       | `nil`                         | `schema: nil`                        |
   #
   # The fast path also assumes:
   #   - `keys` is `:strings` (no post-NIF key transformation needed)
@@ -307,12 +453,31 @@ defmodule RustyJson do
   #
   @default_decode_nif_opts %{
     intern_keys: false,
+    strings: :reference,
+    subbinary_threshold: @default_subbinary_threshold,
     floats_decimals: false,
+    numbers: :native,
     ordered_objects: false,
     integer_digit_limit: @default_integer_digit_limit,
     max_bytes: 0,
-    reject_duplicate_keys: false,
-    validate_strings: true
+    duplicate_keys: :last,
+    validate_strings: true,
+    invalid_unicode: :error,
+    max_depth: @default_max_depth,
+    max_string_bytes: 0,
+    max_array_elements: 0,
+    max_object_keys: 0,
+    max_total_values: 0,
+    max_fraction_digits: 0,
+    max_exponent_digits: 0,
+    atom_keys: nil,
+    max_new_atoms: 0,
+    key_case: nil,
+    integer_keys: false,
+    projection: nil,
+    relaxed: nil,
+    datetimes: false,
+    schema: nil
   }
 
   source_url = Mix.Project.config()[:source_url]
@@ -399,6 +564,130 @@ defmodule RustyJson do
   def nif_encode_fields_dirty(_keys, _values, _escape_mode, _strict_keys),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc false
+  @spec nif_decoder_new(map()) :: reference()
+  defp nif_decoder_new(_opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decoder_feed(reference(), binary()) :: [term()]
+  defp nif_decoder_feed(_decoder, _chunk), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decoder_feed_dirty(reference(), binary()) :: [term()]
+  defp nif_decoder_feed_dirty(_decoder, _chunk), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decoder_finish(reference()) :: [term()]
+  defp nif_decoder_finish(_decoder), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decoder_finish_dirty(reference()) :: [term()]
+  defp nif_decoder_finish_dirty(_decoder), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decoder_buffered(reference()) :: non_neg_integer()
+  defp nif_decoder_buffered(_decoder), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_schema_compile(tuple()) :: reference()
+  def nif_schema_compile(_spec), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_iterator_new(binary(), [binary()], map()) :: reference()
+  defp nif_iterator_new(_input, _path, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_iterator_next(reference(), pos_integer()) :: {:cont | :done, [term()]}
+  defp nif_iterator_next(_iterator, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_iterator_next_dirty(reference(), pos_integer()) :: {:cont | :done, [term()]}
+  defp nif_iterator_next_dirty(_iterator, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_lines(binary(), map(), boolean()) ::
+          {[term()], [{pos_integer(), RustyJson.DecodeError.nif_error()}]}
+  defp nif_decode_lines(_input, _opts_map, _skip_invalid),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_lines_dirty(binary(), map(), boolean()) ::
+          {[term()], [{pos_integer(), RustyJson.DecodeError.nif_error()}]}
+  defp nif_decode_lines_dirty(_input, _opts_map, _skip_invalid),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_prefix(binary(), map()) :: {term(), non_neg_integer(), binary()}
+  defp nif_decode_prefix(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_prefix_dirty(binary(), map()) :: {term(), non_neg_integer(), binary()}
+  defp nif_decode_prefix_dirty(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_all(binary(), map()) :: [term()]
+  defp nif_decode_all(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_decode_all_dirty(binary(), map()) :: [term()]
+  defp nif_decode_all_dirty(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encode_lines([term()], map()) :: String.t()
+  defp nif_encode_lines(_terms, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encode_lines_dirty([term()], map()) :: String.t()
+  defp nif_encode_lines_dirty(_terms, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_get_pointer(binary(), [binary()], map(), pointer_return()) ::
+          {:ok, term()} | :error
+  defp nif_get_pointer(_input, _tokens, _opts_map, _return),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_get_pointer_dirty(binary(), [binary()], map(), pointer_return()) ::
+          {:ok, term()} | :error
+  defp nif_get_pointer_dirty(_input, _tokens, _opts_map, _return),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_query(binary(), binary(), map(), :values | :paths) :: [term()]
+  defp nif_query(_input, _path, _opts_map, _return), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_query_dirty(binary(), binary(), map(), :values | :paths) :: [term()]
+  defp nif_query_dirty(_input, _path, _opts_map, _return),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_validate(binary(), map()) ::
+          {:ok, validation_summary()} | {:error, String.t(), non_neg_integer()}
+  defp nif_validate(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_validate_dirty(binary(), map()) ::
+          {:ok, validation_summary()} | {:error, String.t(), non_neg_integer()}
+  defp nif_validate_dirty(_input, _opts_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encoder_new(map(), :array | :object, pos_integer()) :: reference()
+  defp nif_encoder_new(_opts_map, _container, _chunk_size),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encoder_push(reference(), [term()]) :: [binary()]
+  defp nif_encoder_push(_encoder, _items), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encoder_push_dirty(reference(), [term()]) :: [binary()]
+  defp nif_encoder_push_dirty(_encoder, _items), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec nif_encoder_finish(reference()) :: [binary()]
+  defp nif_encoder_finish(_encoder), do: :erlang.nif_error(:nif_not_loaded)
+
   # ============================================================================
   # Encoding API
   # ============================================================================
@@ -432,6 +721,9 @@ defmodule RustyJson do
     (useful for snapshot tests, caching, or diffing). Note: Jason always sorts
     keys; RustyJson does not by default for performance.
 
+  * `:max_depth` - Maximum nesting depth, from 1 to 256. Deeper terms fail with
+    `"Nesting depth exceeds maximum of N"`. Default: `128`
+
   ## Examples
 
       iex> RustyJson.encode(%{name: "Alice", scores: [95, 87, 92]})
@@ -460,7 +752,7 @@ defmodule RustyJson do
     e in [RustyJson.EncodeError] -> {:error, e}
     e in [Protocol.UndefinedError] -> {:error, e}
     e in [ArgumentError] -> {:error, e}
-    e in [ErlangError] -> {:error, %RustyJson.EncodeError{message: error_message(e)}}
+    e in [ErlangError] -> {:error, encode_error(e)}
   end
 
   @doc """
@@ -524,15 +816,34 @@ defmodule RustyJson do
   """
   @spec encode!(term(), [encode_opt()]) :: String.t()
   def encode!(input, opts \\ []) do
+    {encode_opts, encoder_opts, use_protocol} = parse_encode_opts(opts)
+
+    processed =
+      with_encode_fields_ctx(encode_opts, use_protocol, fn ->
+        protocol_encode(input, use_protocol, encoder_opts)
+      end)
+
+    encode_to_nif(processed, encode_opts)
+  rescue
+    e in [ErlangError] ->
+      raise_encode_error(e)
+  end
+
+  # Parse and validate all encode options, returning
+  # {encode_opts, encoder_opts, use_protocol}.
+  defp parse_encode_opts(opts) do
     {indent, opts} = Keyword.pop(opts, :pretty, nil)
     {compression, opts} = Keyword.pop(opts, :compress, :none)
     {escape, opts} = Keyword.pop(opts, :escape, :json)
     {use_protocol, opts} = Keyword.pop(opts, :protocol, true)
+    use_protocol = use_protocol not in [false, nil]
     {lean, opts} = Keyword.pop(opts, :lean, false)
     {maps_mode, opts} = Keyword.pop(opts, :maps, :naive)
     {sort_keys, opts} = Keyword.pop(opts, :sort_keys, false)
+    {max_depth, opts} = Keyword.pop(opts, :max_depth, @default_max_depth)
     {scheduler, opts} = Keyword.pop(opts, :scheduler, :auto)
     validate_option!(maps_mode, [:naive, :strict], :maps)
+    validate_max_depth!(max_depth, @max_encode_depth)
     validate_option!(scheduler, [:auto, :normal, :dirty], :scheduler)
 
     # Extract pretty print separator opts
@@ -549,29 +860,6 @@ defmodule RustyJson do
     # Encoder protocol and into Encode functions for full Jason compatibility.
     encoder_opts = RustyJson.Encode.build_opts(escape, maps_mode)
 
-    # With protocol: true, preprocess with Elixir Encoder protocol.
-    # Store {escape_mode, strict_keys} in process dict so DerivedNIF.encode/4
-    # can read it. Save/restore previous value to support nested encode! calls.
-    processed =
-      if use_protocol do
-        prev_ctx = Process.get(:rustyjson_encode_fields_ctx)
-        Process.put(:rustyjson_encode_fields_ctx, {escape, strict_keys})
-
-        try do
-          RustyJson.Encoder.encode(input, encoder_opts)
-        after
-          case prev_ctx do
-            nil -> Process.delete(:rustyjson_encode_fields_ctx)
-            val -> Process.put(:rustyjson_encode_fields_ctx, val)
-          end
-        end
-      else
-        input
-      end
-
-    processed =
-      resolve_protocol_fragments(processed, input, use_protocol, encoder_opts)
-
     encode_opts = %{
       indent: indent,
       compression: compression,
@@ -579,17 +867,43 @@ defmodule RustyJson do
       escape: escape,
       strict_keys: strict_keys,
       sort_keys: sort_keys,
+      max_depth: max_depth,
       pretty_opts: pretty_opts,
       scheduler: scheduler
     }
 
-    encode_to_nif(
-      processed,
-      encode_opts
-    )
-  rescue
-    e in [ErlangError] ->
-      raise_encode_error(e)
+    {encode_opts, encoder_opts, use_protocol}
+  end
+
+  # With protocol: true, preprocessing runs the Elixir Encoder protocol.
+  # Store {escape_mode, strict_keys} in process dict so DerivedNIF.encode/4
+  # can read it. Save/restore previous value to support nested encode! calls.
+  defp with_encode_fields_ctx(_encode_opts, false, fun), do: fun.()
+
+  defp with_encode_fields_ctx(%{escape: escape, strict_keys: strict_keys}, true, fun) do
+    prev_ctx = Process.get(:rustyjson_encode_fields_ctx)
+    Process.put(:rustyjson_encode_fields_ctx, {escape, strict_keys})
+
+    try do
+      fun.()
+    after
+      case prev_ctx do
+        nil -> Process.delete(:rustyjson_encode_fields_ctx)
+        val -> Process.put(:rustyjson_encode_fields_ctx, val)
+      end
+    end
+  end
+
+  # Prepare a term for the NIF: apply the Encoder protocol (if enabled) and
+  # resolve function-based Fragments.
+  defp protocol_encode(input, true, encoder_opts) do
+    input
+    |> RustyJson.Encoder.encode(encoder_opts)
+    |> resolve_protocol_fragments(input, true, encoder_opts)
+  end
+
+  defp protocol_encode(input, false, encoder_opts) do
+    resolve_protocol_fragments(input, input, false, encoder_opts)
   end
 
   # Resolve function-based Fragments so the NIF receives iodata.
@@ -629,24 +943,31 @@ defmodule RustyJson do
   end
 
   defp encode_to_nif(processed, opts) do
-    nif_opts = %{
+    nif_opts = encode_nif_opts(opts)
+
+    if use_dirty_encode?(opts) do
+      nif_encode_direct_dirty(processed, nif_opts)
+    else
+      nif_encode_direct(processed, nif_opts)
+    end
+  end
+
+  defp encode_nif_opts(opts) do
+    %{
       indent: opts.indent,
       compression: opts.compression,
       lean: opts.lean == true,
       escape: opts.escape,
       strict_keys: opts.strict_keys,
       sort_keys: opts.sort_keys == true,
+      max_depth: opts.max_depth,
       pretty_opts: opts.pretty_opts
     }
+  end
 
+  defp use_dirty_encode?(opts) do
     uses_compression = match?({:gzip, _}, opts.compression)
-    use_dirty = opts.scheduler == :dirty or (opts.scheduler == :auto and uses_compression)
-
-    if use_dirty do
-      nif_encode_direct_dirty(processed, nif_opts)
-    else
-      nif_encode_direct(processed, nif_opts)
-    end
+    opts.scheduler == :dirty or (opts.scheduler == :auto and uses_compression)
   end
 
   # ============================================================================
@@ -663,7 +984,8 @@ defmodule RustyJson do
   * `:keys` - How to decode object keys. One of:
     * `:strings` - Keep as strings (default, safe)
     * `:atoms` - Convert to atoms (unsafe with untrusted input)
-    * `:atoms!` - Convert to existing atoms only (safe, raises if atom missing)
+    * `:atoms!` - Convert to existing atoms only (safe). A key that names no existing
+      atom fails with `"Key is not an existing atom"` at the key's byte position.
     * `:copy` - Copy key binaries (equivalent to `:strings` in RustyJson)
     * `:intern` - Cache repeated keys during parsing. **~30% faster** for arrays of
       objects with the same schema (REST APIs, GraphQL, database results, webhooks).
@@ -675,9 +997,38 @@ defmodule RustyJson do
     * A function of arity 1 - Applied recursively to each key string.
       Example: `keys: &String.upcase/1`
 
-  * `:strings` - How to handle decoded strings. `:reference` (default) or `:copy`.
-    Both produce copies in RustyJson (Rust NIFs always copy into BEAM binaries),
-    so this option exists for Jason API compatibility.
+    Atom keys are built by the parser. Each distinct key is looked up in the atom
+    table once per call, however many objects repeat it.
+
+  * `:max_new_atoms` - With `keys: :atoms`, the most atoms one call may create.
+    Going over fails with `"keys exceed N new atom limit"` at the byte position of
+    the key. Default: `0` (unlimited).
+
+  * `:key_case` - Rewrite object keys while decoding: `:snake_case` (`user_id`),
+    `:camel_case` (`userId`) or `:kebab_case` (`user-id`). Keys are split into words
+    at `_`, `-` and case changes (`HTTPServer` becomes `http_server`), and leading or
+    trailing `_`/`-` are kept. Applied before `:keys`, so `keys: :atoms` gets the
+    rewritten names. `:only`/`:except` paths see the keys as written in the JSON. Keys
+    that only collide once rewritten are duplicates; `duplicate_keys: :error` reports
+    them at the start of the object. Default: `nil`.
+
+  * `:integer_keys` - Decode keys that are integers in canonical form (`"0"`, `"42"`,
+    `"-7"`, but not `"007"`, `"+1"` or `"-0"`) to integers. Other keys are left alone.
+    Integer keys are held to `:decoding_integer_digit_limit` like integer values.
+    Each distinct key is converted once per call. Default: `false`.
+
+  * `:strings` - Whether decoded strings may be sub-binaries of the input.
+    `:reference` (default) returns strings of `:subbinary_threshold` bytes or more
+    without escapes as sub-binaries, which avoids copying them but keeps the whole
+    input binary in memory for as long as any of them is. `:copy` always copies, so
+    a long-lived process can keep one field of a large body without holding on to
+    the body. `:auto` copies until the strings long enough to share add up to a
+    quarter of the input, and references from then on, so the input is only kept
+    alive by results that hold a good part of it anyway. Object keys follow the same
+    rule; shorter strings and strings with escapes are always copied.
+
+  * `:subbinary_threshold` - Shortest string, in bytes, that `:reference` and `:auto`
+    make a sub-binary. Default: `64`, below which copying is cheaper.
 
   * `:objects` - How to decode JSON objects. `:maps` (default) or `:ordered_objects`.
     When `:ordered_objects`, returns `%RustyJson.OrderedObject{}` structs that
@@ -686,12 +1037,58 @@ defmodule RustyJson do
   * `:floats` - How to decode JSON floats. `:native` (default) returns Elixir floats,
     `:decimals` returns `%Decimal{}` structs for exact decimal representation.
 
+  * `:numbers` - How to decode every JSON number, integers included, when the
+    exact text matters. `:native` (default) returns integers and applies `:floats`.
+    `:raw` returns a `%RustyJson.Fragment{}` holding the number as written, so
+    encoding the result reproduces `1.10` or `1e2` byte for byte. `:strings`
+    returns the text as a binary, and `:decimals` returns `%Decimal{}` structs for
+    integers as well as floats. The `relaxed:` extensions for hex and non-finite
+    numbers still decode as usual.
+
+  * `:datetimes` - Decode string values holding ISO 8601 text to calendar structs,
+    the inverse of what the encoder writes for them. `true` checks every string value.
+    A list of keys, such as `["inserted_at", :updated_at]`, checks only the values of
+    members with those keys, matched as written in the JSON before `:key_case` or
+    `:keys` apply; strings inside arrays under those keys are left alone. The forms
+    recognized are `"2024-01-15"` (`Date`), `"10:30:00"` (`Time`),
+    `"2024-01-15T10:30:00"` (`NaiveDateTime`), and the same with `Z` or an offset
+    such as `+02:00` (`DateTime`). As with `DateTime.from_iso8601/1`, offsets are
+    shifted to UTC and `-00:00` is rejected. Fractions of a second keep up to six
+    digits. Strings that are not an exact match, including ones with out-of-range
+    fields or a space instead of `T`, stay strings. Default: `false`.
+
+  * `:schema` - Build structs while decoding instead of maps. Takes a struct module
+    and a map from JSON keys to fields, nested for structs and lists of structs; see
+    `RustyJson.Schema`. Members the schema does not name are skipped, and fields
+    missing from the JSON keep their defaults. A `{:one_of, key, variants}` spec
+    picks the struct from the value of a discriminator key such as `"type"`. Typed
+    fields, such as `{:age, :integer, min: 0}`, are checked as they are decoded, and
+    a document that breaks them fails with every violation in the error's
+    `:violations`. Pass a schema compiled with `RustyJson.Schema.compile!/1` to
+    reuse it across calls. Cannot be combined with `:only`, `:except`,
+    `:relaxed` or `duplicate_keys: :collect`. Default: `nil`.
+
   * `:decoding_integer_digit_limit` - Maximum number of digits allowed in the integer
     part of a JSON number. Integers exceeding this limit cause a decode error.
     Default: `1024`, or the value of
     `Application.compile_env(:rustyjson, :decoding_integer_digit_limit)`.
     Set to `0` to disable the limit.
 
+  * `:max_depth` - Maximum nesting depth of arrays and objects, from 1 to 256.
+    Deeper input fails with `"Nesting depth exceeds maximum of N"` at the byte
+    position of the container that went past the limit. Default: `128`.
+
+  * `:max_string_bytes`, `:max_array_elements`, `:max_object_keys`, `:max_total_values`,
+    `:max_fraction_digits`, `:max_exponent_digits` - Quotas for untrusted input, each
+    `0` (unlimited) by default. The parser stops at the first violation, before building
+    the offending term, with a message such as `"array exceeds 1000 element limit"` at
+    the byte position of the string, element, member or number that went over. Skipped
+    members (`:only` / `:except`) count as well. The digit quotas also bound numbers
+    decoded to `%Decimal{}` with `floats: :decimals` or `numbers: :decimals`. Whatever
+    the quotas, a Decimal's exponent, with the fraction folded in, must lie within
+    ±1024, the most the encoder writes: `1e999999999` fails with
+    `"decimal exponent 999999999 exceeds limit of 1024"`.
+
   ## Examples
 
       iex> RustyJson.decode(~s({"name":"Alice","age":30}))
@@ -708,7 +1105,8 @@ defmodule RustyJson do
   **Avoid `keys: :atoms` with untrusted input.** Atoms are not garbage collected,
   so an attacker could exhaust your atom table by sending JSON with many unique keys.
 
-  Use `keys: :atoms!` if you expect specific keys to exist, or `keys: :strings` (default).
+  Use `keys: :atoms!` if you expect specific keys to exist, bound `keys: :atoms` with
+  `:max_new_atoms`, or use `keys: :strings` (default).
 
   See `decode!/2` for a version that raises on error.
   """
@@ -752,6 +1150,9 @@ defmodule RustyJson do
       iex> RustyJson.decode!(~s({"price":19.99}), floats: :decimals)
       %{"price" => Decimal.new("19.99")}
 
+      iex> RustyJson.decode!(~s([1.10, 1e2, 7]), numbers: :strings)
+      ["1.10", "1e2", "7"]
+
   ## JSON Types to Elixir
 
   | JSON | Elixir |
@@ -759,7 +1160,7 @@ defmodule RustyJson do
   | object | map (or `RustyJson.OrderedObject` with `objects: :ordered_objects`) |
   | array | list |
   | string | binary |
-  | number (int) | integer |
+  | number (int) | integer (see `:numbers` for the alternatives) |
   | number (float) | float (or `Decimal` with `floats: :decimals`) |
   | true | `true` |
   | false | `false` |
@@ -782,7 +1183,7 @@ defmodule RustyJson do
   # on a binary input — the pattern used by Phoenix.Socket.V2.JSONSerializer,
   # Plug.Parsers, and most application code.
   #
-  # It skips all option parsing (9x Keyword.pop), validation, IO.iodata_to_binary,
+  # It skips all option parsing (24x Keyword.pop), validation, IO.iodata_to_binary,
   # and max_bytes checks, passing a precomputed @default_decode_nif_opts map
   # directly to the NIF. This eliminates ~280ns of per-call overhead.
   #
@@ -808,160 +1209,1121 @@ defmodule RustyJson do
   # Full path: options provided, or iodata input.
   #
   # Parses all options, validates them, converts iodata to binary if needed,
-  # and applies post-decode key transformations. This path handles all
+  # and applies `keys:` functions after decoding. This path handles all
   # decode options including keys: :atoms, floats: :decimals, max_bytes, etc.
-  def decode!(input, opts) do
-    {keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+  def decode!(input, opts), do: decode_with_opts!(input, opts, false)
 
-    max_bytes = Map.get(nif_opts, :max_bytes, 0)
+  @doc """
+  Decodes a JSON string like `decode/2`, also returning statistics about the input.
 
-    if max_bytes > 0 do
-      input_size = IO.iodata_length(input)
+  Returns `{:ok, term, stats}` (see `t:decode_stats/0`) or `{:error, error}`.
+  Use it with `invalid_unicode: :replace` to track how much of a feed had to be
+  repaired.
 
-      if input_size > max_bytes do
-        raise %RustyJson.DecodeError{
-          message: "input size #{input_size} exceeds max_bytes limit of #{max_bytes}",
-          position: 0
-        }
-      end
-    end
+  ## Examples
+
+      iex> RustyJson.decode_with_stats(~S(["a\\ud800b"]), invalid_unicode: :replace)
+      {:ok, ["a\\uFFFDb"], %{replacements: 1}}
+
+      iex> RustyJson.decode_with_stats("[1]")
+      {:ok, [1], %{replacements: 0}}
 
+  """
+  @spec decode_with_stats(iodata(), [decode_opt()]) ::
+          {:ok, term(), decode_stats()} | {:error, RustyJson.DecodeError.t()}
+  def decode_with_stats(input, opts \\ []) do
+    {result, stats} = decode_with_opts!(input, opts, true)
+    {:ok, result, stats}
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  # Shared by `decode!/2` and `decode_with_stats/2`: with `stats?` the NIF
+  # also counts what it saw, and the result is `{term, stats}`.
+  defp decode_with_opts!(input, opts, stats?) do
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    check_max_bytes!(input, nif_opts)
     input_binary = IO.iodata_to_binary(input)
 
     dirty_threshold = validated_opts[:dirty_threshold]
     use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
 
     nif_fn = if use_dirty, do: &nif_decode_dirty/2, else: &nif_decode/2
-    result = nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
-    maybe_transform_keys(result, keys, validated_opts)
+
+    if stats? do
+      nif_opts = Map.put(nif_opts, :stats, true)
+      {result, stats} = nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
+      {maybe_transform_keys(result, validated_opts), stats}
+    else
+      result = nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
+      maybe_transform_keys(result, validated_opts)
+    end
   end
 
   # ============================================================================
-  # Phoenix Interface
+  # Streaming Decode API
   # ============================================================================
 
   @doc """
-  Encodes a term to iodata (for Phoenix compatibility).
+  Creates an incremental decoder.
 
-  This function exists to implement the Phoenix JSON library interface.
-  Returns `{:ok, binary}` on success or `{:error, reason}` on failure.
-
-  ## A Note on iodata
+  Accepts the same options as `decode/2`. `:max_bytes` limits the total number
+  of bytes fed over the decoder's lifetime. `:dirty_threshold` applies to the
+  bytes a call may decode: the chunk passed to `decoder_feed/2` plus the
+  unfinished value already buffered. A root that is not an array is buffered
+  whole, so the call that completes a large object moves to a dirty scheduler.
 
-  RustyJson returns a **single binary**, not an iolist. This is intentional
-  and provides excellent performance for payloads up to ~100MB.
+  See `RustyJson.StreamDecoder` for the decoding model.
 
-  The memory efficiency comes from the encoding process (no intermediate
-  allocations), not from chunked output. A 10MB payload uses ~15MB peak memory.
+  ## Examples
 
-  For truly massive payloads (100MB+), consider:
-  - Streaming the data structure itself (encode in chunks)
-  - Using compression (`compress: :gzip` reduces output 5-10x)
-  - Pagination or chunked API design
+      iex> decoder = RustyJson.decoder_new()
+      iex> RustyJson.decoder_feed(decoder, ~s([1, "a", {"b":))
+      {:ok, [1, "a"]}
+      iex> RustyJson.decoder_feed(decoder, ~s(2}]))
+      {:ok, [%{"b" => 2}]}
+      iex> RustyJson.decoder_finish(decoder)
+      {:ok, []}
 
-  ## Examples
+  """
+  @spec decoder_new([decode_opt()]) :: RustyJson.StreamDecoder.t()
+  def decoder_new(opts \\ []) do
+    {keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "decoder_new/1")
+    reject_schema!(nif_opts, "decoder_new/1")
+
+    %RustyJson.StreamDecoder{
+      resource: nif_decoder_new(nif_opts),
+      keys: keys,
+      keys_fn: validated_opts.keys_fn,
+      dirty_threshold: validated_opts.dirty_threshold
+    }
+  end
 
-      iex> RustyJson.encode_to_iodata(%{status: "ok"})
-      {:ok, ~s({"status":"ok"})}
+  @doc """
+  Feeds a chunk of JSON to an incremental decoder.
 
+  Returns `{:ok, values}` with every value completed by this chunk (possibly
+  none), or `{:error, %RustyJson.DecodeError{}}`. Error positions are byte
+  offsets from the start of the stream.
   """
-  @spec encode_to_iodata(term(), [encode_opt()]) ::
-          {:ok, iodata()} | {:error, RustyJson.EncodeError.t() | Exception.t()}
-  def encode_to_iodata(input, opts \\ []), do: encode(input, opts)
+  @spec decoder_feed(RustyJson.StreamDecoder.t(), iodata()) ::
+          {:ok, [term()]} | {:error, RustyJson.DecodeError.t()}
+  def decoder_feed(%RustyJson.StreamDecoder{} = decoder, chunk) do
+    chunk = IO.iodata_to_binary(chunk)
+    use_dirty = stream_decoder_dirty?(decoder, byte_size(chunk))
+    nif_fn = if use_dirty, do: &nif_decoder_feed_dirty/2, else: &nif_decoder_feed/2
+    stream_decoder_call(decoder, &nif_fn.(&1, chunk))
+  end
 
   @doc """
-  Encodes a term to iodata, raising on error (for Phoenix compatibility).
+  Signals the end of input to an incremental decoder.
 
-  This function exists to implement the Phoenix JSON library interface.
-  Raises `RustyJson.EncodeError` on failure.
+  Returns any value that could only be terminated by end of input (such as a
+  top-level number), or an error if the document is incomplete.
+  """
+  @spec decoder_finish(RustyJson.StreamDecoder.t()) ::
+          {:ok, [term()]} | {:error, RustyJson.DecodeError.t()}
+  def decoder_finish(%RustyJson.StreamDecoder{} = decoder) do
+    use_dirty = stream_decoder_dirty?(decoder, 0)
+    nif_fn = if use_dirty, do: &nif_decoder_finish_dirty/1, else: &nif_decoder_finish/1
+    stream_decoder_call(decoder, nif_fn)
+  end
 
-  See `encode_to_iodata/2` for notes on iodata behavior.
+  # A call decodes at most the new bytes plus the unfinished value already
+  # buffered, so both count towards the dirty threshold, as the input does
+  # in `decode/2`.
+  defp stream_decoder_dirty?(%RustyJson.StreamDecoder{dirty_threshold: 0}, _new_bytes), do: false
 
-  ## Examples
+  defp stream_decoder_dirty?(%RustyJson.StreamDecoder{dirty_threshold: threshold}, new_bytes)
+       when new_bytes >= threshold,
+       do: true
 
-      iex> RustyJson.encode_to_iodata!(%{status: "ok"})
-      ~s({"status":"ok"})
+  defp stream_decoder_dirty?(%RustyJson.StreamDecoder{} = decoder, new_bytes) do
+    new_bytes + nif_decoder_buffered(decoder.resource) >= decoder.dirty_threshold
+  end
 
-  """
-  @spec encode_to_iodata!(term(), [encode_opt()]) :: iodata()
-  def encode_to_iodata!(input, opts \\ []), do: encode!(input, opts)
+  @doc """
+  Lazily decodes a stream of JSON chunks.
 
-  # ============================================================================
-  # Private Functions
-  # ============================================================================
+  Accepts any enumerable of iodata chunks (e.g. `File.stream!/3` or socket
+  reads) or a single binary. When the root is an array, its elements are
+  emitted one by one; otherwise the single root value is emitted. Raises
+  `RustyJson.DecodeError` on invalid input.
 
-  @doc false
-  defp normalize_pretty_opts(indent) when is_list(indent) do
-    # Pretty print with custom separator options
-    raw_indent = Keyword.get(indent, :indent, 2)
+  ## Options
 
-    pretty_opts = %{}
+  Accepts all `decode/2` options, plus:
 
-    pretty_opts =
-      case Keyword.fetch(indent, :line_separator) do
-        {:ok, sep} -> Map.put(pretty_opts, :line_separator, IO.iodata_to_binary(sep))
-        :error -> pretty_opts
-      end
+  * `:chunk_size` - Maximum bytes handed to the NIF per call. Larger input
+    chunks are split so each call does bounded work. Default: `65536`.
 
-    pretty_opts =
-      case Keyword.fetch(indent, :after_colon) do
-        {:ok, sep} -> Map.put(pretty_opts, :after_colon, IO.iodata_to_binary(sep))
-        :error -> pretty_opts
-      end
+  ## Examples
 
-    # Handle iodata indent (e.g. "\t") — pass as binary in pretty_opts
-    {pretty_opts, indent_val} = normalize_indent_value(raw_indent, pretty_opts)
+      iex> [~s([{"a":1},), ~s({"b":2}]) ] |> RustyJson.decode_stream() |> Enum.to_list()
+      [%{"a" => 1}, %{"b" => 2}]
 
-    {if(map_size(pretty_opts) > 0, do: pretty_opts, else: nil), indent_val}
+  """
+  @spec decode_stream(Enumerable.t() | binary(), [decode_opt() | {:chunk_size, pos_integer()}]) ::
+          Enumerable.t()
+  def decode_stream(input, opts \\ [])
+
+  def decode_stream(input, opts) when is_binary(input), do: decode_stream([input], opts)
+
+  def decode_stream(chunks, opts) do
+    {chunk_size, opts} = Keyword.pop(opts, :chunk_size, @default_stream_chunk_size)
+
+    chunks
+    |> Stream.concat([:eof])
+    |> Stream.transform(
+      fn -> decoder_new(opts) end,
+      fn
+        :eof, decoder ->
+          {stream_values!(decoder_finish(decoder)), decoder}
+
+        chunk, decoder ->
+          {feed_in_slices(decoder, IO.iodata_to_binary(chunk), chunk_size), decoder}
+      end,
+      fn _decoder -> :ok end
+    )
   end
 
-  defp normalize_pretty_opts(indent) do
-    {pretty_opts, indent_val} = normalize_indent_value(indent, %{})
-    {if(map_size(pretty_opts) > 0, do: pretty_opts, else: nil), indent_val}
-  end
+  @doc """
+  Creates a lazy iterator over a JSON array.
 
-  # Convert indent value to {pretty_opts_map, integer_indent_for_nif}
-  # For string/iodata indent, we pass the binary through pretty_opts and use
-  # indent=1 as a sentinel to enable pretty mode in the NIF.
-  @doc false
-  defp normalize_indent_value(indent, pretty_opts) do
-    cond do
-      indent == true ->
-        {pretty_opts, 2}
+  The input binary is referenced, not copied. Elements are decoded only when
+  requested with `iterator_next/2`, so a multi-gigabyte array can be processed
+  without building the full list.
 
-      is_integer(indent) and indent > 0 ->
-        {pretty_opts, indent}
+  ## Options
 
-      is_binary(indent) ->
-        {Map.put(pretty_opts, :indent, indent), 1}
+  Accepts all `decode/2` options (except `:max_bytes`, which does not apply),
+  plus:
 
-      is_list(indent) ->
-        # iodata list
-        {Map.put(pretty_opts, :indent, IO.iodata_to_binary(indent)), 1}
+  * `:path` - List of object keys and array indices leading from the root to
+    the array to iterate. Default: `[]` (the root must be an array).
 
-      true ->
-        {pretty_opts, nil}
-    end
-  end
+  `:dirty_threshold` applies to the whole input, since a single batch may
+  read up to its end: over inputs at least that large, `iterator_next/2`
+  decodes each batch on a dirty CPU scheduler.
 
-  @doc false
-  defp extract_token(data, position) when is_binary(data) and is_integer(position) do
-    if position >= 0 and position < byte_size(data) do
-      # Extract a short token around the error position
-      len = min(byte_size(data) - position, 10)
-      binary_part(data, position, len)
-    else
-      nil
-    end
-  end
+  ## Examples
 
-  defp extract_token(_, _), do: nil
+      iex> iterator = RustyJson.iterator_new(~s({"rows":[{"id":1},{"id":2}]}), path: ["rows"])
+      iex> RustyJson.iterator_next(iterator, 1)
+      {:cont, [%{"id" => 1}]}
+      iex> RustyJson.iterator_next(iterator, 1)
+      {:cont, [%{"id" => 2}]}
+      iex> RustyJson.iterator_next(iterator, 1)
+      {:done, []}
 
-  @doc false
-  defp validate_escape!(:json), do: :json
-  defp validate_escape!(:html_safe), do: :html_safe
-  defp validate_escape!(:unicode_safe), do: :unicode_safe
-  defp validate_escape!(:javascript_safe), do: :javascript_safe
+  """
+  @spec iterator_new(iodata(), [decode_opt() | {:path, [String.t() | non_neg_integer()]}]) ::
+          RustyJson.ArrayIterator.t()
+  def iterator_new(input, opts \\ []) do
+    {path, opts} = Keyword.pop(opts, :path, [])
+    {keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "iterator_new/2")
+    reject_schema!(nif_opts, "iterator_new/2")
+    input_binary = IO.iodata_to_binary(input)
+
+    %RustyJson.ArrayIterator{
+      resource: nif_iterator_new(input_binary, path_tokens!(path), nif_opts),
+      data: input_binary,
+      keys: keys,
+      keys_fn: validated_opts.keys_fn,
+      dirty_threshold: validated_opts.dirty_threshold
+    }
+  end
+
+  @doc """
+  Decodes the next `count` elements from an array iterator.
+
+  Returns `{:cont, elements}` while more elements may follow, `{:done, elements}`
+  once the closing bracket has been reached, or `{:error, %RustyJson.DecodeError{}}`.
+  """
+  @spec iterator_next(RustyJson.ArrayIterator.t(), pos_integer()) ::
+          {:cont, [term()]} | {:done, [term()]} | {:error, RustyJson.DecodeError.t()}
+  def iterator_next(%RustyJson.ArrayIterator{dirty_threshold: threshold} = iterator, count)
+      when is_integer(count) and count > 0 do
+    use_dirty = threshold > 0 and byte_size(iterator.data) >= threshold
+    nif_fn = if use_dirty, do: &nif_iterator_next_dirty/2, else: &nif_iterator_next/2
+    {status, values} = nif_fn.(iterator.resource, count)
+    {status, Enum.map(values, &maybe_transform_keys(&1, iterator))}
+  rescue
+    e in [ErlangError] -> {:error, iterator_decode_error(e, iterator.data)}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+  end
+
+  @doc """
+  Lazily decodes the elements of a JSON array as a `Stream`.
+
+  Elements are decoded in batches with `iterator_next/2`. Raises
+  `RustyJson.DecodeError` on invalid input.
+
+  ## Options
+
+  Accepts all `iterator_new/2` options, plus:
+
+  * `:batch_size` - Elements decoded per NIF call. Default: `1000`.
+
+  ## Examples
+
+      iex> RustyJson.stream_array(~s([1,2,3,4]), batch_size: 3) |> Enum.to_list()
+      [1, 2, 3, 4]
+
+  """
+  @spec stream_array(iodata(), keyword()) :: Enumerable.t()
+  def stream_array(input, opts \\ []) do
+    {batch_size, opts} = Keyword.pop(opts, :batch_size, @default_stream_batch_size)
+
+    Stream.resource(
+      fn -> iterator_new(input, opts) end,
+      fn
+        :done ->
+          {:halt, :done}
+
+        iterator ->
+          case iterator_next(iterator, batch_size) do
+            {:cont, values} -> {values, iterator}
+            {:done, values} -> {values, :done}
+            {:error, error} -> raise error
+          end
+      end,
+      fn _ -> :ok end
+    )
+  end
+
+  # ============================================================================
+  # Streaming Encode API
+  # ============================================================================
+
+  @doc """
+  Creates a streaming encoder.
+
+  See `RustyJson.StreamEncoder` for the encoding model.
+
+  ## Options
+
+  Accepts all `encode/2` options, plus:
+
+  * `:container` - `:array` (default) writes each pushed item as an array
+    element. `:object` expects `{key, value}` tuples and writes one object.
+
+  * `:chunk_size` - Maximum size in bytes of each returned binary.
+    Default: `65536`.
+
+  `:sort_keys` applies within each item; object entries are written in push
+  order. With `maps: :strict`, duplicate keys are rejected across the whole
+  streamed object.
+
+  ## Examples
+
+      iex> encoder = RustyJson.encoder_new(container: :object)
+      iex> RustyJson.encoder_push(encoder, {"a", [1, 2]})
+      {:ok, [~s({"a":[1,2])]}
+      iex> RustyJson.encoder_finish(encoder)
+      {:ok, ["}"]}
+
+  """
+  @spec encoder_new([
+          encode_opt() | {:container, :array | :object} | {:chunk_size, pos_integer()}
+        ]) :: RustyJson.StreamEncoder.t()
+  def encoder_new(opts \\ []) do
+    {container, opts} = Keyword.pop(opts, :container, :array)
+    {chunk_size, opts} = Keyword.pop(opts, :chunk_size, @default_stream_chunk_size)
+    validate_option!(container, [:array, :object], :container)
+
+    unless is_integer(chunk_size) and chunk_size > 0 do
+      raise ArgumentError, "invalid :chunk_size option #{inspect(chunk_size)}"
+    end
+
+    {encode_opts, encoder_opts, use_protocol} = parse_encode_opts(opts)
+
+    %RustyJson.StreamEncoder{
+      resource: nif_encoder_new(encode_nif_opts(encode_opts), container, chunk_size),
+      container: container,
+      encode_opts: encode_opts,
+      encoder_opts: encoder_opts,
+      protocol: use_protocol
+    }
+  end
+
+  @doc """
+  Encodes one item with a streaming encoder.
+
+  Returns `{:ok, chunks}` with the output produced so far (possibly none when
+  compressing), or `{:error, reason}`. On error nothing is written and the
+  encoder remains usable.
+  """
+  @spec encoder_push(RustyJson.StreamEncoder.t(), term()) ::
+          {:ok, [binary()]} | {:error, RustyJson.EncodeError.t() | Exception.t()}
+  def encoder_push(%RustyJson.StreamEncoder{} = encoder, item) do
+    encoder_push_many(encoder, [item])
+  end
+
+  @doc """
+  Encodes a batch of items with a streaming encoder in one NIF call.
+
+  The batch is all-or-nothing: if any item fails to encode, none are written.
+  """
+  @spec encoder_push_many(RustyJson.StreamEncoder.t(), [term()]) ::
+          {:ok, [binary()]} | {:error, RustyJson.EncodeError.t() | Exception.t()}
+  def encoder_push_many(%RustyJson.StreamEncoder{} = encoder, items) when is_list(items) do
+    %{encode_opts: encode_opts, encoder_opts: encoder_opts, protocol: use_protocol} = encoder
+
+    processed =
+      with_encode_fields_ctx(encode_opts, use_protocol, fn ->
+        Enum.map(items, &prepare_stream_item(&1, encoder.container, use_protocol, encoder_opts))
+      end)
+
+    if use_dirty_encode?(encode_opts) do
+      {:ok, nif_encoder_push_dirty(encoder.resource, processed)}
+    else
+      {:ok, nif_encoder_push(encoder.resource, processed)}
+    end
+  rescue
+    e in [RustyJson.EncodeError] -> {:error, e}
+    e in [Protocol.UndefinedError] -> {:error, e}
+    e in [ArgumentError] -> {:error, e}
+    e in [ErlangError] -> {:error, encode_error(e)}
+  end
+
+  @doc """
+  Closes a streaming encoder.
+
+  Returns the remaining output: the closing bracket and, with gzip, the rest
+  of the compressed stream. The encoder cannot be used afterwards.
+  """
+  @spec encoder_finish(RustyJson.StreamEncoder.t()) ::
+          {:ok, [binary()]} | {:error, RustyJson.EncodeError.t()}
+  def encoder_finish(%RustyJson.StreamEncoder{resource: resource}) do
+    {:ok, nif_encoder_finish(resource)}
+  rescue
+    e in [ErlangError] -> {:error, encode_error(e)}
+  end
+
+  @doc """
+  Lazily encodes an enumerable as one JSON array (or object), emitting chunks.
+
+  Raises `RustyJson.EncodeError` if an item cannot be encoded.
+
+  ## Options
+
+  Accepts all `encoder_new/1` options, plus:
+
+  * `:batch_size` - Items encoded per NIF call. Default: `1`.
+
+  ## Examples
+
+      iex> 1..3 |> RustyJson.encode_stream() |> Enum.join()
+      "[1,2,3]"
+
+      iex> [] |> RustyJson.encode_stream() |> Enum.to_list()
+      ["[]"]
+
+      iex> %{"a" => 1} |> RustyJson.encode_stream(container: :object) |> Enum.join()
+      ~s({"a":1})
+
+  """
+  @spec encode_stream(Enumerable.t(), keyword()) :: Enumerable.t()
+  def encode_stream(items, opts \\ []) do
+    {batch_size, opts} = Keyword.pop(opts, :batch_size, 1)
+
+    items
+    |> Stream.chunk_every(batch_size)
+    |> Stream.concat([:eof])
+    |> Stream.transform(
+      fn -> encoder_new(opts) end,
+      fn
+        :eof, encoder -> {encode_chunks!(encoder_finish(encoder)), encoder}
+        batch, encoder -> {encode_chunks!(encoder_push_many(encoder, batch)), encoder}
+      end,
+      fn _encoder -> :ok end
+    )
+  end
+
+  # ============================================================================
+  # NDJSON API
+  # ============================================================================
+
+  @doc """
+  Decodes newline-delimited JSON (NDJSON / JSON Lines) to a list of terms.
+
+  Each non-blank line is decoded as one JSON document. Lines are split on
+  every newline, including one inside a string (raw newlines are not valid
+  JSON, so that line is reported as malformed); blank lines are ignored but
+  still counted for line numbers.
+
+  Returns `{:ok, terms}` on success or `{:error, %RustyJson.DecodeError{}}`
+  when a line is malformed. With `invalid_lines: :skip`, returns
+  `{:ok, {terms, errors}}` where `errors` lists every malformed line as
+  `{line_number, message}`.
+
+  ## Options
+
+  Accepts all `decode/2` options, plus:
+
+  * `:invalid_lines` - `:error` (default) fails on the first malformed line,
+    `:skip` drops malformed lines and reports them alongside the results.
+
+  ## Examples
+
+      iex> RustyJson.decode_lines(~s({"a":1}\\n{"a":2}\\n))
+      {:ok, [%{"a" => 1}, %{"a" => 2}]}
+
+      iex> RustyJson.decode_lines(~s(1\\nx\\n3), invalid_lines: :skip)
+      {:ok, {[1, 3], [{2, "Unexpected character at position 2"}]}}
+
+  """
+  @spec decode_lines(iodata(), [decode_opt() | {:invalid_lines, :error | :skip}]) ::
+          {:ok, [term()] | {[term()], [{pos_integer(), String.t()}]}}
+          | {:error, RustyJson.DecodeError.t()}
+  def decode_lines(input, opts \\ []) do
+    {:ok, decode_lines!(input, opts)}
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  @doc """
+  Decodes newline-delimited JSON, raising on error.
+
+  Same as `decode_lines/2` but returns the result directly and raises
+  `RustyJson.DecodeError` on failure. Error positions are byte offsets into
+  the whole input.
+
+  ## Examples
+
+      iex> RustyJson.decode_lines!("[1]\\r\\n\\r\\n[2]\\r\\n")
+      [[1], [2]]
+
+  """
+  @spec decode_lines!(iodata(), [decode_opt() | {:invalid_lines, :error | :skip}]) ::
+          [term()] | {[term()], [{pos_integer(), String.t()}]}
+  def decode_lines!(input, opts \\ []) do
+    {invalid_lines, opts} = Keyword.pop(opts, :invalid_lines, :error)
+    validate_option!(invalid_lines, [:error, :skip], :invalid_lines)
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "decode_lines/2")
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+    skip_invalid = invalid_lines == :skip
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+    use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
+
+    nif_fn =
+      if use_dirty,
+        do: &nif_decode_lines_dirty(&1, &2, skip_invalid),
+        else: &nif_decode_lines(&1, &2, skip_invalid)
+
+    {terms, errors} = nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
+    terms = Enum.map(terms, &maybe_transform_keys(&1, validated_opts))
+
+    if skip_invalid do
+      {terms, Enum.map(errors, fn {line, error} -> {line, error_message(error)} end)}
+    else
+      terms
+    end
+  end
+
+  @doc """
+  Encodes a list of terms as newline-delimited JSON.
+
+  Each term is encoded compactly and followed by `"\\n"`. All lines are written
+  into a single output binary in one NIF call.
+
+  ## Options
+
+  Accepts all `encode/2` options except `:pretty`, which would break the one
+  document per line framing. With `compress: :gzip` the whole output is one
+  gzip stream.
+
+  ## Examples
+
+      iex> RustyJson.encode_lines([%{a: 1}, [2], "three"])
+      {:ok, ~s({"a":1}\\n[2]\\n"three"\\n)}
+
+  """
+  @spec encode_lines([term()], [encode_opt()]) ::
+          {:ok, String.t()} | {:error, RustyJson.EncodeError.t() | Exception.t()}
+  def encode_lines(terms, opts \\ []) do
+    {:ok, encode_lines!(terms, opts)}
+  rescue
+    e in [RustyJson.EncodeError] -> {:error, e}
+    e in [Protocol.UndefinedError] -> {:error, e}
+    e in [ArgumentError] -> {:error, e}
+    e in [ErlangError] -> {:error, encode_error(e)}
+  end
+
+  @doc """
+  Encodes a list of terms as newline-delimited JSON, raising on error.
+
+  Same as `encode_lines/2` but raises `RustyJson.EncodeError` on failure.
+
+  ## Examples
+
+      iex> RustyJson.encode_lines!([1, 2])
+      "1\\n2\\n"
+
+  """
+  @spec encode_lines!([term()], [encode_opt()]) :: String.t()
+  def encode_lines!(terms, opts \\ []) when is_list(terms) do
+    if Keyword.get(opts, :pretty) not in [nil, false] do
+      raise ArgumentError, "the :pretty option is not supported by encode_lines"
+    end
+
+    {encode_opts, encoder_opts, use_protocol} = parse_encode_opts(opts)
+
+    processed =
+      with_encode_fields_ctx(encode_opts, use_protocol, fn ->
+        Enum.map(terms, &protocol_encode(&1, use_protocol, encoder_opts))
+      end)
+
+    nif_opts = encode_nif_opts(encode_opts)
+
+    if use_dirty_encode?(encode_opts) do
+      nif_encode_lines_dirty(processed, nif_opts)
+    else
+      nif_encode_lines(processed, nif_opts)
+    end
+  rescue
+    e in [ErlangError] ->
+      raise_encode_error(e)
+  end
+
+  # ============================================================================
+  # Concatenated JSON API
+  # ============================================================================
+
+  @doc """
+  Decodes the first JSON document of `input` and returns the bytes after it.
+
+  Where `decode/2` fails with `"Unexpected trailing characters"`, this stops
+  after the first value and the whitespace following it. Returns
+  `{:ok, term, rest_offset, rest}`: `rest_offset` is the byte offset where
+  decoding stopped and `rest` is the input from there on, a sub-binary that
+  shares memory with the input rather than a copy.
+
+  This is the building block for framing JSON off a socket: decode while
+  the buffer yields values and keep `rest` for the next read. An error with
+  `code: :unexpected_end` or `:unterminated_string` means the buffer ends
+  inside a document and more input may complete it.
+  A number that runs to the end of the input is taken as complete, since
+  `12` cannot be told apart from the start of `123`.
+
+  ## Options
+
+  Accepts all `decode/2` options except `:relaxed`.
+
+  ## Examples
+
+      iex> RustyJson.decode_prefix(~s({"a":1} {"b":2}))
+      {:ok, %{"a" => 1}, 8, ~s({"b":2})}
+
+      iex> RustyJson.decode_prefix("[1, 2]")
+      {:ok, [1, 2], 6, ""}
+
+  """
+  @spec decode_prefix(iodata(), [decode_opt()]) ::
+          {:ok, term(), non_neg_integer(), binary()} | {:error, RustyJson.DecodeError.t()}
+  def decode_prefix(input, opts \\ []) do
+    {term, rest_offset, rest} = decode_prefix!(input, opts)
+    {:ok, term, rest_offset, rest}
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  @doc """
+  Decodes the first JSON document of `input`, raising on error.
+
+  Same as `decode_prefix/2` but returns `{term, rest_offset, rest}` and
+  raises `RustyJson.DecodeError` on failure.
+
+  ## Examples
+
+      iex> RustyJson.decode_prefix!("true false")
+      {true, 5, "false"}
+
+  """
+  @spec decode_prefix!(iodata(), [decode_opt()]) :: {term(), non_neg_integer(), binary()}
+  def decode_prefix!(input, opts \\ []) do
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "decode_prefix/2")
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+    use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
+    nif_fn = if use_dirty, do: &nif_decode_prefix_dirty/2, else: &nif_decode_prefix/2
+
+    {term, rest_offset, rest} = nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
+    {maybe_transform_keys(term, validated_opts), rest_offset, rest}
+  end
+
+  @doc """
+  Decodes every JSON document in a concatenated stream.
+
+  Documents may follow each other directly (`{...}{...}`), be separated by
+  whitespace, or form an RFC 7464 JSON text sequence, where each document
+  is preceded by the record separator byte `0x1E`. All documents are decoded
+  in one NIF call and, with `keys: :intern`, share one key cache.
+
+  Returns `{:ok, terms}`, with `[]` for input holding only whitespace and
+  separators, or `{:error, %RustyJson.DecodeError{}}` for the first
+  malformed document. Error positions are byte offsets into the whole input,
+  and `:path` starts at the root of the failing document.
+
+  ## Options
+
+  Accepts all `decode/2` options except `:relaxed`.
+
+  ## Examples
+
+      iex> RustyJson.decode_all(~s({"a":1}{"a":2} 3))
+      {:ok, [%{"a" => 1}, %{"a" => 2}, 3]}
+
+      iex> RustyJson.decode_all("\\x1E[1]\\n\\x1E[2]\\n")
+      {:ok, [[1], [2]]}
+
+  """
+  @spec decode_all(iodata(), [decode_opt()]) ::
+          {:ok, [term()]} | {:error, RustyJson.DecodeError.t()}
+  def decode_all(input, opts \\ []) do
+    {:ok, decode_all!(input, opts)}
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  @doc """
+  Decodes every JSON document in a concatenated stream, raising on error.
+
+  Same as `decode_all/2` but returns the list directly and raises
+  `RustyJson.DecodeError` on failure.
+
+  ## Examples
+
+      iex> RustyJson.decode_all!("1 2 3")
+      [1, 2, 3]
+
+  """
+  @spec decode_all!(iodata(), [decode_opt()]) :: [term()]
+  def decode_all!(input, opts \\ []) do
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "decode_all/2")
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+    use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
+    nif_fn = if use_dirty, do: &nif_decode_all_dirty/2, else: &nif_decode_all/2
+
+    input_binary
+    |> nif_decode_with_error_handling(nif_opts, nif_fn)
+    |> Enum.map(&maybe_transform_keys(&1, validated_opts))
+  end
+
+  # ============================================================================
+  # JSON Pointer API
+  # ============================================================================
+
+  @doc """
+  Extracts the value at a JSON Pointer (RFC 6901) without decoding the
+  whole document.
+
+  The NIF walks from the root to the target, skipping sibling values without
+  building terms, and decodes only the targeted subtree. A key repeated along
+  the way resolves as `:duplicate_keys` says, so by default the rest of each
+  object on the path is read to find its last occurrence. With
+  `duplicate_keys: :first`, bytes after the target are not read, and a syntax
+  error later in the document goes unnoticed.
+
+  `pointer` is an RFC 6901 string such as `"/data/items/0/id"` (`""` is the
+  whole document; `~1` and `~0` escape `/` and `~`), or a list of keys and
+  array indices as accepted by `iterator_new/2`'s `:path`.
+
+  Returns `{:ok, result}`, `{:error, :not_found}` when the pointer does not
+  resolve, or `{:error, %RustyJson.DecodeError{}}` for invalid input.
+
+  ## Options
+
+  Accepts all `decode/2` options, which apply to the decoded value, plus:
+
+  * `:return` - What to return for the target:
+    * `:value` (default) - the decoded value
+    * `:type` - one of `:object`, `:array`, `:string`, `:number`, `:boolean`
+      or `:null`, from the value's first byte alone
+    * `:length` - the number of elements of an array
+    * `:key_count` - the number of members of an object (duplicate keys are
+      counted each time)
+
+    `:length` and `:key_count` skip over the container without building terms
+    and return an error if the target has a different type.
+
+  ## Examples
+
+      iex> RustyJson.get_pointer(~s({"data":{"items":[{"id":7}]}}), "/data/items/0/id")
+      {:ok, 7}
+
+      iex> RustyJson.get_pointer(~s({"a/b":{"c":[1,2,3]}}), "/a~1b/c", return: :length)
+      {:ok, 3}
+
+      iex> RustyJson.get_pointer(~s({"a":{"x":1,"y":2}}), ["a"], return: :type)
+      {:ok, :object}
+
+      iex> RustyJson.get_pointer(~s({"a":1}), "/b")
+      {:error, :not_found}
+
+  """
+  @spec get_pointer(iodata(), String.t() | [String.t() | non_neg_integer()], [
+          decode_opt() | {:return, pointer_return()}
+        ]) :: {:ok, term()} | {:error, :not_found | RustyJson.DecodeError.t()}
+  def get_pointer(input, pointer, opts \\ []) do
+    {_keys, nif_opts, validated_opts} =
+      opts |> Keyword.delete(:return) |> parse_decode_opts()
+    reject_relaxed!(nif_opts, "get_pointer/3")
+    reject_schema!(nif_opts, "get_pointer/3")
+
+    return = Keyword.get(opts, :return, :value)
+    validate_option!(return, [:value, :type, :length, :key_count], :return)
+    tokens = pointer_tokens!(pointer)
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+    use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
+
+    nif_fn =
+      if use_dirty,
+        do: &nif_get_pointer_dirty(&1, tokens, &2, return),
+        else: &nif_get_pointer(&1, tokens, &2, return)
+
+    case nif_decode_with_error_handling(input_binary, nif_opts, nif_fn) do
+      {:ok, value} when return == :value ->
+        {:ok, maybe_transform_keys(value, validated_opts)}
+
+      {:ok, result} ->
+        {:ok, result}
+
+      :error ->
+        {:error, :not_found}
+    end
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  @doc """
+  Extracts the value at a JSON Pointer, raising on error.
+
+  Same as `get_pointer/3` but returns the result directly and raises
+  `RustyJson.DecodeError` on invalid input or when the pointer does not
+  resolve.
+
+  ## Examples
+
+      iex> RustyJson.get_pointer!(~s({"users":[{"name":"a"},{"name":"b"}]}), "/users/1")
+      %{"name" => "b"}
+
+  """
+  @spec get_pointer!(iodata(), String.t() | [String.t() | non_neg_integer()], [
+          decode_opt() | {:return, pointer_return()}
+        ]) :: term()
+  def get_pointer!(input, pointer, opts \\ []) do
+    case get_pointer(input, pointer, opts) do
+      {:ok, result} ->
+        result
+
+      {:error, :not_found} ->
+        raise RustyJson.DecodeError, message: "JSON Pointer #{inspect(pointer)} not found"
+
+      {:error, error} ->
+        raise error
+    end
+  end
+
+  # ============================================================================
+  # JSONPath API
+  # ============================================================================
+
+  @doc """
+  Selects values from a JSON document with a JSONPath expression.
+
+  The query runs in the NIF directly on the input bytes. Subtrees that cannot
+  match are skipped without building terms, and only the matches are decoded,
+  so selecting a few records from a large document costs far less than
+  `decode/2`. The whole document is still checked for syntax errors.
+
+  Returns `{:ok, matches}` in document order. Each matching node appears
+  once. Returns `{:error, %RustyJson.DecodeError{}}` for invalid input or an
+  invalid path.
+
+  ## Supported syntax
+
+  A subset of [RFC 9535](https://www.rfc-editor.org/rfc/rfc9535):
+
+  | Syntax | Selects |
+  |--------|---------|
+  | `$` | the root |
+  | `.name`, `['name']` | an object member |
+  | `.*`, `[*]` | every member or element |
+  | `..name`, `..*`, `..[sel]` | recursive descent |
+  | `[2]`, `[-1]` | an array element (negative counts from the end) |
+  | `[start:end:step]` | an array slice (`step` must be positive) |
+  | `[?(@.status == "active")]` | members or elements matching a filter |
+
+  Filters compare a relative `@` path against a string, number, `true`,
+  `false` or `null` literal with `==`, `!=`, `<`, `<=`, `>` or `>=`. A bare
+  `@` path tests for existence. Tests combine with `!`, `&&`, `||` and
+  parentheses, nested at most 64 deep. Values of different types are never
+  equal, and only strings and numbers are ordered.
+
+  ## Options
+
+  Accepts all `decode/2` options, which apply to the decoded matches, plus:
+
+  * `:return` - `:values` (default) returns the decoded matches, `:paths`
+    returns their normalized paths (e.g. `"$['items'][0]"`) without decoding.
+
+  ## Examples
+
+      iex> json = ~s({"users":[{"name":"a","status":"active"},{"name":"b","status":"gone"}]})
+      iex> RustyJson.query(json, "$.users[?(@.status == 'active')].name")
+      {:ok, ["a"]}
+      iex> RustyJson.query(json, "$..name", return: :paths)
+      {:ok, ["$['users'][0]['name']", "$['users'][1]['name']"]}
+
+  """
+  @spec query(iodata(), String.t(), [decode_opt() | {:return, :values | :paths}]) ::
+          {:ok, [term()]} | {:error, RustyJson.DecodeError.t()}
+  def query(input, path, opts \\ []) do
+    {:ok, query!(input, path, opts)}
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+    e in [ErlangError] -> {:error, %RustyJson.DecodeError{message: error_message(e)}}
+  end
+
+  @doc """
+  Selects values from a JSON document with a JSONPath expression, raising on
+  error.
+
+  Same as `query/3` but returns the matches directly. Raises
+  `RustyJson.DecodeError` on invalid input and `ArgumentError` on an invalid
+  path.
+
+  ## Examples
+
+      iex> RustyJson.query!(~s({"a":[1,2,3,4,5]}), "$.a[1:4:2]")
+      [2, 4]
+
+  """
+  @spec query!(iodata(), String.t(), [decode_opt() | {:return, :values | :paths}]) :: [term()]
+  def query!(input, path, opts \\ []) when is_binary(path) do
+    {return, opts} = Keyword.pop(opts, :return, :values)
+    validate_option!(return, [:values, :paths], :return)
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "query/3")
+    reject_schema!(nif_opts, "query/3")
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+    use_dirty = dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold
+
+    nif_fn =
+      if use_dirty,
+        do: &nif_query_dirty(&1, path, &2, return),
+        else: &nif_query(&1, path, &2, return)
+
+    matches =
+      try do
+        nif_decode_with_error_handling(input_binary, nif_opts, nif_fn)
+      rescue
+        e in [ErlangError] -> raise_jsonpath_error(e)
+      end
+
+    if return == :values do
+      Enum.map(matches, &maybe_transform_keys(&1, validated_opts))
+    else
+      matches
+    end
+  end
+
+  # ============================================================================
+  # Validation API
+  # ============================================================================
+
+  @doc """
+  Checks that `input` is valid JSON without decoding it.
+
+  Runs the same checks as `decode/2` — syntax, the nesting depth limit,
+  string escapes and UTF-8, `:decoding_integer_digit_limit`, the decode
+  quotas, `:max_bytes` and `duplicate_keys: :error` — but builds no terms,
+  so memory use does not depend on the size of the document. An input that validates decodes with
+  the same options, and an invalid one fails with the same message and
+  position as `decode/2`.
+
+  Returns `{:ok, summary}` (see `t:validation_summary/0`) or
+  `{:error, message, position}`, where `position` is the byte offset of the
+  error.
+
+  ## Options
+
+  Accepts all `decode/2` options. Only those listed above affect the result;
+  `:dirty_threshold` selects the scheduler as for `decode/2`.
+
+  ## Examples
+
+      iex> RustyJson.validate(~s({"a":[1,2,{"b":null}]}))
+      {:ok, %{type: :object, bytes: 22, max_depth: 3, value_count: 6}}
+
+      iex> RustyJson.validate(~s([1,2,]))
+      {:error, "Unexpected character", 5}
+
+  """
+  @spec validate(iodata(), [decode_opt()]) ::
+          {:ok, validation_summary()} | {:error, String.t(), non_neg_integer()}
+  def validate(input, opts \\ []) do
+    {_keys, nif_opts, validated_opts} = parse_decode_opts(opts)
+    reject_relaxed!(nif_opts, "validate/2")
+    reject_schema!(nif_opts, "validate/2")
+    check_max_bytes!(input, nif_opts)
+    input_binary = IO.iodata_to_binary(input)
+
+    dirty_threshold = validated_opts[:dirty_threshold]
+
+    if dirty_threshold > 0 and byte_size(input_binary) >= dirty_threshold do
+      nif_validate_dirty(input_binary, nif_opts)
+    else
+      nif_validate(input_binary, nif_opts)
+    end
+  rescue
+    e in [RustyJson.DecodeError] -> {:error, e.message, e.position}
+  end
+
+  # ============================================================================
+  # Phoenix Interface
+  # ============================================================================
+
+  @doc """
+  Encodes a term to iodata (for Phoenix compatibility).
+
+  This function exists to implement the Phoenix JSON library interface.
+  Returns `{:ok, binary}` on success or `{:error, reason}` on failure.
+
+  ## A Note on iodata
+
+  RustyJson returns a **single binary**, not an iolist. This is intentional
+  and provides excellent performance for payloads up to ~100MB.
+
+  The memory efficiency comes from the encoding process (no intermediate
+  allocations), not from chunked output. A 10MB payload uses ~15MB peak memory.
+
+  For truly massive payloads (100MB+), consider:
+  - Streaming the data structure itself (encode in chunks)
+  - Using compression (`compress: :gzip` reduces output 5-10x)
+  - Pagination or chunked API design
+
+  ## Examples
+
+      iex> RustyJson.encode_to_iodata(%{status: "ok"})
+      {:ok, ~s({"status":"ok"})}
+
+  """
+  @spec encode_to_iodata(term(), [encode_opt()]) ::
+          {:ok, iodata()} | {:error, RustyJson.EncodeError.t() | Exception.t()}
+  def encode_to_iodata(input, opts \\ []), do: encode(input, opts)
+
+  @doc """
+  Encodes a term to iodata, raising on error (for Phoenix compatibility).
+
+  This function exists to implement the Phoenix JSON library interface.
+  Raises `RustyJson.EncodeError` on failure.
+
+  See `encode_to_iodata/2` for notes on iodata behavior.
+
+  ## Examples
+
+      iex> RustyJson.encode_to_iodata!(%{status: "ok"})
+      ~s({"status":"ok"})
+
+  """
+  @spec encode_to_iodata!(term(), [encode_opt()]) :: iodata()
+  def encode_to_iodata!(input, opts \\ []), do: encode!(input, opts)
+
+  # ============================================================================
+  # Private Functions
+  # ============================================================================
+
+  @doc false
+  defp normalize_pretty_opts(indent) when is_list(indent) do
+    # Pretty print with custom separator options
+    raw_indent = Keyword.get(indent, :indent, 2)
+
+    pretty_opts = %{}
+
+    pretty_opts =
+      case Keyword.fetch(indent, :line_separator) do
+        {:ok, sep} -> Map.put(pretty_opts, :line_separator, IO.iodata_to_binary(sep))
+        :error -> pretty_opts
+      end
+
+    pretty_opts =
+      case Keyword.fetch(indent, :after_colon) do
+        {:ok, sep} -> Map.put(pretty_opts, :after_colon, IO.iodata_to_binary(sep))
+        :error -> pretty_opts
+      end
+
+    # Handle iodata indent (e.g. "\t") — pass as binary in pretty_opts
+    {pretty_opts, indent_val} = normalize_indent_value(raw_indent, pretty_opts)
+
+    {if(map_size(pretty_opts) > 0, do: pretty_opts, else: nil), indent_val}
+  end
+
+  defp normalize_pretty_opts(indent) do
+    {pretty_opts, indent_val} = normalize_indent_value(indent, %{})
+    {if(map_size(pretty_opts) > 0, do: pretty_opts, else: nil), indent_val}
+  end
+
+  # Convert indent value to {pretty_opts_map, integer_indent_for_nif}
+  # For string/iodata indent, we pass the binary through pretty_opts and use
+  # indent=1 as a sentinel to enable pretty mode in the NIF.
+  @doc false
+  defp normalize_indent_value(indent, pretty_opts) do
+    cond do
+      indent == true ->
+        {pretty_opts, 2}
+
+      is_integer(indent) and indent > 0 ->
+        {pretty_opts, indent}
+
+      is_binary(indent) ->
+        {Map.put(pretty_opts, :indent, indent), 1}
+
+      is_list(indent) ->
+        # iodata list
+        {Map.put(pretty_opts, :indent, IO.iodata_to_binary(indent)), 1}
+
+      true ->
+        {pretty_opts, nil}
+    end
+  end
+
+  @doc false
+  defp validate_escape!(:json), do: :json
+  defp validate_escape!(:html_safe), do: :html_safe
+  defp validate_escape!(:unicode_safe), do: :unicode_safe
+  defp validate_escape!(:javascript_safe), do: :javascript_safe
   defp validate_escape!(nil), do: :json
 
   defp validate_escape!(other) do
@@ -1001,15 +2363,31 @@ defmodule RustyJson do
   # Extracted to avoid `raise` inside `rescue` (Credo W: reraise).
   @spec raise_encode_error(Exception.t()) :: no_return()
   defp raise_encode_error(e) do
-    raise %RustyJson.EncodeError{message: error_message(e)}
+    raise encode_error(e)
   end
 
+  # Builds an EncodeError from a NIF ErlangError. The native encoder raises
+  # `{reason, message, path, value}`; anything else only has a message.
+  defp encode_error(%ErlangError{original: {reason, message, path, value}})
+       when is_atom(reason) and is_binary(message) and is_list(path) do
+    RustyJson.EncodeError.from_nif(reason, message, path, value)
+  end
+
+  defp encode_error(e), do: %RustyJson.EncodeError{message: error_message(e)}
+
   # Parse and validate all decode options, returning {keys, nif_opts, validated_opts}.
   defp parse_decode_opts(opts) do
     {keys, opts} = Keyword.pop(opts, :keys, :strings)
     {strings_mode, opts} = Keyword.pop(opts, :strings, :reference)
+
+    {subbinary_threshold, opts} =
+      Keyword.pop(opts, :subbinary_threshold, @default_subbinary_threshold)
+
     {objects_mode, opts} = Keyword.pop(opts, :objects, :maps)
     {floats_mode, opts} = Keyword.pop(opts, :floats, :native)
+    {numbers_mode, opts} = Keyword.pop(opts, :numbers, :native)
+    {datetimes, opts} = Keyword.pop(opts, :datetimes, false)
+    {schema, opts} = Keyword.pop(opts, :schema)
 
     {digit_limit, opts} =
       Keyword.pop(opts, :decoding_integer_digit_limit, @default_integer_digit_limit)
@@ -1017,13 +2395,41 @@ defmodule RustyJson do
     {max_bytes, opts} = Keyword.pop(opts, :max_bytes, 0)
     {duplicate_keys, opts} = Keyword.pop(opts, :duplicate_keys, :last)
     {validate_strings, opts} = Keyword.pop(opts, :validate_strings, true)
+    {invalid_unicode, opts} = Keyword.pop(opts, :invalid_unicode, :error)
+    {max_depth, opts} = Keyword.pop(opts, :max_depth, @default_max_depth)
+    {max_string_bytes, opts} = Keyword.pop(opts, :max_string_bytes, 0)
+    {max_array_elements, opts} = Keyword.pop(opts, :max_array_elements, 0)
+    {max_object_keys, opts} = Keyword.pop(opts, :max_object_keys, 0)
+    {max_total_values, opts} = Keyword.pop(opts, :max_total_values, 0)
+    {max_fraction_digits, opts} = Keyword.pop(opts, :max_fraction_digits, 0)
+    {max_exponent_digits, opts} = Keyword.pop(opts, :max_exponent_digits, 0)
+    {max_new_atoms, opts} = Keyword.pop(opts, :max_new_atoms, 0)
+    {key_case, opts} = Keyword.pop(opts, :key_case)
+    {integer_keys, opts} = Keyword.pop(opts, :integer_keys, false)
+    {only, opts} = Keyword.pop(opts, :only)
+    {except, opts} = Keyword.pop(opts, :except)
+    {relaxed, opts} = Keyword.pop(opts, :relaxed, false)
+    {non_finite, opts} = Keyword.pop(opts, :non_finite, :atoms)
     {dirty_threshold, _opts} = Keyword.pop(opts, :dirty_threshold, @default_dirty_threshold_bytes)
 
     validate_keys!(keys)
-    validate_option!(strings_mode, [:copy, :reference], :strings)
+    validate_option!(strings_mode, [:copy, :reference, :auto], :strings)
+    validate_quota!(subbinary_threshold, :subbinary_threshold)
     validate_option!(objects_mode, [:maps, :ordered_objects], :objects)
     validate_option!(floats_mode, [:native, :decimals], :floats)
-    validate_option!(duplicate_keys, [:last, :error], :duplicate_keys)
+    validate_option!(numbers_mode, [:native, :raw, :strings, :decimals], :numbers)
+    validate_option!(duplicate_keys, [:last, :first, :collect, :error], :duplicate_keys)
+    validate_option!(invalid_unicode, [:error, :replace, :keep], :invalid_unicode)
+    validate_max_depth!(max_depth, @max_decode_depth)
+    validate_quota!(max_string_bytes, :max_string_bytes)
+    validate_quota!(max_array_elements, :max_array_elements)
+    validate_quota!(max_object_keys, :max_object_keys)
+    validate_quota!(max_total_values, :max_total_values)
+    validate_quota!(max_fraction_digits, :max_fraction_digits)
+    validate_quota!(max_exponent_digits, :max_exponent_digits)
+    validate_quota!(max_new_atoms, :max_new_atoms)
+    validate_option!(key_case, [nil, :snake_case, :camel_case, :kebab_case], :key_case)
+    validate_option!(integer_keys, [true, false], :integer_keys)
 
     {intern_keys, keys_fn} =
       case keys do
@@ -1034,17 +2440,181 @@ defmodule RustyJson do
 
     nif_opts = %{
       intern_keys: intern_keys,
+      strings: strings_mode,
+      subbinary_threshold: subbinary_threshold,
       floats_decimals: floats_mode == :decimals,
+      numbers: numbers_mode,
       ordered_objects: objects_mode == :ordered_objects,
       integer_digit_limit: digit_limit,
       max_bytes: max_bytes,
-      reject_duplicate_keys: duplicate_keys == :error,
-      validate_strings: validate_strings == true
+      duplicate_keys: duplicate_keys,
+      validate_strings: validate_strings == true,
+      invalid_unicode: invalid_unicode,
+      max_depth: max_depth,
+      max_string_bytes: max_string_bytes,
+      max_array_elements: max_array_elements,
+      max_object_keys: max_object_keys,
+      max_total_values: max_total_values,
+      max_fraction_digits: max_fraction_digits,
+      max_exponent_digits: max_exponent_digits,
+      atom_keys: atom_keys_mode(keys),
+      max_new_atoms: max_new_atoms,
+      key_case: key_case_mode(key_case),
+      integer_keys: integer_keys,
+      projection: projection!(only, except),
+      relaxed: relaxed_syntax!(relaxed, non_finite),
+      datetimes: datetimes!(datetimes),
+      schema: schema!(schema, only || except, relaxed, duplicate_keys)
     }
 
     {keys, nif_opts, %{keys_fn: keys_fn, dirty_threshold: dirty_threshold}}
   end
 
+  # Atom keys are built by the parser, so only function keys need a post-pass.
+  defp atom_keys_mode(:atoms), do: :create
+  defp atom_keys_mode(:atoms!), do: :existing
+  defp atom_keys_mode(_keys), do: nil
+
+  defp key_case_mode(:snake_case), do: :snake
+  defp key_case_mode(:camel_case), do: :camel
+  defp key_case_mode(:kebab_case), do: :kebab
+  defp key_case_mode(nil), do: nil
+
+  # Build the NIF `projection` option from `:only` / `:except` key paths.
+  defp projection!(nil, nil), do: nil
+  defp projection!(only, nil), do: {:only, projection_paths!(only, :only)}
+  defp projection!(nil, except), do: {:except, projection_paths!(except, :except)}
+
+  defp projection!(_only, _except) do
+    raise ArgumentError, "the :only and :except options cannot be combined"
+  end
+
+  defp projection_paths!(paths, option_name) when is_list(paths) do
+    Enum.map(paths, fn
+      [_ | _] = path ->
+        Enum.map(path, fn
+          key when is_binary(key) -> key
+          key when is_atom(key) and not is_nil(key) -> Atom.to_string(key)
+          other -> raise ArgumentError, "invalid :#{option_name} path step #{inspect(other)}"
+        end)
+
+      other ->
+        raise ArgumentError,
+              "invalid :#{option_name} path #{inspect(other)}, expected a non-empty list of keys"
+    end)
+  end
+
+  defp projection_paths!(paths, option_name) do
+    raise ArgumentError,
+          "invalid :#{option_name} option #{inspect(paths)}, expected a list of key paths"
+  end
+
+  # Build the NIF `schema` option. The struct builder replaces the map
+  # builders that projections, relaxed syntax and collected duplicates hook into.
+  defp schema!(nil, _projection, _relaxed, _duplicate_keys), do: nil
+
+  defp schema!(_schema, _projection, _relaxed, :collect) do
+    raise ArgumentError, "the :schema option cannot be combined with duplicate_keys: :collect"
+  end
+
+  defp schema!(schema, nil, false, _duplicate_keys), do: RustyJson.Schema.nif_spec!(schema)
+
+  defp schema!(_schema, _projection, _relaxed, _duplicate_keys) do
+    raise ArgumentError, "the :schema option cannot be combined with :only, :except or :relaxed"
+  end
+
+  # Build the NIF `datetimes` option: a boolean, or the keys as strings.
+  defp datetimes!(flag) when is_boolean(flag), do: flag
+
+  defp datetimes!(keys) when is_list(keys) do
+    Enum.map(keys, fn
+      key when is_binary(key) -> key
+      key when is_atom(key) and not is_nil(key) -> Atom.to_string(key)
+      other -> raise ArgumentError, "invalid :datetimes key #{inspect(other)}"
+    end)
+  end
+
+  defp datetimes!(other) do
+    raise ArgumentError,
+          "invalid :datetimes option #{inspect(other)}, expected a boolean or a list of keys"
+  end
+
+  @relaxed_extensions [
+    :comments,
+    :trailing_commas,
+    :single_quotes,
+    :unquoted_keys,
+    :hex_numbers,
+    :leading_plus,
+    :non_finite
+  ]
+
+  # Build the NIF `relaxed` option: nil for strict decoding, or a flag map.
+  defp relaxed_syntax!(false, _non_finite), do: nil
+  defp relaxed_syntax!(true, non_finite), do: relaxed_syntax!(@relaxed_extensions, non_finite)
+
+  defp relaxed_syntax!(extensions, non_finite) when is_list(extensions) do
+    Enum.each(extensions, &validate_option!(&1, @relaxed_extensions, :relaxed))
+    flags = Map.new(@relaxed_extensions, &{&1, &1 in extensions})
+    %{flags | non_finite: if(flags.non_finite, do: non_finite_atoms!(non_finite))}
+  end
+
+  defp relaxed_syntax!(other, _non_finite) do
+    raise ArgumentError,
+          "invalid :relaxed option #{inspect(other)}, expected a boolean or a list of extensions"
+  end
+
+  defp non_finite_atoms!(:atoms), do: non_finite_atoms!([])
+  defp non_finite_atoms!(nil), do: {nil, nil, nil}
+
+  defp non_finite_atoms!(overrides) when is_list(overrides) do
+    Enum.each(overrides, fn
+      {key, value} when key in [:nan, :infinity, :neg_infinity] and is_atom(value) -> :ok
+      _ -> invalid_non_finite!(overrides)
+    end)
+
+    defaults = [nan: :nan, infinity: :infinity, neg_infinity: :neg_infinity]
+    atoms = Keyword.merge(defaults, overrides)
+    {atoms[:nan], atoms[:infinity], atoms[:neg_infinity]}
+  end
+
+  defp non_finite_atoms!(other), do: invalid_non_finite!(other)
+
+  defp invalid_non_finite!(value) do
+    raise ArgumentError,
+          "invalid :non_finite option #{inspect(value)}, expected :atoms, nil, " <>
+            "or a keyword list of :nan, :infinity and :neg_infinity atoms"
+  end
+
+  # Relaxed syntax is only understood by the full decoder behind decode/2.
+  defp reject_relaxed!(%{relaxed: nil}, _function), do: :ok
+
+  defp reject_relaxed!(_nif_opts, function) do
+    raise ArgumentError, "the :relaxed option is not supported by #{function}"
+  end
+
+  # Schemas apply to whole documents, which these functions do not decode.
+  defp reject_schema!(%{schema: nil}, _function), do: :ok
+
+  defp reject_schema!(_nif_opts, function) do
+    raise ArgumentError, "the :schema option is not supported by #{function}"
+  end
+
+  defp check_max_bytes!(input, nif_opts) do
+    max_bytes = Map.get(nif_opts, :max_bytes, 0)
+
+    if max_bytes > 0 do
+      input_size = IO.iodata_length(input)
+
+      if input_size > max_bytes do
+        raise %RustyJson.DecodeError{
+          message: "input size #{input_size} exceeds max_bytes limit of #{max_bytes}",
+          position: 0
+        }
+      end
+    end
+  end
+
   # Call the NIF decoder, converting ErlangError to DecodeError.
   # Extracted to avoid `raise` inside `rescue` (Credo W: reraise).
   defp nif_decode_with_error_handling(input_binary, nif_opts, nif_fn) do
@@ -1055,32 +2625,123 @@ defmodule RustyJson do
   end
 
   @spec raise_decode_error(Exception.t(), binary()) :: no_return()
-  defp raise_decode_error(
-         %ErlangError{original: {msg, pos}},
-         input_binary
-       )
-       when is_binary(msg) and is_integer(pos) do
-    raise %RustyJson.DecodeError{
-      message: "#{msg} at position #{pos}",
-      data: input_binary,
-      position: pos,
-      token: extract_token(input_binary, pos)
-    }
+  defp raise_decode_error(%ErlangError{original: {code, _, _, _} = error}, input_binary)
+       when is_atom(code) do
+    raise RustyJson.DecodeError.from_nif(error, input_binary)
+  end
+
+  defp raise_decode_error(%ErlangError{original: {error, path}}, input_binary)
+       when is_tuple(error) and is_list(path) do
+    raise RustyJson.DecodeError.from_nif(error, input_binary, path)
+  end
+
+  defp raise_decode_error(%ErlangError{original: {error, path, violations}}, input_binary)
+       when is_tuple(error) and is_list(path) and is_list(violations) do
+    raise RustyJson.DecodeError.from_nif(error, input_binary, path, violations)
   end
 
   defp raise_decode_error(e, _input_binary) do
     raise e
   end
 
-  # Apply key transformations to decoded result.
-  defp maybe_transform_keys(result, keys, %{keys_fn: keys_fn}) do
-    cond do
-      keys_fn != nil -> transform_keys(result, keys_fn)
-      keys in [:atoms, :atoms!] -> transform_keys(result, keys)
-      true -> result
+  # Run a stream decoder NIF and post-process the values it returns.
+  defp stream_decoder_call(
+         %RustyJson.StreamDecoder{resource: resource, keys: keys} = decoder,
+         fun
+       ) do
+    values = fun.(resource)
+    {:ok, Enum.map(values, &maybe_transform_keys(&1, decoder))}
+  rescue
+    e in [ErlangError] -> {:error, stream_decode_error(e)}
+    e in [ArgumentError] -> {:error, %RustyJson.DecodeError{message: Exception.message(e)}}
+  end
+
+  # Stream errors carry an absolute position but no `:data` — earlier chunks
+  # have already been released.
+  defp stream_decode_error(%ErlangError{original: {code, _, _, _} = error})
+       when is_atom(code) do
+    RustyJson.DecodeError.from_nif(error, nil)
+  end
+
+  defp stream_decode_error(e), do: %RustyJson.DecodeError{message: error_message(e)}
+
+  defp iterator_decode_error(%ErlangError{original: {code, _, _, _} = error}, data)
+       when is_atom(code) do
+    RustyJson.DecodeError.from_nif(error, data)
+  end
+
+  defp iterator_decode_error(e, _data), do: %RustyJson.DecodeError{message: error_message(e)}
+
+  @spec raise_jsonpath_error(Exception.t()) :: no_return()
+  defp raise_jsonpath_error(%ErlangError{original: {:invalid_jsonpath, msg, pos}}) do
+    raise ArgumentError, "invalid JSONPath: #{msg} at position #{pos}"
+  end
+
+  defp raise_jsonpath_error(e), do: raise(e)
+
+  # Convert an iterator path or pointer segment list to NIF tokens.
+  defp path_tokens!(path) do
+    Enum.map(path, fn
+      index when is_integer(index) and index >= 0 -> Integer.to_string(index)
+      key when is_binary(key) -> key
+      key when is_atom(key) -> Atom.to_string(key)
+      other -> raise ArgumentError, "invalid path segment: #{inspect(other)}"
+    end)
+  end
+
+  # Split an RFC 6901 pointer into unescaped reference tokens.
+  defp pointer_tokens!(""), do: []
+
+  defp pointer_tokens!("/" <> rest) do
+    rest
+    |> :binary.split("/", [:global])
+    |> Enum.map(&unescape_pointer_token!/1)
+  end
+
+  defp pointer_tokens!(path) when is_list(path), do: path_tokens!(path)
+
+  defp pointer_tokens!(other) do
+    raise ArgumentError,
+          "invalid JSON Pointer #{inspect(other)}, expected \"\" or a string starting with \"/\""
+  end
+
+  # `~1` must be replaced before `~0` so that `~01` decodes to `~1`, not `/`.
+  defp unescape_pointer_token!(token) do
+    if token =~ ~r/~(?![01])/ do
+      raise ArgumentError, "invalid escape in JSON Pointer token #{inspect(token)}"
     end
+
+    token |> String.replace("~1", "/") |> String.replace("~0", "~")
+  end
+
+  # Apply the Encoder protocol to a streamed item (only the value of an
+  # object entry; keys are encoded by the NIF).
+  defp prepare_stream_item({key, value}, :object, use_protocol, encoder_opts) do
+    {key, protocol_encode(value, use_protocol, encoder_opts)}
+  end
+
+  defp prepare_stream_item(item, _container, use_protocol, encoder_opts) do
+    protocol_encode(item, use_protocol, encoder_opts)
+  end
+
+  defp encode_chunks!({:ok, chunks}), do: chunks
+  defp encode_chunks!({:error, error}), do: raise(error)
+
+  # Split oversized chunks so each NIF call does bounded work.
+  defp feed_in_slices(decoder, chunk, size) when byte_size(chunk) > size do
+    <<head::binary-size(size), rest::binary>> = chunk
+    stream_values!(decoder_feed(decoder, head)) ++ feed_in_slices(decoder, rest, size)
   end
 
+  defp feed_in_slices(decoder, chunk, _size), do: stream_values!(decoder_feed(decoder, chunk))
+
+  defp stream_values!({:ok, values}), do: values
+  defp stream_values!({:error, error}), do: raise(error)
+
+  # Apply a `keys:` function to the decoded result.
+  defp maybe_transform_keys(result, %{keys_fn: nil}), do: result
+  defp maybe_transform_keys(result, %{keys_fn: keys_fn}), do: transform_keys(result, keys_fn)
+
   # Resolve function-based Fragment encode fields to iodata before sending to NIF.
   # This handles both protocol: true (where the Fragment encoder may have already
   # resolved it) and protocol: false (where function fragments would crash the NIF).
@@ -1111,57 +2772,59 @@ defmodule RustyJson do
     end
   end
 
+  defp validate_max_depth!(max_depth, limit) do
+    unless is_integer(max_depth) and max_depth in 1..limit do
+      raise ArgumentError,
+            "invalid :max_depth option #{inspect(max_depth)}, " <>
+              "expected an integer from 1 to #{limit}"
+    end
+  end
+
+  defp validate_quota!(value, option_name) do
+    unless is_integer(value) and value >= 0 do
+      raise ArgumentError,
+            "invalid :#{option_name} option #{inspect(value)}, expected a non-negative integer"
+    end
+  end
+
   @doc false
   # Handle OrderedObject: transform keys within the values list, preserving order
-  defp transform_keys(%RustyJson.OrderedObject{values: values} = obj, keys_mode) do
+  defp transform_keys(%RustyJson.OrderedObject{values: values} = obj, fun) do
     transformed =
       Enum.map(values, fn {k, v} ->
-        new_key =
-          cond do
-            is_function(keys_mode, 1) and is_binary(k) -> keys_mode.(k)
-            is_function(keys_mode, 1) -> k
-            true -> string_to_atom(k, keys_mode)
-          end
-
-        {new_key, transform_keys(v, keys_mode)}
+        {if(is_binary(k), do: fun.(k), else: k), transform_keys(v, fun)}
       end)
 
     %{obj | values: transformed}
   end
 
-  defp transform_keys(value, fun) when is_map(value) and is_function(fun, 1) do
-    Map.new(value, fn {k, v} ->
-      {if(is_binary(k), do: fun.(k), else: k), transform_keys(v, fun)}
-    end)
-  end
+  # Decimals and number fragments have no JSON keys
+  defp transform_keys(%_{} = struct, _fun), do: struct
 
-  defp transform_keys(value, keys_mode) when is_map(value) do
+  defp transform_keys(value, fun) when is_map(value) do
     Map.new(value, fn {k, v} ->
-      {string_to_atom(k, keys_mode), transform_keys(v, keys_mode)}
+      {if(is_binary(k), do: fun.(k), else: k), transform_keys(v, fun)}
     end)
   end
 
-  defp transform_keys(value, keys_mode) when is_list(value) do
-    Enum.map(value, &transform_keys(&1, keys_mode))
+  defp transform_keys(value, fun) when is_list(value) do
+    Enum.map(value, &transform_keys(&1, fun))
   end
 
-  defp transform_keys(value, _keys_mode), do: value
+  defp transform_keys(value, _fun), do: value
 
   @doc false
-  defp string_to_atom(key, :atoms) when is_binary(key) do
-    String.to_atom(key)
+  defp error_message({code, _expected, msg, pos}) when is_atom(code) and is_binary(msg) do
+    "#{msg} at position #{pos}"
   end
 
-  defp string_to_atom(key, :atoms!) when is_binary(key) do
-    String.to_existing_atom(key)
+  defp error_message({error, path}) when is_tuple(error) and is_list(path) do
+    error_message(error)
   end
 
-  defp string_to_atom(key, _), do: key
-
-  @doc false
-  defp error_message(%ErlangError{original: {msg, pos}})
-       when is_binary(msg) and is_integer(pos) do
-    "#{msg} at position #{pos}"
+  defp error_message({error, path, violations})
+       when is_tuple(error) and is_list(path) and is_list(violations) do
+    RustyJson.DecodeError.from_nif(error, nil, path, violations).message
   end
 
   defp error_message(%ErlangError{original: err}), do: error_message(err)
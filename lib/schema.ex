@@ -0,0 +1,363 @@
+defmodule RustyJson.Schema do
+  @moduledoc """
+  Struct schemas for the `:schema` decode option.
+
+  A schema names a struct module and maps JSON keys to its fields. The
+  decoder builds the structs itself, in the same pass that parses the JSON.
+  Members the schema does not name are skipped without being decoded, and
+  fields that no member fills keep their defaults.
+
+      schema =
+        {User,
+         %{
+           "id" => :id,
+           "name" => :name,
+           "address" => {:address, {Address, %{"city" => :city}}},
+           "posts" => {:posts, [{Post, %{"title" => :title}}]}
+         }}
+
+      RustyJson.decode!(json, schema: schema)
+      #=> %User{id: 1, name: "Ada", address: %Address{city: "Paris"}, posts: [%Post{...}]}
+
+  A struct spec is `{module, fields}` or `{module, fields, defaults}`. `fields`
+  maps each JSON key, given as a string or an atom, to one of:
+
+    * `field` - the struct field the value is decoded into as usual
+    * `{field, struct_spec}` - an object decoded to another struct
+    * `{field, [struct_spec]}` - an array whose objects are decoded to a struct
+    * `{field, type}` or `{field, spec, constraints}` - a typed field, see below
+
+  `defaults`, a map or keyword list, overrides the module's own defaults for
+  fields the JSON leaves out. A bare `module` stands for `{module, fields}`
+  with each struct field read from the JSON key of the same name. A schema of
+  `[struct_spec]` decodes a root array of structs.
+
+  ## Discriminators
+
+  When a key such as `"type"` decides the shape of an object, a union spec
+  maps its values to struct specs:
+
+      events =
+        {:one_of, "type",
+         %{
+           "order.created" => OrderCreated,
+           "order.shipped" => {OrderShipped, %{"id" => :order_id}}
+         }}
+
+      RustyJson.decode!(json, schema: [events])
+      #=> [%OrderCreated{...}, %OrderShipped{...}]
+
+  A union spec can appear wherever a struct spec can. The discriminator may be
+  any member of the object, not only the first; the decoder finds it, then
+  builds the struct it selects. An object whose discriminator is missing, not
+  a string or not in `variants` decodes as a plain map, or fails with an
+  `:unknown_discriminator` error given `{:one_of, key, variants, unknown:
+  :error}`.
+
+  ## Typed Fields
+
+  A typed field is checked while it is decoded, with no second pass over the
+  result. A type is one of `:any`, `:string`, `:integer`, `:float`, `:number`,
+  `:boolean`, `:map` or `:list`, `{type, constraints}`, or `[type]` for an
+  array whose elements all have that type:
+
+      {User,
+       %{
+         "id" => {:id, :integer, required: true, coerce: true},
+         "name" => {:name, :string, min_length: 1, max_length: 100},
+         "role" => {:role, :string, in: ["admin", "member"]},
+         "age" => {:age, :integer, min: 0, nullable: true},
+         "tags" => {:tags, [{:string, max_length: 20}], max_length: 10},
+         "posts" => {:posts, [{Post, %{"title" => :title}}], required: true}
+       }}
+
+  The constraints are:
+
+    * `:required` - a member with the key must be present
+    * `:nullable` - `null` passes; otherwise it only does for `:any`
+    * `:coerce` - convert a value of another type when it can be: numeric
+      strings to numbers, `"true"` and `"false"` to booleans, integers to
+      floats, and numbers and booleans to strings
+    * `:in` - the allowed strings, integers or booleans
+    * `:min`, `:max` - bounds on a number
+    * `:min_length`, `:max_length` - bounds on the code points of a string or
+      the elements of an array
+
+  Types are JSON types: an `:integer` has no fraction or exponent in the JSON,
+  and a `:float` has one. Checks see the JSON value, before options such as
+  `:numbers` or `:datetimes` apply to it; a coerced value is always an integer,
+  float, boolean or string. A struct, list or union spec with constraints
+  requires an object or an array.
+
+  A value that breaks its field does not stop the decode. The rest of the
+  document is still checked, and the document then fails with a
+  `RustyJson.DecodeError` whose `:code` is `:schema_violation` and whose
+  `:violations` lists every broken value with its path:
+
+      {:error, %RustyJson.DecodeError{violations: violations}} =
+        RustyJson.decode(~s({"age": -1, "tags": ["x", 5]}), schema: schema)
+
+      Enum.map(violations, &{&1.path, &1.message})
+      #=> [{"$.age", "must be at least 0"},
+      #=>  {"$.tags[1]", "expected string, got integer"},
+      #=>  {"$", ~s(missing required key "id")},
+      #=>  {"$", ~s(missing required key "posts")}]
+
+  ## Compiling
+
+  Every decode call compiles its schema anew. `compile!/1` does it once and
+  returns a `RustyJson.Schema` that decode calls share:
+
+      :persistent_term.put(:user_schema, RustyJson.Schema.compile!({User, %{...}}))
+
+      RustyJson.decode(body, schema: :persistent_term.get(:user_schema))
+
+  A compiled schema holds a NIF resource, so it is built at runtime and cannot
+  be stored in a module attribute.
+
+  Keys are matched as written in the JSON, before `:key_case` or `:keys`
+  apply. An untyped field of another JSON type than the spec expects is
+  decoded as usual, so `"address": null` gives `address: nil`. When a key
+  repeats, the last value wins, or the first with `duplicate_keys: :first`,
+  and `duplicate_keys: :error` rejects the object.
+  """
+
+  defstruct [:resource]
+
+  @typedoc "A schema compiled by `compile!/1`."
+  @type t :: %__MODULE__{resource: reference()}
+
+  @type definition :: spec() | [spec()] | [type()]
+
+  @type spec :: struct_spec() | union_spec()
+
+  @type struct_spec ::
+          module() | {module(), fields()} | {module(), fields(), map() | keyword()}
+
+  @type union_spec ::
+          {:one_of, String.t() | atom(), variants()}
+          | {:one_of, String.t() | atom(), variants(), [unknown: :map | :error]}
+
+  @type variants :: %{optional(String.t() | atom()) => struct_spec()}
+
+  @type fields :: %{optional(String.t() | atom()) => field()}
+
+  @type field ::
+          atom()
+          | {atom(), spec() | [spec()] | type()}
+          | {atom(), spec() | [spec()] | type(), constraints()}
+
+  @type type ::
+          :any
+          | :string
+          | :integer
+          | :float
+          | :number
+          | :boolean
+          | :map
+          | :list
+          | {type(), constraints()}
+          | [type()]
+
+  @type constraints :: [
+          required: boolean(),
+          nullable: boolean(),
+          coerce: boolean(),
+          in: [String.t() | integer() | boolean()],
+          min: number(),
+          max: number(),
+          min_length: non_neg_integer(),
+          max_length: non_neg_integer()
+        ]
+
+  @types [:any, :string, :integer, :float, :number, :boolean, :map, :list]
+
+  @constraints %{
+    nullable: false,
+    coerce: false,
+    in: nil,
+    min: nil,
+    max: nil,
+    min_length: nil,
+    max_length: nil
+  }
+
+  @doc """
+  Compiles a schema definition once, for reuse across decode calls.
+
+  Raises `ArgumentError` if the definition is invalid. A compiled schema is
+  returned as is.
+
+      iex> schema = RustyJson.Schema.compile!([:integer])
+      iex> RustyJson.decode!("[1, 2]", schema: schema)
+      [1, 2]
+
+  """
+  @spec compile!(definition() | t()) :: t()
+  def compile!(%__MODULE__{} = schema), do: schema
+  def compile!(definition) do
+    %__MODULE__{resource: RustyJson.nif_schema_compile(nif_spec!(definition))}
+  end
+
+  @doc false
+  # The NIF form, or the resource of a compiled schema. A target is
+  # `{:struct | :list, node}`,
+  # `{:union | :union_list, {key, [{value, node}], :map | :error}}` or
+  # `{:items, check}`, where a node is
+  # `{keys, defaults, [{json_key, slot, required, child}]}`, a child is `nil`,
+  # a target or `{:check, check}`, and a check is
+  # `{type, target | nil, constraints}` with every constraint but `:required`.
+  @spec nif_spec!(definition() | t()) :: tuple() | reference()
+  def nif_spec!(%__MODULE__{resource: resource}), do: resource
+
+  def nif_spec!([spec]) do
+    if typed?(spec), do: {:items, check!(spec, [])}, else: list_target!(spec)
+  end
+
+  def nif_spec!(spec), do: target!(spec)
+
+  defp list_target!(spec) do
+    case target!(spec) do
+      {:struct, node} -> {:list, node}
+      {:union, union} -> {:union_list, union}
+    end
+  end
+
+  defp target!({:one_of, key, variants}), do: target!({:one_of, key, variants, []})
+
+  defp target!({:one_of, key, variants, opts}) when is_map(variants) do
+    unknown =
+      case opts do
+        [] -> :map
+        [unknown: unknown] when unknown in [:map, :error] -> unknown
+        _ -> raise ArgumentError, "invalid :schema union options #{inspect(opts)}"
+      end
+
+    variants =
+      Enum.map(variants, fn {value, spec} ->
+        {json_string!(value, "discriminator value"), node!(spec)}
+      end)
+
+    {:union, {json_string!(key, "key"), variants, unknown}}
+  end
+
+  defp target!(spec), do: {:struct, node!(spec)}
+
+  defp node!(module) when is_atom(module) do
+    fields =
+      for {field, _} <- defaults!(module, []), field != :__struct__, into: %{} do
+        {field, field}
+      end
+
+    node!({module, fields})
+  end
+
+  defp node!({module, fields}), do: node!({module, fields, []})
+
+  defp node!({module, fields, defaults})
+       when is_atom(module) and is_map(fields) and (is_map(defaults) or is_list(defaults)) do
+    struct = defaults!(module, defaults)
+    {keys, values} = struct |> Map.to_list() |> Enum.unzip()
+    slots = keys |> Enum.with_index() |> Map.new()
+    members = Enum.map(fields, fn {key, field} -> member!(module, slots, key, field) end)
+    {keys, values, members}
+  end
+
+  defp node!(other) do
+    raise ArgumentError,
+          "invalid :schema struct spec #{inspect(other)}, expected a module or {module, fields}"
+  end
+
+  defp defaults!(module, defaults) do
+    unless Code.ensure_loaded?(module) and function_exported?(module, :__struct__, 0) do
+      raise ArgumentError, "invalid :schema module #{inspect(module)}, expected a struct"
+    end
+
+    base = module.__struct__()
+
+    Enum.reduce(defaults, base, fn
+      {field, value}, acc when field != :__struct__ and is_map_key(base, field) ->
+        Map.put(acc, field, value)
+
+      other, _acc ->
+        raise ArgumentError, "invalid :schema default #{inspect(other)} for #{inspect(module)}"
+    end)
+  end
+
+  defp member!(module, slots, key, field) do
+    {name, required, child} =
+      case field do
+        {name, spec, constraints} when is_list(constraints) ->
+          {required, constraints} = Keyword.pop(constraints, :required, false)
+          {name, required, {:check, check!(spec, constraints)}}
+
+        {name, spec} ->
+          {name, false, if(typed?(spec), do: {:check, check!(spec, [])}, else: nif_spec!(spec))}
+
+        name ->
+          {name, false, nil}
+      end
+
+    unless is_boolean(required) do
+      raise ArgumentError, "invalid :schema constraint #{inspect({:required, required})}"
+    end
+
+    case Map.fetch(slots, name) do
+      {:ok, slot} when name != :__struct__ -> {json_string!(key, "key"), slot, required, child}
+      _ -> raise ArgumentError, "unknown :schema field #{inspect(name)} for #{inspect(module)}"
+    end
+  end
+
+  defp typed?([spec]), do: typed?(spec)
+  defp typed?({type, constraints}) when type in @types and is_list(constraints), do: true
+  defp typed?(spec), do: spec in @types
+
+  defp check!({type, constraints}, outer) when type in @types and is_list(constraints) do
+    check!(type, constraints ++ outer)
+  end
+
+  defp check!(type, constraints) when type in @types, do: {type, nil, constraints!(constraints)}
+
+  defp check!([spec], constraints) do
+    items = if typed?(spec), do: {:items, check!(spec, [])}, else: list_target!(spec)
+    {:list, items, constraints!(constraints)}
+  end
+
+  defp check!(spec, constraints), do: {:map, target!(spec), constraints!(constraints)}
+
+  defp constraints!(constraints) do
+    Enum.reduce(constraints, @constraints, fn
+      {key, value}, acc when key in [:nullable, :coerce] and is_boolean(value) ->
+        Map.put(acc, key, value)
+
+      {key, value}, acc when key in [:min, :max] and is_number(value) ->
+        Map.put(acc, key, value / 1)
+
+      {key, value}, acc
+      when key in [:min_length, :max_length] and is_integer(value) and value >= 0 ->
+        Map.put(acc, key, value)
+
+      {:in, values}, acc when is_list(values) ->
+        Map.put(acc, :in, Enum.map(values, &allowed!/1))
+
+      other, _acc ->
+        raise ArgumentError, "invalid :schema constraint #{inspect(other)}"
+    end)
+  end
+
+  defp allowed!(value) when is_boolean(value) or is_binary(value), do: value
+
+  # Integers must fit the NIF's 64 bits.
+  defp allowed!(value) when value in -0x8000000000000000..0x7FFFFFFFFFFFFFFF, do: value
+
+  defp allowed!(value) do
+    raise ArgumentError, "invalid :schema allowed value #{inspect(value)}"
+  end
+
+  defp json_string!(value, _what) when is_binary(value), do: value
+  defp json_string!(value, _what) when is_atom(value) and not is_nil(value),
+    do: Atom.to_string(value)
+
+  defp json_string!(value, what),
+    do: raise(ArgumentError, "invalid :schema #{what} #{inspect(value)}")
+end
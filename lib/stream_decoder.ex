@@ -0,0 +1,34 @@
+defmodule RustyJson.StreamDecoder do
+  @moduledoc """
+  Handle for an incremental JSON decoder.
+
+  Created by `RustyJson.decoder_new/1` and driven with `RustyJson.decoder_feed/2`
+  and `RustyJson.decoder_finish/1`. The parser state lives in a Rust NIF resource,
+  so input can arrive in chunks of any size — including chunks that split a
+  string, an escape sequence, or a number — without being re-scanned.
+
+  When the document root is an array, each top-level element is returned as soon
+  as it is complete and its bytes are released. Any other root is returned as a
+  single value once complete.
+
+      decoder = RustyJson.decoder_new()
+      {:ok, [%{"id" => 1}]} = RustyJson.decoder_feed(decoder, ~s([{"id":1},{"i))
+      {:ok, [%{"id" => 2}]} = RustyJson.decoder_feed(decoder, ~s(d":2}]))
+      {:ok, []} = RustyJson.decoder_finish(decoder)
+
+  The handle is not tied to a process, but calls on the same handle are
+  serialized. After an error the decoder is unusable and every further call
+  returns the same error.
+  """
+
+  @typedoc "Opaque incremental decoder handle."
+  @type t :: %__MODULE__{
+          resource: reference(),
+          keys: RustyJson.keys(),
+          keys_fn: (String.t() -> term()) | nil,
+          dirty_threshold: non_neg_integer()
+        }
+
+  @enforce_keys [:resource]
+  defstruct [:resource, keys: :strings, keys_fn: nil, dirty_threshold: 0]
+end
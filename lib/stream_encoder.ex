@@ -0,0 +1,36 @@
+defmodule RustyJson.StreamEncoder do
+  @moduledoc """
+  Handle for a streaming JSON encoder.
+
+  Created by `RustyJson.encoder_new/1` and driven with `RustyJson.encoder_push/2`,
+  `RustyJson.encoder_push_many/2` and `RustyJson.encoder_finish/1`. The encoder
+  writes one JSON array (or, with `container: :object`, one object built from
+  `{key, value}` pairs) across many calls. Each call returns the output produced
+  so far as a list of binaries of at most `:chunk_size` bytes; concatenated,
+  they are the same bytes `RustyJson.encode!/2` would produce for the whole
+  collection.
+
+      encoder = RustyJson.encoder_new()
+      {:ok, ["[{\\"id\\":1}"]} = RustyJson.encoder_push(encoder, %{id: 1})
+      {:ok, [",2,3"]} = RustyJson.encoder_push_many(encoder, [2, 3])
+      {:ok, ["]"]} = RustyJson.encoder_finish(encoder)
+
+  With `compress: :gzip`, a single gzip stream spans all chunks, so they can be
+  sent as-is with `Plug.Conn.chunk/2` under `content-encoding: gzip`.
+
+  A push that fails to encode emits nothing and leaves the encoder usable.
+  Calls on the same handle are serialized.
+  """
+
+  @typedoc "Opaque streaming encoder handle."
+  @type t :: %__MODULE__{
+          resource: reference(),
+          container: :array | :object,
+          encode_opts: map(),
+          encoder_opts: term(),
+          protocol: boolean()
+        }
+
+  @enforce_keys [:resource, :encode_opts, :encoder_opts]
+  defstruct [:resource, :encode_opts, :encoder_opts, container: :array, protocol: true]
+end
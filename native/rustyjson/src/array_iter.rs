@@ -0,0 +1,206 @@
+//! Lazy, batched iteration over the elements of one JSON array.
+//!
+//! An `ArrayIter` keeps the input binary alive in a process-independent
+//! environment and remembers where the previous batch stopped, so each
+//! `next` call decodes only the next N elements. The input is never copied:
+//! every call re-borrows the same refc binary, and strings still come back as
+//! zero-copy sub-binaries.
+//!
+//! The `KeyShape` captured from the first object is carried across batches.
+//! Its raw keys are stored as byte ranges into the input and its key terms
+//! are saved in a dedicated `OwnedEnv`, so homogeneous rows keep the
+//! shape-matched fast path for the whole array, not just within one batch.
+
+use crate::direct_decode::{
+    ArrayCursor, DecodeError, DecodeErrorKind, DecodeOptions, DirectParser, DuplicateKeys, KeyShape,
+};
+use crate::projection::Action;
+use rustler::env::{OwnedEnv, SavedTerm};
+use rustler::{Binary, Env, Term};
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// `KeyShape` detached from any `Env` so it can outlive a NIF call.
+struct DetachedShape {
+    raw_keys: Vec<Range<usize>>,
+    key_terms: Vec<SavedTerm>,
+    is_flat: bool,
+    actions: Vec<Action>,
+}
+
+pub struct ArrayIter {
+    /// Holds the input binary between calls.
+    input_env: OwnedEnv,
+    input: SavedTerm,
+    /// Holds the shape's key terms; cleared whenever the shape changes.
+    shape_env: OwnedEnv,
+    shape: Option<DetachedShape>,
+    opts: DecodeOptions,
+    /// Path tokens from the root to the array being iterated.
+    path: Vec<Vec<u8>>,
+    pos: usize,
+    depth: usize,
+    entered: bool,
+    cursor: ArrayCursor,
+    /// First error encountered; the iterator is unusable afterwards.
+    failed: Option<DecodeError>,
+}
+
+/// NIF resource wrapping an `ArrayIter`. `OwnedEnv` is `Send` but not
+/// `Sync`, so the mutex is what makes the resource shareable.
+pub struct ArrayIterResource {
+    pub inner: Mutex<ArrayIter>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for ArrayIterResource {}
+
+impl ArrayIterResource {
+    pub fn new<'a>(
+        env: Env<'a>,
+        input: Binary<'a>,
+        path: Vec<Vec<u8>>,
+        opts: DecodeOptions,
+    ) -> Self {
+        let input_env = OwnedEnv::new();
+        let saved = input_env.save(input.to_term(env));
+        Self {
+            inner: Mutex::new(ArrayIter {
+                input_env,
+                input: saved,
+                shape_env: OwnedEnv::new(),
+                shape: None,
+                opts,
+                path,
+                pos: 0,
+                depth: 0,
+                entered: false,
+                cursor: ArrayCursor::default(),
+                failed: None,
+            }),
+        }
+    }
+}
+
+impl ArrayIter {
+    /// Decode up to `max` further elements. The flag is `true` once the
+    /// array's closing bracket has been consumed.
+    pub fn next<'a>(
+        &mut self,
+        env: Env<'a>,
+        max: usize,
+    ) -> Result<(Vec<Term<'a>>, bool), DecodeError> {
+        if let Some(e) = &self.failed {
+            return Err(e.clone());
+        }
+        if self.cursor.done {
+            return Ok((Vec::new(), true));
+        }
+        let result = self.step(env, max);
+        if let Err(e) = &result {
+            self.failed = Some(e.clone());
+        }
+        result
+    }
+
+    fn step<'a>(&mut self, env: Env<'a>, max: usize) -> Result<(Vec<Term<'a>>, bool), DecodeError> {
+        let input: Binary<'a> = self
+            .input_env
+            .run(|owned| self.input.load(owned).in_env(env))
+            .decode()
+            .map_err(|_| {
+                DecodeError::new(
+                    DecodeErrorKind::Unknown,
+                    "Failed to load input binary",
+                    self.pos,
+                )
+            })?;
+        let bytes = input.as_slice();
+
+        let mut parser =
+            DirectParser::resume(env, bytes, input, self.opts.clone(), self.pos, self.depth);
+        if !self.entered {
+            // The first matching key wins: finding a later repeat would
+            // mean skipping the whole array before the first batch.
+            if !parser.seek(&self.path, DuplicateKeys::First)? {
+                return Err(DecodeError::new(
+                    DecodeErrorKind::NotFound,
+                    "Path not found",
+                    parser.position(),
+                ));
+            }
+            parser.enter_array()?;
+            self.entered = true;
+        }
+
+        let mut shape = self.attach_shape(env, bytes);
+        let elements = parser.next_array_elements(max, &mut self.cursor, &mut shape)?;
+        // Only a root array owns the rest of the input; with a path, bytes
+        // after the target array belong to enclosing containers we never
+        // finish reading.
+        if self.cursor.done && self.path.is_empty() {
+            parser.expect_end()?;
+        }
+
+        self.pos = parser.position();
+        self.depth = parser.nesting_depth();
+        self.detach_shape(bytes, shape);
+        Ok((elements, self.cursor.done))
+    }
+
+    fn attach_shape<'a, 'b>(&self, env: Env<'a>, input: &'b [u8]) -> Option<KeyShape<'a, 'b>> {
+        let detached = self.shape.as_ref()?;
+        let key_terms = self.shape_env.run(|owned| {
+            detached
+                .key_terms
+                .iter()
+                .map(|t| t.load(owned).in_env(env))
+                .collect()
+        });
+        Some(KeyShape {
+            raw_keys: detached
+                .raw_keys
+                .iter()
+                .map(|r| &input[r.clone()])
+                .collect(),
+            key_terms,
+            is_flat: detached.is_flat,
+            actions: detached.actions.clone(),
+            fields: Vec::new(),
+        })
+    }
+
+    fn detach_shape(&mut self, input: &[u8], shape: Option<KeyShape<'_, '_>>) {
+        let Some(shape) = shape else {
+            if self.shape.take().is_some() {
+                self.shape_env.clear();
+            }
+            return;
+        };
+        // Raw keys are slices of `input`; store them as offsets.
+        let base = input.as_ptr() as usize;
+        let raw_keys: Vec<Range<usize>> = shape
+            .raw_keys
+            .iter()
+            .map(|k| {
+                let start = k.as_ptr() as usize - base;
+                start..start + k.len()
+            })
+            .collect();
+        if self.shape.as_ref().is_some_and(|s| s.raw_keys == raw_keys) {
+            return;
+        }
+        self.shape_env.clear();
+        let key_terms = shape
+            .key_terms
+            .iter()
+            .map(|t| self.shape_env.save(*t))
+            .collect();
+        self.shape = Some(DetachedShape {
+            raw_keys,
+            key_terms,
+            is_flat: shape.is_flat,
+            actions: shape.actions,
+        });
+    }
+}
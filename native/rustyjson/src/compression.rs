@@ -62,21 +62,17 @@ const GZIP_OUTPUT_CAPACITY: usize = 4096;
 /// Initial capacity for the plain (uncompressed) output Vec.
 const PLAIN_OUTPUT_CAPACITY: usize = 4096;
 
+/// Create a gzip encoder writing into a fresh output Vec.
+pub fn gzip_encoder(level: Option<u32>) -> GzEncoder<Vec<u8>> {
+    let level = level.map_or_else(Compression::default, Compression::new);
+    GzEncoder::new(Vec::with_capacity(GZIP_OUTPUT_CAPACITY), level)
+}
+
 pub fn get_writer(opts: Option<(Algs, Option<u32>)>) -> Writer {
     match opts {
-        Some((Algs::Gzip, None)) => Writer::Gzip(BufWriter::with_capacity(
-            GZIP_BUF_CAPACITY,
-            GzEncoder::new(
-                Vec::with_capacity(GZIP_OUTPUT_CAPACITY),
-                Compression::default(),
-            ),
-        )),
-        Some((Algs::Gzip, Some(lv))) => Writer::Gzip(BufWriter::with_capacity(
+        Some((Algs::Gzip, level)) => Writer::Gzip(BufWriter::with_capacity(
             GZIP_BUF_CAPACITY,
-            GzEncoder::new(
-                Vec::with_capacity(GZIP_OUTPUT_CAPACITY),
-                Compression::new(lv),
-            ),
+            gzip_encoder(level),
         )),
         _ => Writer::Plain(Vec::with_capacity(PLAIN_OUTPUT_CAPACITY)),
     }
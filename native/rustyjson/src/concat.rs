@@ -0,0 +1,60 @@
+//! Decoding a prefix of the input, and concatenated JSON documents.
+//!
+//! `decode_prefix` parses the first document and reports where it ended, so
+//! a reader framing JSON off a socket can keep the unconsumed bytes for the
+//! next call. `decode_all` parses every document of a concatenated stream:
+//! values back to back (`{...}{...}`), separated by whitespace, or written as
+//! an RFC 7464 JSON text sequence, where each text starts with the record
+//! separator `0x1E`.
+//!
+//! A number that runs to the end of the input is taken as complete, since
+//! the parser cannot tell `12` from the start of `123`. Framed streams
+//! should end such values with whitespace or use a container.
+//!
+//! Like NDJSON, all documents are parsed by one `DirectParser` built with
+//! `resume`, so `keys: :intern` shares its cache across them and no
+//! structural index is built.
+
+use crate::direct_decode::{check_max_bytes, DecodeFailure, DecodeOptions, DirectParser};
+use rustler::{Binary, Env, Term};
+
+/// Decode the first document of `input_binary`. Returns it with the offset
+/// just past it and the whitespace after it.
+pub fn prefix_to_term<'a>(
+    env: Env<'a>,
+    input_binary: &Binary<'a>,
+    opts: DecodeOptions,
+) -> Result<(Term<'a>, usize), DecodeFailure> {
+    let input = input_binary.as_slice();
+    check_max_bytes(input, &opts)?;
+
+    let mut parser = DirectParser::resume(env, input, *input_binary, opts, 0, 0);
+    parser.skip_separators();
+    let start = parser.position();
+    match parser.parse_next() {
+        Ok(term) => Ok((term, parser.position())),
+        Err(error) => Err(parser.failure(error, start)),
+    }
+}
+
+/// Decode every document of `input_binary`, in order. Input that holds only
+/// whitespace and separators gives no documents.
+pub fn all_to_terms<'a>(
+    env: Env<'a>,
+    input_binary: &Binary<'a>,
+    opts: DecodeOptions,
+) -> Result<Vec<Term<'a>>, DecodeFailure> {
+    let input = input_binary.as_slice();
+    check_max_bytes(input, &opts)?;
+
+    let mut parser = DirectParser::resume(env, input, *input_binary, opts, 0, 0);
+    let mut terms = Vec::new();
+    while parser.skip_separators() {
+        let start = parser.position();
+        match parser.parse_next() {
+            Ok(term) => terms.push(term),
+            Err(error) => return Err(parser.failure(error, start)),
+        }
+    }
+    Ok(terms)
+}
@@ -0,0 +1,343 @@
+//! ISO 8601 strings decoded to calendar structs (`datetimes:`).
+//!
+//! Recognizes the forms the encoder writes, in extended format only:
+//!
+//! - `2024-01-15` → `%Date{}`
+//! - `10:30:00`, `10:30:00.123` → `%Time{}`
+//! - `2024-01-15T10:30:00` → `%NaiveDateTime{}`
+//! - `2024-01-15T10:30:00Z`, `2024-01-15T10:30:00+02:00` → `%DateTime{}`
+//!
+//! Like `DateTime.from_iso8601/1`, an offset datetime is shifted to UTC and
+//! decoded in the `Etc/UTC` zone, and `-00:00` is rejected. Fractions keep up
+//! to six digits; the precision is the number of digits kept. Anything else,
+//! including out-of-range fields, leap seconds, a space instead of `T` and
+//! lowercase designators, is not a match and decodes as a string.
+
+use crate::atoms;
+use rustler::{Encoder, Env, NifResult, Term};
+
+/// A calendar date in `Calendar.ISO`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// A time of day; `microsecond` is `{value, precision}` as in Elixir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub microsecond: (u32, u32),
+}
+
+/// A recognized ISO 8601 string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Temporal {
+    Date(Date),
+    Time(Time),
+    NaiveDateTime(Date, Time),
+    /// Already shifted to UTC.
+    DateTime(Date, Time),
+}
+
+/// Parse `s` as one of the supported forms, or `None` when it is not one.
+pub fn parse(s: &[u8]) -> Option<Temporal> {
+    if s.get(2) == Some(&b':') {
+        return parse_time(s).map(Temporal::Time);
+    }
+    let date = parse_date(s.get(..10)?)?;
+    let rest = &s[10..];
+    if rest.is_empty() {
+        return Some(Temporal::Date(date));
+    }
+    let rest = rest.strip_prefix(b"T")?;
+    let zone = rest.iter().position(|&b| matches!(b, b'Z' | b'+' | b'-'));
+    let Some(zone) = zone else {
+        return Some(Temporal::NaiveDateTime(date, parse_time(rest)?));
+    };
+    let time = parse_time(&rest[..zone])?;
+    let offset = parse_offset(&rest[zone..])?;
+    let (date, time) = shift(date, time, -offset);
+    Some(Temporal::DateTime(date, time))
+}
+
+/// `YYYY-MM-DD`, with the day checked against the month.
+fn parse_date(s: &[u8]) -> Option<Date> {
+    if s.len() != 10 || s[4] != b'-' || s[7] != b'-' {
+        return None;
+    }
+    let year = digits(&s[..4])? as i32;
+    let month = digits(&s[5..7])?;
+    let day = digits(&s[8..10])?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some(Date { year, month, day })
+}
+
+/// `HH:MM:SS` with an optional `.fraction` of at least one digit.
+fn parse_time(s: &[u8]) -> Option<Time> {
+    if s.len() < 8 || s[2] != b':' || s[5] != b':' {
+        return None;
+    }
+    let hour = digits(&s[..2])?;
+    let minute = digits(&s[3..5])?;
+    let second = digits(&s[6..8])?;
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let microsecond = match &s[8..] {
+        [] => (0, 0),
+        [b'.', fraction @ ..] if !fraction.is_empty() => {
+            if !fraction.iter().all(u8::is_ascii_digit) {
+                return None;
+            }
+            let kept = &fraction[..fraction.len().min(6)];
+            let precision = kept.len() as u32;
+            (digits(kept)? * 10u32.pow(6 - precision), precision)
+        }
+        _ => return None,
+    };
+    Some(Time {
+        hour,
+        minute,
+        second,
+        microsecond,
+    })
+}
+
+/// `Z` or `±HH:MM`, as seconds east of UTC.
+fn parse_offset(s: &[u8]) -> Option<i64> {
+    match s {
+        b"Z" => Some(0),
+        b"-00:00" => None,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let hours = digits(&[*h1, *h2])?;
+            let minutes = digits(&[*m1, *m2])?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let seconds = (hours * 3600 + minutes * 60) as i64;
+            Some(if *sign == b'-' { -seconds } else { seconds })
+        }
+        _ => None,
+    }
+}
+
+/// Move `date` and `time` by `seconds`, keeping the fraction.
+fn shift(date: Date, time: Time, seconds: i64) -> (Date, Time) {
+    let total = days_from_civil(date) * 86_400
+        + (time.hour * 3600 + time.minute * 60 + time.second) as i64
+        + seconds;
+    let of_day = total.rem_euclid(86_400) as u32;
+    let date = civil_from_days(total.div_euclid(86_400));
+    let time = Time {
+        hour: of_day / 3600,
+        minute: of_day % 3600 / 60,
+        second: of_day % 60,
+        ..time
+    };
+    (date, time)
+}
+
+/// Days since 1970-01-01 (Howard Hinnant's `days_from_civil`).
+fn days_from_civil(date: Date) -> i64 {
+    let month = date.month as i64;
+    let year = date.year as i64 - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + date.day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> Date {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = (year_of_era + era * 400 + (month <= 2) as i64) as i32;
+    Date { year, month, day }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Value of a short all-digit slice, or `None` if any byte is not a digit.
+fn digits(s: &[u8]) -> Option<u32> {
+    s.iter().try_fold(0u32, |acc, &b| {
+        b.is_ascii_digit().then(|| acc * 10 + (b - b'0') as u32)
+    })
+}
+
+impl Temporal {
+    /// Build the `%Date{}`, `%Time{}`, `%NaiveDateTime{}` or `%DateTime{}`.
+    pub fn to_term<'a>(self, env: Env<'a>) -> NifResult<Term<'a>> {
+        let mut keys = Vec::with_capacity(13);
+        let mut values = Vec::with_capacity(13);
+        let mut put = |key: rustler::Atom, value: Term<'a>| {
+            keys.push(key.to_term(env));
+            values.push(value);
+        };
+        let (name, date, time) = match self {
+            Temporal::Date(date) => (atoms::date_struct(), Some(date), None),
+            Temporal::Time(time) => (atoms::time_struct(), None, Some(time)),
+            Temporal::NaiveDateTime(date, time) => {
+                (atoms::naive_datetime_struct(), Some(date), Some(time))
+            }
+            Temporal::DateTime(date, time) => (atoms::datetime_struct(), Some(date), Some(time)),
+        };
+        put(atoms::__struct__(), name.to_term(env));
+        put(atoms::calendar(), atoms::calendar_iso().to_term(env));
+        if let Some(date) = date {
+            put(atoms::year(), date.year.encode(env));
+            put(atoms::month(), date.month.encode(env));
+            put(atoms::day(), date.day.encode(env));
+        }
+        if let Some(time) = time {
+            put(atoms::hour(), time.hour.encode(env));
+            put(atoms::minute(), time.minute.encode(env));
+            put(atoms::second(), time.second.encode(env));
+            put(atoms::microsecond(), time.microsecond.encode(env));
+        }
+        if let Temporal::DateTime(..) = self {
+            put(atoms::time_zone(), "Etc/UTC".encode(env));
+            put(atoms::zone_abbr(), "UTC".encode(env));
+            put(atoms::utc_offset(), 0.encode(env));
+            put(atoms::std_offset(), 0.encode(env));
+        }
+        Term::map_from_term_arrays(env, &keys, &values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    fn time(hour: u32, minute: u32, second: u32, microsecond: (u32, u32)) -> Time {
+        Time {
+            hour,
+            minute,
+            second,
+            microsecond,
+        }
+    }
+
+    #[test]
+    fn parses_each_form() {
+        assert_eq!(
+            parse(b"2024-02-29"),
+            Some(Temporal::Date(date(2024, 2, 29)))
+        );
+        assert_eq!(
+            parse(b"23:59:59.5"),
+            Some(Temporal::Time(time(23, 59, 59, (500_000, 1))))
+        );
+        assert_eq!(
+            parse(b"2024-01-15T10:30:00"),
+            Some(Temporal::NaiveDateTime(
+                date(2024, 1, 15),
+                time(10, 30, 0, (0, 0))
+            ))
+        );
+        assert_eq!(
+            parse(b"2024-01-15T10:30:00.000123Z"),
+            Some(Temporal::DateTime(
+                date(2024, 1, 15),
+                time(10, 30, 0, (123, 6))
+            ))
+        );
+    }
+
+    #[test]
+    fn truncates_fractions_to_microseconds() {
+        assert_eq!(
+            parse(b"00:00:00.123456789"),
+            Some(Temporal::Time(time(0, 0, 0, (123_456, 6))))
+        );
+        assert_eq!(parse(b"00:00:00.12345x"), None);
+        assert_eq!(parse(b"00:00:00."), None);
+    }
+
+    #[test]
+    fn shifts_offsets_to_utc() {
+        assert_eq!(
+            parse(b"2024-01-01T01:30:00+02:00"),
+            Some(Temporal::DateTime(
+                date(2023, 12, 31),
+                time(23, 30, 0, (0, 0))
+            ))
+        );
+        assert_eq!(
+            parse(b"2024-02-28T20:00:00.25-05:30"),
+            Some(Temporal::DateTime(
+                date(2024, 2, 29),
+                time(1, 30, 0, (250_000, 2))
+            ))
+        );
+        assert_eq!(
+            parse(b"0000-01-01T00:00:00+00:01"),
+            Some(Temporal::DateTime(
+                date(-1, 12, 31),
+                time(23, 59, 0, (0, 0))
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_near_misses() {
+        for input in [
+            &b""[..],
+            b"2024",
+            b"2024-1-15",
+            b"2024-13-01",
+            b"2023-02-29",
+            b"1900-02-29",
+            b"2024-04-31",
+            b"2024-01-15 10:30:00",
+            b"2024-01-15t10:30:00",
+            b"2024-01-15T10:30",
+            b"2024-01-15T24:00:00",
+            b"2024-01-15T23:59:60",
+            b"2024-01-15T10:30:00z",
+            b"2024-01-15T10:30:00-00:00",
+            b"2024-01-15T10:30:00+0200",
+            b"2024-01-15T10:30:00+24:00",
+            b"2024-01-15T10:30:00Z ",
+            b"20240115",
+            b"10:30",
+            b"1x:30:00",
+            b"+1:30:00",
+        ] {
+            assert_eq!(parse(input), None, "{}", String::from_utf8_lossy(input));
+        }
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(date(1970, 1, 1)), 0);
+        assert_eq!(days_from_civil(date(2000, 3, 1)), 11_017);
+        for days in (-800_000..3_000_000).step_by(97) {
+            assert_eq!(days_from_civil(civil_from_days(days)), days);
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use crate::direct_decode::{DecodeError, DecodeErrorKind};
 use num_bigint::BigInt;
-use rustler::{Term, TermType};
+use rustler::{Encoder, Env, Term, TermType};
+use std::borrow::Cow;
 
-/// Maximum absolute exponent allowed for Decimal formatting.
+/// Maximum absolute exponent allowed for Decimal formatting, and for the
+/// Decimals the decoder builds, so every decoded Decimal can be encoded again.
 /// Matches the decoder's `integer_digit_limit` default of 1024. Exponents beyond
 /// this would produce strings exceeding 1024 characters from the exponent alone,
 /// which is either malformed data or a DoS attempt. Returns `None` (falling back
@@ -10,7 +13,7 @@ use rustler::{Term, TermType};
 /// Note: coefficient size is not capped here — BigInt coefficients with millions
 /// of digits could still produce large strings. In practice, Elixir's Decimal
 /// library constrains coefficient size more than it constrains exponents.
-const MAX_EXP: i32 = 1024;
+pub(crate) const MAX_EXP: i32 = 1024;
 
 /// Checks if a term is an Elixir.Decimal struct and returns its string representation if so.
 ///
@@ -105,6 +108,135 @@ fn format_decimal_str(coef_str: &str, exp: i32, sign: i32) -> String {
     }
 }
 
+/// A Decimal coefficient: `i128` when it fits, otherwise `BigInt`. Both
+/// encode to the same Elixir integer.
+#[derive(Debug, PartialEq)]
+pub enum Coef {
+    Small(i128),
+    Big(BigInt),
+}
+
+impl Encoder for Coef {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            Coef::Small(c) => c.encode(env),
+            Coef::Big(c) => c.encode(env),
+        }
+    }
+}
+
+/// The fields of a `%Decimal{}`.
+#[derive(Debug, PartialEq)]
+pub struct DecimalParts {
+    pub sign: i64,
+    pub coef: Coef,
+    pub exp: i64,
+}
+
+/// Split a JSON number token, already checked against the grammar and the
+/// digit quotas, into Decimal fields. The exponent is computed with overflow
+/// checks, so a token such as `1e99999999999999999999` fails instead of
+/// wrapping, and must then lie within `MAX_EXP` once the fraction is folded
+/// into it (`1.5e-1024` has exponent -1025). Errors are at position 0; the
+/// caller places them with `DecodeError::at`.
+pub fn parse_decimal(token: &[u8]) -> Result<DecimalParts, DecodeError> {
+    let (sign, rest) = match token.strip_prefix(b"-") {
+        Some(rest) => (-1, rest),
+        None => (1, token),
+    };
+    let (mantissa, written_exp) = match rest.iter().position(|&b| b == b'e' || b == b'E') {
+        Some(e) => (&rest[..e], parse_exponent(&rest[e + 1..])?),
+        None => (rest, 0),
+    };
+    let (int_part, frac_part) = match mantissa.iter().position(|&b| b == b'.') {
+        Some(dot) => (&mantissa[..dot], &mantissa[dot + 1..]),
+        None => (mantissa, &[][..]),
+    };
+
+    let exp = i64::try_from(frac_part.len())
+        .ok()
+        .and_then(|frac_len| written_exp.checked_sub(frac_len))
+        .ok_or_else(exponent_range)?;
+    if exp.unsigned_abs() > MAX_EXP as u64 {
+        let msg = format!("decimal exponent {} exceeds limit of {}", exp, MAX_EXP);
+        return Err(DecodeError::new(DecodeErrorKind::LimitExceeded, msg, 0));
+    }
+
+    let digits: Cow<[u8]> = if frac_part.is_empty() {
+        Cow::Borrowed(int_part)
+    } else {
+        Cow::Owned([int_part, frac_part].concat())
+    };
+    let digits = match digits.iter().position(|&b| b != b'0') {
+        Some(first) => &digits[first..],
+        None => &b"0"[..],
+    };
+    Ok(DecimalParts {
+        sign,
+        coef: parse_coef(digits)?,
+        exp,
+    })
+}
+
+const EXPONENT_RANGE: &str = "decimal exponent out of range";
+
+#[cold]
+fn exponent_range() -> DecodeError {
+    DecodeError::new(DecodeErrorKind::LimitExceeded, EXPONENT_RANGE, 0)
+}
+
+/// Parse the exponent after `e`: an optional sign and decimal digits.
+fn parse_exponent(bytes: &[u8]) -> Result<i64, DecodeError> {
+    let (neg, digits) = match bytes.first() {
+        Some(b'-') => (true, &bytes[1..]),
+        Some(b'+') => (false, &bytes[1..]),
+        _ => (false, bytes),
+    };
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(DecodeError::new(
+            DecodeErrorKind::InvalidNumber,
+            "Invalid exponent in decimal number",
+            0,
+        ));
+    }
+    let mut exp: i64 = 0;
+    for &d in digits {
+        exp = exp
+            .checked_mul(10)
+            .and_then(|e| e.checked_add(i64::from(d - b'0')))
+            .ok_or_else(exponent_range)?;
+    }
+    Ok(if neg { -exp } else { exp })
+}
+
+/// Parse coefficient digits (no sign, no leading zeros) as `i128`, or as
+/// `BigInt` when they overflow it.
+fn parse_coef(digits: &[u8]) -> Result<Coef, DecodeError> {
+    let invalid = || {
+        DecodeError::new(
+            DecodeErrorKind::InvalidNumber,
+            "Invalid decimal coefficient",
+            0,
+        )
+    };
+    // 38 digits always fit: i128::MAX is about 1.7e38.
+    if digits.len() <= 38 {
+        let mut coef: i128 = 0;
+        for &d in digits {
+            if !d.is_ascii_digit() {
+                return Err(invalid());
+            }
+            coef = coef * 10 + i128::from(d - b'0');
+        }
+        return Ok(Coef::Small(coef));
+    }
+    let big = BigInt::parse_bytes(digits, 10).ok_or_else(invalid)?;
+    Ok(match i128::try_from(&big) {
+        Ok(c) => Coef::Small(c),
+        Err(_) => Coef::Big(big),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,4 +284,161 @@ mod tests {
             "-17014118346046923173168730371588410572800000"
         );
     }
+
+    fn parts(token: &str) -> Result<DecimalParts, DecodeError> {
+        parse_decimal(token.as_bytes())
+    }
+
+    fn small(sign: i64, coef: i128, exp: i64) -> DecimalParts {
+        DecimalParts {
+            sign,
+            coef: Coef::Small(coef),
+            exp,
+        }
+    }
+
+    /// Render parts the way the encoder does.
+    fn format_parts(parts: &DecimalParts) -> String {
+        let coef = match &parts.coef {
+            Coef::Small(c) => c.to_string(),
+            Coef::Big(c) => c.to_string(),
+        };
+        format_decimal_str(&coef, parts.exp as i32, parts.sign as i32)
+    }
+
+    /// Coefficient and exponent with trailing zeros folded into the exponent,
+    /// so numerically equal Decimals compare equal.
+    fn normalize(sign: i64, coef: &str, exp: i64) -> (i64, String, i64) {
+        let trimmed = coef.trim_end_matches('0');
+        if trimmed.is_empty() {
+            return (sign, "0".to_string(), 0);
+        }
+        let exp = exp + (coef.len() - trimmed.len()) as i64;
+        (sign, trimmed.to_string(), exp)
+    }
+
+    #[test]
+    fn test_parse_decimal_fields() {
+        assert_eq!(parts("1.10"), Ok(small(1, 110, -2)));
+        assert_eq!(parts("-0.00123"), Ok(small(-1, 123, -5)));
+        assert_eq!(parts("1.5E+2"), Ok(small(1, 15, 1)));
+        assert_eq!(parts("42"), Ok(small(1, 42, 0)));
+        assert_eq!(parts("-0"), Ok(small(-1, 0, 0)));
+        assert_eq!(parts("0.0e-3"), Ok(small(1, 0, -4)));
+    }
+
+    #[test]
+    fn test_parse_decimal_coefficient_widths() {
+        let max = i128::MAX.to_string();
+        assert_eq!(parts(&max), Ok(small(1, i128::MAX, 0)));
+        let above = (BigInt::from(i128::MAX) + 1u8).to_string();
+        assert_eq!(
+            parts(&above).unwrap().coef,
+            Coef::Big(BigInt::from(i128::MAX) + 1u8)
+        );
+        let padded = format!("0.{}", "0".repeat(60) + "7");
+        assert_eq!(parts(&padded), Ok(small(1, 7, -61)));
+    }
+
+    #[test]
+    fn test_parse_decimal_exponent_range() {
+        assert_eq!(parts("1e1024").map(|p| p.exp), Ok(1024));
+        assert_eq!(
+            parts("1e999999999").unwrap_err().message,
+            "decimal exponent 999999999 exceeds limit of 1024"
+        );
+        // The fraction counts towards the exponent
+        assert_eq!(parts("1.5e1025").map(|p| p.exp), Ok(1024));
+        assert_eq!(
+            parts("1.5e-1024").unwrap_err().message,
+            "decimal exponent -1025 exceeds limit of 1024"
+        );
+        let frac = format!("0.{}", "1".repeat(1025));
+        assert!(parts(&frac).is_err());
+        for token in ["1e9223372036854775808", "1e99999999999999999999"] {
+            assert_eq!(parts(token).unwrap_err().message, EXPONENT_RANGE);
+        }
+        assert_eq!(
+            parts("0.05e-9223372036854775807").unwrap_err().message,
+            EXPONENT_RANGE
+        );
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn coef_digits() -> impl Strategy<Value = String> {
+            prop_oneof![
+                any::<u64>().prop_map(|c| c.to_string()),
+                (0..=i128::MAX).prop_map(|c| c.to_string()),
+                "[1-9][0-9]{38,80}",
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn decode_then_encode_round_trips(
+                negative in any::<bool>(),
+                coef in coef_digits(),
+                exp in -MAX_EXP..=MAX_EXP,
+            ) {
+                let sign = if negative { -1 } else { 1 };
+                let text = format_decimal_str(&coef, exp, sign);
+                let decoded = parse_decimal(text.as_bytes()).unwrap();
+
+                // The encoder writes the decoded Decimal back as the same text...
+                prop_assert_eq!(format_parts(&decoded), text.clone());
+                // ...and it is the Decimal that was encoded.
+                let decoded_coef = match &decoded.coef {
+                    Coef::Small(c) => c.to_string(),
+                    Coef::Big(c) => c.to_string(),
+                };
+                prop_assert_eq!(
+                    normalize(decoded.sign, &decoded_coef, decoded.exp),
+                    normalize(sign as i64, &coef, exp as i64)
+                );
+            }
+
+            #[test]
+            fn coefficients_use_i128_exactly_when_they_fit(coef in coef_digits()) {
+                let parsed = parse_decimal(coef.as_bytes()).unwrap().coef;
+                let big: BigInt = coef.parse().unwrap();
+                match parsed {
+                    Coef::Small(c) => prop_assert_eq!(BigInt::from(c), big),
+                    Coef::Big(c) => {
+                        prop_assert!(i128::try_from(&big).is_err());
+                        prop_assert_eq!(c, big);
+                    }
+                }
+            }
+
+            #[test]
+            fn i128_and_bigint_formatting_agree(
+                coef in 0..=i128::MAX,
+                exp in -MAX_EXP..=MAX_EXP,
+                negative in any::<bool>(),
+            ) {
+                let sign = if negative { -1 } else { 1 };
+                prop_assert_eq!(
+                    format_decimal_i128(coef, exp, sign),
+                    format_decimal_str(&coef.to_string(), exp, sign)
+                );
+            }
+
+            #[test]
+            fn exponents_are_exact_within_the_limit(
+                coef in 1..1000u32,
+                exp in (i64::MIN + 1)..=i64::MAX,
+            ) {
+                let token = format!("{coef}e{exp}");
+                let parsed = parse_decimal(token.as_bytes()).map(|p| p.exp);
+                if exp.unsigned_abs() <= MAX_EXP as u64 {
+                    prop_assert_eq!(parsed, Ok(exp));
+                } else {
+                    prop_assert!(parsed.is_err());
+                }
+            }
+        }
+    }
 }
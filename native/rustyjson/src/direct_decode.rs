@@ -1,20 +1,257 @@
 use crate::atoms;
+use crate::datetime;
+use crate::decimal;
+use crate::key_case::{self, KeyCase};
+use crate::projection::{Action, Projection};
+use crate::schema::{Check, Coerced, Field, Json, Schema, Target};
 use num_bigint::BigInt;
-use rustler::{types::atom, Binary, Encoder, Env, NewBinary, Term};
+use rustler::{
+    types::atom, Atom, Binary, Encoder, Env, NewBinary, NifMap, NifTuple, NifUnitEnum, Term,
+};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::hash::{BuildHasher, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Why a decode failed, returned to Elixir as the error `code`.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecodeErrorKind {
+    /// A byte that cannot appear here; `expected` lists what could.
+    UnexpectedToken,
+    /// The input ends inside a value.
+    UnexpectedEnd,
+    /// More content after the document.
+    TrailingCharacters,
+    /// A string with no closing quote.
+    UnterminatedString,
+    /// A `/*` comment with no `*/` (relaxed mode).
+    UnterminatedComment,
+    /// A malformed number.
+    InvalidNumber,
+    /// A malformed escape sequence in a string.
+    InvalidEscape,
+    /// A `\uXXXX` surrogate escape without its pair.
+    LoneSurrogate,
+    /// Invalid UTF-8 in a string.
+    InvalidUtf8,
+    /// An unescaped control character in a string.
+    ControlCharacter,
+    /// Nesting deeper than `max_depth`.
+    DepthExceeded,
+    /// A size limit such as `max_bytes` or a decode quota.
+    LimitExceeded,
+    /// A repeated key under `duplicate_keys: :error`.
+    DuplicateKey,
+    /// A key that cannot become an atom.
+    InvalidKey,
+    /// A pointer or iterator path that matches nothing.
+    NotFound,
+    /// Values that break typed schema fields.
+    SchemaViolation,
+    /// A missing or unknown discriminator in a schema union.
+    UnknownDiscriminator,
+    /// A term the NIF could not build, or misuse of a decoder resource.
+    Unknown,
+}
+
+/// A decode failure: why, a message, the byte position, and for syntax
+/// errors the tokens that would have been accepted there. Encodes as
+/// `{code, expected, message, position}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    pub message: Cow<'static, str>,
+    pub pos: usize,
+    pub expected: &'static [&'static str],
+}
+
+impl DecodeError {
+    #[cold]
+    pub fn new(kind: DecodeErrorKind, message: impl Into<Cow<'static, str>>, pos: usize) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            pos,
+            expected: &[],
+        }
+    }
+
+    /// The same error at `pos`, for errors raised by helpers that do not
+    /// know where their input starts.
+    #[cold]
+    pub fn at(mut self, pos: usize) -> Self {
+        self.pos = pos;
+        self
+    }
+}
+
+impl Encoder for DecodeError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        (self.kind, self.expected, self.message.as_ref(), self.pos).encode(env)
+    }
+}
+
+/// Tokens that can start a JSON value.
+const VALUE_TOKENS: &[&str] = &[
+    "string", "number", "object", "array", "true", "false", "null",
+];
+
+/// A grammar error: something other than the tokens allowed at a position.
+#[derive(Clone, Copy, Debug)]
+pub enum Syntax {
+    UnexpectedCharacter,
+    UnexpectedEnd,
+    TrailingCharacters,
+    ExpectedColon,
+    ExpectedCommaOrBrace,
+    ExpectedCommaOrBracket,
+    ExpectedKey,
+    ExpectedNull,
+    ExpectedTrue,
+    ExpectedFalse,
+    ExpectedArray,
+    ExpectedObject,
+    ExpectedArrayOrObject,
+    UnterminatedString,
+    UnterminatedComment,
+}
+
+impl Syntax {
+    /// The `DecodeError` for this grammar error at `pos`.
+    #[cold]
+    pub fn at(self, pos: usize) -> DecodeError {
+        use DecodeErrorKind::*;
+        let (kind, message, expected): (_, _, &'static [&'static str]) = match self {
+            Syntax::UnexpectedCharacter => (UnexpectedToken, "Unexpected character", VALUE_TOKENS),
+            Syntax::UnexpectedEnd => (UnexpectedEnd, "Unexpected end of input", VALUE_TOKENS),
+            Syntax::TrailingCharacters => (
+                TrailingCharacters,
+                "Unexpected trailing characters",
+                &["end of input"],
+            ),
+            Syntax::ExpectedColon => (UnexpectedToken, "Expected ':'", &[":"]),
+            Syntax::ExpectedCommaOrBrace => (UnexpectedToken, "Expected ',' or '}'", &[",", "}"]),
+            Syntax::ExpectedCommaOrBracket => (UnexpectedToken, "Expected ',' or ']'", &[",", "]"]),
+            Syntax::ExpectedKey => (UnexpectedToken, "Expected string key", &["string"]),
+            Syntax::ExpectedNull => (UnexpectedToken, "Expected 'null'", &["null"]),
+            Syntax::ExpectedTrue => (UnexpectedToken, "Expected 'true'", &["true"]),
+            Syntax::ExpectedFalse => (UnexpectedToken, "Expected 'false'", &["false"]),
+            Syntax::ExpectedArray => (UnexpectedToken, "Expected array", &["array"]),
+            Syntax::ExpectedObject => (UnexpectedToken, "Expected object", &["object"]),
+            Syntax::ExpectedArrayOrObject => (
+                UnexpectedToken,
+                "Expected array or object",
+                &["array", "object"],
+            ),
+            Syntax::UnterminatedString => (UnterminatedString, "Unterminated string", &["\""]),
+            Syntax::UnterminatedComment => (UnterminatedComment, "Unterminated comment", &["*/"]),
+        };
+        DecodeError {
+            kind,
+            message: Cow::Borrowed(message),
+            pos,
+            expected,
+        }
+    }
+}
+
+/// A decode error with the path of the value that was being parsed, as
+/// returned by `json_to_term`. Encodes as `{error, path}`, or
+/// `{error, path, violations}` when the document broke its schema.
+#[derive(Debug)]
+pub struct DecodeFailure {
+    pub error: DecodeError,
+    /// From the root down; empty for errors outside any container.
+    pub path: Vec<PathSegment>,
+    /// Every schema violation in the document, when that is why it failed.
+    pub violations: Vec<Violation>,
+}
+
+impl From<DecodeError> for DecodeFailure {
+    fn from(error: DecodeError) -> Self {
+        Self {
+            error,
+            path: Vec::new(),
+            violations: Vec::new(),
+        }
+    }
+}
+
+impl Encoder for DecodeFailure {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        if self.violations.is_empty() {
+            return (&self.error, &self.path).encode(env);
+        }
+        (&self.error, &self.path, &self.violations).encode(env)
+    }
+}
+
+/// A value that broke its typed schema field. Parsing goes on after one, so
+/// a document reports all of its violations. Encodes as
+/// `{message, position, path}`.
+#[derive(Debug)]
+pub struct Violation {
+    pub message: String,
+    pub pos: usize,
+    pub path: Vec<PathSegment>,
+}
+
+impl Encoder for Violation {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        (&self.message, self.pos, &self.path).encode(env)
+    }
+}
+
+/// One step of an error path: an array index or an object key. Encodes as
+/// an integer or a binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+impl Encoder for PathSegment {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            PathSegment::Index(index) => index.encode(env),
+            PathSegment::Key(key) => key.encode(env),
+        }
+    }
+}
+
+/// The item a container is parsing: element `index`, or with `index` unset,
+/// the member whose key starts at `pos`. Recorded per depth by
+/// `count_element` and `count_member`, and only read after an error.
+#[derive(Clone, Copy, Default)]
+struct PathSlot {
+    pos: usize,
+    index: Option<usize>,
+}
+
+/// Default maximum nesting depth (`max_depth:` option).
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
 
-/// Error type for decode operations: static string message + byte position.
-pub type DecodeError = (Cow<'static, str>, usize);
+/// Highest `max_depth` accepted, for decoding and for encoding
+/// (`direct_json::MAX_DEPTH_LIMIT`). In a release build a nesting level costs
+/// up to ~0.8 KiB of native stack to decode and ~1 KiB to encode (a map, or a
+/// `MapSet` through the struct dispatch), so 256 levels fit the smallest
+/// default scheduler stack (40 kilowords on dirty CPU schedulers). The
+/// "deep nesting" tests in `test/safety_test.exs` run 256 levels both ways.
+pub(crate) const MAX_DEPTH_LIMIT: usize = 256;
 
-/// Maximum nesting depth to prevent stack overflow
-const MAX_DEPTH: usize = 128;
+/// Default minimum string length to use a zero-copy sub-binary reference
+/// instead of copying to a heap binary (`subbinary_threshold:`). Below
+/// this, the overhead of the sub-binary indirection exceeds the copy cost.
+pub(crate) const SUBBINARY_THRESHOLD: usize = 64;
 
-/// Minimum string length to use a zero-copy sub-binary reference
-/// instead of copying to a heap binary. Below this threshold, the
-/// overhead of the sub-binary indirection exceeds the copy cost.
-const SUBBINARY_THRESHOLD: usize = 64;
+/// Under `Strings::Auto`, sub-binaries are made once the strings eligible
+/// for them add up to at least 1/AUTO_REFERENCE_SHARE of the input.
+const AUTO_REFERENCE_SHARE: usize = 4;
+
+/// RFC 7464 record separator, which starts each text of a JSON text
+/// sequence. Skipped between concatenated documents.
+const RECORD_SEPARATOR: u8 = 0x1E;
 
 // ============================================================================
 // Structural Index - pre-scan for structural JSON characters
@@ -236,30 +473,209 @@ fn build_structural_index(input: &[u8]) -> StructuralIndex {
 const MAX_INTERN_KEYS: usize = 4096;
 
 /// Options controlling decode behavior, parsed from the Elixir opts map.
+#[derive(Clone)]
 pub struct DecodeOptions {
     pub intern_keys: bool,
+    /// Whether strings may be sub-binaries of the input.
+    pub strings: Strings,
+    /// Shortest string, in bytes, made a sub-binary under `Reference` and
+    /// `Auto`.
+    pub subbinary_threshold: usize,
     pub floats_decimals: bool,
+    /// How number tokens are decoded; anything but `Native` overrides
+    /// `floats_decimals`.
+    pub numbers: Numbers,
     pub ordered_objects: bool,
     pub integer_digit_limit: usize,
     pub max_bytes: usize,
-    pub reject_duplicate_keys: bool,
+    /// What to do with repeated object keys.
+    pub duplicate_keys: DuplicateKeys,
     pub validate_strings: bool,
+    /// What to do with lone surrogate escapes and invalid UTF-8.
+    pub invalid_unicode: InvalidUnicode,
+    /// Maximum container nesting depth, at most `MAX_DEPTH_LIMIT`.
+    pub max_depth: usize,
+    /// Quotas for untrusted input; 0 disables each one. String lengths are
+    /// measured on the raw bytes between the quotes.
+    pub max_string_bytes: usize,
+    pub max_array_elements: usize,
+    pub max_object_keys: usize,
+    /// Values in the whole document, the root and every container member
+    /// included.
+    pub max_total_values: usize,
+    pub max_fraction_digits: usize,
+    pub max_exponent_digits: usize,
+    /// Decode object keys to atoms instead of strings.
+    pub atom_keys: Option<AtomKeys>,
+    /// Atoms `AtomKeys::Create` may add to the atom table; 0 is unlimited.
+    pub max_new_atoms: usize,
+    /// Naming style object keys are rewritten to.
+    pub key_case: Option<KeyCase>,
+    /// Decode keys that are canonical integers (`"42"`) to integers.
+    pub integer_keys: bool,
+    /// `only:` / `except:` key paths; members outside them are skipped.
+    pub projection: Option<Arc<Projection>>,
+    /// JSON5-style extensions; `None` keeps the strict parser.
+    pub relaxed: Option<RelaxedSyntax>,
+    /// String values decoded as calendar structs when they are ISO 8601.
+    pub datetimes: Datetimes,
+    /// Structs each document is decoded to.
+    pub schema: Option<Arc<Schema>>,
+}
+
+/// How object keys become atoms.
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AtomKeys {
+    /// Create atoms that do not exist yet (`keys: :atoms`).
+    Create,
+    /// Only accept keys naming existing atoms (`keys: :atoms!`).
+    Existing,
+}
+
+/// Whether strings that need no unescaping may be sub-binaries of the
+/// input (`strings:`). A sub-binary avoids a copy but keeps the whole
+/// input binary alive for as long as the string is.
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strings {
+    /// Sub-binaries from `subbinary_threshold` bytes up.
+    Reference,
+    /// Always copy, so no result keeps the input alive.
+    Copy,
+    /// Copy until the strings eligible for sub-binaries make up a
+    /// significant share of the input, then reference.
+    Auto,
+}
+
+/// How numbers are decoded (`numbers:`).
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Numbers {
+    /// Integers, and floats or Decimals as `floats_decimals` says.
+    Native,
+    /// A `RustyJson.Fragment` holding the number's text, which encodes back
+    /// to the same bytes.
+    Raw,
+    /// The number's text as a binary.
+    Strings,
+    /// A `Decimal` for integers as well as floats.
+    Decimals,
+}
+
+/// Which string values are decoded as `Date`, `Time`, `NaiveDateTime` or
+/// `DateTime` when they hold ISO 8601 text (`datetimes:`).
+#[derive(Clone, Debug, Default)]
+pub enum Datetimes {
+    /// None; every string stays a binary.
+    #[default]
+    Off,
+    /// Every string value.
+    All,
+    /// Values of object members with these unescaped keys, as written in
+    /// the input.
+    Keys(Arc<HashSet<Vec<u8>>>),
+}
+
+/// What to do when an object repeats a key.
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// The last value wins (`duplicate_keys: :last`).
+    Last,
+    /// The first value wins.
+    First,
+    /// The values are merged into a list, in input order.
+    Collect,
+    /// The object is rejected.
+    Error,
+}
+
+/// What to do with lone surrogate escapes (`"\ud800"`) and, when
+/// `validate_strings` is on, invalid UTF-8 in strings.
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidUnicode {
+    /// Reject the document.
+    Error,
+    /// Write U+FFFD in place of each lone surrogate and each maximal invalid
+    /// byte sequence, counting them in `DecodeStats::replacements`.
+    Replace,
+    /// Keep invalid bytes as they are and write lone surrogates in their
+    /// WTF-8 form (the three-byte encoding of the surrogate code point).
+    Keep,
+}
+
+/// Facts about a finished decode, returned by `json_to_term_with_stats`.
+#[derive(NifMap, Clone, Copy, Debug, Default)]
+pub struct DecodeStats {
+    /// U+FFFD characters written by `InvalidUnicode::Replace`.
+    pub replacements: usize,
+}
+
+/// JSON5-style syntax extensions accepted by relaxed decoding, one flag each.
+#[derive(NifMap, Clone, Copy, Debug, Default)]
+pub struct RelaxedSyntax {
+    /// `// line` and `/* block */` comments wherever whitespace is allowed.
+    pub comments: bool,
+    /// A `,` after the last array element or object member.
+    pub trailing_commas: bool,
+    /// `'single-quoted'` strings and keys; `\'` escapes a quote.
+    pub single_quotes: bool,
+    /// Object keys written as ASCII identifiers (`[A-Za-z_$][A-Za-z0-9_$]*`).
+    pub unquoted_keys: bool,
+    /// Hexadecimal integers such as `0x1F` and `-0xff`.
+    pub hex_numbers: bool,
+    /// A `+` sign in front of numbers.
+    pub leading_plus: bool,
+    /// Atoms returned for `NaN`, `Infinity` and `-Infinity`; `None` rejects them.
+    pub non_finite: Option<NonFinite>,
+}
+
+/// Atoms that non-finite numbers decode to.
+#[derive(NifTuple, Clone, Copy, Debug)]
+pub struct NonFinite {
+    pub nan: Atom,
+    pub infinity: Atom,
+    pub neg_infinity: Atom,
 }
 
 impl Default for DecodeOptions {
     fn default() -> Self {
         Self {
             intern_keys: false,
+            strings: Strings::Reference,
+            subbinary_threshold: SUBBINARY_THRESHOLD,
             floats_decimals: false,
+            numbers: Numbers::Native,
             ordered_objects: false,
             integer_digit_limit: 1024,
             max_bytes: 0,
-            reject_duplicate_keys: false,
+            duplicate_keys: DuplicateKeys::Last,
             validate_strings: true,
+            invalid_unicode: InvalidUnicode::Error,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_bytes: 0,
+            max_array_elements: 0,
+            max_object_keys: 0,
+            max_total_values: 0,
+            max_fraction_digits: 0,
+            max_exponent_digits: 0,
+            atom_keys: None,
+            max_new_atoms: 0,
+            key_case: None,
+            integer_keys: false,
+            projection: None,
+            relaxed: None,
+            datetimes: Datetimes::Off,
+            schema: None,
         }
     }
 }
 
+impl DecodeOptions {
+    /// Whether key terms go through the parser's key cache: interning, or keys
+    /// that take more than a copy to build.
+    fn caches_keys(&self) -> bool {
+        self.intern_keys || self.atom_keys.is_some() || self.key_case.is_some() || self.integer_keys
+    }
+}
+
 // ============================================================================
 // FNV-1a Hasher - fast non-cryptographic hash for key interning
 // ============================================================================
@@ -319,17 +735,72 @@ impl BuildHasher for FnvBuildHasher {
 
 type FastHashMap<K, V> = HashMap<K, V, FnvBuildHasher>;
 
+/// Projection node for a parser's top-level value.
+#[inline]
+fn root_projection(opts: &DecodeOptions) -> Option<u32> {
+    opts.projection.as_ref().map(|_| Projection::ROOT)
+}
+
+/// Schema target for the root value, if `schema` is set.
+fn root_target(opts: &DecodeOptions) -> Option<Target> {
+    opts.schema.as_ref().map(|schema| schema.root())
+}
+
 /// Cached key shape from the first object in an array.
 /// When an array contains multiple objects with the same keys in the same order,
 /// we can reuse the key Terms from the first object instead of rebuilding them.
-struct KeyShape<'a, 'b> {
-    raw_keys: Vec<&'b [u8]>,  // raw byte slices for comparison
-    key_terms: Vec<Term<'a>>, // reusable key Terms
-    is_flat: bool,            // true if first value was a scalar (no nested containers)
+pub(crate) struct KeyShape<'a, 'b> {
+    pub(crate) raw_keys: Vec<&'b [u8]>, // raw byte slices for comparison
+    pub(crate) key_terms: Vec<Term<'a>>, // reusable key Terms
+    pub(crate) is_flat: bool,           // true if first value was a scalar (no nested containers)
+    /// Per-raw-key projection actions; empty when no projection applies.
+    /// With a projection, `key_terms` holds only the kept keys.
+    pub(crate) actions: Vec<Action>,
+    /// Per-raw-key struct fields; empty when no schema applies. Struct
+    /// keys come from the schema, so `key_terms` is empty then.
+    pub(crate) fields: Vec<Option<Field>>,
+}
+
+/// Key and default value terms of a schema struct, loaded on first use.
+struct StructTerms<'a> {
+    keys: Vec<Term<'a>>,
+    defaults: Vec<Term<'a>>,
+}
+
+/// The discriminator member of a union object.
+struct Discriminator<'b> {
+    /// The unescaped value, or `None` when it is not a string.
+    value: Option<Cow<'b, [u8]>>,
+    /// Start and end of the value's JSON text.
+    start: usize,
+    end: usize,
+}
+
+/// Where the discriminator of an object is, as found by
+/// `index_discriminators`.
+#[derive(Clone, Copy)]
+struct DiscriminatorSpan {
+    /// Start and end of the JSON text of the value `duplicate_keys` selects.
+    start: usize,
+    end: usize,
+    /// Start of the second member with the key, if the object repeats it.
+    repeated: Option<usize>,
+}
+
+/// The discriminators of the objects within `span` that have a `key`
+/// member, by object start.
+struct UnionIndex {
+    key: Box<[u8]>,
+    span: Range<usize>,
+    found: FastHashMap<usize, DiscriminatorSpan>,
 }
 
 /// Direct JSON-to-Term parser - builds Erlang terms during parsing without intermediate representation
-pub struct DirectParser<'a, 'b> {
+///
+/// `RELAXED` selects the JSON5-style parser (`new_relaxed`). Every relaxed
+/// branch is guarded by the constant, so the strict parser compiles without
+/// them.
+pub struct DirectParser<'a, 'b, const RELAXED: bool = false> {
     input: &'b [u8],
     pos: usize,
     depth: usize,
@@ -338,13 +809,45 @@ pub struct DirectParser<'a, 'b> {
     /// references for non-escaped strings instead of allocating + copying.
     input_binary: Binary<'a>,
     /// Optional key cache for interning repeated object keys.
-    /// Only allocated when `intern_keys=true`.
+    /// Only allocated when `intern_keys=true` or keys are converted.
     key_cache: Option<FastHashMap<&'b [u8], Term<'a>>>,
     /// Decode options controlling behavior
     opts: DecodeOptions,
     /// Optional structural index for fast whitespace skipping.
     /// Only built for inputs >= STRUCTURAL_INDEX_THRESHOLD bytes.
     structural_index: Option<StructuralIndex>,
+    /// Projection node restricting the value being parsed, or `None` when
+    /// every member is decoded.
+    proj: Option<u32>,
+    /// Schema target of the value about to be parsed. Taken by the next
+    /// object or array, so nested values see `None` unless a schema loop
+    /// sets it for them.
+    target: Option<Target>,
+    /// Terms of the schema's structs, indexed by node.
+    struct_terms: Vec<Option<StructTerms<'a>>>,
+    /// Schema violations in the document so far.
+    violations: Vec<Violation>,
+    /// The violations of a document rejected for them, until `failure`
+    /// takes them.
+    rejected: Vec<Violation>,
+    /// Values seen so far, checked against `max_total_values`.
+    values: usize,
+    /// Atoms created for keys so far, checked against `max_new_atoms`.
+    new_atoms: usize,
+    /// Invalid sequences replaced so far under `InvalidUnicode::Replace`.
+    replacements: usize,
+    /// Bytes of the strings so far that were long enough for a sub-binary,
+    /// weighed against the input length under `Strings::Auto`.
+    shareable_bytes: usize,
+    /// End of the last key listed in `Datetimes::Keys`, or `None` when the
+    /// last key is not listed.
+    datetime_key_end: Option<usize>,
+    /// Item being parsed at each depth (index 0 unused), grown on demand.
+    /// Slots of containers that have closed are left stale; `error_path`
+    /// tells them apart by position.
+    path: Vec<PathSlot>,
+    /// Discriminators found for schema unions, one index per union key.
+    union_index: Vec<UnionIndex>,
 }
 
 impl<'a, 'b> DirectParser<'a, 'b> {
@@ -355,7 +858,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         input_binary: Binary<'a>,
         opts: DecodeOptions,
     ) -> Self {
-        let key_cache = if opts.intern_keys {
+        let key_cache = if opts.caches_keys() {
             Some(FastHashMap::with_capacity_and_hasher(
                 32,
                 FnvBuildHasher::new(),
@@ -375,22 +878,233 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             env,
             input_binary,
             key_cache,
+            proj: root_projection(&opts),
+            target: root_target(&opts),
+            struct_terms: Vec::new(),
+            violations: Vec::new(),
+            rejected: Vec::new(),
             opts,
             structural_index,
+            values: 0,
+            new_atoms: 0,
+            replacements: 0,
+            shareable_bytes: 0,
+            datetime_key_end: None,
+            path: Vec::new(),
+            union_index: Vec::new(),
+        }
+    }
+
+    /// Create a parser that resumes at byte `pos` with nesting `depth`.
+    ///
+    /// No structural index is built: resumable callers touch only a window of
+    /// the input per call, so indexing the whole input each time would cost
+    /// more than it saves.
+    pub(crate) fn resume(
+        env: Env<'a>,
+        input: &'b [u8],
+        input_binary: Binary<'a>,
+        opts: DecodeOptions,
+        pos: usize,
+        depth: usize,
+    ) -> Self {
+        let key_cache = if opts.caches_keys() {
+            Some(FastHashMap::with_capacity_and_hasher(
+                32,
+                FnvBuildHasher::new(),
+            ))
+        } else {
+            None
+        };
+        Self {
+            input,
+            pos,
+            depth,
+            env,
+            input_binary,
+            key_cache,
+            proj: root_projection(&opts),
+            target: root_target(&opts),
+            struct_terms: Vec::new(),
+            violations: Vec::new(),
+            rejected: Vec::new(),
+            opts,
+            structural_index: None,
+            values: 0,
+            new_atoms: 0,
+            replacements: 0,
+            shareable_bytes: 0,
+            datetime_key_end: None,
+            path: Vec::new(),
+            union_index: Vec::new(),
+        }
+    }
+}
+
+impl<'a, 'b> DirectParser<'a, 'b, true> {
+    /// Create a parser that accepts the extensions enabled in `opts.relaxed`.
+    ///
+    /// No structural index is built: comments and single-quoted strings would
+    /// hide or invent structural characters.
+    pub fn new_relaxed(
+        env: Env<'a>,
+        input: &'b [u8],
+        input_binary: Binary<'a>,
+        opts: DecodeOptions,
+    ) -> Self {
+        let key_cache = if opts.caches_keys() {
+            Some(FastHashMap::with_capacity_and_hasher(
+                32,
+                FnvBuildHasher::new(),
+            ))
+        } else {
+            None
+        };
+        Self {
+            input,
+            pos: 0,
+            depth: 0,
+            env,
+            input_binary,
+            key_cache,
+            proj: root_projection(&opts),
+            target: root_target(&opts),
+            struct_terms: Vec::new(),
+            violations: Vec::new(),
+            rejected: Vec::new(),
+            opts,
+            structural_index: None,
+            values: 0,
+            new_atoms: 0,
+            replacements: 0,
+            shareable_bytes: 0,
+            datetime_key_end: None,
+            path: Vec::new(),
+            union_index: Vec::new(),
         }
     }
+}
 
+impl<'a, 'b, const RELAXED: bool> DirectParser<'a, 'b, RELAXED> {
     #[inline]
     pub fn parse(mut self) -> Result<Term<'a>, DecodeError> {
+        self.parse_document()
+    }
+
+    /// Like `parse`, also returning the `DecodeStats` of the document, and
+    /// on failure the path of the value being parsed.
+    pub fn parse_with_stats(mut self) -> Result<(Term<'a>, DecodeStats), DecodeFailure> {
+        match self.parse_document() {
+            Ok(term) => {
+                let stats = DecodeStats {
+                    replacements: self.replacements,
+                };
+                Ok((term, stats))
+            }
+            Err(error) => Err(self.failure(error, 0)),
+        }
+    }
+
+    /// Parse one complete document occupying exactly `input[start..end]`.
+    ///
+    /// Lets a single parser decode many documents out of one buffer (e.g.
+    /// NDJSON lines) so the key cache is shared between them. Intended for
+    /// parsers built with `resume`: a structural index would be consumed out
+    /// of order. Error positions are relative to the whole input.
+    pub(crate) fn parse_range(
+        &mut self,
+        start: usize,
+        end: usize,
+    ) -> Result<Term<'a>, DecodeError> {
+        let input = self.input;
+        self.input = &input[..end];
+        self.pos = start;
+        self.depth = 0;
+        self.values = 0;
+        self.proj = root_projection(&self.opts);
+        self.target = root_target(&self.opts);
+        let result = self.parse_document();
+        self.input = input;
+        result
+    }
+
+    /// Skip whitespace and RFC 7464 record separators before the next of
+    /// several concatenated documents. Returns whether any input remains.
+    pub(crate) fn skip_separators(&mut self) -> bool {
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(RECORD_SEPARATOR) => self.advance(),
+                Some(_) => return true,
+                None => return false,
+            }
+        }
+    }
+
+    /// Parse the document at the current position and stop after the
+    /// whitespace that follows it. Unlike `parse`, more input may follow:
+    /// `position` is then where the next document, if any, begins. Call
+    /// `skip_separators` first.
+    pub(crate) fn parse_next(&mut self) -> Result<Term<'a>, DecodeError> {
+        self.depth = 0;
+        self.values = 0;
+        self.proj = root_projection(&self.opts);
+        self.target = root_target(&self.opts);
+        self.violations.clear();
+        self.count_value()?;
+        let term = self.parse_value()?;
+        if !self.violations.is_empty() {
+            return Err(self.reject());
+        }
+        self.skip_whitespace();
+        Ok(term)
+    }
+
+    #[inline(always)]
+    fn parse_document(&mut self) -> Result<Term<'a>, DecodeError> {
+        self.violations.clear();
         self.skip_whitespace();
+        self.count_value()?;
         let term = self.parse_value()?;
         self.skip_whitespace();
         if self.pos < self.input.len() {
-            return Err((Cow::Borrowed("Unexpected trailing characters"), self.pos));
+            return Err(Syntax::TrailingCharacters.at(self.pos));
+        }
+        if !self.violations.is_empty() {
+            return Err(self.reject());
         }
         Ok(term)
     }
 
+    /// Error for a document that broke its schema, at its first violation.
+    /// The violations move to `rejected` for `failure` to report.
+    #[cold]
+    fn reject(&mut self) -> DecodeError {
+        self.rejected = std::mem::take(&mut self.violations);
+        DecodeError::new(
+            DecodeErrorKind::SchemaViolation,
+            "Schema validation failed",
+            self.rejected[0].pos,
+        )
+    }
+
+    /// The `DecodeFailure` for `error` in the document that starts at
+    /// `doc_start`: its schema violations if `error` rejected it for them,
+    /// with the path of the first, or else the error path.
+    #[cold]
+    pub(crate) fn failure(&mut self, error: DecodeError, doc_start: usize) -> DecodeFailure {
+        let violations = std::mem::take(&mut self.rejected);
+        let path = match violations.first() {
+            Some(first) => first.path.clone(),
+            None => self.error_path(doc_start),
+        };
+        DecodeFailure {
+            error,
+            path,
+            violations,
+        }
+    }
+
     #[inline(always)]
     fn peek(&self) -> Option<u8> {
         self.input.get(self.pos).copied()
@@ -413,6 +1127,10 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 _ => break,
             }
         }
+
+        if RELAXED {
+            self.skip_comments();
+        }
     }
 
     /// Jump self.pos to the next structural character position.
@@ -486,8 +1204,132 @@ impl<'a, 'b> DirectParser<'a, 'b> {
     }
 
     #[inline(always)]
-    fn err(&self, msg: &'static str) -> DecodeError {
-        (Cow::Borrowed(msg), self.pos)
+    fn err(&self, kind: DecodeErrorKind, msg: &'static str) -> DecodeError {
+        DecodeError::new(kind, msg, self.pos)
+    }
+
+    #[inline(always)]
+    fn syntax(&self, error: Syntax) -> DecodeError {
+        error.at(self.pos)
+    }
+
+    /// Error for a container opened past `max_depth`.
+    #[cold]
+    fn depth_error(&self) -> DecodeError {
+        let msg = format!("Nesting depth exceeds maximum of {}", self.opts.max_depth);
+        DecodeError::new(DecodeErrorKind::DepthExceeded, msg, self.pos)
+    }
+
+    /// Count the array element starting at the current position, `index`
+    /// elements having come before it.
+    #[inline(always)]
+    fn count_element(&mut self, index: usize) -> Result<(), DecodeError> {
+        self.track(PathSlot {
+            pos: self.pos,
+            index: Some(index),
+        });
+        let limit = self.opts.max_array_elements;
+        if limit > 0 && index >= limit {
+            return Err(quota_error("array", limit, "element", self.pos));
+        }
+        self.count_value()
+    }
+
+    /// Count the object member whose key starts at the current position,
+    /// `index` members having come before it.
+    #[inline(always)]
+    fn count_member(&mut self, index: usize) -> Result<(), DecodeError> {
+        self.track(PathSlot {
+            pos: self.pos,
+            index: None,
+        });
+        let limit = self.opts.max_object_keys;
+        if limit > 0 && index >= limit {
+            return Err(quota_error("object", limit, "key", self.pos));
+        }
+        self.count_value()
+    }
+
+    /// Record the item the current container is parsing.
+    #[inline(always)]
+    fn track(&mut self, slot: PathSlot) {
+        match self.path.get_mut(self.depth) {
+            Some(current) => *current = slot,
+            None => self.grow_path(slot),
+        }
+    }
+
+    #[cold]
+    fn grow_path(&mut self, slot: PathSlot) {
+        self.path.resize(self.depth + 1, PathSlot::default());
+        self.path[self.depth] = slot;
+    }
+
+    /// Path of the value being parsed when an error left the parser at its
+    /// current depth, in the document that starts at `doc_start`. A slot is
+    /// current only if its item starts after the parent's item; anything
+    /// older belongs to a container that has closed, or to an earlier
+    /// document.
+    #[cold]
+    pub(crate) fn error_path(&mut self, doc_start: usize) -> Vec<PathSegment> {
+        let mut segments = Vec::new();
+        let mut parent_pos = doc_start;
+        for depth in 1..=self.depth.min(self.path.len().saturating_sub(1)) {
+            let slot = self.path[depth];
+            if slot.pos <= parent_pos {
+                break;
+            }
+            parent_pos = slot.pos;
+            match slot.index {
+                Some(index) => segments.push(PathSegment::Index(index)),
+                None => match self.key_at(slot.pos) {
+                    Some(key) => segments.push(PathSegment::Key(key)),
+                    None => break,
+                },
+            }
+        }
+        segments
+    }
+
+    /// The unescaped key starting at `pos`, or `None` if it cannot be read.
+    #[cold]
+    fn key_at(&mut self, pos: usize) -> Option<String> {
+        self.pos = pos;
+        let bytes = match self.peek()? {
+            b'"' => self.read_string().ok()?,
+            b'\'' if RELAXED => self.scan_single_quoted().ok()?,
+            _ if RELAXED => {
+                let len = self.input[pos..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_' || **b == b'$')
+                    .count();
+                Cow::Borrowed(&self.input[pos..pos + len])
+            }
+            _ => return None,
+        };
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    #[inline(always)]
+    fn count_value(&mut self) -> Result<(), DecodeError> {
+        let limit = self.opts.max_total_values;
+        if limit > 0 {
+            if self.values >= limit {
+                return Err(quota_error("document", limit, "value", self.pos));
+            }
+            self.values += 1;
+        }
+        Ok(())
+    }
+
+    /// Check a string's raw length against `max_string_bytes`.
+    #[inline(always)]
+    fn check_string_bytes(&self, len: usize, string_start: usize) -> Result<(), DecodeError> {
+        let limit = self.opts.max_string_bytes;
+        if limit > 0 && len > limit {
+            return Err(quota_error("string", limit, "byte", string_start));
+        }
+        Ok(())
     }
 
     /// Optimized value parser for flat objects (no nested containers).
@@ -517,8 +1359,9 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             Some(b'[') => self.parse_array(),
             Some(b'{') => self.parse_object(),
             Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
-            Some(_) => Err(self.err("Unexpected character")),
-            None => Err(self.err("Unexpected end of input")),
+            Some(_) if RELAXED => self.parse_relaxed_scalar(),
+            Some(_) => Err(self.syntax(Syntax::UnexpectedCharacter)),
+            None => Err(self.syntax(Syntax::UnexpectedEnd)),
         }
     }
 
@@ -528,7 +1371,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             self.pos += 4;
             Ok(atom::nil().encode(self.env))
         } else {
-            Err(self.err("Expected 'null'"))
+            Err(self.syntax(Syntax::ExpectedNull))
         }
     }
 
@@ -538,7 +1381,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             self.pos += 4;
             Ok(true.encode(self.env))
         } else {
-            Err(self.err("Expected 'true'"))
+            Err(self.syntax(Syntax::ExpectedTrue))
         }
     }
 
@@ -548,7 +1391,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             self.pos += 5;
             Ok(false.encode(self.env))
         } else {
-            Err(self.err("Expected 'false'"))
+            Err(self.syntax(Syntax::ExpectedFalse))
         }
     }
 
@@ -578,57 +1421,68 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             match self.peek() {
                 Some(b'"') => {
                     let end = self.pos;
+                    self.check_string_bytes(end - start, string_start)?;
                     self.advance(); // Skip closing quote
+                    if for_key {
+                        self.note_raw_key(start, end);
+                    }
 
                     // Escaped strings: decode and return (cannot intern - decoded
                     // bytes differ from input slice, and escaped keys are rare)
                     if has_escape {
                         let decoded = self
-                            .decode_escaped_string(start, end)
-                            .map_err(|msg| (msg, string_start))?;
-                        if self.opts.validate_strings
-                            && simdutf8::basic::from_utf8(&decoded).is_err()
-                        {
-                            return Err((Cow::Borrowed("Invalid UTF-8 in string"), string_start));
+                            .unescape_counted(start, end)
+                            .map_err(|e| e.at(string_start))?;
+                        let decoded = match self.check_utf8(&decoded, string_start)? {
+                            Some(replaced) => replaced,
+                            None => decoded,
+                        };
+                        if for_key {
+                            return self.key_term(&decoded, string_start);
+                        }
+                        if let Some(term) = self.datetime_value(&decoded, string_start)? {
+                            return Ok(term);
                         }
                         return Ok(encode_binary(self.env, &decoded));
                     }
 
                     let str_bytes = &self.input[start..end];
 
-                    // Optional UTF-8 validation for non-escaped strings
-                    if self.opts.validate_strings && simdutf8::basic::from_utf8(str_bytes).is_err()
-                    {
-                        return Err((Cow::Borrowed("Invalid UTF-8 in string"), string_start));
+                    // Optional UTF-8 validation for non-escaped strings. Replaced
+                    // strings are built like escaped ones.
+                    if let Some(replaced) = self.check_utf8(str_bytes, string_start)? {
+                        if for_key {
+                            return self.key_term(&replaced, string_start);
+                        }
+                        if let Some(term) = self.datetime_value(&replaced, string_start)? {
+                            return Ok(term);
+                        }
+                        return Ok(encode_binary(self.env, &replaced));
                     }
 
                     // Key interning: check cache if enabled and parsing a key.
-                    if for_key {
+                    // Converted keys always go through the cache, so each
+                    // distinct key is converted once.
+                    if for_key && self.key_cache.is_some() {
+                        if let Some(&cached) =
+                            self.key_cache.as_ref().and_then(|c| c.get(str_bytes))
+                        {
+                            return Ok(cached);
+                        }
+                        // For interned keys, we must copy (cache needs stable term).
+                        let term = self.key_term(str_bytes, string_start)?;
                         if let Some(ref mut cache) = self.key_cache {
-                            if let Some(&cached) = cache.get(str_bytes) {
-                                return Ok(cached);
-                            }
-                            // For interned keys, we must copy (cache needs stable term).
-                            let term = encode_binary(self.env, str_bytes);
                             if cache.len() < MAX_INTERN_KEYS {
                                 cache.insert(str_bytes, term);
                             }
-                            return Ok(term);
                         }
+                        return Ok(term);
                     }
 
-                    // For short strings, copying to a heap binary is faster than
-                    // sub-binary overhead. For longer strings (>=64 bytes),
-                    // zero-copy sub-binary avoids allocation + memcpy.
-                    let len = end - start;
-                    if len >= SUBBINARY_THRESHOLD {
-                        if let Ok(sub) = self.input_binary.make_subbinary(start, len) {
-                            return Ok(sub.to_term(self.env));
-                        }
-                        return Ok(encode_binary(self.env, str_bytes));
-                    } else {
-                        return Ok(encode_binary(self.env, str_bytes));
+                    if let Some(term) = self.datetime_value(str_bytes, string_start)? {
+                        return Ok(term);
                     }
+                    return Ok(self.input_term(start, end));
                 }
                 Some(b'\\') => {
                     has_escape = true;
@@ -639,16 +1493,83 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 }
                 // JSON spec: control characters (0x00-0x1F) must be escaped
                 Some(0x00..=0x1F) => {
-                    return Err(self.err("Unescaped control character"));
+                    return Err(self.err(
+                        DecodeErrorKind::ControlCharacter,
+                        "Unescaped control character",
+                    ));
                 }
                 Some(_) => self.advance(),
                 None => {
-                    return Err((Cow::Borrowed("Unterminated string"), string_start));
+                    return Err(Syntax::UnterminatedString.at(string_start));
                 }
             }
         }
     }
 
+    /// Build the term for an object key from its unescaped bytes: rewritten
+    /// in `key_case`, then an integer under `integer_keys`, an atom under
+    /// `atom_keys`, or else a copied binary. Integer keys are held to
+    /// `integer_digit_limit` like integer values.
+    fn key_term(&mut self, bytes: &[u8], key_start: usize) -> Result<Term<'a>, DecodeError> {
+        let converted;
+        let bytes = match self.opts.key_case {
+            Some(case) => {
+                converted = key_case::convert(case, bytes);
+                &converted[..]
+            }
+            None => bytes,
+        };
+        if self.opts.integer_keys && key_case::is_integer_key(bytes) {
+            let limit = self.opts.integer_digit_limit;
+            let digits = bytes.len() - usize::from(bytes[0] == b'-');
+            if limit > 0 && digits > limit {
+                let msg = format!("integer key exceeds {} digit limit", limit);
+                return Err(DecodeError::new(
+                    DecodeErrorKind::LimitExceeded,
+                    msg,
+                    key_start,
+                ));
+            }
+            if let Ok(i) = lexical_core::parse::<i64>(bytes) {
+                return Ok(i.encode(self.env));
+            }
+            if let Some(big) = BigInt::parse_bytes(bytes, 10) {
+                return Ok(big.encode(self.env));
+            }
+        }
+        let Some(mode) = self.opts.atom_keys else {
+            return Ok(encode_binary(self.env, bytes));
+        };
+        if let Some(atom) = existing_atom(self.env, bytes) {
+            return Ok(atom);
+        }
+        if mode == AtomKeys::Existing {
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidKey,
+                "Key is not an existing atom",
+                key_start,
+            ));
+        }
+        let limit = self.opts.max_new_atoms;
+        if limit > 0 && self.new_atoms >= limit {
+            let msg = format!("keys exceed {} new atom limit", limit);
+            return Err(DecodeError::new(
+                DecodeErrorKind::LimitExceeded,
+                msg,
+                key_start,
+            ));
+        }
+        let atom = new_atom(self.env, bytes).ok_or_else(|| {
+            DecodeError::new(
+                DecodeErrorKind::InvalidKey,
+                "Key cannot be converted to an atom",
+                key_start,
+            )
+        })?;
+        self.new_atoms += 1;
+        Ok(atom)
+    }
+
     /// Parse a string value (not interned)
     #[inline]
     fn parse_string(&mut self) -> Result<Term<'a>, DecodeError> {
@@ -661,22 +1582,79 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         self.parse_string_impl(true)
     }
 
-    /// Scan past a JSON string and return the raw bytes between the quotes.
-    /// Does not build a Term — used for shape-matching key comparison.
-    /// Returns the raw byte slice (between opening and closing quotes).
+    /// Record whether the key just parsed, unescaped to `key`, is listed in
+    /// `Datetimes::Keys`. Its value is matched by position in
+    /// `datetime_value`, so nothing needs resetting when the member ends.
     #[inline]
-    fn scan_string_raw(&mut self) -> Result<&'b [u8], DecodeError> {
-        let string_start = self.pos;
-        self.advance(); // Skip opening quote
-        let start = self.pos;
+    fn note_key(&mut self, key: &[u8]) {
+        if let Datetimes::Keys(keys) = &self.opts.datetimes {
+            self.datetime_key_end = keys.contains(key).then_some(self.pos);
+        }
+    }
 
-        // Use SIMD scanning to skip past plain bytes, same as parse_string_impl
+    /// `note_key` for the raw key bytes `input[start..end]`.
+    #[inline]
+    fn note_raw_key(&mut self, start: usize, end: usize) {
+        if !matches!(self.opts.datetimes, Datetimes::Keys(_)) {
+            return;
+        }
+        let raw = &self.input[start..end];
+        if !raw.contains(&b'\\') {
+            return self.note_key(raw);
+        }
+        match self.decode_escaped_string(start, end) {
+            Ok(key) => self.note_key(&key),
+            Err(_) => self.datetime_key_end = None,
+        }
+    }
+
+    /// The calendar struct for the string value `bytes` starting at
+    /// `string_start`, if `datetimes:` covers the value and it is ISO 8601.
+    /// Under `Datetimes::Keys` the value must directly follow a listed key.
+    #[inline]
+    fn datetime_value(
+        &self,
+        bytes: &[u8],
+        string_start: usize,
+    ) -> Result<Option<Term<'a>>, DecodeError> {
+        let wanted = match self.opts.datetimes {
+            Datetimes::Off => return Ok(None),
+            Datetimes::All => true,
+            Datetimes::Keys(_) => self.datetime_key_end.is_some_and(|end| {
+                self.input[end..string_start]
+                    .iter()
+                    .all(|&b| matches!(b, b':' | b' ' | b'\t' | b'\n' | b'\r'))
+            }),
+        };
+        let Some(value) = wanted.then(|| datetime::parse(bytes)).flatten() else {
+            return Ok(None);
+        };
+        value.to_term(self.env).map(Some).map_err(|_| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to create calendar struct",
+                string_start,
+            )
+        })
+    }
+
+    /// Scan past a JSON string and return the raw bytes between the quotes.
+    /// Does not build a Term — used for shape-matching key comparison.
+    /// Returns the raw byte slice (between opening and closing quotes).
+    #[inline]
+    fn scan_string_raw(&mut self) -> Result<&'b [u8], DecodeError> {
+        let string_start = self.pos;
+        self.advance(); // Skip opening quote
+        let start = self.pos;
+
+        // Use SIMD scanning to skip past plain bytes, same as parse_string_impl
         loop {
             crate::simd_utils::skip_plain_string_bytes(self.input, &mut self.pos);
 
             match self.peek() {
                 Some(b'"') => {
                     let end = self.pos;
+                    self.check_string_bytes(end - start, string_start)?;
                     self.advance(); // Skip closing quote
                     return Ok(&self.input[start..end]);
                 }
@@ -687,22 +1665,96 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     }
                 }
                 Some(0x00..=0x1F) => {
-                    return Err(self.err("Unescaped control character"));
+                    return Err(self.err(
+                        DecodeErrorKind::ControlCharacter,
+                        "Unescaped control character",
+                    ));
                 }
                 Some(_) => self.advance(),
                 None => {
-                    return Err((Cow::Borrowed("Unterminated string"), string_start));
+                    return Err(Syntax::UnterminatedString.at(string_start));
+                }
+            }
+        }
+    }
+
+    /// Term for the input bytes `start..end`, which need no unescaping: a
+    /// zero-copy sub-binary of the input when the `strings:` mode allows
+    /// one, otherwise a copy. Short strings are always copied, which is
+    /// faster than the sub-binary indirection.
+    #[inline]
+    fn input_term(&mut self, start: usize, end: usize) -> Term<'a> {
+        let len = end - start;
+        let share = len >= self.opts.subbinary_threshold
+            && match self.opts.strings {
+                Strings::Reference => true,
+                Strings::Copy => false,
+                Strings::Auto => {
+                    self.shareable_bytes += len;
+                    self.shareable_bytes * AUTO_REFERENCE_SHARE >= self.input_binary.len()
                 }
+            };
+        if share {
+            if let Ok(sub) = self.input_binary.make_subbinary(start, len) {
+                return sub.to_term(self.env);
             }
         }
+        encode_binary(self.env, &self.input[start..end])
+    }
+
+    /// Check `bytes` for invalid UTF-8 when `validate_strings` is on. Returns
+    /// a repaired copy under `InvalidUnicode::Replace`, or `None` when the
+    /// bytes can be used as they are.
+    #[inline]
+    fn check_utf8(
+        &mut self,
+        bytes: &[u8],
+        string_start: usize,
+    ) -> Result<Option<Vec<u8>>, DecodeError> {
+        if !self.opts.validate_strings
+            || self.opts.invalid_unicode == InvalidUnicode::Keep
+            || simdutf8::basic::from_utf8(bytes).is_ok()
+        {
+            return Ok(None);
+        }
+        if self.opts.invalid_unicode == InvalidUnicode::Error {
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidUtf8,
+                "Invalid UTF-8 in string",
+                string_start,
+            ));
+        }
+        let (replaced, count) = replace_invalid_utf8(bytes);
+        self.replacements += count;
+        Ok(Some(replaced))
+    }
+
+    /// Unescape `input[start..end]`, counting replaced lone surrogates in
+    /// `replacements`. Used where the result ends up in a term.
+    #[inline]
+    fn unescape_counted(&mut self, start: usize, end: usize) -> Result<Vec<u8>, DecodeError> {
+        let mut replaced = 0;
+        let result = self.unescape(start, end, &mut replaced)?;
+        self.replacements += replaced;
+        Ok(result)
     }
 
+    /// Unescape `input[start..end]` for lookups and checks that build no term.
     #[inline]
-    fn decode_escaped_string(
+    fn decode_escaped_string(&self, start: usize, end: usize) -> Result<Vec<u8>, DecodeError> {
+        self.unescape(start, end, &mut 0)
+    }
+
+    /// Unescape `input[start..end]`, handling lone surrogates according to
+    /// `invalid_unicode` and adding the ones replaced to `replaced`.
+    /// Errors are at position 0; callers place them with `DecodeError::at`.
+    #[inline]
+    fn unescape(
         &self,
         start: usize,
         end: usize,
-    ) -> Result<Vec<u8>, Cow<'static, str>> {
+        replaced: &mut usize,
+    ) -> Result<Vec<u8>, DecodeError> {
         let mut result = Vec::with_capacity(end - start);
         let mut i = start;
 
@@ -721,19 +1773,19 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     b'u' => {
                         // Need exactly 4 hex digits
                         if i + 4 >= end {
-                            return Err(Cow::Borrowed("Incomplete unicode escape"));
+                            return Err(escape_error("Incomplete unicode escape"));
                         }
                         let hex = &self.input[i + 1..i + 5];
                         // Validate all 4 are hex digits
                         if !hex.iter().all(|&b| b.is_ascii_hexdigit()) {
-                            return Err(Cow::Borrowed("Invalid unicode escape"));
+                            return Err(escape_error("Invalid unicode escape"));
                         }
                         // Validated hex ASCII is always valid UTF-8, so these
                         // conversions cannot fail; propagate instead of panicking.
                         let hex_str = std::str::from_utf8(hex)
-                            .map_err(|_| Cow::Borrowed("Invalid unicode escape"))?;
+                            .map_err(|_| escape_error("Invalid unicode escape"))?;
                         let cp = u16::from_str_radix(hex_str, 16)
-                            .map_err(|_| Cow::Borrowed("Invalid unicode escape"))?;
+                            .map_err(|_| escape_error("Invalid unicode escape"))?;
 
                         // Handle UTF-16 surrogate pairs
                         if (0xD800..=0xDBFF).contains(&cp) {
@@ -745,9 +1797,9 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                                 let hex2 = &self.input[i + 7..i + 11];
                                 if hex2.iter().all(|&b| b.is_ascii_hexdigit()) {
                                     let hex2_str = std::str::from_utf8(hex2)
-                                        .map_err(|_| Cow::Borrowed("Invalid unicode escape"))?;
+                                        .map_err(|_| escape_error("Invalid unicode escape"))?;
                                     let cp2 = u16::from_str_radix(hex2_str, 16)
-                                        .map_err(|_| Cow::Borrowed("Invalid unicode escape"))?;
+                                        .map_err(|_| escape_error("Invalid unicode escape"))?;
                                     if (0xDC00..=0xDFFF).contains(&cp2) {
                                         // Valid surrogate pair
                                         let full_cp = 0x10000
@@ -764,15 +1816,13 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                                     }
                                 }
                             }
-                            // Lone high surrogate - invalid
-                            return Err(Cow::Borrowed("Lone surrogate in string"));
+                            // Lone high surrogate
+                            self.lone_surrogate(cp, &mut result, replaced)?;
                         } else if (0xDC00..=0xDFFF).contains(&cp) {
-                            // Lone low surrogate - invalid
-                            return Err(Cow::Borrowed("Lone surrogate in string"));
-                        }
-
-                        // Regular BMP character
-                        if let Some(c) = char::from_u32(cp as u32) {
+                            // Lone low surrogate
+                            self.lone_surrogate(cp, &mut result, replaced)?;
+                        } else if let Some(c) = char::from_u32(cp as u32) {
+                            // Regular BMP character
                             let mut buf = [0u8; 4];
                             result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
                         }
@@ -780,10 +1830,8 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     }
                     c => {
                         // Invalid escape sequence - only the above are valid in JSON
-                        return Err(Cow::Owned(format!(
-                            "Invalid escape sequence: \\{}",
-                            c as char
-                        )));
+                        let msg = format!("Invalid escape sequence: \\{}", c as char);
+                        return Err(DecodeError::new(DecodeErrorKind::InvalidEscape, msg, 0));
                     }
                 }
                 i += 1;
@@ -807,13 +1855,47 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         Ok(result)
     }
 
+    /// Write the lone surrogate `cp` per `invalid_unicode`, or fail.
+    #[cold]
+    fn lone_surrogate(
+        &self,
+        cp: u16,
+        out: &mut Vec<u8>,
+        replaced: &mut usize,
+    ) -> Result<(), DecodeError> {
+        match self.opts.invalid_unicode {
+            InvalidUnicode::Error => Err(DecodeError::new(
+                DecodeErrorKind::LoneSurrogate,
+                "Lone surrogate in string",
+                0,
+            )),
+            InvalidUnicode::Replace => {
+                out.extend_from_slice(REPLACEMENT_CHARACTER);
+                *replaced += 1;
+                Ok(())
+            }
+            InvalidUnicode::Keep => {
+                out.extend_from_slice(&[
+                    0xE0 | (cp >> 12) as u8,
+                    0x80 | ((cp >> 6) & 0x3F) as u8,
+                    0x80 | (cp & 0x3F) as u8,
+                ]);
+                Ok(())
+            }
+        }
+    }
+
     /// Fast-path integer parser for homogeneous number arrays.
     /// Scans digits via direct slice indexing (no per-byte peek/advance),
     /// and parses small positive/negative integers inline (≤18 digits)
     /// without lexical_core overhead.
-    /// Falls back to parse_number for floats, leading zeros, digit limits, large numbers.
+    /// Falls back to parse_number for floats, leading zeros, digit limits, large numbers,
+    /// and `numbers:` modes other than `Native`.
     #[inline]
     fn parse_number_fast(&mut self) -> Result<Term<'a>, DecodeError> {
+        if self.opts.numbers != Numbers::Native {
+            return self.parse_number();
+        }
         let start = self.pos;
         let bytes = self.input;
         let mut pos = start;
@@ -878,17 +1960,25 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         } else if let Ok(u) = lexical_core::parse::<u64>(num_bytes) {
             Ok(u.encode(self.env))
         } else {
-            let num_str = std::str::from_utf8(num_bytes)
-                .map_err(|_| (Cow::Borrowed("Invalid number encoding"), start))?;
-            let big: BigInt = num_str
-                .parse()
-                .map_err(|_| (Cow::Borrowed("Invalid number"), start))?;
+            let num_str = std::str::from_utf8(num_bytes).map_err(|_| {
+                DecodeError::new(
+                    DecodeErrorKind::InvalidNumber,
+                    "Invalid number encoding",
+                    start,
+                )
+            })?;
+            let big: BigInt = num_str.parse().map_err(|_| {
+                DecodeError::new(DecodeErrorKind::InvalidNumber, "Invalid number", start)
+            })?;
             Ok(big.encode(self.env))
         }
     }
 
+    /// Scan a number token per the JSON grammar, advancing past it without
+    /// converting it. Enforces the digit limits. Returns `true` for
+    /// floats (fraction or exponent present).
     #[inline]
-    fn parse_number(&mut self) -> Result<Term<'a>, DecodeError> {
+    fn scan_number(&mut self) -> Result<bool, DecodeError> {
         let start = self.pos;
         let bytes = self.input;
         let len = bytes.len();
@@ -903,7 +1993,11 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         // Integer part - track digit count for digit limit
         let int_digit_start = pos;
         if pos >= len {
-            return Err((Cow::Borrowed("Invalid number"), start));
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidNumber,
+                "Invalid number",
+                start,
+            ));
         }
         match bytes[pos] {
             b'0' => pos += 1,
@@ -914,17 +2008,21 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     pos += 1;
                 }
             }
-            _ => return Err((Cow::Borrowed("Invalid number"), start)),
+            _ => {
+                return Err(DecodeError::new(
+                    DecodeErrorKind::InvalidNumber,
+                    "Invalid number",
+                    start,
+                ))
+            }
         }
         let int_digit_count = pos - int_digit_start;
 
         // Check integer digit limit
         let limit = self.opts.integer_digit_limit;
         if limit > 0 && int_digit_count > limit {
-            return Err((
-                Cow::Owned(format!("integer exceeds {} digit limit", limit)),
-                start,
-            ));
+            let msg = format!("integer exceeds {} digit limit", limit);
+            return Err(DecodeError::new(DecodeErrorKind::LimitExceeded, msg, start));
         }
 
         // Fractional part
@@ -932,12 +2030,21 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             is_float = true;
             pos += 1;
             if pos >= len || !bytes[pos].is_ascii_digit() {
-                return Err((Cow::Borrowed("Invalid number"), start));
+                return Err(DecodeError::new(
+                    DecodeErrorKind::InvalidNumber,
+                    "Invalid number",
+                    start,
+                ));
             }
+            let frac_digit_start = pos;
             crate::simd_utils::skip_ascii_digits(bytes, &mut pos);
             while pos < len && bytes[pos].is_ascii_digit() {
                 pos += 1;
             }
+            let limit = self.opts.max_fraction_digits;
+            if limit > 0 && pos - frac_digit_start > limit {
+                return Err(quota_error("fraction", limit, "digit", start));
+            }
         }
 
         // Exponent
@@ -948,25 +2055,52 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 pos += 1;
             }
             if pos >= len || !bytes[pos].is_ascii_digit() {
-                return Err((Cow::Borrowed("Invalid number"), start));
+                return Err(DecodeError::new(
+                    DecodeErrorKind::InvalidNumber,
+                    "Invalid number",
+                    start,
+                ));
             }
+            let exp_digit_start = pos;
             crate::simd_utils::skip_ascii_digits(bytes, &mut pos);
             while pos < len && bytes[pos].is_ascii_digit() {
                 pos += 1;
             }
+            let limit = self.opts.max_exponent_digits;
+            if limit > 0 && pos - exp_digit_start > limit {
+                return Err(quota_error("exponent", limit, "digit", start));
+            }
         }
 
         self.pos = pos;
+        Ok(is_float)
+    }
 
+    #[inline]
+    fn parse_number(&mut self) -> Result<Term<'a>, DecodeError> {
+        if RELAXED {
+            if let Some(term) = self.parse_relaxed_number()? {
+                return Ok(term);
+            }
+        }
+        let start = self.pos;
+        let is_float = self.scan_number()?;
         let num_bytes = &self.input[start..self.pos];
 
+        match self.opts.numbers {
+            Numbers::Native => {}
+            Numbers::Raw => return self.raw_number(start),
+            Numbers::Strings => return Ok(self.number_text(start)),
+            Numbers::Decimals => return self.parse_number_as_decimal(num_bytes, start),
+        }
         if is_float {
             if self.opts.floats_decimals {
                 return self.parse_number_as_decimal(num_bytes, start);
             }
             // Use lexical-core for fast float parsing
-            let f: f64 = lexical_core::parse(num_bytes)
-                .map_err(|_| (Cow::Borrowed("Invalid float"), start))?;
+            let f: f64 = lexical_core::parse(num_bytes).map_err(|_| {
+                DecodeError::new(DecodeErrorKind::InvalidNumber, "Invalid float", start)
+            })?;
             Ok(f.encode(self.env))
         } else {
             // Try i64 first using lexical-core
@@ -976,73 +2110,58 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 Ok(u.encode(self.env))
             } else {
                 // Parse as BigInt to preserve arbitrary precision (matches Jason behavior)
-                let num_str = std::str::from_utf8(num_bytes)
-                    .map_err(|_| (Cow::Borrowed("Invalid number encoding"), start))?;
-                let big: BigInt = num_str
-                    .parse()
-                    .map_err(|_| (Cow::Borrowed("Invalid number"), start))?;
+                let num_str = std::str::from_utf8(num_bytes).map_err(|_| {
+                    DecodeError::new(
+                        DecodeErrorKind::InvalidNumber,
+                        "Invalid number encoding",
+                        start,
+                    )
+                })?;
+                let big: BigInt = num_str.parse().map_err(|_| {
+                    DecodeError::new(DecodeErrorKind::InvalidNumber, "Invalid number", start)
+                })?;
                 Ok(big.encode(self.env))
             }
         }
     }
 
-    /// Parse a float number string into a %Decimal{} struct term
+    /// The text of the number from `start` to the current position, built
+    /// like a string.
+    fn number_text(&mut self, start: usize) -> Term<'a> {
+        self.input_term(start, self.pos)
+    }
+
+    /// Wrap the number's text in a `%RustyJson.Fragment{}`, which the
+    /// encoder writes back out unchanged.
+    fn raw_number(&mut self, start: usize) -> Result<Term<'a>, DecodeError> {
+        let env = self.env;
+        let keys = [
+            atoms::__struct__().to_term(env),
+            atoms::encode().to_term(env),
+        ];
+        let values = [
+            atoms::rustyjson_fragment().to_term(env),
+            self.number_text(start),
+        ];
+        Term::map_from_term_arrays(env, &keys, &values).map_err(|_| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to create Fragment struct",
+                start,
+            )
+        })
+    }
+
+    /// Parse a number string into a %Decimal{} struct term
     fn parse_number_as_decimal(
         &self,
         num_bytes: &[u8],
         start: usize,
     ) -> Result<Term<'a>, DecodeError> {
-        let num_str = std::str::from_utf8(num_bytes)
-            .map_err(|_| (Cow::Borrowed("Invalid number encoding"), start))?;
-
-        // Determine sign
-        let (sign_val, rest) = if let Some(stripped) = num_str.strip_prefix('-') {
-            (-1i64, stripped)
-        } else {
-            (1i64, num_str)
-        };
-
-        // Split into integer/fraction and exponent parts
-        let (mantissa_str, exp_part) = if let Some(e_pos) = rest.find(['e', 'E']) {
-            let exp_val = rest[e_pos + 1..]
-                .parse::<i64>()
-                .map_err(|_| (Cow::Borrowed("Invalid exponent in decimal number"), start))?;
-            (&rest[..e_pos], exp_val)
-        } else {
-            (rest, 0i64)
-        };
-
-        // Split mantissa into integer and fraction
-        let (coef_str, frac_len) = if let Some(dot_pos) = mantissa_str.find('.') {
-            let int_part = &mantissa_str[..dot_pos];
-            let frac_part = &mantissa_str[dot_pos + 1..];
-            let combined = format!("{}{}", int_part, frac_part);
-            (combined, frac_part.len() as i64)
-        } else {
-            (mantissa_str.to_string(), 0i64)
-        };
-
-        // Remove leading zeros from coefficient (but keep at least one digit)
-        let coef_str = coef_str.trim_start_matches('0');
-        let coef_str = if coef_str.is_empty() { "0" } else { coef_str };
-
-        let exp_val = exp_part - frac_len;
-
-        let env = self.env;
-
-        // Parse coef as integer (could be large)
-        let coef_term = if let Ok(c) = coef_str.parse::<i64>() {
-            c.encode(env)
-        } else if let Ok(c) = coef_str.parse::<u64>() {
-            c.encode(env)
-        } else {
-            use std::str::FromStr;
-            let big = num_bigint::BigInt::from_str(coef_str)
-                .map_err(|_| (Cow::Borrowed("Invalid decimal coefficient"), start))?;
-            big.encode(env)
-        };
+        let parts = decimal::parse_decimal(num_bytes).map_err(|e| e.at(start))?;
 
         // Build %Decimal{sign: sign, coef: coef, exp: exp} using pre-declared atoms
+        let env = self.env;
         let keys = [
             atoms::__struct__().to_term(env),
             atoms::coef().to_term(env),
@@ -1051,20 +2170,65 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         ];
         let values = [
             atoms::decimal_struct().to_term(env),
-            coef_term,
-            exp_val.encode(env),
-            sign_val.encode(env),
+            parts.coef.encode(env),
+            parts.exp.encode(env),
+            parts.sign.encode(env),
         ];
 
-        Term::map_from_term_arrays(env, &keys, &values)
-            .map_err(|_| (Cow::Borrowed("Failed to create Decimal struct"), start))
+        Term::map_from_term_arrays(env, &keys, &values).map_err(|_| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to create Decimal struct",
+                start,
+            )
+        })
     }
 
     #[inline]
     fn parse_array(&mut self) -> Result<Term<'a>, DecodeError> {
+        if RELAXED {
+            return self.parse_array_relaxed();
+        }
+        match self.target.take() {
+            Some(Target::List(node)) => return self.parse_struct_list(Target::Struct(node)),
+            Some(Target::UnionList(node)) => return self.parse_struct_list(Target::Union(node)),
+            Some(Target::Items(check)) => return self.parse_items(check),
+            _ => {}
+        }
+        match self.proj {
+            Some(node) => self.parse_array_projected(node),
+            None => self.parse_array_elements(),
+        }
+    }
+
+    /// Parse an array restricted by projection `node`. Every element gets
+    /// the same action: an array whose elements are all dropped decodes as
+    /// `[]`.
+    #[inline(never)]
+    fn parse_array_projected(&mut self, node: u32) -> Result<Term<'a>, DecodeError> {
+        let action = match self.opts.projection.as_deref() {
+            Some(projection) => projection.element_action(node),
+            None => Action::Keep(None),
+        };
+        match action {
+            Action::Skip => {
+                self.skip_array()?;
+                Ok(Term::list_new_empty(self.env))
+            }
+            Action::Keep(state) => {
+                self.proj = state;
+                let result = self.parse_array_elements();
+                self.proj = Some(node);
+                result
+            }
+        }
+    }
+
+    #[inline]
+    fn parse_array_elements(&mut self) -> Result<Term<'a>, DecodeError> {
         self.depth += 1;
-        if self.depth > MAX_DEPTH {
-            return Err(self.err("Nesting depth exceeds maximum"));
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
 
         self.consume_structural(); // Skip '['
@@ -1078,11 +2242,8 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         // Parse first element; if it's an object, capture its key shape
         let mut shape: Option<KeyShape<'a, 'b>> = None;
-        let first = if self.peek() == Some(b'{') {
-            self.parse_object_shaped(&mut shape)?
-        } else {
-            self.parse_value()?
-        };
+        self.count_element(0)?;
+        let first = self.parse_array_element(&mut shape, true)?;
         self.advance_to_structural();
 
         match self.peek() {
@@ -1095,7 +2256,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             Some(b',') => {
                 self.consume_structural_and_skip_ws();
             }
-            _ => return Err(self.err("Expected ',' or ']'")),
+            _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
         }
 
         // Multi-element: allocate Vec and continue
@@ -1113,21 +2274,13 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         };
 
         loop {
+            self.count_element(elements.len())?;
             let elem = match elem_class {
                 1 if matches!(self.peek(), Some(b'0'..=b'9') | Some(b'-')) => {
                     self.parse_number_fast()?
                 }
                 2 if self.peek() == Some(b'"') => self.parse_string()?,
-                _ => {
-                    if self.peek() == Some(b'{') && shape.is_some() {
-                        self.parse_object_shaped(&mut shape)?
-                    } else {
-                        if self.peek() != Some(b'{') {
-                            shape = None;
-                        }
-                        self.parse_value()?
-                    }
-                }
+                _ => self.parse_array_element(&mut shape, false)?,
             };
             elements.push(elem);
             self.advance_to_structural();
@@ -1140,7 +2293,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     self.consume_structural();
                     break;
                 }
-                _ => return Err(self.err("Expected ',' or ']'")),
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
             }
         }
 
@@ -1153,11 +2306,41 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         Ok(list)
     }
 
+    /// Parse one array element, sharing a `KeyShape` across same-shaped objects.
+    /// The shape is captured from the first element only; a non-object element
+    /// or a shape mismatch drops it for the rest of the array.
+    #[inline(always)]
+    fn parse_array_element(
+        &mut self,
+        shape: &mut Option<KeyShape<'a, 'b>>,
+        first: bool,
+    ) -> Result<Term<'a>, DecodeError> {
+        if self.peek() == Some(b'{') {
+            if first || shape.is_some() {
+                return self.parse_object_shaped(shape);
+            }
+        } else {
+            *shape = None;
+        }
+        self.parse_value()
+    }
+
     #[inline]
     fn parse_object(&mut self) -> Result<Term<'a>, DecodeError> {
+        if RELAXED {
+            return self.parse_object_relaxed();
+        }
+        match self.target.take() {
+            Some(Target::Struct(node)) => return self.parse_struct(node, None),
+            Some(Target::Union(node)) => return self.parse_union(node),
+            _ => {}
+        }
+        if let Some(node) = self.proj {
+            return self.parse_object_projected(node, None);
+        }
         self.depth += 1;
-        if self.depth > MAX_DEPTH {
-            return Err(self.err("Nesting depth exceeds maximum"));
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
 
         let obj_start = self.pos;
@@ -1175,15 +2358,16 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         // Parse first key-value pair on stack before allocating
         if self.peek() != Some(b'"') {
-            return Err(self.err("Expected string key"));
+            return Err(self.syntax(Syntax::ExpectedKey));
         }
+        self.count_member(0)?;
         let first_key_start = self.pos;
         let first_key = self.parse_key()?;
         let first_key_end = self.pos;
 
         self.advance_to_structural();
         if self.peek() != Some(b':') {
-            return Err(self.err("Expected ':'"));
+            return Err(self.syntax(Syntax::ExpectedColon));
         }
         self.consume_structural_and_skip_ws();
         // Capture first value's leading byte to detect flat objects
@@ -1200,13 +2384,20 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 if self.opts.ordered_objects {
                     return self.build_ordered_object(&[first_key], &[first_value], obj_start);
                 }
-                return Term::map_from_term_arrays(self.env, &[first_key], &[first_value])
-                    .map_err(|_| (Cow::Borrowed("Failed to create map"), obj_start));
+                return Term::map_from_term_arrays(self.env, &[first_key], &[first_value]).map_err(
+                    |_| {
+                        DecodeError::new(
+                            DecodeErrorKind::Unknown,
+                            "Failed to create map",
+                            obj_start,
+                        )
+                    },
+                );
             }
             Some(b',') => {
                 self.consume_structural_and_skip_ws();
             }
-            _ => return Err(self.err("Expected ',' or '}'")),
+            _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
         }
 
         // Multi-entry path: allocate Vecs and continue
@@ -1222,20 +2413,22 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         // Optional duplicate key tracking (only needed for multi-entry objects)
         let seen_cap = cap;
-        let mut seen_keys: Option<HashSet<&'b [u8]>> = if self.opts.reject_duplicate_keys {
-            let mut set = HashSet::with_capacity(seen_cap);
-            // Insert first key's raw bytes (between opening and closing quotes)
-            let raw_first = &self.input[first_key_start + 1..first_key_end - 1];
-            set.insert(raw_first);
-            Some(set)
-        } else {
-            None
-        };
+        let mut seen_keys: Option<HashSet<&'b [u8]>> =
+            if self.opts.duplicate_keys == DuplicateKeys::Error {
+                let mut set = HashSet::with_capacity(seen_cap);
+                // Insert first key's raw bytes (between opening and closing quotes)
+                let raw_first = &self.input[first_key_start + 1..first_key_end - 1];
+                set.insert(raw_first);
+                Some(set)
+            } else {
+                None
+            };
 
         loop {
             if self.peek() != Some(b'"') {
-                return Err(self.err("Expected string key"));
+                return Err(self.syntax(Syntax::ExpectedKey));
             }
+            self.count_member(keys.len())?;
             let key_start = self.pos;
             let key = self.parse_key()?;
 
@@ -1243,7 +2436,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             if let Some(ref mut seen) = seen_keys {
                 let raw_key = &self.input[key_start + 1..self.pos - 1];
                 if !seen.insert(raw_key) {
-                    return Err((Cow::Borrowed("Duplicate key in object"), self.pos));
+                    return Err(duplicate_key_error(raw_key, key_start));
                 }
             }
 
@@ -1251,7 +2444,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
             self.advance_to_structural();
             if self.peek() != Some(b':') {
-                return Err(self.err("Expected ':'"));
+                return Err(self.syntax(Syntax::ExpectedColon));
             }
             self.consume_structural_and_skip_ws();
 
@@ -1273,7 +2466,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     self.consume_structural();
                     break;
                 }
-                _ => return Err(self.err("Expected ',' or '}'")),
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
             }
         }
 
@@ -1302,6 +2495,14 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         &mut self,
         shape: &mut Option<KeyShape<'a, 'b>>,
     ) -> Result<Term<'a>, DecodeError> {
+        match self.target.take() {
+            Some(Target::Struct(node)) => return self.parse_struct(node, Some(shape)),
+            Some(Target::Union(node)) => return self.parse_union(node),
+            _ => {}
+        }
+        if let Some(node) = self.proj {
+            return self.parse_object_projected(node, Some(shape));
+        }
         if shape.is_none() {
             // First object: parse normally and capture shape
             return self.parse_object_capture_shape(shape);
@@ -1309,23 +2510,32 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         // Subsequent objects: try shape-matched fast path
         self.depth += 1;
-        if self.depth > MAX_DEPTH {
-            return Err(self.err("Nesting depth exceeds maximum"));
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
 
         let obj_start = self.pos;
         let saved_cursor = self.structural_index.as_ref().map(|idx| idx.cursor);
+        let saved_values = self.values;
         self.consume_structural(); // Skip '{'
         self.skip_whitespace();
 
         let cached = shape.as_ref().unwrap();
         let num_keys = cached.raw_keys.len();
 
-        // Macro to rewind both pos and structural index cursor on shape mismatch
+        // Macro to rewind pos, value count and structural index cursor on
+        // shape mismatch
         macro_rules! rewind_and_fallback {
-            ($self:ident, $shape:ident, $obj_start:ident, $saved_cursor:ident) => {{
+            (
+                $self:ident,
+                $shape:ident,
+                $obj_start:ident,
+                $saved_cursor:ident,
+                $saved_values:ident
+            ) => {{
                 $self.depth -= 1;
                 $self.pos = $obj_start;
+                $self.values = $saved_values;
                 if let Some(ref mut idx) = $self.structural_index {
                     idx.cursor = $saved_cursor.unwrap();
                 }
@@ -1345,7 +2555,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 return Ok(Term::map_new(self.env));
             }
             // Not empty — shape mismatch, fall back
-            rewind_and_fallback!(self, shape, obj_start, saved_cursor);
+            rewind_and_fallback!(self, shape, obj_start, saved_cursor, saved_values);
         }
 
         // Try to match each key against the shape
@@ -1357,26 +2567,28 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 // Expect comma between entries
                 if self.peek() != Some(b',') {
                     // Fewer keys than shape — mismatch
-                    rewind_and_fallback!(self, shape, obj_start, saved_cursor);
+                    rewind_and_fallback!(self, shape, obj_start, saved_cursor, saved_values);
                 }
                 self.consume_structural_and_skip_ws();
             }
 
             if self.peek() != Some(b'"') {
                 // Not a string key — mismatch
-                rewind_and_fallback!(self, shape, obj_start, saved_cursor);
+                rewind_and_fallback!(self, shape, obj_start, saved_cursor, saved_values);
             }
 
+            self.count_member(i)?;
             let raw_key = self.scan_string_raw()?;
 
             if raw_key != cached.raw_keys[i] {
                 // Key mismatch — abandon shape, reparse this object from scratch
-                rewind_and_fallback!(self, shape, obj_start, saved_cursor);
+                rewind_and_fallback!(self, shape, obj_start, saved_cursor, saved_values);
             }
+            self.note_raw_key(self.pos - 1 - raw_key.len(), self.pos - 1);
 
             self.advance_to_structural();
             if self.peek() != Some(b':') {
-                return Err(self.err("Expected ':'"));
+                return Err(self.syntax(Syntax::ExpectedColon));
             }
             self.consume_structural_and_skip_ws();
 
@@ -1398,9 +2610,9 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             }
             Some(b',') => {
                 // More keys than shape — mismatch
-                rewind_and_fallback!(self, shape, obj_start, saved_cursor);
+                rewind_and_fallback!(self, shape, obj_start, saved_cursor, saved_values);
             }
-            _ => return Err(self.err("Expected ',' or '}'")),
+            _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
         }
 
         self.depth -= 1;
@@ -1423,8 +2635,8 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         shape: &mut Option<KeyShape<'a, 'b>>,
     ) -> Result<Term<'a>, DecodeError> {
         self.depth += 1;
-        if self.depth > MAX_DEPTH {
-            return Err(self.err("Nesting depth exceeds maximum"));
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
 
         let obj_start = self.pos;
@@ -1439,6 +2651,8 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                 raw_keys: Vec::new(),
                 key_terms: Vec::new(),
                 is_flat: true,
+                actions: Vec::new(),
+                fields: Vec::new(),
             });
             if self.opts.ordered_objects {
                 return self.build_ordered_object(&[], &[], obj_start);
@@ -1448,8 +2662,9 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         // Parse first key-value pair
         if self.peek() != Some(b'"') {
-            return Err(self.err("Expected string key"));
+            return Err(self.syntax(Syntax::ExpectedKey));
         }
+        self.count_member(0)?;
         let first_key_start = self.pos;
         let first_key = self.parse_key()?;
         let first_key_end = self.pos;
@@ -1457,7 +2672,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
         self.advance_to_structural();
         if self.peek() != Some(b':') {
-            return Err(self.err("Expected ':'"));
+            return Err(self.syntax(Syntax::ExpectedColon));
         }
         self.consume_structural_and_skip_ws();
         // Capture first value's leading byte to detect flat objects
@@ -1475,17 +2690,26 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     raw_keys: vec![first_raw_key],
                     key_terms: vec![first_key],
                     is_flat,
+                    actions: Vec::new(),
+                    fields: Vec::new(),
                 });
                 if self.opts.ordered_objects {
                     return self.build_ordered_object(&[first_key], &[first_value], obj_start);
                 }
-                return Term::map_from_term_arrays(self.env, &[first_key], &[first_value])
-                    .map_err(|_| (Cow::Borrowed("Failed to create map"), obj_start));
+                return Term::map_from_term_arrays(self.env, &[first_key], &[first_value]).map_err(
+                    |_| {
+                        DecodeError::new(
+                            DecodeErrorKind::Unknown,
+                            "Failed to create map",
+                            obj_start,
+                        )
+                    },
+                );
             }
             Some(b',') => {
                 self.consume_structural_and_skip_ws();
             }
-            _ => return Err(self.err("Expected ',' or '}'")),
+            _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
         }
 
         // Multi-entry path
@@ -1499,18 +2723,20 @@ impl<'a, 'b> DirectParser<'a, 'b> {
         raw_keys.push(first_raw_key);
 
         let seen_cap = cap;
-        let mut seen_keys: Option<HashSet<&'b [u8]>> = if self.opts.reject_duplicate_keys {
-            let mut set = HashSet::with_capacity(seen_cap);
-            set.insert(first_raw_key);
-            Some(set)
-        } else {
-            None
-        };
+        let mut seen_keys: Option<HashSet<&'b [u8]>> =
+            if self.opts.duplicate_keys == DuplicateKeys::Error {
+                let mut set = HashSet::with_capacity(seen_cap);
+                set.insert(first_raw_key);
+                Some(set)
+            } else {
+                None
+            };
 
         loop {
             if self.peek() != Some(b'"') {
-                return Err(self.err("Expected string key"));
+                return Err(self.syntax(Syntax::ExpectedKey));
             }
+            self.count_member(keys.len())?;
             let key_start = self.pos;
             let key = self.parse_key()?;
             let key_end = self.pos;
@@ -1518,7 +2744,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
             if let Some(ref mut seen) = seen_keys {
                 if !seen.insert(raw_key) {
-                    return Err((Cow::Borrowed("Duplicate key in object"), self.pos));
+                    return Err(duplicate_key_error(raw_key, key_start));
                 }
             }
 
@@ -1527,7 +2753,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
 
             self.advance_to_structural();
             if self.peek() != Some(b':') {
-                return Err(self.err("Expected ':'"));
+                return Err(self.syntax(Syntax::ExpectedColon));
             }
             self.consume_structural_and_skip_ws();
 
@@ -1548,7 +2774,7 @@ impl<'a, 'b> DirectParser<'a, 'b> {
                     self.consume_structural();
                     break;
                 }
-                _ => return Err(self.err("Expected ',' or '}'")),
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
             }
         }
 
@@ -1570,126 +2796,2030 @@ impl<'a, 'b> DirectParser<'a, 'b> {
             raw_keys,
             key_terms: keys,
             is_flat,
+            actions: Vec::new(),
+            fields: Vec::new(),
         });
 
         result
     }
 
-    /// Build %RustyJson.OrderedObject{values: [{k, v}, ...]} preserving order.
-    /// Pass empty vecs for an empty ordered object.
-    fn build_ordered_object(
-        &self,
-        keys: &[Term<'a>],
-        values: &[Term<'a>],
-        pos: usize,
+    /// Object parser used while a projection restricts the current value.
+    ///
+    /// Each key is scanned raw and looked up in the projection before any term
+    /// is built: dropped members are passed over with `skip_value`, kept ones
+    /// are decoded under the projection node for their path. With `shape`
+    /// (array elements), behaves like `parse_object_shaped`: the first object
+    /// captures its raw keys, kept key terms and per-key actions, and later
+    /// objects with the same raw keys reuse all three.
+    #[inline(never)]
+    fn parse_object_projected(
+        &mut self,
+        node: u32,
+        mut shape: Option<&mut Option<KeyShape<'a, 'b>>>,
     ) -> Result<Term<'a>, DecodeError> {
-        let env = self.env;
+        if let Some(shape) = shape.as_deref_mut() {
+            if shape.is_some() {
+                if let Some(term) = self.parse_object_projected_shaped(node, shape)? {
+                    return Ok(term);
+                }
+                // Shape mismatch: the object was rewound and the shape dropped.
+                return self.parse_object_projected(node, None);
+            }
+        }
 
-        // Build list of {key, value} tuples in order
-        let mut list = Term::list_new_empty(env);
-        for i in (0..keys.len()).rev() {
-            let tuple = rustler::types::tuple::make_tuple(env, &[keys[i], values[i]]);
-            list = list.list_prepend(tuple);
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
 
-        let map_keys = [
-            atoms::__struct__().to_term(env),
-            atoms::values().to_term(env),
-        ];
-        let map_vals = [atoms::ordered_object_struct().to_term(env), list];
+        let obj_start = self.pos;
+        self.consume_structural(); // Skip '{'
+        self.skip_whitespace();
 
-        Term::map_from_term_arrays(env, &map_keys, &map_vals)
-            .map_err(|_| (Cow::Borrowed("Failed to create OrderedObject"), pos))
-    }
+        let capture = shape.is_some();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        let mut raw_keys: Vec<&'b [u8]> = Vec::new();
+        let mut actions = Vec::new();
+        let mut seen_keys: Option<HashSet<&'b [u8]>> =
+            if self.opts.duplicate_keys == DuplicateKeys::Error {
+                Some(HashSet::new())
+            } else {
+                None
+            };
 
-    /// Build a map handling duplicate keys with "last wins" semantics
-    #[cold]
-    fn build_map_with_duplicates(
-        &self,
-        keys: &[Term<'a>],
-        values: &[Term<'a>],
-        pos: usize,
-    ) -> Result<Term<'a>, DecodeError> {
-        // Extract key bytes and deduplicate
-        let mut key_map: HashMap<&'a [u8], usize> = HashMap::with_capacity(keys.len());
-        let mut final_keys = Vec::with_capacity(keys.len());
-        let mut final_values = Vec::with_capacity(keys.len());
+        if self.peek() == Some(b'}') {
+            self.consume_structural();
+        } else {
+            // Skipped members count against the quotas too.
+            let mut members = 0;
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Err(self.syntax(Syntax::ExpectedKey));
+                }
+                self.count_member(members)?;
+                members += 1;
+                let key_start = self.pos;
+                let raw_key = self.scan_string_raw()?;
+
+                if let Some(ref mut seen) = seen_keys {
+                    if !seen.insert(raw_key) {
+                        return Err(duplicate_key_error(raw_key, key_start));
+                    }
+                }
 
-        for (i, key) in keys.iter().enumerate() {
-            // Decode as Binary to get a slice into the BEAM heap (no allocation)
-            let key_bin: Binary = key
-                .decode()
-                .map_err(|_| (Cow::Borrowed("Failed to decode key"), pos))?;
-            let key_bytes = key_bin.as_slice();
+                let action = self.member_action(node, raw_key, key_start)?;
+                if matches!(action, Action::Keep(_)) {
+                    // Build the key term only for members that are kept.
+                    self.pos = key_start;
+                    keys.push(self.parse_key()?);
+                }
+                if capture {
+                    raw_keys.push(raw_key);
+                    actions.push(action);
+                }
 
-            if let Some(&existing_idx) = key_map.get(key_bytes) {
-                // Duplicate - update value at existing position
-                final_values[existing_idx] = values[i];
-            } else {
-                // New key
-                let idx = final_keys.len();
-                key_map.insert(key_bytes, idx);
-                final_keys.push(keys[i]);
-                final_values.push(values[i]);
+                self.advance_to_structural();
+                if self.peek() != Some(b':') {
+                    return Err(self.syntax(Syntax::ExpectedColon));
+                }
+                self.consume_structural_and_skip_ws();
+                if let Some(value) = self.parse_member_value(node, action)? {
+                    values.push(value);
+                }
+
+                self.advance_to_structural();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                    }
+                    Some(b'}') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+                }
             }
         }
 
-        Term::map_from_term_arrays(self.env, &final_keys, &final_values)
-            .map_err(|_| (Cow::Borrowed("Failed to create map"), pos))
-    }
-}
-
-#[inline(always)]
-fn encode_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Term<'a> {
-    let mut bin = NewBinary::new(env, bytes.len());
-    bin.as_mut_slice().copy_from_slice(bytes);
-    bin.into()
-}
-
-// ============================================================================
-// Benchmark helpers - feature-gated, excluded from production binary
-// ============================================================================
+        self.depth -= 1;
 
-#[cfg(feature = "bench")]
-pub mod bench_helpers {
-    /// Skip whitespace bytes (space, tab, newline, carriage return) starting at `pos`.
-    /// Returns the new position after all whitespace.
-    #[inline]
-    pub fn skip_whitespace(input: &[u8], mut pos: usize) -> usize {
-        while pos < input.len() {
-            match input[pos] {
-                b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
-                _ => break,
-            }
+        let result = self.build_object(&keys, &values, obj_start);
+        if let Some(shape) = shape {
+            *shape = Some(KeyShape {
+                raw_keys,
+                key_terms: keys,
+                is_flat: false,
+                actions,
+                fields: Vec::new(),
+            });
         }
-        pos
+        result
     }
 
-    /// Scan a JSON string starting at `pos` (which should point to the opening `"`).
-    /// Returns `(end_pos, has_escape)` where `end_pos` is one past the closing quote.
-    /// Uses the same SIMD fast-path as the real parser.
-    #[inline]
-    pub fn scan_string(input: &[u8], mut pos: usize) -> (usize, bool) {
-        if pos >= input.len() || input[pos] != b'"' {
-            return (pos, false);
+    /// Shape-matched fast path of `parse_object_projected`. Returns `Ok(None)`
+    /// after rewinding and dropping the shape when the object's raw keys
+    /// differ from the cached ones.
+    fn parse_object_projected_shaped(
+        &mut self,
+        node: u32,
+        shape: &mut Option<KeyShape<'a, 'b>>,
+    ) -> Result<Option<Term<'a>>, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
         }
-        pos += 1; // skip opening quote
-        let mut has_escape = false;
 
-        loop {
-            // SIMD bulk skip for plain bytes (portable SIMD)
-            crate::simd_utils::skip_plain_string_bytes(input, &mut pos);
+        let obj_start = self.pos;
+        let saved_cursor = self.structural_index.as_ref().map(|idx| idx.cursor);
+        let saved_values = self.values;
+        self.consume_structural(); // Skip '{'
+        self.skip_whitespace();
 
-            // Byte-at-a-time for remainder
-            if pos >= input.len() {
-                return (pos, has_escape);
-            }
-            match input[pos] {
-                b'"' => {
-                    pos += 1; // skip closing quote
-                    return (pos, has_escape);
+        macro_rules! rewind {
+            () => {{
+                self.depth -= 1;
+                self.pos = obj_start;
+                self.values = saved_values;
+                if let (Some(idx), Some(cursor)) = (self.structural_index.as_mut(), saved_cursor) {
+                    idx.cursor = cursor;
                 }
-                b'\\' => {
+                *shape = None;
+                return Ok(None);
+            }};
+        }
+
+        let Some(cached) = shape.as_ref() else {
+            rewind!()
+        };
+        let mut values = Vec::with_capacity(cached.key_terms.len());
+
+        for (i, (&expected, &action)) in cached.raw_keys.iter().zip(&cached.actions).enumerate() {
+            if i > 0 {
+                if self.peek() != Some(b',') {
+                    rewind!();
+                }
+                self.consume_structural_and_skip_ws();
+            }
+            if self.peek() != Some(b'"') {
+                rewind!();
+            }
+            self.count_member(i)?;
+            if self.scan_string_raw()? != expected {
+                rewind!();
+            }
+            self.note_raw_key(self.pos - 1 - expected.len(), self.pos - 1);
+
+            self.advance_to_structural();
+            if self.peek() != Some(b':') {
+                return Err(self.syntax(Syntax::ExpectedColon));
+            }
+            self.consume_structural_and_skip_ws();
+            if let Some(value) = self.parse_member_value(node, action)? {
+                values.push(value);
+            }
+            self.advance_to_structural();
+        }
+
+        match self.peek() {
+            Some(b'}') => {
+                self.consume_structural();
+            }
+            Some(b',') | Some(b'"') => rewind!(),
+            _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+        }
+
+        self.depth -= 1;
+
+        let key_terms = &cached.key_terms;
+        self.build_object(key_terms, &values, obj_start).map(Some)
+    }
+
+    /// Parse an object into the struct of schema `node`. Members the node
+    /// does not name are skipped without building terms, and slots no
+    /// member fills keep their defaults. Within an array, objects share a
+    /// `KeyShape` mapping each raw key to its field, as in
+    /// `parse_object_shaped`, so same-shaped objects skip the lookups.
+    fn parse_struct(
+        &mut self,
+        node: u32,
+        shape: Option<&mut Option<KeyShape<'a, 'b>>>,
+    ) -> Result<Term<'a>, DecodeError> {
+        let Some(schema) = self.opts.schema.clone() else {
+            return self.parse_object();
+        };
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+
+        let obj_start = self.pos;
+        self.consume_structural(); // Skip '{'
+        self.skip_whitespace();
+
+        let mut values = self.struct_defaults(&schema, node, obj_start)?;
+        let required = &schema.node(node).required;
+        let policy = self.opts.duplicate_keys;
+        let mut filled = match policy {
+            DuplicateKeys::First | DuplicateKeys::Error => vec![false; values.len()],
+            _ if !required.is_empty() => vec![false; values.len()],
+            _ => Vec::new(),
+        };
+        let capture = matches!(shape.as_deref(), Some(None));
+        let mut raw_keys = Vec::new();
+        let mut fields = Vec::new();
+
+        if self.peek() == Some(b'}') {
+            self.consume_structural();
+        } else {
+            let mut members = 0;
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Err(self.syntax(Syntax::ExpectedKey));
+                }
+                self.count_member(members)?;
+                let key_start = self.pos;
+                let raw_key = self.scan_string_raw()?;
+                self.note_raw_key(key_start + 1, self.pos - 1);
+
+                let cached = shape
+                    .as_deref()
+                    .and_then(Option::as_ref)
+                    .filter(|cached| cached.raw_keys.get(members) == Some(&raw_key))
+                    .map(|cached| cached.fields[members]);
+                let field = match cached {
+                    Some(field) => field,
+                    None => self.struct_field(&schema, node, raw_key, key_start)?,
+                };
+                if capture {
+                    raw_keys.push(raw_key);
+                    fields.push(field);
+                }
+                members += 1;
+
+                self.advance_to_structural();
+                if self.peek() != Some(b':') {
+                    return Err(self.syntax(Syntax::ExpectedColon));
+                }
+                self.consume_structural_and_skip_ws();
+
+                match field {
+                    None => self.skip_value()?,
+                    Some(field) => {
+                        let repeated = filled.get(field.slot) == Some(&true);
+                        if repeated && policy == DuplicateKeys::Error {
+                            return Err(duplicate_key_error(raw_key, key_start));
+                        }
+                        let value = match field.check {
+                            Some(check) => self.parse_checked(schema.check(check)),
+                            None => {
+                                self.target = field.target;
+                                let value = self.parse_value();
+                                self.target = None;
+                                value
+                            }
+                        };
+                        let value = value?;
+                        if !(repeated && policy == DuplicateKeys::First) {
+                            values[field.slot] = value;
+                        }
+                        if let Some(slot) = filled.get_mut(field.slot) {
+                            *slot = true;
+                        }
+                    }
+                }
+
+                self.advance_to_structural();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                    }
+                    Some(b'}') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+                }
+            }
+        }
+
+        for (slot, key) in required {
+            if !filled[*slot] {
+                let message = format!("missing required key \"{}\"", shown_text(key));
+                self.violation(obj_start, message, self.depth - 1);
+            }
+        }
+        self.depth -= 1;
+
+        if capture {
+            if let Some(shape) = shape {
+                *shape = Some(KeyShape {
+                    raw_keys,
+                    key_terms: Vec::new(),
+                    is_flat: false,
+                    actions: Vec::new(),
+                    fields,
+                });
+            }
+        }
+
+        let keys = match &self.struct_terms[node as usize] {
+            Some(terms) => &terms.keys,
+            None => {
+                return Err(DecodeError::new(
+                    DecodeErrorKind::Unknown,
+                    "Failed to create struct",
+                    obj_start,
+                ))
+            }
+        };
+        Term::map_from_term_arrays(self.env, keys, &values).map_err(|_| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to create struct",
+                obj_start,
+            )
+        })
+    }
+
+    /// Parse an object into the struct that the discriminator of schema
+    /// union `node` selects. The object is scanned for the discriminator
+    /// first, wherever it is, then parsed again from the start; the scan
+    /// also records the discriminators of the objects nested in it, so
+    /// nested unions do not scan their bytes again. An unknown
+    /// or missing discriminator gives a plain map, or an error when the
+    /// union rejects them. Struct shapes are not shared between the objects
+    /// of a union list, since each may select a different struct.
+    fn parse_union(&mut self, node: u32) -> Result<Term<'a>, DecodeError> {
+        let Some(schema) = self.opts.schema.clone() else {
+            return self.parse_object();
+        };
+        let union = schema.union(node);
+        let obj_start = self.pos;
+        let checkpoint = self.checkpoint();
+        let values = self.values;
+        let found = self.find_discriminator(union.key());
+        self.restore(checkpoint);
+        self.values = values;
+        // A malformed object is parsed as usual, so that its error carries
+        // the path of the member at fault.
+        let Ok(found) = found else {
+            return self.parse_object();
+        };
+
+        let variant = found
+            .as_ref()
+            .and_then(|found| found.value.as_deref())
+            .and_then(|value| union.variant(value));
+        match variant {
+            Some(variant) => self.parse_struct(variant, None),
+            None if union.reject_unknown => Err(match found {
+                Some(found) => unknown_discriminator_error(
+                    union.key(),
+                    &self.input[found.start..found.end],
+                    found.start,
+                ),
+                None => missing_discriminator_error(union.key(), obj_start),
+            }),
+            None => self.parse_object(),
+        }
+    }
+
+    /// Find the member `key` of the object at the current position, the
+    /// last one unless `duplicate_keys` says otherwise. Leaves the position
+    /// anywhere; callers restore a checkpoint.
+    fn find_discriminator(&mut self, key: &[u8]) -> Result<Option<Discriminator<'b>>, DecodeError> {
+        let obj_start = self.pos;
+        let indexed = self.union_index.iter().position(|index| *index.key == *key);
+        let slot = match indexed {
+            Some(slot) if self.union_index[slot].span.contains(&obj_start) => slot,
+            _ => {
+                let mut found = match indexed {
+                    Some(slot) => self.union_index.swap_remove(slot).found,
+                    None => FastHashMap::with_hasher(FnvBuildHasher::new()),
+                };
+                found.clear();
+                self.index_discriminators(key, &mut found)?;
+                self.union_index.push(UnionIndex {
+                    key: key.into(),
+                    span: obj_start..self.pos,
+                    found,
+                });
+                self.union_index.len() - 1
+            }
+        };
+
+        let Some(&span) = self.union_index[slot].found.get(&obj_start) else {
+            return Ok(None);
+        };
+        if let (Some(pos), DuplicateKeys::Error) = (span.repeated, self.opts.duplicate_keys) {
+            return Err(duplicate_key_error(key, pos));
+        }
+        self.pos = span.start;
+        let value = if self.peek() == Some(b'"') {
+            Some(self.read_string()?)
+        } else {
+            None
+        };
+        Ok(Some(Discriminator {
+            value,
+            start: span.start,
+            end: span.end,
+        }))
+    }
+
+    /// Walk the value at the current position, recording in `found` the
+    /// member `key` of every object in it: the first under
+    /// `DuplicateKeys::First`, else the last.
+    fn index_discriminators(
+        &mut self,
+        key: &[u8],
+        found: &mut FastHashMap<usize, DiscriminatorSpan>,
+    ) -> Result<(), DecodeError> {
+        let container_start = self.pos;
+        match self.peek() {
+            Some(b'[') => {
+                self.enter_container()?;
+                let mut first = true;
+                while self.next_element(first)? {
+                    first = false;
+                    self.index_discriminators(key, found)?;
+                }
+            }
+            Some(b'{') => {
+                self.enter_container()?;
+                let mut first = true;
+                while let Some((key_start, raw_key)) = self.next_member_raw(first)? {
+                    first = false;
+                    let start = self.pos;
+                    self.index_discriminators(key, found)?;
+                    let matches = raw_key == key
+                        || raw_key.contains(&b'\\')
+                            && self
+                                .decode_escaped_string(key_start + 1, key_start + 1 + raw_key.len())
+                                .is_ok_and(|unescaped| unescaped == key);
+                    if !matches {
+                        continue;
+                    }
+                    let end = self.pos;
+                    let span = found.entry(container_start).or_insert(DiscriminatorSpan {
+                        start,
+                        end,
+                        repeated: None,
+                    });
+                    if span.start != start {
+                        span.repeated.get_or_insert(key_start);
+                        if self.opts.duplicate_keys != DuplicateKeys::First {
+                            span.start = start;
+                            span.end = end;
+                        }
+                    }
+                }
+            }
+            _ => self.skip_value()?,
+        }
+        Ok(())
+    }
+
+    /// Parse an array whose objects are decoded under `element`, a
+    /// `Target::Struct` or `Target::Union`. Other elements decode as usual.
+    fn parse_struct_list(&mut self, element: Target) -> Result<Term<'a>, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+
+        self.consume_structural(); // Skip '['
+        self.skip_whitespace();
+
+        let mut elements = Vec::new();
+        let mut shape: Option<KeyShape<'a, 'b>> = None;
+        if self.peek() == Some(b']') {
+            self.consume_structural();
+        } else {
+            loop {
+                self.count_element(elements.len())?;
+                let element = if self.peek() == Some(b'{') {
+                    self.target = Some(element);
+                    self.parse_object_shaped(&mut shape)?
+                } else {
+                    self.parse_value()?
+                };
+                elements.push(element);
+
+                self.advance_to_structural();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                    }
+                    Some(b']') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+                }
+            }
+        }
+
+        let mut list = Term::list_new_empty(self.env);
+        for element in elements.into_iter().rev() {
+            list = list.list_prepend(element);
+        }
+        self.depth -= 1;
+        Ok(list)
+    }
+
+    /// Parse an array whose elements are each checked against schema check
+    /// `check`.
+    fn parse_items(&mut self, check: u32) -> Result<Term<'a>, DecodeError> {
+        let Some(schema) = self.opts.schema.clone() else {
+            return self.parse_array();
+        };
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+
+        self.consume_structural(); // Skip '['
+        self.skip_whitespace();
+
+        let mut elements = Vec::new();
+        if self.peek() == Some(b']') {
+            self.consume_structural();
+        } else {
+            loop {
+                self.count_element(elements.len())?;
+                elements.push(self.parse_checked(schema.check(check))?);
+
+                self.advance_to_structural();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                    }
+                    Some(b']') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+                }
+            }
+        }
+
+        let mut list = Term::list_new_empty(self.env);
+        for element in elements.into_iter().rev() {
+            list = list.list_prepend(element);
+        }
+        self.depth -= 1;
+        Ok(list)
+    }
+
+    /// Parse a value under `check`. A value that breaks it is recorded as a
+    /// violation and kept, so that the rest of the document is still
+    /// checked; a coerced value is replaced.
+    fn parse_checked(&mut self, check: &Check) -> Result<Term<'a>, DecodeError> {
+        let start = self.pos;
+        self.target = check.target;
+        let value = self.parse_value();
+        self.target = None;
+        let value = value?;
+
+        let text = &self.input[start..self.pos];
+        let unescaped;
+        let json = match text[0] {
+            b'n' => Json::Null,
+            b't' => Json::Bool(true),
+            b'f' => Json::Bool(false),
+            b'{' => Json::Object,
+            b'[' => Json::Array(value.list_length().unwrap_or(0)),
+            b'"' if text.contains(&b'\\') => {
+                unescaped = self
+                    .decode_escaped_string(start + 1, self.pos - 1)
+                    .map_err(|e| e.at(start))?;
+                Json::String(&unescaped)
+            }
+            b'"' => Json::String(&text[1..text.len() - 1]),
+            _ => Json::Number(text),
+        };
+        match check.apply(json) {
+            Ok(None) => Ok(value),
+            Ok(Some(coerced)) => Ok(match coerced {
+                Coerced::Integer(n) => n.encode(self.env),
+                Coerced::Float(n) => n.encode(self.env),
+                Coerced::Bool(b) => b.encode(self.env),
+                Coerced::String(s) => encode_binary(self.env, &s),
+            }),
+            Err(message) => {
+                self.violation(start, message, self.depth);
+                Ok(value)
+            }
+        }
+    }
+
+    /// Record a violation for the value at `pos`, whose path is that of the
+    /// items being parsed at depths 1 to `depth`.
+    #[cold]
+    fn violation(&mut self, pos: usize, message: String, depth: usize) {
+        let checkpoint = self.checkpoint();
+        self.depth = depth;
+        let path = self.error_path(0);
+        self.restore(checkpoint);
+        self.violations.push(Violation { message, pos, path });
+    }
+
+    /// The field of schema `node` that the member whose raw key starts at
+    /// `key_start` fills.
+    #[inline]
+    fn struct_field(
+        &self,
+        schema: &Schema,
+        node: u32,
+        raw_key: &[u8],
+        key_start: usize,
+    ) -> Result<Option<Field>, DecodeError> {
+        let struct_node = schema.node(node);
+        if raw_key.contains(&b'\\') {
+            let key = self
+                .decode_escaped_string(key_start + 1, key_start + 1 + raw_key.len())
+                .map_err(|e| e.at(key_start))?;
+            return Ok(struct_node.field(&key));
+        }
+        Ok(struct_node.field(raw_key))
+    }
+
+    /// Default values of schema `node`'s struct, loading its terms on first
+    /// use.
+    fn struct_defaults(
+        &mut self,
+        schema: &Schema,
+        node: u32,
+        obj_start: usize,
+    ) -> Result<Vec<Term<'a>>, DecodeError> {
+        let index = node as usize;
+        if self.struct_terms.len() < schema.len() {
+            self.struct_terms.resize_with(schema.len(), || None);
+        }
+        if let Some(terms) = &self.struct_terms[index] {
+            return Ok(terms.defaults.clone());
+        }
+        let struct_node = schema.node(node);
+        let defaults = self
+            .env
+            .binary_to_term(&struct_node.defaults)
+            .and_then(|(list, _)| list.decode::<Vec<Term<'a>>>().ok())
+            .ok_or_else(|| {
+                DecodeError::new(
+                    DecodeErrorKind::Unknown,
+                    "Failed to load struct defaults",
+                    obj_start,
+                )
+            })?;
+        let keys = struct_node
+            .keys
+            .iter()
+            .map(|key| key.to_term(self.env))
+            .collect();
+        self.struct_terms[index] = Some(StructTerms {
+            keys,
+            defaults: defaults.clone(),
+        });
+        Ok(defaults)
+    }
+
+    /// Projection action for the member whose raw key starts at `key_start`.
+    #[inline]
+    fn member_action(
+        &self,
+        node: u32,
+        raw_key: &[u8],
+        key_start: usize,
+    ) -> Result<Action, DecodeError> {
+        let Some(projection) = self.opts.projection.as_deref() else {
+            return Ok(Action::Keep(None));
+        };
+        if raw_key.contains(&b'\\') {
+            let key = self
+                .decode_escaped_string(key_start + 1, key_start + 1 + raw_key.len())
+                .map_err(|e| e.at(key_start))?;
+            return Ok(projection.member_action(node, &key));
+        }
+        Ok(projection.member_action(node, raw_key))
+    }
+
+    /// Skip or decode one member value according to `action`, restoring the
+    /// projection node of the enclosing object afterwards.
+    #[inline]
+    fn parse_member_value(
+        &mut self,
+        node: u32,
+        action: Action,
+    ) -> Result<Option<Term<'a>>, DecodeError> {
+        match action {
+            // `skip_value` only knows strict syntax; relaxed input is parsed
+            // and the result dropped.
+            Action::Skip if RELAXED => {
+                self.parse_value()?;
+                Ok(None)
+            }
+            Action::Skip => {
+                self.skip_value()?;
+                Ok(None)
+            }
+            Action::Keep(state) => {
+                self.proj = state;
+                let value = self.parse_value();
+                self.proj = Some(node);
+                value.map(Some)
+            }
+        }
+    }
+
+    /// Build a map (or ordered object) from parallel key and value slices.
+    #[inline]
+    fn build_object(
+        &self,
+        keys: &[Term<'a>],
+        values: &[Term<'a>],
+        obj_start: usize,
+    ) -> Result<Term<'a>, DecodeError> {
+        if self.opts.ordered_objects {
+            return self.build_ordered_object(keys, values, obj_start);
+        }
+        match Term::map_from_term_arrays(self.env, keys, values) {
+            Ok(map) => Ok(map),
+            Err(_) => self.build_map_with_duplicates(keys, values, obj_start),
+        }
+    }
+
+    /// Build %RustyJson.OrderedObject{values: [{k, v}, ...]} preserving order.
+    /// Pass empty vecs for an empty ordered object. Every pair is kept under
+    /// `DuplicateKeys::Last`; the other policies dedupe like the map path.
+    fn build_ordered_object(
+        &self,
+        keys: &[Term<'a>],
+        values: &[Term<'a>],
+        pos: usize,
+    ) -> Result<Term<'a>, DecodeError> {
+        let env = self.env;
+
+        let deduped;
+        let (keys, values) = if self.opts.duplicate_keys != DuplicateKeys::Last && keys.len() > 1 {
+            deduped = self.dedupe_members(keys, values, pos)?;
+            (&deduped.0[..], &deduped.1[..])
+        } else {
+            (keys, values)
+        };
+
+        // Build list of {key, value} tuples in order
+        let mut list = Term::list_new_empty(env);
+        for i in (0..keys.len()).rev() {
+            let tuple = rustler::types::tuple::make_tuple(env, &[keys[i], values[i]]);
+            list = list.list_prepend(tuple);
+        }
+
+        let map_keys = [
+            atoms::__struct__().to_term(env),
+            atoms::values().to_term(env),
+        ];
+        let map_vals = [atoms::ordered_object_struct().to_term(env), list];
+
+        Term::map_from_term_arrays(env, &map_keys, &map_vals).map_err(|_| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to create OrderedObject",
+                pos,
+            )
+        })
+    }
+
+    /// Build a map whose keys repeat, applying the `duplicate_keys` policy.
+    #[cold]
+    fn build_map_with_duplicates(
+        &self,
+        keys: &[Term<'a>],
+        values: &[Term<'a>],
+        pos: usize,
+    ) -> Result<Term<'a>, DecodeError> {
+        let (final_keys, final_values) = self.dedupe_members(keys, values, pos)?;
+        Term::map_from_term_arrays(self.env, &final_keys, &final_values)
+            .map_err(|_| DecodeError::new(DecodeErrorKind::Unknown, "Failed to create map", pos))
+    }
+
+    /// Collapse repeated keys per the `duplicate_keys` policy, keeping each
+    /// key at its first position. Keys are compared as terms, so keys that
+    /// only differ in escapes, or that `key_case` maps to the same name,
+    /// count as repeats; `DuplicateKeys::Error` reports those at `pos`, the
+    /// object start, since the raw-byte check in the parser cannot see them.
+    fn dedupe_members(
+        &self,
+        keys: &[Term<'a>],
+        values: &[Term<'a>],
+        pos: usize,
+    ) -> Result<(Vec<Term<'a>>, Vec<Term<'a>>), DecodeError> {
+        let mut index: HashMap<Term<'a>, usize> = HashMap::with_capacity(keys.len());
+        let mut final_keys = Vec::with_capacity(keys.len());
+        let mut final_values = Vec::with_capacity(keys.len());
+        // Values of repeated keys under `Collect`, by index into `final_values`.
+        let mut collected: HashMap<usize, Vec<Term<'a>>> = HashMap::new();
+
+        for (&key, &value) in keys.iter().zip(values) {
+            let Some(&i) = index.get(&key) else {
+                index.insert(key, final_keys.len());
+                final_keys.push(key);
+                final_values.push(value);
+                continue;
+            };
+            match self.opts.duplicate_keys {
+                DuplicateKeys::Last => final_values[i] = value,
+                DuplicateKeys::First => {}
+                DuplicateKeys::Collect => collected
+                    .entry(i)
+                    .or_insert_with(|| vec![final_values[i]])
+                    .push(value),
+                DuplicateKeys::Error => return Err(duplicate_key_error(&key_text(key), pos)),
+            }
+        }
+
+        for (i, items) in collected {
+            final_values[i] = items.encode(self.env);
+        }
+        Ok((final_keys, final_values))
+    }
+}
+
+// ============================================================================
+// Relaxed (JSON5-style) syntax
+// ============================================================================
+
+impl<'a, 'b, const RELAXED: bool> DirectParser<'a, 'b, RELAXED> {
+    #[inline]
+    fn relaxed(&self) -> RelaxedSyntax {
+        self.opts.relaxed.unwrap_or_default()
+    }
+
+    /// Skip comments, and the whitespace after each one. An unterminated
+    /// block comment is left in place for the caller to reject.
+    fn skip_comments(&mut self) {
+        if !self.relaxed().comments {
+            return;
+        }
+        loop {
+            let rest = &self.input[self.pos..];
+            if rest.starts_with(b"//") {
+                self.pos = match rest.iter().position(|&b| b == b'\n') {
+                    Some(offset) => self.pos + offset + 1,
+                    None => self.input.len(),
+                };
+            } else if rest.starts_with(b"/*") {
+                match rest[2..].windows(2).position(|w| w == b"*/") {
+                    Some(offset) => self.pos += offset + 4,
+                    None => return,
+                }
+            } else {
+                return;
+            }
+            while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Values that strict JSON has no syntax for: single-quoted strings,
+    /// `+` numbers, `NaN` and `Infinity`.
+    #[cold]
+    fn parse_relaxed_scalar(&mut self) -> Result<Term<'a>, DecodeError> {
+        let relaxed = self.relaxed();
+        match self.peek() {
+            Some(b'\'') if relaxed.single_quotes => {
+                let string_start = self.pos;
+                let bytes = self.scan_single_quoted()?;
+                if let Some(term) = self.datetime_value(&bytes, string_start)? {
+                    return Ok(term);
+                }
+                Ok(encode_binary(self.env, &bytes))
+            }
+            Some(b'+') if relaxed.leading_plus => {
+                let start = self.pos;
+                self.advance();
+                match self.peek() {
+                    Some(b'0'..=b'9') => self.parse_number(),
+                    Some(b'I' | b'N') if relaxed.non_finite.is_some() => {
+                        self.parse_non_finite(false, start)
+                    }
+                    _ => Err(DecodeError::new(
+                        DecodeErrorKind::InvalidNumber,
+                        "Invalid number",
+                        start,
+                    )),
+                }
+            }
+            Some(b'I' | b'N') if relaxed.non_finite.is_some() => {
+                let start = self.pos;
+                self.parse_non_finite(false, start)
+            }
+            Some(b'/') if relaxed.comments && self.input[self.pos..].starts_with(b"/*") => {
+                Err(self.syntax(Syntax::UnterminatedComment))
+            }
+            _ => Err(self.syntax(Syntax::UnexpectedCharacter)),
+        }
+    }
+
+    /// Hex integers and signed non-finite numbers. Returns `Ok(None)` for
+    /// anything `scan_number` should handle.
+    fn parse_relaxed_number(&mut self) -> Result<Option<Term<'a>>, DecodeError> {
+        let relaxed = self.relaxed();
+        let start = self.pos;
+        let neg = self.peek() == Some(b'-');
+        let digits = start + neg as usize;
+        match self.input.get(digits..digits + 2) {
+            Some([b'0', b'x' | b'X']) if relaxed.hex_numbers => {
+                self.pos = digits + 2;
+                self.parse_hex(neg, start).map(Some)
+            }
+            Some([b'I' | b'N', _]) if neg && relaxed.non_finite.is_some() => {
+                self.pos = digits;
+                self.parse_non_finite(true, start).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse the hex digits after `0x`. `start` is where the number began.
+    fn parse_hex(&mut self, neg: bool, start: usize) -> Result<Term<'a>, DecodeError> {
+        let digit_start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+            self.advance();
+        }
+        let digits = &self.input[digit_start..self.pos];
+        if digits.is_empty() {
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidNumber,
+                "Invalid number",
+                start,
+            ));
+        }
+        let limit = self.opts.integer_digit_limit;
+        if limit > 0 && digits.len() > limit {
+            let msg = format!("integer exceeds {} digit limit", limit);
+            return Err(DecodeError::new(DecodeErrorKind::LimitExceeded, msg, start));
+        }
+        let mut value = BigInt::parse_bytes(digits, 16).ok_or_else(|| {
+            DecodeError::new(DecodeErrorKind::InvalidNumber, "Invalid number", start)
+        })?;
+        if neg {
+            value = -value;
+        }
+        Ok(match i64::try_from(&value) {
+            Ok(i) => i.encode(self.env),
+            Err(_) => match u64::try_from(&value) {
+                Ok(u) => u.encode(self.env),
+                Err(_) => value.encode(self.env),
+            },
+        })
+    }
+
+    /// Parse `NaN` or `Infinity` at the current position into the configured
+    /// atom. `start` is where the number (including any sign) began.
+    fn parse_non_finite(&mut self, neg: bool, start: usize) -> Result<Term<'a>, DecodeError> {
+        let Some(atoms) = self.relaxed().non_finite else {
+            return Err(self.syntax(Syntax::UnexpectedCharacter));
+        };
+        let rest = &self.input[self.pos..];
+        let atom = if rest.starts_with(b"Infinity") {
+            self.pos += 8;
+            if neg {
+                atoms.neg_infinity
+            } else {
+                atoms.infinity
+            }
+        } else if rest.starts_with(b"NaN") {
+            self.pos += 3;
+            atoms.nan
+        } else {
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidNumber,
+                "Invalid number",
+                start,
+            ));
+        };
+        Ok(atom.encode(self.env))
+    }
+
+    /// Scan a single-quoted string and return its decoded bytes. Escapes are
+    /// those of double-quoted strings plus `\'`.
+    fn scan_single_quoted(&mut self) -> Result<Cow<'b, [u8]>, DecodeError> {
+        let string_start = self.pos;
+        self.advance(); // Skip opening quote
+        let start = self.pos;
+        let mut has_escape = false;
+        let end = loop {
+            match self.peek() {
+                Some(b'\'') => {
+                    let end = self.pos;
+                    self.check_string_bytes(end - start, string_start)?;
+                    self.advance();
+                    break end;
+                }
+                Some(b'\\') => {
+                    has_escape = true;
+                    self.advance();
+                    if self.peek().is_some() {
+                        self.advance();
+                    }
+                }
+                Some(0x00..=0x1F) => {
+                    return Err(self.err(
+                        DecodeErrorKind::ControlCharacter,
+                        "Unescaped control character",
+                    ))
+                }
+                Some(_) => self.advance(),
+                None => return Err(Syntax::UnterminatedString.at(string_start)),
+            }
+        };
+
+        let bytes = if has_escape {
+            // Decode the runs between `\'` escapes with the JSON unescaper.
+            let mut decoded = Vec::with_capacity(end - start);
+            let mut run_start = start;
+            let mut i = start;
+            while i < end {
+                if self.input[i] != b'\\' {
+                    i += 1;
+                    continue;
+                }
+                if self.input[i + 1] == b'\'' {
+                    decoded.extend(
+                        self.unescape_counted(run_start, i)
+                            .map_err(|e| e.at(string_start))?,
+                    );
+                    decoded.push(b'\'');
+                    run_start = i + 2;
+                }
+                i += 2;
+            }
+            decoded.extend(
+                self.unescape_counted(run_start, end)
+                    .map_err(|e| e.at(string_start))?,
+            );
+            Cow::Owned(decoded)
+        } else {
+            Cow::Borrowed(&self.input[start..end])
+        };
+
+        match self.check_utf8(&bytes, string_start)? {
+            Some(replaced) => Ok(Cow::Owned(replaced)),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Parse an object key and return its term and unescaped bytes.
+    fn parse_relaxed_key(&mut self) -> Result<(Term<'a>, Cow<'b, [u8]>), DecodeError> {
+        let relaxed = self.relaxed();
+        let key_start = self.pos;
+        match self.peek() {
+            Some(b'"') => {
+                let term = self.parse_key()?;
+                let raw = &self.input[key_start + 1..self.pos - 1];
+                let bytes = if raw.contains(&b'\\') {
+                    Cow::Owned(
+                        self.decode_escaped_string(key_start + 1, self.pos - 1)
+                            .map_err(|e| e.at(key_start))?,
+                    )
+                } else {
+                    Cow::Borrowed(raw)
+                };
+                Ok((term, bytes))
+            }
+            Some(b'\'') if relaxed.single_quotes => {
+                let bytes = self.scan_single_quoted()?;
+                Ok((self.key_term(&bytes, key_start)?, bytes))
+            }
+            Some(b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$') if relaxed.unquoted_keys => {
+                while let Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'$') = self.peek()
+                {
+                    self.advance();
+                }
+                let bytes = &self.input[key_start..self.pos];
+                Ok((self.key_term(bytes, key_start)?, Cow::Borrowed(bytes)))
+            }
+            _ => Err(self.syntax(Syntax::ExpectedKey)),
+        }
+    }
+
+    /// Array parser for relaxed mode. Skips the strict parser's shape cache
+    /// and element fast paths, and accepts a trailing comma when enabled.
+    fn parse_array_relaxed(&mut self) -> Result<Term<'a>, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+
+        let node = self.proj;
+        let action = match (node, self.opts.projection.as_deref()) {
+            (Some(node), Some(projection)) => projection.element_action(node),
+            _ => Action::Keep(None),
+        };
+        let trailing_commas = self.relaxed().trailing_commas;
+
+        self.consume_structural_and_skip_ws(); // Skip '['
+        let mut elements = Vec::new();
+        if self.peek() == Some(b']') {
+            self.consume_structural();
+        } else {
+            let mut count = 0;
+            loop {
+                self.count_element(count)?;
+                count += 1;
+                self.proj = match action {
+                    Action::Keep(state) => state,
+                    Action::Skip => None,
+                };
+                let elem = self.parse_value();
+                self.proj = node;
+                let elem = elem?;
+                if action != Action::Skip {
+                    elements.push(elem);
+                }
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                        if trailing_commas && self.peek() == Some(b']') {
+                            self.consume_structural();
+                            break;
+                        }
+                    }
+                    Some(b']') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+                }
+            }
+        }
+
+        self.depth -= 1;
+        let mut list = Term::list_new_empty(self.env);
+        for elem in elements.into_iter().rev() {
+            list = list.list_prepend(elem);
+        }
+        Ok(list)
+    }
+
+    /// Object parser for relaxed mode: single-quoted and identifier keys, and
+    /// a trailing comma when enabled. Projections and duplicate-key rejection
+    /// work on the unescaped key bytes.
+    fn parse_object_relaxed(&mut self) -> Result<Term<'a>, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+
+        let obj_start = self.pos;
+        let node = self.proj;
+        let trailing_commas = self.relaxed().trailing_commas;
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        let mut seen_keys: Option<HashSet<Cow<'b, [u8]>>> =
+            if self.opts.duplicate_keys == DuplicateKeys::Error {
+                Some(HashSet::new())
+            } else {
+                None
+            };
+
+        self.consume_structural_and_skip_ws(); // Skip '{'
+        if self.peek() == Some(b'}') {
+            self.consume_structural();
+        } else {
+            let mut members = 0;
+            loop {
+                self.count_member(members)?;
+                members += 1;
+                let key_start = self.pos;
+                let (key, key_bytes) = self.parse_relaxed_key()?;
+                self.note_key(&key_bytes);
+                let action = match (node, self.opts.projection.as_deref()) {
+                    (Some(node), Some(projection)) => projection.member_action(node, &key_bytes),
+                    _ => Action::Keep(None),
+                };
+                if let Some(ref mut seen) = seen_keys {
+                    if seen.contains(&key_bytes) {
+                        return Err(duplicate_key_error(&key_bytes, key_start));
+                    }
+                    seen.insert(key_bytes);
+                }
+
+                self.skip_whitespace();
+                if self.peek() != Some(b':') {
+                    return Err(self.syntax(Syntax::ExpectedColon));
+                }
+                self.consume_structural_and_skip_ws();
+                self.proj = match action {
+                    Action::Keep(state) => state,
+                    Action::Skip => None,
+                };
+                let value = self.parse_value();
+                self.proj = node;
+                let value = value?;
+                if action != Action::Skip {
+                    keys.push(key);
+                    values.push(value);
+                }
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => {
+                        self.consume_structural_and_skip_ws();
+                        if trailing_commas && self.peek() == Some(b'}') {
+                            self.consume_structural();
+                            break;
+                        }
+                    }
+                    Some(b'}') => {
+                        self.consume_structural();
+                        break;
+                    }
+                    _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+                }
+            }
+        }
+
+        self.depth -= 1;
+        self.build_object(&keys, &values, obj_start)
+    }
+}
+
+// ============================================================================
+// Navigation and resumable array iteration
+// ============================================================================
+
+/// Progress through an array whose elements are decoded across several calls.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ArrayCursor {
+    /// At least one element has been consumed, so a `,` must precede the next.
+    pub(crate) started: bool,
+    /// The closing `]` has been consumed.
+    pub(crate) done: bool,
+}
+
+impl<'a, 'b, const RELAXED: bool> DirectParser<'a, 'b, RELAXED> {
+    /// Current byte offset into the input.
+    #[inline]
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Current container nesting depth.
+    #[inline]
+    pub(crate) fn nesting_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Skip over one value without building terms. Applies the same grammar,
+    /// depth limit and error messages as `parse_value`, but does not decode
+    /// string contents or convert numbers.
+    pub(crate) fn skip_value(&mut self) -> Result<(), DecodeError> {
+        match self.peek() {
+            Some(b'"') => self.scan_string_raw().map(|_| ()),
+            Some(b'[') => self.skip_array().map(|_| ()),
+            Some(b'{') => self.skip_object().map(|_| ()),
+            Some(b'-') | Some(b'0'..=b'9') => self.scan_number().map(|_| ()),
+            Some(b'n') => self.skip_literal(b"null", Syntax::ExpectedNull),
+            Some(b't') => self.skip_literal(b"true", Syntax::ExpectedTrue),
+            Some(b'f') => self.skip_literal(b"false", Syntax::ExpectedFalse),
+            Some(_) => Err(self.syntax(Syntax::UnexpectedCharacter)),
+            None => Err(self.syntax(Syntax::UnexpectedEnd)),
+        }
+    }
+
+    #[inline]
+    fn skip_literal(&mut self, literal: &[u8], error: Syntax) -> Result<(), DecodeError> {
+        if self.input[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.syntax(error))
+        }
+    }
+
+    /// Skip an array, returning its element count.
+    fn skip_array(&mut self) -> Result<usize, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws(); // Skip '['
+        if self.peek() == Some(b']') {
+            self.consume_structural();
+            self.depth -= 1;
+            return Ok(0);
+        }
+        let mut count = 0;
+        loop {
+            self.count_element(count)?;
+            self.skip_value()?;
+            count += 1;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b']') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    return Ok(count);
+                }
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+            }
+        }
+    }
+
+    /// Skip an object, returning its member count (duplicate keys included).
+    fn skip_object(&mut self) -> Result<usize, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws(); // Skip '{'
+        if self.peek() == Some(b'}') {
+            self.consume_structural();
+            self.depth -= 1;
+            return Ok(0);
+        }
+        let mut count = 0;
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.syntax(Syntax::ExpectedKey));
+            }
+            self.count_member(count)?;
+            self.scan_string_raw()?;
+            self.advance_to_structural();
+            if self.peek() != Some(b':') {
+                return Err(self.syntax(Syntax::ExpectedColon));
+            }
+            self.consume_structural_and_skip_ws();
+            self.skip_value()?;
+            count += 1;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b'}') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    return Ok(count);
+                }
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+            }
+        }
+    }
+
+    /// Move from the value at the current position to the descendant named by
+    /// `tokens`. Each token is an object key (unescaped bytes) or, inside an
+    /// array, a decimal index without leading zeros. Returns `Ok(false)` when
+    /// the path does not exist. On success the parser is positioned at the
+    /// first byte of the target value, and `nesting_depth()` counts the
+    /// containers entered. A repeated key is resolved by `duplicates` as
+    /// decoding resolves it: `First` stops at the first match, `Error`
+    /// rejects a repeat, and the others take the last match, reading the
+    /// rest of the object to find it.
+    pub(crate) fn seek(
+        &mut self,
+        tokens: &[Vec<u8>],
+        duplicates: DuplicateKeys,
+    ) -> Result<bool, DecodeError> {
+        for token in tokens {
+            self.skip_whitespace();
+            let found = match self.peek() {
+                Some(b'{') => self.seek_key(token, duplicates)?,
+                Some(b'[') => match parse_array_index(token) {
+                    Some(index) => self.seek_index(index)?,
+                    None => false,
+                },
+                _ => false,
+            };
+            if !found {
+                return Ok(false);
+            }
+        }
+        self.skip_whitespace();
+        Ok(true)
+    }
+
+    fn seek_key(&mut self, token: &[u8], duplicates: DuplicateKeys) -> Result<bool, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws(); // Skip '{'
+        if self.peek() == Some(b'}') {
+            return Ok(false);
+        }
+        let mut found = None;
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.syntax(Syntax::ExpectedKey));
+            }
+            let key_start = self.pos;
+            let raw = self.scan_string_raw()?;
+            let matches = if raw.contains(&b'\\') {
+                self.decode_escaped_string(key_start + 1, self.pos - 1)
+                    .map_err(|e| e.at(key_start))?
+                    == token
+            } else {
+                raw == token
+            };
+            self.advance_to_structural();
+            if self.peek() != Some(b':') {
+                return Err(self.syntax(Syntax::ExpectedColon));
+            }
+            self.consume_structural_and_skip_ws();
+            if matches {
+                match duplicates {
+                    DuplicateKeys::First => return Ok(true),
+                    DuplicateKeys::Error if found.is_some() => {
+                        return Err(duplicate_key_error(token, key_start));
+                    }
+                    _ => found = Some(self.checkpoint()),
+                }
+            }
+            self.skip_value()?;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b'}') => break,
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+            }
+        }
+        match found {
+            Some(checkpoint) => {
+                self.restore(checkpoint);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn seek_index(&mut self, index: usize) -> Result<bool, DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws(); // Skip '['
+        if self.peek() == Some(b']') {
+            return Ok(false);
+        }
+        for _ in 0..index {
+            self.skip_value()?;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b']') => return Ok(false),
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Decode the value at the current position, e.g. the target of `seek`.
+    /// Bytes after the value are not examined.
+    pub(crate) fn parse_current(&mut self) -> Result<Term<'a>, DecodeError> {
+        self.parse_value()
+    }
+
+    /// First byte of the value at the current position, without consuming it.
+    #[inline]
+    pub(crate) fn current_byte(&self) -> Option<u8> {
+        self.peek()
+    }
+
+    /// Count the elements of the array at the current position by skipping
+    /// them, without building terms.
+    pub(crate) fn count_array_elements(&mut self) -> Result<usize, DecodeError> {
+        if self.peek() != Some(b'[') {
+            return Err(self.syntax(Syntax::ExpectedArray));
+        }
+        self.skip_array()
+    }
+
+    /// Check the whole input as `parse` would, without building terms.
+    ///
+    /// Applies the same grammar, depth limit, escape and UTF-8 checks, digit
+    /// limits, quotas and duplicate-key rejection as decoding, with the
+    /// same error messages and positions, so an input that validates also
+    /// decodes with the same options.
+    pub(crate) fn validate(&mut self) -> Result<ValidationSummary, DecodeError> {
+        self.skip_whitespace();
+        let mut summary = ValidationSummary {
+            root: self.peek().unwrap_or(0),
+            max_depth: 0,
+            value_count: 0,
+        };
+        self.count_value()?;
+        self.validate_value(&mut summary)?;
+        self.expect_end()?;
+        Ok(summary)
+    }
+
+    fn validate_value(&mut self, summary: &mut ValidationSummary) -> Result<(), DecodeError> {
+        summary.value_count += 1;
+        match self.peek() {
+            Some(b'"') => self.validate_string().map(|_| ()),
+            Some(b'[') => self.validate_array(summary),
+            Some(b'{') => self.validate_object(summary),
+            _ => self.skip_value(),
+        }
+    }
+
+    /// Scan a string, checking escapes and UTF-8 like `parse_string_impl`.
+    /// Returns the raw bytes between the quotes.
+    fn validate_string(&mut self) -> Result<&'b [u8], DecodeError> {
+        let string_start = self.pos;
+        let raw = self.scan_string_raw()?;
+        if raw.contains(&b'\\') {
+            let decoded = self
+                .decode_escaped_string(string_start + 1, self.pos - 1)
+                .map_err(|e| e.at(string_start))?;
+            self.check_utf8(&decoded, string_start)?;
+        } else {
+            self.check_utf8(raw, string_start)?;
+        }
+        Ok(raw)
+    }
+
+    fn validate_array(&mut self, summary: &mut ValidationSummary) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        summary.max_depth = summary.max_depth.max(self.depth);
+        self.consume_structural_and_skip_ws(); // Skip '['
+        if self.peek() == Some(b']') {
+            self.consume_structural();
+            self.depth -= 1;
+            return Ok(());
+        }
+        let mut count = 0;
+        loop {
+            self.count_element(count)?;
+            count += 1;
+            self.validate_value(summary)?;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b']') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    return Ok(());
+                }
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+            }
+        }
+    }
+
+    fn validate_object(&mut self, summary: &mut ValidationSummary) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        summary.max_depth = summary.max_depth.max(self.depth);
+        self.consume_structural_and_skip_ws(); // Skip '{'
+        if self.peek() == Some(b'}') {
+            self.consume_structural();
+            self.depth -= 1;
+            return Ok(());
+        }
+        let mut seen_keys: Option<HashSet<&'b [u8]>> =
+            if self.opts.duplicate_keys == DuplicateKeys::Error {
+                Some(HashSet::new())
+            } else {
+                None
+            };
+        let mut count = 0;
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.syntax(Syntax::ExpectedKey));
+            }
+            self.count_member(count)?;
+            count += 1;
+            let key_start = self.pos;
+            let key = self.validate_string()?;
+            if let Some(ref mut seen) = seen_keys {
+                if !seen.insert(key) {
+                    return Err(duplicate_key_error(key, key_start));
+                }
+            }
+            self.advance_to_structural();
+            if self.peek() != Some(b':') {
+                return Err(self.syntax(Syntax::ExpectedColon));
+            }
+            self.consume_structural_and_skip_ws();
+            self.validate_value(summary)?;
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b'}') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    return Ok(());
+                }
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+            }
+        }
+    }
+
+    /// Count the members of the object at the current position by skipping
+    /// them, without building terms. Duplicate keys are counted each time.
+    pub(crate) fn count_object_members(&mut self) -> Result<usize, DecodeError> {
+        if self.peek() != Some(b'{') {
+            return Err(self.syntax(Syntax::ExpectedObject));
+        }
+        self.skip_object()
+    }
+
+    /// Save the current position so the parser can re-read a value, e.g. to
+    /// both decode a match and search inside it.
+    #[inline]
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pos: self.pos,
+            depth: self.depth,
+            cursor: self.structural_index.as_ref().map_or(0, |idx| idx.cursor),
+        }
+    }
+
+    /// Return to a position saved with `checkpoint`.
+    #[inline]
+    pub(crate) fn restore(&mut self, checkpoint: Checkpoint) {
+        self.pos = checkpoint.pos;
+        self.depth = checkpoint.depth;
+        if let Some(ref mut idx) = self.structural_index {
+            idx.cursor = checkpoint.cursor;
+        }
+    }
+
+    /// Enter the array or object at the current position, consuming its
+    /// opening bracket. Walk it with `next_element` or `next_member`.
+    pub(crate) fn enter_container(&mut self) -> Result<(), DecodeError> {
+        if !matches!(self.peek(), Some(b'[') | Some(b'{')) {
+            return Err(self.syntax(Syntax::ExpectedArrayOrObject));
+        }
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws();
+        Ok(())
+    }
+
+    /// Move to the next element of an array entered with `enter_container`.
+    /// `first` is `true` for the first call; afterwards the previous element
+    /// must have been consumed. Returns `Ok(false)` once `]` is consumed.
+    pub(crate) fn next_element(&mut self, first: bool) -> Result<bool, DecodeError> {
+        if !first {
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => {
+                    self.consume_structural_and_skip_ws();
+                    return Ok(true);
+                }
+                Some(b']') => {}
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+            }
+        } else if self.peek() != Some(b']') {
+            return Ok(true);
+        }
+        self.consume_structural();
+        self.depth -= 1;
+        Ok(false)
+    }
+
+    /// Move to the value of the next member of an object entered with
+    /// `enter_container`, returning its unescaped key. `first` works as in
+    /// `next_element`. Returns `Ok(None)` once `}` is consumed.
+    pub(crate) fn next_member(
+        &mut self,
+        first: bool,
+    ) -> Result<Option<Cow<'b, [u8]>>, DecodeError> {
+        let Some((start, raw)) = self.next_member_raw(first)? else {
+            return Ok(None);
+        };
+        if !raw.contains(&b'\\') {
+            return Ok(Some(Cow::Borrowed(raw)));
+        }
+        self.decode_escaped_string(start + 1, start + 1 + raw.len())
+            .map(|key| Some(Cow::Owned(key)))
+            .map_err(|e| e.at(start))
+    }
+
+    /// Like `next_member`, but returns the start of the key and its raw,
+    /// still escaped bytes.
+    fn next_member_raw(&mut self, first: bool) -> Result<Option<(usize, &'b [u8])>, DecodeError> {
+        if !first {
+            self.advance_to_structural();
+            match self.peek() {
+                Some(b',') => self.consume_structural_and_skip_ws(),
+                Some(b'}') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    return Ok(None);
+                }
+                _ => return Err(self.syntax(Syntax::ExpectedCommaOrBrace)),
+            }
+        } else if self.peek() == Some(b'}') {
+            self.consume_structural();
+            self.depth -= 1;
+            return Ok(None);
+        }
+        if self.peek() != Some(b'"') {
+            return Err(self.syntax(Syntax::ExpectedKey));
+        }
+        let start = self.pos;
+        let raw = self.scan_string_raw()?;
+        self.advance_to_structural();
+        if self.peek() != Some(b':') {
+            return Err(self.syntax(Syntax::ExpectedColon));
+        }
+        self.consume_structural_and_skip_ws();
+        Ok(Some((start, raw)))
+    }
+
+    /// Read the string at the current position as unescaped bytes, without
+    /// building a term.
+    pub(crate) fn read_string(&mut self) -> Result<Cow<'b, [u8]>, DecodeError> {
+        let start = self.pos;
+        let raw = self.scan_string_raw()?;
+        if raw.contains(&b'\\') {
+            self.decode_escaped_string(start + 1, self.pos - 1)
+                .map(Cow::Owned)
+                .map_err(|e| e.at(start))
+        } else {
+            Ok(Cow::Borrowed(raw))
+        }
+    }
+
+    /// Read the number at the current position as its raw bytes.
+    pub(crate) fn read_number(&mut self) -> Result<&'b [u8], DecodeError> {
+        let start = self.pos;
+        self.scan_number()?;
+        Ok(&self.input[start..self.pos])
+    }
+
+    /// Enter the array at the current position, consuming its `[`.
+    pub(crate) fn enter_array(&mut self) -> Result<(), DecodeError> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'[') {
+            return Err(self.syntax(Syntax::ExpectedArray));
+        }
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(self.depth_error());
+        }
+        self.consume_structural_and_skip_ws();
+        Ok(())
+    }
+
+    /// Decode up to `max` further elements of an array entered with
+    /// `enter_array`. `shape` is shared with `parse_array`'s key-shape policy
+    /// and may be carried over from a previous call.
+    pub(crate) fn next_array_elements(
+        &mut self,
+        max: usize,
+        cursor: &mut ArrayCursor,
+        shape: &mut Option<KeyShape<'a, 'b>>,
+    ) -> Result<Vec<Term<'a>>, DecodeError> {
+        let mut elements = Vec::with_capacity(max.min(1024));
+        while !cursor.done && elements.len() < max {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') => {
+                    self.consume_structural();
+                    self.depth -= 1;
+                    cursor.done = true;
+                    break;
+                }
+                // After a comma a value is always parsed, which rejects `[1,]`.
+                Some(b',') if cursor.started => self.consume_structural_and_skip_ws(),
+                _ if cursor.started => return Err(self.syntax(Syntax::ExpectedCommaOrBracket)),
+                _ => {}
+            }
+            let first = !cursor.started;
+            elements.push(self.parse_array_element(shape, first)?);
+            cursor.started = true;
+        }
+        Ok(elements)
+    }
+
+    /// Verify that only whitespace remains after the root value.
+    pub(crate) fn expect_end(&mut self) -> Result<(), DecodeError> {
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(self.syntax(Syntax::TrailingCharacters));
+        }
+        Ok(())
+    }
+}
+
+/// Facts about a document gathered by `DirectParser::validate`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ValidationSummary {
+    /// First byte of the root value.
+    pub(crate) root: u8,
+    /// Deepest container nesting; 0 for a scalar root.
+    pub(crate) max_depth: usize,
+    /// Number of values, containers included and object keys excluded.
+    pub(crate) value_count: usize,
+}
+
+/// Parser position saved by `DirectParser::checkpoint`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Checkpoint {
+    pos: usize,
+    depth: usize,
+    /// Structural index cursor (0 when there is no index).
+    cursor: usize,
+}
+
+/// Parse an RFC 6901 array index token: `0` or digits without a leading zero.
+#[inline]
+pub(crate) fn parse_array_index(token: &[u8]) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token[0] == b'0') {
+        return None;
+    }
+    if !token.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(token).ok()?.parse().ok()
+}
+
+/// The existing atom named by the UTF-8 bytes `name`, if there is one.
+fn existing_atom<'a>(env: Env<'a>, name: &[u8]) -> Option<Term<'a>> {
+    if name.is_ascii() {
+        // ASCII reads the same as Latin-1, the only encoding rustler's atom
+        // functions accept before NIF 2.17.
+        let atom = Atom::try_from_bytes(env, name).ok()??;
+        return Some(atom.to_term(env));
+    }
+    // A safe `binary_to_term` never creates atoms.
+    env.binary_to_term(&atom_ext(name)?).map(|(term, _)| term)
+}
+
+/// The atom named by the UTF-8 bytes `name`, created if needed. `None` when
+/// `name` is longer than 255 characters or not valid UTF-8.
+fn new_atom<'a>(env: Env<'a>, name: &[u8]) -> Option<Term<'a>> {
+    if name.is_ascii() {
+        let atom = Atom::from_bytes(env, name).ok()?;
+        return Some(atom.to_term(env));
+    }
+    if std::str::from_utf8(name).ok()?.chars().count() > 255 {
+        return None;
+    }
+    // SAFETY: `atom_ext` builds a well-formed ATOM_UTF8_EXT term from valid
+    // UTF-8 within the atom length limit.
+    unsafe { env.binary_to_term_trusted(&atom_ext(name)?) }.map(|(term, _)| term)
+}
+
+/// External term format encoding of the atom `name` (ATOM_UTF8_EXT).
+fn atom_ext(name: &[u8]) -> Option<Vec<u8>> {
+    let len = u16::try_from(name.len()).ok()?;
+    let mut ext = Vec::with_capacity(name.len() + 4);
+    ext.extend_from_slice(&[131, 118]);
+    ext.extend_from_slice(&len.to_be_bytes());
+    ext.extend_from_slice(name);
+    Some(ext)
+}
+
+/// Error for a malformed escape sequence. The unescape helpers do not know
+/// where their string starts, so callers place it with `DecodeError::at`.
+#[cold]
+fn escape_error(msg: &'static str) -> DecodeError {
+    DecodeError::new(DecodeErrorKind::InvalidEscape, msg, 0)
+}
+
+/// Error for an exceeded decode quota, e.g. "string exceeds 64 byte limit".
+#[cold]
+fn quota_error(subject: &str, limit: usize, unit: &str, pos: usize) -> DecodeError {
+    let msg = format!("{} exceeds {} {} limit", subject, limit, unit);
+    DecodeError::new(DecodeErrorKind::LimitExceeded, msg, pos)
+}
+
+/// Error for a repeated key, e.g. `Duplicate key "id" in object`. Keys longer
+/// than 64 bytes are cut short.
+#[cold]
+fn duplicate_key_error(key: &[u8], pos: usize) -> DecodeError {
+    let msg = format!("Duplicate key \"{}\" in object", shown_text(key));
+    DecodeError::new(DecodeErrorKind::DuplicateKey, msg, pos)
+}
+
+/// Error for a discriminator that selects no struct, e.g.
+/// `Unknown discriminator "order.x" for key "type"`. `value` is the JSON
+/// text of the discriminator's value; like keys, both are cut short.
+#[cold]
+fn unknown_discriminator_error(key: &[u8], value: &[u8], pos: usize) -> DecodeError {
+    let msg = format!(
+        "Unknown discriminator {} for key \"{}\"",
+        shown_text(value),
+        shown_text(key)
+    );
+    DecodeError::new(DecodeErrorKind::UnknownDiscriminator, msg, pos)
+}
+
+/// Error for an object without its discriminator key.
+#[cold]
+fn missing_discriminator_error(key: &[u8], pos: usize) -> DecodeError {
+    let msg = format!("Missing discriminator key \"{}\"", shown_text(key));
+    DecodeError::new(DecodeErrorKind::UnknownDiscriminator, msg, pos)
+}
+
+/// `text` for an error message, cut to 64 bytes.
+#[cold]
+fn shown_text(text: &[u8]) -> String {
+    const MAX_SHOWN: usize = 64;
+    let shown = String::from_utf8_lossy(&text[..text.len().min(MAX_SHOWN)]);
+    let more = if text.len() > MAX_SHOWN { "..." } else { "" };
+    format!("{}{}", shown, more)
+}
+
+/// Printable form of a decoded key for error messages.
+#[cold]
+fn key_text(key: Term<'_>) -> Vec<u8> {
+    if let Ok(bin) = key.decode::<Binary>() {
+        bin.as_slice().to_vec()
+    } else if let Ok(name) = key.atom_to_string() {
+        name.into_bytes()
+    } else {
+        format!("{:?}", key).into_bytes()
+    }
+}
+
+/// U+FFFD in UTF-8.
+const REPLACEMENT_CHARACTER: &[u8] = "\u{FFFD}".as_bytes();
+
+/// Copy `bytes` with U+FFFD in place of each maximal invalid UTF-8 sequence,
+/// as `String::from_utf8_lossy` does. Returns the copy and the replacements.
+#[cold]
+fn replace_invalid_utf8(bytes: &[u8]) -> (Vec<u8>, usize) {
+    let mut out = Vec::with_capacity(bytes.len() + REPLACEMENT_CHARACTER.len());
+    let mut count = 0;
+    for chunk in bytes.utf8_chunks() {
+        out.extend_from_slice(chunk.valid().as_bytes());
+        if !chunk.invalid().is_empty() {
+            out.extend_from_slice(REPLACEMENT_CHARACTER);
+            count += 1;
+        }
+    }
+    (out, count)
+}
+
+#[inline(always)]
+fn encode_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Term<'a> {
+    let mut bin = NewBinary::new(env, bytes.len());
+    bin.as_mut_slice().copy_from_slice(bytes);
+    bin.into()
+}
+
+// ============================================================================
+// Benchmark helpers - feature-gated, excluded from production binary
+// ============================================================================
+
+#[cfg(feature = "bench")]
+pub mod bench_helpers {
+    /// Skip whitespace bytes (space, tab, newline, carriage return) starting at `pos`.
+    /// Returns the new position after all whitespace.
+    #[inline]
+    pub fn skip_whitespace(input: &[u8], mut pos: usize) -> usize {
+        while pos < input.len() {
+            match input[pos] {
+                b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
+                _ => break,
+            }
+        }
+        pos
+    }
+
+    /// Scan a JSON string starting at `pos` (which should point to the opening `"`).
+    /// Returns `(end_pos, has_escape)` where `end_pos` is one past the closing quote.
+    /// Uses the same SIMD fast-path as the real parser.
+    #[inline]
+    pub fn scan_string(input: &[u8], mut pos: usize) -> (usize, bool) {
+        if pos >= input.len() || input[pos] != b'"' {
+            return (pos, false);
+        }
+        pos += 1; // skip opening quote
+        let mut has_escape = false;
+
+        loop {
+            // SIMD bulk skip for plain bytes (portable SIMD)
+            crate::simd_utils::skip_plain_string_bytes(input, &mut pos);
+
+            // Byte-at-a-time for remainder
+            if pos >= input.len() {
+                return (pos, has_escape);
+            }
+            match input[pos] {
+                b'"' => {
+                    pos += 1; // skip closing quote
+                    return (pos, has_escape);
+                }
+                b'\\' => {
                     has_escape = true;
                     pos += 1;
                     if pos < input.len() {
@@ -2034,23 +5164,57 @@ pub mod bench_helpers {
     }
 }
 
+/// Fail when `input` is longer than the `max_bytes` option.
+#[inline]
+pub(crate) fn check_max_bytes(input: &[u8], opts: &DecodeOptions) -> Result<(), DecodeError> {
+    check_input_size(input.len(), opts.max_bytes)
+}
+
+/// Fail when `len` bytes of input exceed `max_bytes`; 0 means no limit.
+#[inline]
+pub(crate) fn check_input_size(len: usize, max_bytes: usize) -> Result<(), DecodeError> {
+    if max_bytes > 0 && len > max_bytes {
+        let msg = format!(
+            "input size {} exceeds max_bytes limit of {}",
+            len, max_bytes
+        );
+        return Err(DecodeError::new(DecodeErrorKind::LimitExceeded, msg, 0));
+    }
+    Ok(())
+}
+
+/// Validate JSON without building any terms. See `DirectParser::validate`.
+pub(crate) fn validate<'a>(
+    env: Env<'a>,
+    input_binary: &Binary<'a>,
+    opts: DecodeOptions,
+) -> Result<ValidationSummary, DecodeError> {
+    let json = input_binary.as_slice();
+    check_max_bytes(json, &opts)?;
+    DirectParser::new(env, json, *input_binary, opts).validate()
+}
+
 /// Parse JSON directly to Erlang terms without intermediate representation
 #[inline]
 pub fn json_to_term<'a>(
     env: Env<'a>,
     input_binary: &Binary<'a>,
     opts: DecodeOptions,
-) -> Result<Term<'a>, DecodeError> {
+) -> Result<Term<'a>, DecodeFailure> {
+    json_to_term_with_stats(env, input_binary, opts).map(|(term, _)| term)
+}
+
+/// `json_to_term`, also returning the `DecodeStats` of the document.
+#[inline]
+pub fn json_to_term_with_stats<'a>(
+    env: Env<'a>,
+    input_binary: &Binary<'a>,
+    opts: DecodeOptions,
+) -> Result<(Term<'a>, DecodeStats), DecodeFailure> {
     let json = input_binary.as_slice();
-    if opts.max_bytes > 0 && json.len() > opts.max_bytes {
-        return Err((
-            Cow::Owned(format!(
-                "input size {} exceeds max_bytes limit of {}",
-                json.len(),
-                opts.max_bytes
-            )),
-            0,
-        ));
+    check_max_bytes(json, &opts)?;
+    if opts.relaxed.is_some() {
+        return DirectParser::new_relaxed(env, json, *input_binary, opts).parse_with_stats();
     }
-    DirectParser::new(env, json, *input_binary, opts).parse()
+    DirectParser::new(env, json, *input_binary, opts).parse_with_stats()
 }
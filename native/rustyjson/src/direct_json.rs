@@ -1,8 +1,10 @@
 use crate::decimal::try_format_decimal;
 use rustler::types::{ListIterator, MapIterator};
-use rustler::{Binary, Term, TermType};
+use rustler::{Binary, Encoder, Env, NifUnitEnum, Term, TermType};
 use smallvec::SmallVec;
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::fmt;
 use std::io::Write;
 
 /// Escape mode for JSON string encoding
@@ -41,6 +43,8 @@ pub struct FormatContext {
     pub indent: SmallVec<[u8; 16]>,
     pub strict_keys: bool,
     pub sort_keys: bool,
+    /// Maximum nesting depth, at most `MAX_DEPTH_LIMIT`.
+    pub max_depth: u32,
 }
 
 impl Default for FormatContext {
@@ -51,6 +55,7 @@ impl Default for FormatContext {
             indent: SmallVec::from_slice(b"  "),
             strict_keys: false,
             sort_keys: false,
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
 }
@@ -127,7 +132,7 @@ impl<'ctx> FormatOptions<'ctx> {
     }
 
     #[inline(always)]
-    fn nested(&self) -> Self {
+    pub(crate) fn nested(&self) -> Self {
         Self {
             pretty: self.pretty,
             depth: self.depth + 1,
@@ -138,7 +143,7 @@ impl<'ctx> FormatOptions<'ctx> {
     }
 
     #[inline(always)]
-    fn write_newline<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+    pub(crate) fn write_newline<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
         if self.is_pretty() {
             writer.write_all(&self.ctx.line_separator)?;
             let indent = &self.ctx.indent;
@@ -150,7 +155,7 @@ impl<'ctx> FormatOptions<'ctx> {
     }
 
     #[inline(always)]
-    fn write_space<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+    pub(crate) fn write_space<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
         if self.is_pretty() {
             writer.write_all(&self.ctx.after_colon)?;
         }
@@ -158,17 +163,145 @@ impl<'ctx> FormatOptions<'ctx> {
     }
 }
 
-/// Maximum nesting depth to prevent stack overflow
-const MAX_DEPTH: u32 = 128;
+/// Default maximum nesting depth (`max_depth:` option).
+pub(crate) const DEFAULT_MAX_DEPTH: u32 = 128;
+
+/// Highest `max_depth` accepted, shared with decoding. The container writers
+/// keep key and struct handling out of line to stay within it.
+pub(crate) const MAX_DEPTH_LIMIT: u32 = crate::direct_decode::MAX_DEPTH_LIMIT as u32;
+
+/// Why an encode failed, raised to Elixir as the `reason` atom.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EncodeReason {
+    /// A binary that is not valid UTF-8.
+    InvalidUtf8,
+    /// NaN or an infinity.
+    NonFiniteFloat,
+    /// A PID, reference, function or other term with no JSON form.
+    UnsupportedType,
+    /// A map key that is not an atom, string or integer.
+    InvalidKey,
+    /// Two keys that write the same JSON key under `maps: :strict`.
+    DuplicateKey,
+    /// Nesting deeper than `max_depth`.
+    DepthExceeded,
+    /// A malformed `OrderedObject`, fragment or stream item, or a term the
+    /// NIF could not read.
+    InvalidValue,
+    /// A push to a streaming encoder that was already finished.
+    Finished,
+    /// The output writer failed.
+    Io,
+}
+
+/// One step on the way from the root term to the value that failed.
+pub enum PathItem<'a> {
+    /// Position in a list, tuple or `MapSet`.
+    Index(usize),
+    /// Map or `OrderedObject` key, as the original term.
+    Key(Term<'a>),
+}
+
+impl Encoder for PathItem<'_> {
+    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+        match self {
+            PathItem::Index(i) => i.encode(env),
+            PathItem::Key(key) => key.in_env(env),
+        }
+    }
+}
+
+/// An encode failure: what went wrong, where, and the offending term.
+///
+/// The path is not tracked while writing. Each container writer adds its
+/// step as the error passes back up through it, so `path` holds the steps
+/// innermost first and encoding that succeeds pays nothing for it.
+pub struct EncodeError<'a> {
+    pub reason: EncodeReason,
+    pub message: Cow<'static, str>,
+    path: Vec<PathItem<'a>>,
+    /// The value (or map key) that could not be encoded. `None` for writer
+    /// failures.
+    pub value: Option<Term<'a>>,
+}
+
+impl<'a> EncodeError<'a> {
+    #[cold]
+    pub fn new(
+        reason: EncodeReason,
+        message: impl Into<Cow<'static, str>>,
+        value: Term<'a>,
+    ) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+            path: Vec::new(),
+            value: Some(value),
+        }
+    }
+
+    /// A failure with no offending term, such as misuse of a stream encoder.
+    #[cold]
+    pub fn without_value(reason: EncodeReason, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+            path: Vec::new(),
+            value: None,
+        }
+    }
+
+    /// Record that the failing value sits at `index` of the enclosing list.
+    #[cold]
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathItem::Index(index));
+        self
+    }
+
+    /// Record that the failing value sits under `key` of the enclosing map.
+    #[cold]
+    pub fn at_key(mut self, key: Term<'a>) -> Self {
+        self.path.push(PathItem::Key(key));
+        self
+    }
+
+    /// The path from the root to the failing value.
+    pub fn path(&self) -> impl Iterator<Item = &PathItem<'a>> {
+        self.path.iter().rev()
+    }
+}
+
+impl From<std::io::Error> for EncodeError<'_> {
+    #[cold]
+    fn from(e: std::io::Error) -> Self {
+        Self {
+            reason: EncodeReason::Io,
+            message: e.to_string().into(),
+            path: Vec::new(),
+            value: None,
+        }
+    }
+}
+
+impl fmt::Display for EncodeError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
 /// Check for duplicate keys in strict mode. Returns an error if the key was already seen.
 #[inline]
-fn check_strict_key(seen: &mut Option<HashSet<String>>, key: &str) -> Result<(), std::io::Error> {
+fn check_strict_key<'a>(
+    seen: &mut Option<HashSet<String>>,
+    key_str: &str,
+    key: Term<'a>,
+) -> Result<(), EncodeError<'a>> {
     if let Some(ref mut set) = seen {
-        if !set.insert(key.to_string()) {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("duplicate key: {:?}", key),
+        if !set.insert(key_str.to_string()) {
+            return Err(EncodeError::new(
+                EncodeReason::DuplicateKey,
+                format!("duplicate key: {:?}", key_str),
+                key,
             ));
         }
     }
@@ -176,15 +309,16 @@ fn check_strict_key(seen: &mut Option<HashSet<String>>, key: &str) -> Result<(),
 }
 
 /// Write a term directly to JSON, bypassing serde.
-pub fn term_to_json<W: Write>(
-    term: Term,
+pub fn term_to_json<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
-    if opts.depth > MAX_DEPTH {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Nesting depth exceeds maximum of {}", MAX_DEPTH),
+) -> Result<(), EncodeError<'a>> {
+    if opts.depth > opts.ctx.max_depth {
+        return Err(EncodeError::new(
+            EncodeReason::DepthExceeded,
+            format!("Nesting depth exceeds maximum of {}", opts.ctx.max_depth),
+            term,
         ));
     }
 
@@ -196,19 +330,20 @@ pub fn term_to_json<W: Write>(
         TermType::List => write_list(term, writer, opts),
         TermType::Map => write_map(term, writer, opts),
         TermType::Tuple => write_tuple(term, writer, opts),
-        _ => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
+        _ => Err(EncodeError::new(
+            EncodeReason::UnsupportedType,
             format!("Unsupported term type: {:?}", term.get_type()),
+            term,
         )),
     }
 }
 
-#[inline(always)]
-fn write_atom<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_atom<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
+) -> Result<(), EncodeError<'a>> {
     if let Ok(s) = term.atom_to_string() {
         match s.as_str() {
             "true" => writer.write_all(b"true")?,
@@ -218,19 +353,20 @@ fn write_atom<W: Write>(
         }
         Ok(())
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
+        Err(EncodeError::new(
+            EncodeReason::InvalidValue,
             "Failed to decode atom",
+            term,
         ))
     }
 }
 
-#[inline(always)]
-fn write_binary<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_binary<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
+) -> Result<(), EncodeError<'a>> {
     // Use Binary for zero-copy access to bytes
     if let Ok(binary) = term.decode::<Binary>() {
         let bytes = binary.as_slice();
@@ -240,21 +376,23 @@ fn write_binary<W: Write>(
                 write_json_string_escaped(s, writer, opts.escape_mode())?;
                 Ok(())
             }
-            Err(_) => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            Err(_) => Err(EncodeError::new(
+                EncodeReason::InvalidUtf8,
                 "Failed to decode binary",
+                term,
             )),
         }
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
+        Err(EncodeError::new(
+            EncodeReason::InvalidValue,
             "Failed to decode binary",
+            term,
         ))
     }
 }
 
-#[inline(always)]
-fn write_integer<W: Write>(term: Term, writer: &mut W) -> Result<(), std::io::Error> {
+#[inline(never)]
+fn write_integer<'a, W: Write>(term: Term<'a>, writer: &mut W) -> Result<(), EncodeError<'a>> {
     if let Ok(n) = term.decode::<i64>() {
         let mut buf = itoa::Buffer::new();
         writer.write_all(buf.format(n).as_bytes())?;
@@ -273,42 +411,45 @@ fn write_integer<W: Write>(term: Term, writer: &mut W) -> Result<(), std::io::Er
         write!(writer, "{}", n)?;
         return Ok(());
     }
-    Err(std::io::Error::new(
-        std::io::ErrorKind::InvalidData,
+    Err(EncodeError::new(
+        EncodeReason::InvalidValue,
         "Failed to decode integer",
+        term,
     ))
 }
 
-#[inline(always)]
-fn write_float<W: Write>(term: Term, writer: &mut W) -> Result<(), std::io::Error> {
+#[inline(never)]
+fn write_float<'a, W: Write>(term: Term<'a>, writer: &mut W) -> Result<(), EncodeError<'a>> {
     if let Ok(f) = term.decode::<f64>() {
         if f.is_finite() {
             let mut buf = ryu::Buffer::new();
             writer.write_all(buf.format(f).as_bytes())?;
             Ok(())
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            Err(EncodeError::new(
+                EncodeReason::NonFiniteFloat,
                 "Non-finite float",
+                term,
             ))
         }
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
+        Err(EncodeError::new(
+            EncodeReason::InvalidValue,
             "Failed to decode float",
+            term,
         ))
     }
 }
 
-#[inline(always)]
-fn write_list<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_list<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
-    let mut iter: ListIterator = term.decode().map_err(|_| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, "Failed to decode list")
-    })?;
+) -> Result<(), EncodeError<'a>> {
+    let mut iter: ListIterator = term
+        .decode()
+        .map_err(|_| EncodeError::new(EncodeReason::InvalidValue, "Failed to decode list", term))?;
 
     let first_item = match iter.next() {
         None => {
@@ -321,12 +462,12 @@ fn write_list<W: Write>(
     let nested = opts.nested();
     writer.write_all(b"[")?;
     nested.write_newline(writer)?;
-    term_to_json(first_item, writer, nested)?;
+    term_to_json(first_item, writer, nested).map_err(|e| e.at_index(0))?;
 
-    for item in iter {
+    for (i, item) in iter.enumerate() {
         writer.write_all(b",")?;
         nested.write_newline(writer)?;
-        term_to_json(item, writer, nested)?;
+        term_to_json(item, writer, nested).map_err(|e| e.at_index(i + 1))?;
     }
 
     opts.write_newline(writer)?;
@@ -337,7 +478,7 @@ fn write_list<W: Write>(
 /// Extract the JSON key string from a Term (atom, binary, or integer).
 /// Returns Ok(key_string) or Err. For atoms, returns None for "__struct__" to signal skipping.
 #[inline]
-fn key_to_string(key: &Term) -> Result<Option<String>, std::io::Error> {
+pub(crate) fn key_to_string<'a>(key: &Term<'a>) -> Result<Option<String>, EncodeError<'a>> {
     match key.get_type() {
         TermType::Atom => {
             if let Ok(key_str) = key.atom_to_string() {
@@ -347,9 +488,10 @@ fn key_to_string(key: &Term) -> Result<Option<String>, std::io::Error> {
                     Ok(Some(key_str))
                 }
             } else {
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                Err(EncodeError::new(
+                    EncodeReason::InvalidKey,
                     "Failed to decode atom key",
+                    *key,
                 ))
             }
         }
@@ -358,15 +500,17 @@ fn key_to_string(key: &Term) -> Result<Option<String>, std::io::Error> {
                 if let Ok(s) = std::str::from_utf8(binary.as_slice()) {
                     Ok(Some(s.to_string()))
                 } else {
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
+                    Err(EncodeError::new(
+                        EncodeReason::InvalidUtf8,
                         "Non-UTF8 binary as map key",
+                        *key,
                     ))
                 }
             } else {
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                Err(EncodeError::new(
+                    EncodeReason::InvalidKey,
                     "Failed to decode binary key",
+                    *key,
                 ))
             }
         }
@@ -375,25 +519,27 @@ fn key_to_string(key: &Term) -> Result<Option<String>, std::io::Error> {
                 let mut buf = itoa::Buffer::new();
                 Ok(Some(buf.format(n).to_string()))
             } else {
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                Err(EncodeError::new(
+                    EncodeReason::InvalidKey,
                     "Failed to decode integer key",
+                    *key,
                 ))
             }
         }
-        _ => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
+        _ => Err(EncodeError::new(
+            EncodeReason::InvalidKey,
             "Map key must be atom, string, or integer",
+            *key,
         )),
     }
 }
 
 #[inline(always)]
-fn write_map<W: Write>(
-    term: Term,
+fn write_map<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
+) -> Result<(), EncodeError<'a>> {
     // Check for special struct types using pre-interned __struct__ atom.
     // This is a single map_get with a cached atom — no Atom::from_str overhead.
     // Only done when not in lean mode.
@@ -415,42 +561,24 @@ fn write_map<W: Write>(
 }
 
 /// Write map entries in sorted key order.
-fn write_map_sorted<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_map_sorted<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
-    let iter = MapIterator::new(term).ok_or_else(|| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, "Failed to iterate map")
-    })?;
-
-    let strict = opts.strict_keys();
-    let mut seen_keys: Option<HashSet<String>> = if strict { Some(HashSet::new()) } else { None };
-
-    // Collect entries, skipping __struct__
-    let mut entries: Vec<(String, Term)> = Vec::new();
-    for (key, value) in iter {
-        match key_to_string(&key)? {
-            None => continue, // __struct__
-            Some(key_str) => {
-                check_strict_key(&mut seen_keys, &key_str)?;
-                entries.push((key_str, value));
-            }
-        }
-    }
+) -> Result<(), EncodeError<'a>> {
+    let entries = sorted_map_entries(term, opts.strict_keys())?;
 
     if entries.is_empty() {
         writer.write_all(b"{}")?;
         return Ok(());
     }
 
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
-
     let nested = opts.nested();
     let escape = opts.escape_mode();
 
     writer.write_all(b"{")?;
-    for (i, (key_str, value)) in entries.iter().enumerate() {
+    for (i, (key_str, key, value)) in entries.iter().enumerate() {
         if i > 0 {
             writer.write_all(b",")?;
         }
@@ -458,26 +586,61 @@ fn write_map_sorted<W: Write>(
         write_json_string(key_str, writer, escape)?;
         writer.write_all(b":")?;
         nested.write_space(writer)?;
-        term_to_json(*value, writer, nested)?;
+        term_to_json(*value, writer, nested).map_err(|e| e.at_key(*key))?;
     }
     opts.write_newline(writer)?;
     writer.write_all(b"}")?;
     Ok(())
 }
 
+/// Collect a map's entries sorted by key, skipping `__struct__`. Kept out of
+/// `write_map_sorted` so the map iterator stays out of its recursive frame.
+#[inline(never)]
+fn sorted_map_entries<'a>(
+    term: Term<'a>,
+    strict: bool,
+) -> Result<Vec<(String, Term<'a>, Term<'a>)>, EncodeError<'a>> {
+    let iter = MapIterator::new(term).ok_or_else(|| {
+        EncodeError::new(EncodeReason::InvalidValue, "Failed to iterate map", term)
+    })?;
+
+    let mut seen_keys: Option<HashSet<String>> = if strict { Some(HashSet::new()) } else { None };
+
+    // Collect entries, skipping __struct__
+    let mut entries: Vec<(String, Term, Term)> = Vec::new();
+    for (key, value) in iter {
+        match key_to_string(&key)? {
+            None => continue, // __struct__
+            Some(key_str) => {
+                check_strict_key(&mut seen_keys, &key_str, key)?;
+                entries.push((key_str, key, value));
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
 /// Write map entries in iteration order (unsorted, zero overhead).
-#[inline(always)]
-fn write_map_unsorted<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_map_unsorted<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
-    let iter = MapIterator::new(term).ok_or_else(|| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, "Failed to iterate map")
-    })?;
+) -> Result<(), EncodeError<'a>> {
+    // Borrowed in place: moving the 240-byte iterator out of the `Option`
+    // would leave a second copy of it in this recursive frame.
+    let mut iter = MapIterator::new(term);
+    let Some(iter) = iter.as_mut() else {
+        return Err(EncodeError::new(
+            EncodeReason::InvalidValue,
+            "Failed to iterate map",
+            term,
+        ));
+    };
 
     let nested = opts.nested();
-    let escape = opts.escape_mode();
     let strict = opts.strict_keys();
     let mut seen_keys: Option<HashSet<String>> = if strict { Some(HashSet::new()) } else { None };
 
@@ -485,117 +648,172 @@ fn write_map_unsorted<W: Write>(
     let mut started = false;
 
     for (key, value) in iter {
-        if key.get_type() == TermType::Atom {
-            if let Ok(key_str) = key.atom_to_string() {
-                if key_str == "__struct__" {
-                    // Skip __struct__ key from output
-                    continue;
-                }
+        if !write_map_key(key, writer, nested, started, &mut seen_keys)? {
+            // Skip __struct__ key from output
+            continue;
+        }
+        started = true;
 
-                check_strict_key(&mut seen_keys, &key_str)?;
+        writer.write_all(b":")?;
+        nested.write_space(writer)?;
+        term_to_json(value, writer, nested).map_err(|e| e.at_key(key))?;
+    }
 
-                // Write opening brace on first non-filtered entry
-                if !started {
-                    writer.write_all(b"{")?;
-                    started = true;
-                } else {
-                    writer.write_all(b",")?;
-                }
-                nested.write_newline(writer)?;
-                write_json_string(&key_str, writer, escape)?;
-            } else {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Failed to decode atom key",
-                ));
-            }
-        } else {
-            // Non-atom key — this map is definitely not a struct, no need to
-            // check for __struct__. Write opening brace if needed.
-            if !started {
-                writer.write_all(b"{")?;
-                started = true;
-            } else {
-                writer.write_all(b",")?;
+    if started {
+        opts.write_newline(writer)?;
+        writer.write_all(b"}")?;
+    } else {
+        // Empty map (or map with only __struct__)
+        writer.write_all(b"{}")?;
+    }
+    Ok(())
+}
+
+/// Write the opening `{` or separating `,` and one key of an unsorted map.
+/// Returns `false`, writing nothing, for the `__struct__` key. Kept out of
+/// `write_map_unsorted` so the key buffers stay out of its recursive frame.
+#[inline(never)]
+fn write_map_key<'a, W: Write>(
+    key: Term<'a>,
+    writer: &mut W,
+    nested: FormatOptions<'_>,
+    started: bool,
+    seen_keys: &mut Option<HashSet<String>>,
+) -> Result<bool, EncodeError<'a>> {
+    let escape = nested.escape_mode();
+
+    if key.get_type() == TermType::Atom {
+        if let Ok(key_str) = key.atom_to_string() {
+            if key_str == "__struct__" {
+                return Ok(false);
             }
+
+            check_strict_key(seen_keys, &key_str, key)?;
+
+            // Write opening brace on first non-filtered entry
+            writer.write_all(if started { b"," } else { b"{" })?;
             nested.write_newline(writer)?;
+            write_json_string(&key_str, writer, escape)?;
+        } else {
+            return Err(EncodeError::new(
+                EncodeReason::InvalidKey,
+                "Failed to decode atom key",
+                key,
+            ));
+        }
+    } else {
+        // Non-atom key — this map is definitely not a struct, no need to
+        // check for __struct__. Write opening brace if needed.
+        writer.write_all(if started { b"," } else { b"{" })?;
+        nested.write_newline(writer)?;
 
-            // Write key - strings and integers
-            match key.get_type() {
-                TermType::Binary => {
-                    if let Ok(binary) = key.decode::<Binary>() {
-                        if let Ok(s) = std::str::from_utf8(binary.as_slice()) {
-                            check_strict_key(&mut seen_keys, s)?;
-                            write_json_string(s, writer, escape)?;
-                        } else {
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                "Non-UTF8 binary as map key",
-                            ));
-                        }
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Failed to decode binary key",
-                        ));
-                    }
-                }
-                TermType::Integer => {
-                    // Convert integer keys to strings
-                    if let Ok(n) = key.decode::<i64>() {
-                        let mut buf = itoa::Buffer::new();
-                        let key_str = buf.format(n);
-                        check_strict_key(&mut seen_keys, key_str)?;
-                        write_json_string(key_str, writer, escape)?;
+        // Write key - strings and integers
+        match key.get_type() {
+            TermType::Binary => {
+                if let Ok(binary) = key.decode::<Binary>() {
+                    if let Ok(s) = std::str::from_utf8(binary.as_slice()) {
+                        check_strict_key(seen_keys, s, key)?;
+                        write_json_string(s, writer, escape)?;
                     } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Failed to decode integer key",
+                        return Err(EncodeError::new(
+                            EncodeReason::InvalidUtf8,
+                            "Non-UTF8 binary as map key",
+                            key,
                         ));
                     }
+                } else {
+                    return Err(EncodeError::new(
+                        EncodeReason::InvalidKey,
+                        "Failed to decode binary key",
+                        key,
+                    ));
                 }
-                _ => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Map key must be atom, string, or integer",
+            }
+            TermType::Integer => {
+                // Convert integer keys to strings
+                if let Ok(n) = key.decode::<i64>() {
+                    let mut buf = itoa::Buffer::new();
+                    let key_str = buf.format(n);
+                    check_strict_key(seen_keys, key_str, key)?;
+                    write_json_string(key_str, writer, escape)?;
+                } else {
+                    return Err(EncodeError::new(
+                        EncodeReason::InvalidKey,
+                        "Failed to decode integer key",
+                        key,
                     ));
                 }
             }
+            _ => {
+                return Err(EncodeError::new(
+                    EncodeReason::InvalidKey,
+                    "Map key must be atom, string, or integer",
+                    key,
+                ));
+            }
         }
-
-        writer.write_all(b":")?;
-        nested.write_space(writer)?;
-        term_to_json(value, writer, nested)?;
     }
 
-    if started {
-        opts.write_newline(writer)?;
-        writer.write_all(b"}")?;
-    } else {
-        // Empty map (or map with only __struct__)
-        writer.write_all(b"{}")?;
-    }
-    Ok(())
+    Ok(true)
 }
 
-/// Try to format special Elixir structs (Decimal, Date, Time, DateTime, NaiveDateTime, URI).
+/// Try to format special Elixir structs (MapSet, Range, OrderedObject, Fragment, and
+/// through `try_format_scalar_struct` Decimal, Date, Time, DateTime, NaiveDateTime, URI).
 /// Called when we encounter __struct__ during map iteration — the struct name value
 /// is passed directly, avoiding a separate map_get lookup.
 /// Returns Ok(Some(())) if handled, Ok(None) if not a special struct, Err on error.
-fn try_format_special_struct_from_name<W: Write>(
-    term: &Term,
+#[inline(never)]
+fn try_format_special_struct_from_name<'a, W: Write>(
+    term: &Term<'a>,
     struct_name_term: &Term,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<Option<()>, std::io::Error> {
+) -> Result<Option<()>, EncodeError<'a>> {
     let struct_str = match struct_name_term.atom_to_string() {
         Ok(s) => s,
         Err(_) => return Ok(None),
     };
 
-    let escape = opts.escape_mode();
-
     match struct_str.as_str() {
+        "Elixir.MapSet" => {
+            // MapSet has a "map" field containing the actual data as map keys
+            if let Some(()) = try_format_mapset(term, writer, opts)? {
+                return Ok(Some(()));
+            }
+        }
+        "Elixir.Range" => {
+            // Range has first, last, step fields - encode as array [first, last] or [first, last, step]
+            if let Some(()) = try_format_range(term, writer, opts)? {
+                return Ok(Some(()));
+            }
+        }
+        "Elixir.RustyJson.OrderedObject" => {
+            if let Some(()) = try_format_ordered_object(term, writer, opts)? {
+                return Ok(Some(()));
+            }
+        }
+        "Elixir.RustyJson.Fragment" | "Elixir.Jason.Fragment" => {
+            if let Some(()) = try_format_fragment(term, writer, opts)? {
+                return Ok(Some(()));
+            }
+        }
+        name => return try_format_scalar_struct(name, term, writer, opts.escape_mode()),
+    }
+
+    Ok(None)
+}
+
+/// Format the structs that encode as a single JSON string (Decimal, Date, Time,
+/// DateTime, NaiveDateTime, URI). Kept out of line: its buffers would otherwise
+/// sit in the frame of every nesting level that passes through a struct.
+#[inline(never)]
+fn try_format_scalar_struct<'a, W: Write>(
+    struct_name: &str,
+    term: &Term<'a>,
+    writer: &mut W,
+    escape: EscapeMode,
+) -> Result<Option<()>, EncodeError<'a>> {
+    match struct_name {
         "Elixir.Decimal" => {
             if let Some(decimal_string) = try_format_decimal(term) {
                 write_json_string(&decimal_string, writer, escape)?;
@@ -628,28 +846,6 @@ fn try_format_special_struct_from_name<W: Write>(
                 return Ok(Some(()));
             }
         }
-        "Elixir.MapSet" => {
-            // MapSet has a "map" field containing the actual data as map keys
-            if let Some(()) = try_format_mapset(term, writer, opts)? {
-                return Ok(Some(()));
-            }
-        }
-        "Elixir.Range" => {
-            // Range has first, last, step fields - encode as array [first, last] or [first, last, step]
-            if let Some(()) = try_format_range(term, writer, opts)? {
-                return Ok(Some(()));
-            }
-        }
-        "Elixir.RustyJson.OrderedObject" => {
-            if let Some(()) = try_format_ordered_object(term, writer, opts)? {
-                return Ok(Some(()));
-            }
-        }
-        "Elixir.RustyJson.Fragment" | "Elixir.Jason.Fragment" => {
-            if let Some(()) = try_format_fragment(term, writer, opts)? {
-                return Ok(Some(()));
-            }
-        }
         _ => {}
     }
 
@@ -658,11 +854,12 @@ fn try_format_special_struct_from_name<W: Write>(
 
 /// Format OrderedObject as a JSON object preserving key order with proper pretty-printing.
 /// The `values` field is a list of {key, value} 2-tuples.
-fn try_format_ordered_object<W: Write>(
-    term: &Term,
+#[inline(never)]
+fn try_format_ordered_object<'a, W: Write>(
+    term: &Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<Option<()>, std::io::Error> {
+) -> Result<Option<()>, EncodeError<'a>> {
     let env = term.get_env();
     let t = *term;
 
@@ -688,15 +885,17 @@ fn try_format_ordered_object<W: Write>(
     while let Ok((head, tail)) = current.list_get_cell() {
         // Each element is a {key, value} tuple
         let items = rustler::types::tuple::get_tuple(head).map_err(|_| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            EncodeError::new(
+                EncodeReason::InvalidValue,
                 "OrderedObject value must be a tuple",
+                head,
             )
         })?;
         if items.len() != 2 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(EncodeError::new(
+                EncodeReason::InvalidValue,
                 "OrderedObject value must be a 2-tuple",
+                head,
             ));
         }
 
@@ -713,22 +912,16 @@ fn try_format_ordered_object<W: Write>(
         match key.get_type() {
             TermType::Atom => {
                 let key_str = key.atom_to_string().map_err(|_| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Failed to decode atom key",
-                    )
+                    EncodeError::new(EncodeReason::InvalidKey, "Failed to decode atom key", key)
                 })?;
                 write_json_string(&key_str, writer, escape)?;
             }
             TermType::Binary => {
                 let binary: Binary = key.decode().map_err(|_| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Failed to decode binary key",
-                    )
+                    EncodeError::new(EncodeReason::InvalidKey, "Failed to decode binary key", key)
                 })?;
                 let s = std::str::from_utf8(binary.as_slice()).map_err(|_| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, "Non-UTF8 binary key")
+                    EncodeError::new(EncodeReason::InvalidUtf8, "Non-UTF8 binary key", key)
                 })?;
                 write_json_string(s, writer, escape)?;
             }
@@ -738,23 +931,25 @@ fn try_format_ordered_object<W: Write>(
                     let key_str = buf.format(n);
                     write_json_string(key_str, writer, escape)?;
                 } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
+                    return Err(EncodeError::new(
+                        EncodeReason::InvalidKey,
                         "Failed to decode integer key",
+                        key,
                     ));
                 }
             }
             _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                return Err(EncodeError::new(
+                    EncodeReason::InvalidKey,
                     "OrderedObject key must be atom, string, or integer",
+                    key,
                 ));
             }
         }
 
         writer.write_all(b":")?;
         nested.write_space(writer)?;
-        term_to_json(value, writer, nested)?;
+        term_to_json(value, writer, nested).map_err(|e| e.at_key(key))?;
 
         current = tail;
     }
@@ -767,11 +962,12 @@ fn try_format_ordered_object<W: Write>(
 /// Format pre-encoded JSON fragment.
 /// When pretty-printing is active, reformats the compact iodata with proper
 /// depth-aware indentation (streaming, zero-allocation). Otherwise dumps as-is.
-fn try_format_fragment<W: Write>(
-    term: &Term,
+#[inline(never)]
+fn try_format_fragment<'a, W: Write>(
+    term: &Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<Option<()>, std::io::Error> {
+) -> Result<Option<()>, EncodeError<'a>> {
     let env = term.get_env();
     let t = *term;
 
@@ -789,10 +985,17 @@ fn try_format_fragment<W: Write>(
             pending_newline: false,
             ctx: opts.ctx,
         };
-        reformat_iodata(writer, encode_term, &mut state)?;
+        reformat_iodata(writer, encode_term, &mut state)
     } else {
-        write_iodata(writer, encode_term)?;
+        write_iodata(writer, encode_term)
     }
+    .map_err(|e| match e.kind() {
+        // Raised by the iodata walkers themselves; anything else is the writer
+        std::io::ErrorKind::InvalidData => {
+            EncodeError::new(EncodeReason::InvalidValue, e.to_string(), encode_term)
+        }
+        _ => e.into(),
+    })?;
     Ok(Some(()))
 }
 
@@ -1280,11 +1483,12 @@ fn try_format_uri(term: &Term) -> Option<String> {
 
 /// Format Elixir MapSet as JSON array
 /// MapSet stores data as %MapSet{map: %{elem1 => [], elem2 => [], ...}}
-fn try_format_mapset<W: Write>(
-    term: &Term,
+#[inline(never)]
+fn try_format_mapset<'a, W: Write>(
+    term: &Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<Option<()>, std::io::Error> {
+) -> Result<Option<()>, EncodeError<'a>> {
     let env = term.get_env();
     let t = *term;
 
@@ -1294,23 +1498,22 @@ fn try_format_mapset<W: Write>(
         Err(_) => return Ok(None),
     };
 
-    // The keys of the internal map are the set elements
-    let iter = match MapIterator::new(map_term) {
-        Some(i) => i,
-        None => return Ok(None),
+    // The keys of the internal map are the set elements. Borrowed in place,
+    // like in `write_map_unsorted`.
+    let mut iter = MapIterator::new(map_term);
+    let Some(iter) = iter.as_mut() else {
+        return Ok(None);
     };
 
     let nested = opts.nested();
     writer.write_all(b"[")?;
 
-    let mut first = true;
-    for (key, _value) in iter {
-        if !first {
+    for (i, (key, _value)) in iter.enumerate() {
+        if i > 0 {
             writer.write_all(b",")?;
         }
-        first = false;
         nested.write_newline(writer)?;
-        term_to_json(key, writer, nested)?;
+        term_to_json(key, writer, nested).map_err(|e| e.at_index(i))?;
     }
 
     opts.write_newline(writer)?;
@@ -1320,11 +1523,12 @@ fn try_format_mapset<W: Write>(
 
 /// Format Elixir Range as JSON object {first, last, step}
 /// Range is %Range{first: x, last: y, step: z}
-fn try_format_range<W: Write>(
-    term: &Term,
+#[inline(never)]
+fn try_format_range<'a, W: Write>(
+    term: &Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<Option<()>, std::io::Error> {
+) -> Result<Option<()>, EncodeError<'a>> {
     let env = term.get_env();
     let t = *term;
 
@@ -1427,14 +1631,14 @@ where
     Some((v1, v2))
 }
 
-#[inline(always)]
-fn write_tuple<W: Write>(
-    term: Term,
+#[inline(never)]
+fn write_tuple<'a, W: Write>(
+    term: Term<'a>,
     writer: &mut W,
     opts: FormatOptions<'_>,
-) -> Result<(), std::io::Error> {
+) -> Result<(), EncodeError<'a>> {
     let items = rustler::types::tuple::get_tuple(term).map_err(|_| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, "Failed to decode tuple")
+        EncodeError::new(EncodeReason::InvalidValue, "Failed to decode tuple", term)
     })?;
 
     if items.is_empty() {
@@ -1445,14 +1649,12 @@ fn write_tuple<W: Write>(
     let nested = opts.nested();
     writer.write_all(b"[")?;
 
-    let mut first = true;
-    for item in items.iter() {
-        if !first {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
             writer.write_all(b",")?;
         }
-        first = false;
         nested.write_newline(writer)?;
-        term_to_json(*item, writer, nested)?;
+        term_to_json(*item, writer, nested).map_err(|e| e.at_index(i))?;
     }
 
     opts.write_newline(writer)?;
@@ -1728,6 +1930,7 @@ pub fn write_json_string_escaped_pub<W: Write>(
 /// Public wrapper for write_integer, used by encode_fields NIF
 pub fn write_integer_pub<W: Write>(term: Term, writer: &mut W) -> Result<(), std::io::Error> {
     write_integer(term, writer)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
 }
 
 #[cfg(feature = "bench")]
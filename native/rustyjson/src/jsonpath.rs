@@ -0,0 +1,1105 @@
+//! JSONPath queries evaluated directly on the input bytes.
+//!
+//! Supported subset of RFC 9535:
+//!
+//! - root `$`, child `.name` / `['name']`, wildcard `.*` / `[*]`
+//! - recursive descent `..name`, `..*`, `..[selector]`
+//! - array index `[2]`, `[-1]` and slice `[start:end:step]` (positive step)
+//! - filters `[?(@.status == "active")]` over relative paths, with `==`,
+//!   `!=`, `<`, `<=`, `>`, `>=`, existence tests (`?@.email`), `!`, `&&`,
+//!   `||` and parentheses
+//!
+//! Evaluation is one pre-order walk of the document. Every value carries the
+//! set of path segments it may still match; a value whose set is empty is
+//! skipped with `skip_value` (grammar-checked, no terms built, SIMD string
+//! skipping and the structural index for large inputs). Matches are decoded
+//! with the regular `DirectParser` value builders, so selecting a few records
+//! from a huge document only builds terms for those records. Each matching
+//! node is reported once, in document order.
+
+use crate::direct_decode::{DecodeError, DirectParser};
+use rustler::{Binary, Env, NewBinary, NifUnitEnum, Term};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// Path syntax error: message and byte offset into the path string.
+pub type PathError = (Cow<'static, str>, usize);
+
+/// Deepest nesting of `(` and `!` in a filter expression. Parsing and
+/// evaluating recurse once per level.
+const MAX_FILTER_NESTING: usize = 64;
+
+/// What a query returns for each match.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueryReturn {
+    /// The decoded values.
+    Values,
+    /// Normalized paths such as `$['items'][0]`.
+    Paths,
+}
+
+/// A parsed JSONPath expression.
+#[derive(Debug, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Segment {
+    /// `..` segment: applies to the node's descendants as well.
+    descendant: bool,
+    selector: Selector,
+}
+
+#[derive(Debug, PartialEq)]
+enum Selector {
+    Name(Vec<u8>),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    Filter(Expr),
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Exists(Vec<Step>),
+    Compare(Vec<Step>, CmpOp, Literal),
+}
+
+/// One step of a relative (`@`) path inside a filter.
+#[derive(Debug, PartialEq)]
+enum Step {
+    Name(Vec<u8>),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    /// The operator with its operands swapped (`1 < @.x` is `@.x > 1`).
+    fn flip(self) -> Self {
+        match self {
+            CmpOp::Lt => CmpOp::Gt,
+            CmpOp::Le => CmpOp::Ge,
+            CmpOp::Gt => CmpOp::Lt,
+            CmpOp::Ge => CmpOp::Le,
+            op => op,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Literal {
+    String(Vec<u8>),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+// ============================================================================
+// Path parsing
+// ============================================================================
+
+impl Path {
+    /// Parse a JSONPath expression.
+    pub fn parse(src: &[u8]) -> Result<Self, PathError> {
+        let mut parser = PathParser {
+            src,
+            pos: 0,
+            nesting: 0,
+        };
+        parser.parse_path()
+    }
+}
+
+struct PathParser<'s> {
+    src: &'s [u8],
+    pos: usize,
+    /// Open `(` and `!` around the filter expression being parsed.
+    nesting: usize,
+}
+
+impl PathParser<'_> {
+    fn err<T>(&self, msg: &'static str) -> Result<T, PathError> {
+        Err((Cow::Borrowed(msg), self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8, msg: &'static str) -> Result<(), PathError> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            self.err(msg)
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<Path, PathError> {
+        self.expect(b'$', "Path must start with '$'")?;
+        let mut segments = Vec::new();
+        while self.pos < self.src.len() {
+            let descendant = if self.src[self.pos..].starts_with(b"..") {
+                self.pos += 2;
+                true
+            } else {
+                false
+            };
+            let selector = match self.peek() {
+                Some(b'[') => {
+                    self.pos += 1;
+                    self.parse_bracket_selector()?
+                }
+                Some(b'.') if !descendant => {
+                    self.pos += 1;
+                    self.parse_dot_selector()?
+                }
+                _ if descendant => self.parse_dot_selector()?,
+                _ => return self.err("Expected '.', '..' or '['"),
+            };
+            segments.push(Segment {
+                descendant,
+                selector,
+            });
+        }
+        Ok(Path { segments })
+    }
+
+    /// `*` or a member name after `.` or `..`.
+    fn parse_dot_selector(&mut self) -> Result<Selector, PathError> {
+        if self.eat(b'*') {
+            return Ok(Selector::Wildcard);
+        }
+        Ok(Selector::Name(self.parse_name()?))
+    }
+
+    /// Member-name shorthand: ASCII letters, digits and `_`, or any non-ASCII
+    /// byte; may not start with a digit.
+    fn parse_name(&mut self) -> Result<Vec<u8>, PathError> {
+        let start = self.pos;
+        if matches!(self.peek(), Some(b'0'..=b'9')) {
+            return self.err("Expected member name");
+        }
+        while matches!(self.peek(), Some(b) if b == b'_' || b.is_ascii_alphanumeric() || b >= 0x80)
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return self.err("Expected member name");
+        }
+        Ok(self.src[start..self.pos].to_vec())
+    }
+
+    /// The selector between `[` and `]`; consumes the closing bracket.
+    fn parse_bracket_selector(&mut self) -> Result<Selector, PathError> {
+        self.skip_ws();
+        let selector = match self.peek() {
+            Some(b'*') => {
+                self.pos += 1;
+                Selector::Wildcard
+            }
+            Some(b'\'' | b'"') => Selector::Name(self.parse_string()?),
+            Some(b'?') => {
+                self.pos += 1;
+                self.skip_ws();
+                Selector::Filter(self.parse_or()?)
+            }
+            Some(b'-' | b'0'..=b'9' | b':') => self.parse_index_or_slice()?,
+            _ => return self.err("Invalid selector"),
+        };
+        self.skip_ws();
+        self.expect(b']', "Expected ']'")?;
+        Ok(selector)
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, PathError> {
+        let start = self.parse_opt_int()?;
+        self.skip_ws();
+        if !self.eat(b':') {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None => self.err("Expected array index"),
+            };
+        }
+        self.skip_ws();
+        let end = self.parse_opt_int()?;
+        self.skip_ws();
+        let mut step = 1;
+        if self.eat(b':') {
+            self.skip_ws();
+            let step_pos = self.pos;
+            if let Some(s) = self.parse_opt_int()? {
+                if s <= 0 {
+                    self.pos = step_pos;
+                    return self.err("Slice step must be a positive integer");
+                }
+                step = s;
+            }
+        }
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn parse_opt_int(&mut self) -> Result<Option<i64>, PathError> {
+        let start = self.pos;
+        self.eat(b'-');
+        let digits = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        if self.pos == digits {
+            if digits != start {
+                return self.err("Expected digit");
+            }
+            return Ok(None);
+        }
+        std::str::from_utf8(&self.src[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Some)
+            .ok_or((Cow::Borrowed("Integer out of range"), start))
+    }
+
+    /// A single- or double-quoted string literal with JSON-style escapes.
+    fn parse_string(&mut self) -> Result<Vec<u8>, PathError> {
+        let quote = self.src[self.pos];
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None => return self.err("Unterminated string"),
+                Some(b) if b == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some(b @ (b'\'' | b'"' | b'\\' | b'/')) => b,
+                        Some(b'b') => 0x08,
+                        Some(b'f') => 0x0C,
+                        Some(b'n') => b'\n',
+                        Some(b'r') => b'\r',
+                        Some(b't') => b'\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let c = self.parse_unicode_escape()?;
+                            let mut buf = [0u8; 4];
+                            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                            continue;
+                        }
+                        _ => return self.err("Invalid escape"),
+                    };
+                    out.push(escaped);
+                    self.pos += 1;
+                }
+                Some(b) => {
+                    out.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// The `XXXX` of a `\uXXXX` escape, combining a surrogate pair.
+    fn parse_unicode_escape(&mut self) -> Result<char, PathError> {
+        let start = self.pos;
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.src[self.pos..].starts_with(b"\\u") {
+                return Err((Cow::Borrowed("Invalid surrogate pair"), start));
+            }
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err((Cow::Borrowed("Invalid surrogate pair"), start));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or((Cow::Borrowed("Invalid unicode escape"), start))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, PathError> {
+        let hex = self
+            .src
+            .get(self.pos..self.pos + 4)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok());
+        match hex {
+            Some(value) => {
+                self.pos += 4;
+                Ok(value)
+            }
+            None => self.err("Invalid unicode escape"),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Filter expressions
+    // ------------------------------------------------------------------------
+
+    fn parse_or(&mut self) -> Result<Expr, PathError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.src[self.pos..].starts_with(b"||") {
+            self.pos += 2;
+            self.skip_ws();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, PathError> {
+        let mut terms = vec![self.parse_unary()?];
+        while self.src[self.pos..].starts_with(b"&&") {
+            self.pos += 2;
+            self.skip_ws();
+            terms.push(self.parse_unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    /// A negation, parenthesized expression or test, followed by whitespace.
+    fn parse_unary(&mut self) -> Result<Expr, PathError> {
+        let expr = match self.peek() {
+            Some(b'!') if self.src.get(self.pos + 1) != Some(&b'=') => {
+                self.enter_nesting()?;
+                self.pos += 1;
+                self.skip_ws();
+                let inner = self.parse_unary()?;
+                self.nesting -= 1;
+                Expr::Not(Box::new(inner))
+            }
+            Some(b'(') => {
+                self.enter_nesting()?;
+                self.pos += 1;
+                self.skip_ws();
+                let inner = self.parse_or()?;
+                self.expect(b')', "Expected ')'")?;
+                self.nesting -= 1;
+                inner
+            }
+            _ => self.parse_test()?,
+        };
+        self.skip_ws();
+        Ok(expr)
+    }
+
+    fn enter_nesting(&mut self) -> Result<(), PathError> {
+        if self.nesting >= MAX_FILTER_NESTING {
+            return self.err("Filter expression nested too deeply");
+        }
+        self.nesting += 1;
+        Ok(())
+    }
+
+    /// `@path`, `@path op literal` or `literal op @path`.
+    fn parse_test(&mut self) -> Result<Expr, PathError> {
+        if self.peek() == Some(b'@') {
+            let steps = self.parse_relative_path()?;
+            self.skip_ws();
+            return match self.parse_cmp_op() {
+                Some(op) => {
+                    self.skip_ws();
+                    Ok(Expr::Compare(steps, op, self.parse_literal()?))
+                }
+                None => Ok(Expr::Exists(steps)),
+            };
+        }
+        if self.peek() == Some(b'$') {
+            return self.err("Only relative '@' paths are supported in filters");
+        }
+        let literal = self.parse_literal()?;
+        self.skip_ws();
+        let Some(op) = self.parse_cmp_op() else {
+            return self.err("Expected comparison operator");
+        };
+        self.skip_ws();
+        if self.peek() != Some(b'@') {
+            return self.err("Comparison needs an '@' path on one side");
+        }
+        let steps = self.parse_relative_path()?;
+        Ok(Expr::Compare(steps, op.flip(), literal))
+    }
+
+    fn parse_relative_path(&mut self) -> Result<Vec<Step>, PathError> {
+        self.expect(b'@', "Expected '@'")?;
+        let mut steps = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'.') => {
+                    self.pos += 1;
+                    steps.push(Step::Name(self.parse_name()?));
+                }
+                Some(b'[') => {
+                    self.pos += 1;
+                    self.skip_ws();
+                    let step = match self.peek() {
+                        Some(b'\'' | b'"') => Step::Name(self.parse_string()?),
+                        Some(b'0'..=b'9') => match self.parse_opt_int()? {
+                            Some(index) => Step::Index(index as usize),
+                            None => return self.err("Expected array index"),
+                        },
+                        _ => return self.err("Expected name or non-negative index"),
+                    };
+                    self.skip_ws();
+                    self.expect(b']', "Expected ']'")?;
+                    steps.push(step);
+                }
+                _ => return Ok(steps),
+            }
+        }
+    }
+
+    fn parse_cmp_op(&mut self) -> Option<CmpOp> {
+        let rest = &self.src[self.pos..];
+        let (op, len) = if rest.starts_with(b"==") {
+            (CmpOp::Eq, 2)
+        } else if rest.starts_with(b"!=") {
+            (CmpOp::Ne, 2)
+        } else if rest.starts_with(b"<=") {
+            (CmpOp::Le, 2)
+        } else if rest.starts_with(b">=") {
+            (CmpOp::Ge, 2)
+        } else if rest.starts_with(b"<") {
+            (CmpOp::Lt, 1)
+        } else if rest.starts_with(b">") {
+            (CmpOp::Gt, 1)
+        } else {
+            return None;
+        };
+        self.pos += len;
+        Some(op)
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, PathError> {
+        let rest = &self.src[self.pos..];
+        for (word, literal) in [
+            (&b"true"[..], Literal::Bool(true)),
+            (b"false", Literal::Bool(false)),
+            (b"null", Literal::Null),
+        ] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(literal);
+            }
+        }
+        match self.peek() {
+            Some(b'\'' | b'"') => Ok(Literal::String(self.parse_string()?)),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while matches!(
+                    self.peek(),
+                    Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                ) {
+                    self.pos += 1;
+                }
+                std::str::from_utf8(&self.src[start..self.pos])
+                    .ok()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .map(Literal::Number)
+                    .ok_or((Cow::Borrowed("Invalid number"), start))
+            }
+            _ => self.err("Expected literal"),
+        }
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// One step of the location of the value being visited.
+enum Location<'b> {
+    Key(Cow<'b, [u8]>),
+    Index(usize),
+}
+
+/// A filter operand read from the document.
+enum Operand<'b> {
+    /// The relative path did not resolve.
+    Nothing,
+    String(Cow<'b, [u8]>),
+    Number(f64),
+    Bool(bool),
+    Null,
+    Container,
+}
+
+/// States are segment indices: state `i` means the value matched the first
+/// `i` segments. A handful is typical, so a sorted `Vec` beats a set.
+type States = Vec<usize>;
+
+struct Evaluator<'p, 'a, 'b> {
+    env: Env<'a>,
+    parser: DirectParser<'a, 'b>,
+    segments: &'p [Segment],
+    mode: QueryReturn,
+    location: Vec<Location<'b>>,
+    out: Vec<Term<'a>>,
+}
+
+/// Run `path` over `input`, returning the matching values or their
+/// normalized paths in document order.
+pub fn query<'a, 'b>(
+    env: Env<'a>,
+    parser: DirectParser<'a, 'b>,
+    path: &Path,
+    mode: QueryReturn,
+) -> Result<Vec<Term<'a>>, DecodeError> {
+    let mut evaluator = Evaluator {
+        env,
+        parser,
+        segments: &path.segments,
+        mode,
+        location: Vec::new(),
+        out: Vec::new(),
+    };
+    evaluator.walk(&[0])?;
+    evaluator.parser.expect_end()?;
+    Ok(evaluator.out)
+}
+
+impl<'a, 'b> Evaluator<'_, 'a, 'b> {
+    /// Visit the value at the current position with the given states.
+    fn walk(&mut self, states: &[usize]) -> Result<(), DecodeError> {
+        let done = self.segments.len();
+        let matched = states.last() == Some(&done);
+        let active = if matched {
+            &states[..states.len() - 1]
+        } else {
+            states
+        };
+        let descend =
+            !active.is_empty() && matches!(self.parser.current_byte(), Some(b'[') | Some(b'{'));
+
+        if matched {
+            match self.mode {
+                QueryReturn::Paths => self.out.push(self.normalized_path()),
+                QueryReturn::Values if descend => {
+                    let checkpoint = self.parser.checkpoint();
+                    let term = self.parser.parse_current()?;
+                    self.out.push(term);
+                    self.parser.restore(checkpoint);
+                }
+                QueryReturn::Values => {
+                    let term = self.parser.parse_current()?;
+                    self.out.push(term);
+                    return Ok(());
+                }
+            }
+        }
+
+        match self.parser.current_byte() {
+            Some(b'[') if descend => self.walk_array(active),
+            Some(b'{') if descend => self.walk_object(active),
+            _ => self.parser.skip_value(),
+        }
+    }
+
+    fn walk_array(&mut self, states: &[usize]) -> Result<(), DecodeError> {
+        let needs_length = states.iter().any(|&s| match &self.segments[s].selector {
+            Selector::Index(i) => *i < 0,
+            Selector::Slice { start, end, .. } => {
+                start.is_some_and(|i| i < 0) || end.is_some_and(|i| i < 0)
+            }
+            _ => false,
+        });
+        let length = if needs_length {
+            let checkpoint = self.parser.checkpoint();
+            let length = self.parser.count_array_elements()?;
+            self.parser.restore(checkpoint);
+            length as i64
+        } else {
+            0
+        };
+
+        self.parser.enter_container()?;
+        let mut index = 0;
+        while self.parser.next_element(index == 0)? {
+            let child = self.child_states(states, |selector, evaluator| {
+                Ok(match selector {
+                    Selector::Wildcard => true,
+                    Selector::Index(i) => normalize_index(*i, length) == Some(index),
+                    Selector::Slice { start, end, step } => {
+                        in_slice(index, *start, *end, *step, length)
+                    }
+                    Selector::Filter(expr) => evaluator.eval_filter(expr)?,
+                    Selector::Name(_) => false,
+                })
+            })?;
+            self.visit_child(Location::Index(index), &child)?;
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn walk_object(&mut self, states: &[usize]) -> Result<(), DecodeError> {
+        self.parser.enter_container()?;
+        let mut first = true;
+        while let Some(key) = self.parser.next_member(first)? {
+            first = false;
+            let child = self.child_states(states, |selector, evaluator| {
+                Ok(match selector {
+                    Selector::Wildcard => true,
+                    Selector::Name(name) => *name == *key,
+                    Selector::Filter(expr) => evaluator.eval_filter(expr)?,
+                    Selector::Index(_) | Selector::Slice { .. } => false,
+                })
+            })?;
+            self.visit_child(Location::Key(key), &child)?;
+        }
+        Ok(())
+    }
+
+    /// Compute the states of the child at the current position. A `..`
+    /// segment keeps searching below every child; a matching selector
+    /// advances to the next segment.
+    fn child_states(
+        &mut self,
+        states: &[usize],
+        mut selects: impl FnMut(&Selector, &mut Self) -> Result<bool, DecodeError>,
+    ) -> Result<States, DecodeError> {
+        let segments = self.segments;
+        let mut child = States::new();
+        for &state in states {
+            let segment = &segments[state];
+            if segment.descendant {
+                child.push(state);
+            }
+            if selects(&segment.selector, self)? {
+                child.push(state + 1);
+            }
+        }
+        child.sort_unstable();
+        child.dedup();
+        Ok(child)
+    }
+
+    fn visit_child(&mut self, location: Location<'b>, states: &[usize]) -> Result<(), DecodeError> {
+        if states.is_empty() {
+            return self.parser.skip_value();
+        }
+        self.location.push(location);
+        let result = self.walk(states);
+        self.location.pop();
+        result
+    }
+
+    /// Evaluate a filter against the value at the current position, leaving
+    /// the parser where it was.
+    fn eval_filter(&mut self, expr: &Expr) -> Result<bool, DecodeError> {
+        match expr {
+            Expr::Or(terms) => {
+                for term in terms {
+                    if self.eval_filter(term)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Expr::And(terms) => {
+                for term in terms {
+                    if !self.eval_filter(term)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Expr::Not(inner) => Ok(!self.eval_filter(inner)?),
+            Expr::Exists(steps) => {
+                let checkpoint = self.parser.checkpoint();
+                let found = self.seek_relative(steps);
+                self.parser.restore(checkpoint);
+                found
+            }
+            Expr::Compare(steps, op, literal) => {
+                let checkpoint = self.parser.checkpoint();
+                let operand = match self.seek_relative(steps) {
+                    Ok(true) => self.read_operand(),
+                    Ok(false) => Ok(Operand::Nothing),
+                    Err(e) => Err(e),
+                };
+                self.parser.restore(checkpoint);
+                Ok(compare(&operand?, *op, literal))
+            }
+        }
+    }
+
+    /// Move to the descendant named by `steps`; `Ok(false)` if it is absent.
+    fn seek_relative(&mut self, steps: &[Step]) -> Result<bool, DecodeError> {
+        for step in steps {
+            let found = match (step, self.parser.current_byte()) {
+                (Step::Name(name), Some(b'{')) => {
+                    self.parser.enter_container()?;
+                    let mut first = true;
+                    loop {
+                        match self.parser.next_member(first)? {
+                            Some(key) if *key == **name => break true,
+                            Some(_) => self.parser.skip_value()?,
+                            None => break false,
+                        }
+                        first = false;
+                    }
+                }
+                (Step::Index(index), Some(b'[')) => {
+                    self.parser.enter_container()?;
+                    let mut i = 0;
+                    loop {
+                        if !self.parser.next_element(i == 0)? {
+                            break false;
+                        }
+                        if i == *index {
+                            break true;
+                        }
+                        self.parser.skip_value()?;
+                        i += 1;
+                    }
+                }
+                _ => false,
+            };
+            if !found {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_operand(&mut self) -> Result<Operand<'b>, DecodeError> {
+        Ok(match self.parser.current_byte() {
+            Some(b'"') => Operand::String(self.parser.read_string()?),
+            Some(b'-' | b'0'..=b'9') => {
+                let bytes = self.parser.read_number()?;
+                // Grammar already checked; the digit limit bounds the length.
+                let value = std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(f64::NAN);
+                Operand::Number(value)
+            }
+            Some(b @ (b't' | b'f' | b'n')) => {
+                self.parser.skip_value()?;
+                match b {
+                    b't' => Operand::Bool(true),
+                    b'f' => Operand::Bool(false),
+                    _ => Operand::Null,
+                }
+            }
+            Some(b'[' | b'{') => Operand::Container,
+            _ => {
+                self.parser.skip_value()?;
+                Operand::Nothing
+            }
+        })
+    }
+
+    /// RFC 9535 normalized path of the current location, e.g. `$['a'][0]`.
+    fn normalized_path(&self) -> Term<'a> {
+        let mut path = vec![b'$'];
+        for step in &self.location {
+            match step {
+                Location::Index(i) => {
+                    path.push(b'[');
+                    path.extend_from_slice(i.to_string().as_bytes());
+                    path.push(b']');
+                }
+                Location::Key(key) => {
+                    path.extend_from_slice(b"['");
+                    escape_name(key, &mut path);
+                    path.extend_from_slice(b"']");
+                }
+            }
+        }
+        let mut bin = NewBinary::new(self.env, path.len());
+        bin.as_mut_slice().copy_from_slice(&path);
+        Binary::from(bin).to_term(self.env)
+    }
+}
+
+/// Resolve a possibly negative index against an array of `length` elements.
+fn normalize_index(index: i64, length: i64) -> Option<usize> {
+    let index = if index < 0 { index + length } else { index };
+    usize::try_from(index).ok()
+}
+
+/// Whether `index` is selected by `[start:end:step]` (step > 0) in an array
+/// of `length` elements. `length` is only meaningful when a bound is negative.
+fn in_slice(index: usize, start: Option<i64>, end: Option<i64>, step: i64, length: i64) -> bool {
+    let bound = |i: i64| if i < 0 { (i + length).max(0) } else { i };
+    let index = index as i64;
+    let lower = start.map_or(0, bound);
+    if index < lower || end.is_some_and(|e| index >= bound(e)) {
+        return false;
+    }
+    (index - lower) % step == 0
+}
+
+fn compare(left: &Operand, op: CmpOp, right: &Literal) -> bool {
+    let ordering = match (left, right) {
+        (Operand::String(a), Literal::String(b)) => Some(a.as_ref().cmp(b.as_slice())),
+        (Operand::Number(a), Literal::Number(b)) => a.partial_cmp(b),
+        (Operand::Bool(a), Literal::Bool(b)) => (a == b).then_some(Ordering::Equal),
+        (Operand::Null, Literal::Null) => Some(Ordering::Equal),
+        _ => None,
+    };
+    // Booleans and nulls are only equal or unequal, never ordered.
+    let ordered = matches!(
+        (left, right),
+        (Operand::String(_), Literal::String(_)) | (Operand::Number(_), Literal::Number(_))
+    );
+    match op {
+        CmpOp::Eq => ordering == Some(Ordering::Equal),
+        CmpOp::Ne => ordering != Some(Ordering::Equal),
+        CmpOp::Lt => ordered && ordering == Some(Ordering::Less),
+        CmpOp::Gt => ordered && ordering == Some(Ordering::Greater),
+        CmpOp::Le => {
+            ordering == Some(Ordering::Equal) || (ordered && ordering == Some(Ordering::Less))
+        }
+        CmpOp::Ge => {
+            ordering == Some(Ordering::Equal) || (ordered && ordering == Some(Ordering::Greater))
+        }
+    }
+}
+
+/// Escape a member name for a normalized path: `'` and `\` are
+/// backslash-escaped, control characters use JSON escapes.
+fn escape_name(name: &[u8], out: &mut Vec<u8>) {
+    for &b in name {
+        match b {
+            b'\'' => out.extend_from_slice(b"\\'"),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0C => out.extend_from_slice(b"\\f"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            0x00..=0x1F => out.extend_from_slice(format!("\\u{:04x}", b).as_bytes()),
+            _ => out.push(b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(descendant: bool, selector: Selector) -> Segment {
+        Segment {
+            descendant,
+            selector,
+        }
+    }
+
+    fn parse(src: &str) -> Vec<Segment> {
+        Path::parse(src.as_bytes()).unwrap().segments
+    }
+
+    fn parse_err(src: &str) -> PathError {
+        Path::parse(src.as_bytes()).unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_child_and_descendant() {
+        assert!(parse("$").is_empty());
+        assert_eq!(
+            parse("$.store..price[*]"),
+            vec![
+                seg(false, Selector::Name(b"store".to_vec())),
+                seg(true, Selector::Name(b"price".to_vec())),
+                seg(false, Selector::Wildcard),
+            ]
+        );
+        assert_eq!(
+            parse("$['a b'][\"c\\\"\"]..*..[0]"),
+            vec![
+                seg(false, Selector::Name(b"a b".to_vec())),
+                seg(false, Selector::Name(b"c\"".to_vec())),
+                seg(true, Selector::Wildcard),
+                seg(true, Selector::Index(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_index_and_slice() {
+        assert_eq!(parse("$[-1]"), vec![seg(false, Selector::Index(-1))]);
+        assert_eq!(
+            parse("$[1:]"),
+            vec![seg(
+                false,
+                Selector::Slice {
+                    start: Some(1),
+                    end: None,
+                    step: 1
+                }
+            )]
+        );
+        assert_eq!(
+            parse("$[ : -2 : 3 ]"),
+            vec![seg(
+                false,
+                Selector::Slice {
+                    start: None,
+                    end: Some(-2),
+                    step: 3
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        let segments = parse("$[?(@.status == 'active' && !@.deleted || 5 < @['n'][0])]");
+        let Selector::Filter(expr) = &segments[0].selector else {
+            panic!("expected filter");
+        };
+        assert_eq!(
+            *expr,
+            Expr::Or(vec![
+                Expr::And(vec![
+                    Expr::Compare(
+                        vec![Step::Name(b"status".to_vec())],
+                        CmpOp::Eq,
+                        Literal::String(b"active".to_vec())
+                    ),
+                    Expr::Not(Box::new(Expr::Exists(vec![Step::Name(
+                        b"deleted".to_vec()
+                    )]))),
+                ]),
+                Expr::Compare(
+                    vec![Step::Name(b"n".to_vec()), Step::Index(0)],
+                    CmpOp::Gt,
+                    Literal::Number(5.0)
+                ),
+            ])
+        );
+        assert_eq!(
+            parse("$[?@.a != null]")[0].selector,
+            Selector::Filter(Expr::Compare(
+                vec![Step::Name(b"a".to_vec())],
+                CmpOp::Ne,
+                Literal::Null
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_err("a.b"),
+            (Cow::Borrowed("Path must start with '$'"), 0)
+        );
+        assert_eq!(parse_err("$.").1, 2);
+        assert_eq!(parse_err("$[1"), (Cow::Borrowed("Expected ']'"), 3));
+        assert_eq!(parse_err("$[::0]").1, 4);
+        assert_eq!(parse_err("$['a]").0, "Unterminated string");
+        assert_eq!(
+            parse_err("$[?($.a)]").0,
+            "Only relative '@' paths are supported in filters"
+        );
+        assert_eq!(parse_err("$x").0, "Expected '.', '..' or '['");
+    }
+
+    #[test]
+    fn test_filter_nesting_limit() {
+        let nested = |depth: usize, open: &str, close: &str| {
+            format!("$[?{}@.a{}]", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(Path::parse(nested(MAX_FILTER_NESTING, "(", ")").as_bytes()).is_ok());
+        assert!(Path::parse(nested(MAX_FILTER_NESTING, "!", "").as_bytes()).is_ok());
+        assert_eq!(
+            parse_err(&nested(MAX_FILTER_NESTING + 1, "(", ")")),
+            (Cow::Borrowed("Filter expression nested too deeply"), 67)
+        );
+        assert_eq!(
+            parse_err(&nested(100_000, "!(", ")")).0,
+            "Filter expression nested too deeply"
+        );
+    }
+
+    #[test]
+    fn test_slice_membership() {
+        let selected = |start, end, step, length| -> Vec<usize> {
+            (0..length as usize)
+                .filter(|&i| in_slice(i, start, end, step, length))
+                .collect()
+        };
+        assert_eq!(selected(Some(1), Some(4), 1, 6), vec![1, 2, 3]);
+        assert_eq!(selected(None, None, 2, 5), vec![0, 2, 4]);
+        assert_eq!(selected(Some(-2), None, 1, 5), vec![3, 4]);
+        assert_eq!(selected(None, Some(-3), 1, 5), vec![0, 1]);
+        assert_eq!(selected(Some(-10), Some(2), 1, 5), vec![0, 1]);
+        assert_eq!(normalize_index(-1, 3), Some(2));
+        assert_eq!(normalize_index(-4, 3), None);
+    }
+
+    #[test]
+    fn test_compare_semantics() {
+        let s = |v: &str| Operand::String(Cow::Owned(v.as_bytes().to_vec()));
+        let lit = |v: &str| Literal::String(v.as_bytes().to_vec());
+        assert!(compare(&s("a"), CmpOp::Eq, &lit("a")));
+        assert!(compare(&s("a"), CmpOp::Lt, &lit("b")));
+        assert!(compare(
+            &Operand::Number(2.0),
+            CmpOp::Ge,
+            &Literal::Number(2.0)
+        ));
+        assert!(compare(&Operand::Null, CmpOp::Le, &Literal::Null));
+        assert!(!compare(
+            &Operand::Bool(true),
+            CmpOp::Lt,
+            &Literal::Bool(true)
+        ));
+        // Mismatched types and missing values are unequal and unordered.
+        assert!(compare(&s("1"), CmpOp::Ne, &Literal::Number(1.0)));
+        assert!(!compare(&s("1"), CmpOp::Lt, &Literal::Number(2.0)));
+        assert!(compare(&Operand::Nothing, CmpOp::Ne, &Literal::Null));
+        assert!(!compare(&Operand::Container, CmpOp::Eq, &Literal::Null));
+    }
+
+    #[test]
+    fn test_escape_name() {
+        let mut out = Vec::new();
+        escape_name(b"it's a\\b\n\x01", &mut out);
+        assert_eq!(out, b"it\\'s a\\\\b\\n\\u0001".to_vec());
+    }
+}
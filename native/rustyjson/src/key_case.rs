@@ -0,0 +1,137 @@
+//! Object key rewriting applied while decoding (`key_case:`, `integer_keys:`).
+//!
+//! A key is split into words at `_` and `-` and at case changes: a lowercase
+//! letter, digit or non-ASCII byte followed by an uppercase letter (`userId`),
+//! and the last capital of an acronym followed by a lowercase letter
+//! (`HTTPServer`). The words are then joined in the target style. Leading and
+//! trailing `_` / `-` are kept as written, so `_id` and `__typename` survive.
+//! Only ASCII letters change case; other bytes are copied unchanged.
+
+use rustler::NifUnitEnum;
+
+/// Naming style object keys are converted to.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyCase {
+    /// `user_id`
+    Snake,
+    /// `userId`
+    Camel,
+    /// `user-id`
+    Kebab,
+}
+
+/// Rewrite `key` in the style `case`.
+pub fn convert(case: KeyCase, key: &[u8]) -> Vec<u8> {
+    let is_sep = |b: &u8| *b == b'_' || *b == b'-';
+    let start = key.iter().position(|b| !is_sep(b)).unwrap_or(key.len());
+    let end = key
+        .iter()
+        .rposition(|b| !is_sep(b))
+        .map_or(start, |i| i + 1);
+
+    let mut out = Vec::with_capacity(key.len() + 4);
+    out.extend_from_slice(&key[..start]);
+    for (index, word) in words(&key[start..end]).enumerate() {
+        match case {
+            KeyCase::Snake | KeyCase::Kebab => {
+                if index > 0 {
+                    out.push(if case == KeyCase::Snake { b'_' } else { b'-' });
+                }
+                out.extend(word.iter().map(u8::to_ascii_lowercase));
+            }
+            KeyCase::Camel => {
+                let (first, rest) = word.split_at(1);
+                if index == 0 {
+                    out.push(first[0].to_ascii_lowercase());
+                } else {
+                    out.push(first[0].to_ascii_uppercase());
+                }
+                out.extend(rest.iter().map(u8::to_ascii_lowercase));
+            }
+        }
+    }
+    out.extend_from_slice(&key[end..]);
+    out
+}
+
+/// Split `key` into non-empty words (see the module docs).
+fn words(key: &[u8]) -> impl Iterator<Item = &[u8]> {
+    key.split(|b| *b == b'_' || *b == b'-')
+        .filter(|part| !part.is_empty())
+        .flat_map(|part| {
+            let mut bounds = vec![0];
+            for i in 1..part.len() {
+                let (prev, cur) = (part[i - 1], part[i]);
+                let next_lower = part.get(i + 1).is_some_and(u8::is_ascii_lowercase);
+                if cur.is_ascii_uppercase()
+                    && (prev.is_ascii_lowercase()
+                        || prev.is_ascii_digit()
+                        || !prev.is_ascii()
+                        || (prev.is_ascii_uppercase() && next_lower))
+                {
+                    bounds.push(i);
+                }
+            }
+            bounds.push(part.len());
+            (0..bounds.len() - 1).map(move |i| &part[bounds[i]..bounds[i + 1]])
+        })
+}
+
+/// Whether `key` is an integer written canonically: an optional `-` and
+/// digits without a leading zero, `-0` excluded, so that encoding the
+/// integer gives back the same key.
+pub fn is_integer_key(key: &[u8]) -> bool {
+    let digits = key.strip_prefix(b"-").unwrap_or(key);
+    match digits {
+        [] => false,
+        [b'0'] => digits.len() == key.len(),
+        [b'0', ..] => false,
+        _ => digits.iter().all(u8::is_ascii_digit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conv(case: KeyCase, key: &str) -> String {
+        String::from_utf8(convert(case, key.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_converts_between_styles() {
+        for (key, snake, camel, kebab) in [
+            ("userId", "user_id", "userId", "user-id"),
+            ("user_id", "user_id", "userId", "user-id"),
+            ("user-id", "user_id", "userId", "user-id"),
+            ("UserID", "user_id", "userId", "user-id"),
+            ("HTTPServer", "http_server", "httpServer", "http-server"),
+            ("field1Name", "field1_name", "field1Name", "field1-name"),
+            ("a__b", "a_b", "aB", "a-b"),
+            ("id", "id", "id", "id"),
+        ] {
+            assert_eq!(conv(KeyCase::Snake, key), snake, "{key}");
+            assert_eq!(conv(KeyCase::Camel, key), camel, "{key}");
+            assert_eq!(conv(KeyCase::Kebab, key), kebab, "{key}");
+        }
+    }
+
+    #[test]
+    fn test_keeps_edge_separators_and_non_ascii() {
+        assert_eq!(conv(KeyCase::Camel, "__type_name"), "__typeName");
+        assert_eq!(conv(KeyCase::Snake, "_privateKey_"), "_private_key_");
+        assert_eq!(conv(KeyCase::Snake, "___"), "___");
+        assert_eq!(conv(KeyCase::Snake, ""), "");
+        assert_eq!(conv(KeyCase::Snake, "caféCrème"), "café_crème");
+    }
+
+    #[test]
+    fn test_integer_keys_are_canonical() {
+        for key in ["0", "7", "-12", "18446744073709551616"] {
+            assert!(is_integer_key(key.as_bytes()), "{key}");
+        }
+        for key in ["", "-", "-0", "01", "1.5", "1e3", "+1", " 1", "x1"] {
+            assert!(!is_integer_key(key.as_bytes()), "{key}");
+        }
+    }
+}
@@ -1,6 +1,8 @@
 #![feature(portable_simd)]
 
-use rustler::{Env, Error, Term};
+#[cfg(not(fuzzing))]
+use rustler::env::{OwnedEnv, SavedTerm};
+use rustler::{Encoder, Env, Error, ResourceArc, Term};
 
 #[cfg(all(feature = "mimalloc", not(fuzzing)))]
 #[global_allocator]
@@ -14,10 +16,28 @@ static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: snmalloc_rs::SnMalloc = snmalloc_rs::SnMalloc;
 
+#[cfg(not(fuzzing))]
+mod array_iter;
 mod compression;
+#[cfg(not(fuzzing))]
+mod concat;
+mod datetime;
 mod decimal;
+#[cfg(not(fuzzing))]
+mod jsonpath;
+mod key_case;
+#[cfg(not(fuzzing))]
+mod ndjson;
 mod nif_binary_writer;
+#[cfg(not(fuzzing))]
+mod pointer;
+mod projection;
+mod schema;
 mod simd_utils;
+#[cfg(not(fuzzing))]
+mod stream_decode;
+#[cfg(not(fuzzing))]
+mod stream_encode;
 
 #[cfg(not(feature = "bench"))]
 mod direct_decode;
@@ -32,6 +52,14 @@ pub mod direct_json;
 mod atoms {
     rustler::atoms! {
         encode,
+        cont,
+        done,
+        invalid_jsonpath,
+        // Validation summary keys (`max_depth` is also an option key)
+        type_ = "type",
+        bytes,
+        max_depth,
+        value_count,
         rustyjson_fragment = "Elixir.RustyJson.Fragment",
         __pre_encoded__,
         // Encode option keys
@@ -46,12 +74,34 @@ mod atoms {
         after_colon,
         // Decode option keys
         intern_keys,
+        strings,
+        subbinary_threshold,
         floats_decimals,
+        numbers,
         ordered_objects,
         integer_digit_limit,
         max_bytes,
-        reject_duplicate_keys,
+        duplicate_keys,
         validate_strings,
+        invalid_unicode,
+        stats,
+        max_string_bytes,
+        max_array_elements,
+        max_object_keys,
+        max_total_values,
+        max_fraction_digits,
+        max_exponent_digits,
+        atom_keys,
+        max_new_atoms,
+        key_case,
+        integer_keys,
+        projection,
+        relaxed,
+        datetimes,
+        schema,
+        list,
+        union,
+        union_list,
         // Struct construction atoms
         __struct__,
         decimal_struct = "Elixir.Decimal",
@@ -66,6 +116,16 @@ mod atoms {
         std_offset, utc_offset,
         scheme, userinfo, host, port, path, query, fragment,
         calendar, map, first, last, step,
+        // Calendar structs built on decode (`datetimes:`)
+        date_struct = "Elixir.Date",
+        time_struct = "Elixir.Time",
+        naive_datetime_struct = "Elixir.NaiveDateTime",
+        datetime_struct = "Elixir.DateTime",
+        calendar_iso = "Elixir.Calendar.ISO",
+        time_zone, zone_abbr,
+        // Typed schema fields (`schema:`)
+        items, check, any, string, integer, float, number, boolean,
+        nullable, coerce, in_ = "in", min, max, min_length, max_length,
     }
 }
 
@@ -104,12 +164,31 @@ fn get_opt_bool<'a>(
 }
 
 #[cfg(not(fuzzing))]
-/// Shared encode implementation used by both normal and dirty scheduler NIFs
-fn encode_direct_impl<'a>(
-    env: Env<'a>,
-    term: Term,
-    opts_map: Term<'a>,
-) -> Result<rustler::Binary<'a>, Error> {
+/// Encoder settings unpacked from the Elixir opts map.
+pub(crate) struct EncodeSettings {
+    pub(crate) ctx: direct_json::FormatContext,
+    pub(crate) pretty: bool,
+    pub(crate) lean: bool,
+    pub(crate) escape_mode: direct_json::EscapeMode,
+    pub(crate) compression: Option<(compression::Algs, Option<u32>)>,
+}
+
+#[cfg(not(fuzzing))]
+impl EncodeSettings {
+    pub(crate) fn format_options(&self) -> direct_json::FormatOptions<'_> {
+        if self.pretty {
+            direct_json::FormatOptions::pretty(&self.ctx)
+        } else {
+            direct_json::FormatOptions::compact(&self.ctx)
+        }
+        .with_lean(self.lean)
+        .with_escape(self.escape_mode)
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Unpack encode options from the Elixir map.
+fn encode_settings_from_map<'a>(env: Env<'a>, opts_map: Term<'a>) -> EncodeSettings {
     // Unpack options from the Elixir map
     let indent_size: Option<u32> = get_opt(env, opts_map, atoms::indent(), None);
     let comp_opts: Option<(compression::Algs, Option<u32>)> =
@@ -117,6 +196,12 @@ fn encode_direct_impl<'a>(
     let lean: bool = get_opt_bool(env, opts_map, atoms::lean(), false);
     let strict_keys: bool = get_opt_bool(env, opts_map, atoms::strict_keys(), false);
     let sort_keys: bool = get_opt_bool(env, opts_map, atoms::sort_keys(), false);
+    let max_depth: u32 = get_opt(
+        env,
+        opts_map,
+        atoms::max_depth(),
+        direct_json::DEFAULT_MAX_DEPTH,
+    );
 
     let escape_term = opts_map
         .map_get(atoms::escape().to_term(env))
@@ -131,6 +216,7 @@ fn encode_direct_impl<'a>(
     let mut ctx = direct_json::FormatContext {
         strict_keys,
         sort_keys,
+        max_depth: max_depth.min(direct_json::MAX_DEPTH_LIMIT),
         ..Default::default()
     };
 
@@ -167,14 +253,26 @@ fn encode_direct_impl<'a>(
         }
     }
 
-    let opts = match indent_size {
-        Some(n) if n > 0 => direct_json::FormatOptions::pretty(&ctx)
-            .with_lean(lean)
-            .with_escape(escape_mode),
-        _ => direct_json::FormatOptions::compact(&ctx)
-            .with_lean(lean)
-            .with_escape(escape_mode),
-    };
+    EncodeSettings {
+        ctx,
+        pretty: matches!(indent_size, Some(n) if n > 0),
+        lean,
+        escape_mode,
+        compression: comp_opts,
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Shared encode implementation used by both normal and dirty scheduler NIFs
+fn encode_direct_impl<'a>(
+    env: Env<'a>,
+    term: Term,
+    opts_map: Term<'a>,
+) -> Result<rustler::Binary<'a>, Error> {
+    let mut settings = encode_settings_from_map(env, opts_map);
+    let comp_opts = settings.compression.take();
+
+    let opts = settings.format_options();
 
     // Check if compression is requested
     let uses_compression = matches!(comp_opts, Some((compression::Algs::Gzip, _)));
@@ -182,8 +280,7 @@ fn encode_direct_impl<'a>(
     if uses_compression {
         // Use compression writer
         let mut buf = compression::get_writer(comp_opts);
-        direct_json::term_to_json(term, &mut buf, opts)
-            .map_err(|e| Error::RaiseTerm(Box::new(e.to_string())))?;
+        direct_json::term_to_json(term, &mut buf, opts).map_err(|e| raise_encode_error(env, e))?;
         let output = buf
             .get_buf()
             .map_err(|e| Error::RaiseTerm(Box::new(e.to_string())))?;
@@ -194,31 +291,244 @@ fn encode_direct_impl<'a>(
         // Fast path: write directly to a NIF binary (no intermediate Vec copy)
         let mut writer = nif_binary_writer::NifBinaryWriter::new(128);
         direct_json::term_to_json(term, &mut writer, opts)
+            .map_err(|e| raise_encode_error(env, e))?;
+        Ok(writer.into_binary(env))
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Shared encode_lines implementation used by both normal and dirty scheduler NIFs.
+///
+/// Every term is written compactly (pretty printing would break the one
+/// document per line framing), each followed by `\n`.
+fn encode_lines_impl<'a>(
+    env: Env<'a>,
+    terms: Vec<Term<'a>>,
+    opts_map: Term<'a>,
+) -> Result<rustler::Binary<'a>, Error> {
+    let mut settings = encode_settings_from_map(env, opts_map);
+    let comp_opts = settings.compression.take();
+    let opts = direct_json::FormatOptions::compact(&settings.ctx)
+        .with_lean(settings.lean)
+        .with_escape(settings.escape_mode);
+
+    if matches!(comp_opts, Some((compression::Algs::Gzip, _))) {
+        let mut buf = compression::get_writer(comp_opts);
+        ndjson::terms_to_lines(&terms, &mut buf, opts).map_err(|e| raise_encode_error(env, e))?;
+        let output = buf
+            .get_buf()
             .map_err(|e| Error::RaiseTerm(Box::new(e.to_string())))?;
+        let mut bin = rustler::NewBinary::new(env, output.len());
+        bin.as_mut_slice().copy_from_slice(&output);
+        Ok(bin.into())
+    } else {
+        let mut writer = nif_binary_writer::NifBinaryWriter::new(128 * terms.len().max(1));
+        ndjson::terms_to_lines(&terms, &mut writer, opts)
+            .map_err(|e| raise_encode_error(env, e))?;
         Ok(writer.into_binary(env))
     }
 }
 
 #[cfg(not(fuzzing))]
-/// Shared decode implementation used by both normal and dirty scheduler NIFs
-fn decode_impl<'a>(
+/// Build `DecodeOptions` from the Elixir opts map. Fails with `badarg` when
+/// the `schema` option is malformed, or combined with options the struct
+/// builder does not honour.
+fn decode_opts_from_map<'a>(
     env: Env<'a>,
-    input: rustler::Binary<'a>,
     opts_map: Term<'a>,
-) -> Result<Term<'a>, Error> {
-    // Parse options from Elixir map
-    let decode_opts = direct_decode::DecodeOptions {
+) -> Result<direct_decode::DecodeOptions, Error> {
+    let opts = direct_decode::DecodeOptions {
         intern_keys: get_opt_bool(env, opts_map, atoms::intern_keys(), false),
+        strings: get_opt(
+            env,
+            opts_map,
+            atoms::strings(),
+            direct_decode::Strings::Reference,
+        ),
+        subbinary_threshold: get_opt(
+            env,
+            opts_map,
+            atoms::subbinary_threshold(),
+            direct_decode::SUBBINARY_THRESHOLD,
+        ),
         floats_decimals: get_opt_bool(env, opts_map, atoms::floats_decimals(), false),
+        numbers: get_opt(
+            env,
+            opts_map,
+            atoms::numbers(),
+            direct_decode::Numbers::Native,
+        ),
         ordered_objects: get_opt_bool(env, opts_map, atoms::ordered_objects(), false),
         integer_digit_limit: get_opt(env, opts_map, atoms::integer_digit_limit(), 1024usize),
         max_bytes: get_opt(env, opts_map, atoms::max_bytes(), 0usize),
-        reject_duplicate_keys: get_opt_bool(env, opts_map, atoms::reject_duplicate_keys(), false),
+        duplicate_keys: get_opt(
+            env,
+            opts_map,
+            atoms::duplicate_keys(),
+            direct_decode::DuplicateKeys::Last,
+        ),
         validate_strings: get_opt_bool(env, opts_map, atoms::validate_strings(), true),
+        invalid_unicode: get_opt(
+            env,
+            opts_map,
+            atoms::invalid_unicode(),
+            direct_decode::InvalidUnicode::Error,
+        ),
+        max_depth: get_opt(
+            env,
+            opts_map,
+            atoms::max_depth(),
+            direct_decode::DEFAULT_MAX_DEPTH,
+        )
+        .min(direct_decode::MAX_DEPTH_LIMIT),
+        max_string_bytes: get_opt(env, opts_map, atoms::max_string_bytes(), 0usize),
+        max_array_elements: get_opt(env, opts_map, atoms::max_array_elements(), 0usize),
+        max_object_keys: get_opt(env, opts_map, atoms::max_object_keys(), 0usize),
+        max_total_values: get_opt(env, opts_map, atoms::max_total_values(), 0usize),
+        max_fraction_digits: get_opt(env, opts_map, atoms::max_fraction_digits(), 0usize),
+        max_exponent_digits: get_opt(env, opts_map, atoms::max_exponent_digits(), 0usize),
+        atom_keys: get_opt(env, opts_map, atoms::atom_keys(), None),
+        max_new_atoms: get_opt(env, opts_map, atoms::max_new_atoms(), 0usize),
+        key_case: get_opt(env, opts_map, atoms::key_case(), None),
+        integer_keys: get_opt_bool(env, opts_map, atoms::integer_keys(), false),
+        projection: projection_from_opts(env, opts_map),
+        relaxed: get_opt(env, opts_map, atoms::relaxed(), None),
+        datetimes: datetimes_from_opts(env, opts_map),
+        schema: schema_from_opts(env, opts_map)?,
     };
+    if opts.schema.is_some()
+        && (opts.projection.is_some()
+            || opts.relaxed.is_some()
+            || opts.duplicate_keys == direct_decode::DuplicateKeys::Collect)
+    {
+        return Err(Error::BadArg);
+    }
+    Ok(opts)
+}
+
+#[cfg(not(fuzzing))]
+/// Compile the `projection` option (`{:only | :except, paths}` or `nil`).
+fn projection_from_opts<'a>(
+    env: Env<'a>,
+    opts_map: Term<'a>,
+) -> Option<std::sync::Arc<projection::Projection>> {
+    let (mode, paths): (projection::Mode, Vec<Vec<rustler::Binary>>) =
+        get_opt(env, opts_map, atoms::projection(), None)?;
+    let paths: Vec<Vec<Vec<u8>>> = paths
+        .iter()
+        .map(|path| path.iter().map(|step| step.as_slice().to_vec()).collect())
+        .collect();
+    Some(std::sync::Arc::new(projection::Projection::new(
+        mode, &paths,
+    )))
+}
+
+#[cfg(not(fuzzing))]
+/// Read the `schema` option: a compiled `SchemaResource`, or its NIF form
+/// compiled for this call. A schema that does not compile is an error rather
+/// than no schema, so a bad spec never decodes unchecked.
+fn schema_from_opts<'a>(
+    env: Env<'a>,
+    opts_map: Term<'a>,
+) -> Result<Option<std::sync::Arc<schema::Schema>>, Error> {
+    let Some(term) = get_opt::<Option<Term>>(env, opts_map, atoms::schema(), None) else {
+        return Ok(None);
+    };
+    if let Ok(compiled) = term.decode::<ResourceArc<schema::SchemaResource>>() {
+        return Ok(Some(compiled.0.clone()));
+    }
+    schema::Schema::from_term(term).map(|schema| Some(std::sync::Arc::new(schema)))
+}
+
+#[cfg(not(fuzzing))]
+/// Decode options for a NIF whose values are never checked against a
+/// `schema`; raises `badarg` if one is given rather than ignore it.
+fn unchecked_decode_opts<'a>(
+    env: Env<'a>,
+    opts_map: Term<'a>,
+) -> Result<direct_decode::DecodeOptions, Error> {
+    let opts = decode_opts_from_map(env, opts_map)?;
+    if opts.schema.is_some() {
+        return Err(Error::BadArg);
+    }
+    Ok(opts)
+}
+
+#[cfg(not(fuzzing))]
+/// Read the `datetimes` option: `true` for every string value, or a list of
+/// keys whose values are decoded.
+fn datetimes_from_opts<'a>(env: Env<'a>, opts_map: Term<'a>) -> direct_decode::Datetimes {
+    if get_opt_bool(env, opts_map, atoms::datetimes(), false) {
+        return direct_decode::Datetimes::All;
+    }
+    match get_opt::<Option<Vec<rustler::Binary>>>(env, opts_map, atoms::datetimes(), None) {
+        Some(keys) => direct_decode::Datetimes::Keys(std::sync::Arc::new(
+            keys.iter().map(|key| key.as_slice().to_vec()).collect(),
+        )),
+        None => direct_decode::Datetimes::Off,
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Convert a decode error into the `{code, expected, message, position}`
+/// term raised to Elixir.
+#[inline]
+fn raise_decode_error(error: direct_decode::DecodeError) -> Error {
+    Error::RaiseTerm(Box::new(error))
+}
+
+#[cfg(not(fuzzing))]
+/// Convert a decode failure into the `{error, path}` term raised to Elixir,
+/// or `{error, path, violations}` when the document broke its schema.
+/// `error` is the `{code, expected, message, position}` of the failure.
+#[inline]
+fn raise_decode_failure(failure: direct_decode::DecodeFailure) -> Error {
+    Error::RaiseTerm(Box::new(failure))
+}
+
+#[cfg(not(fuzzing))]
+/// An encode failure on its way to Elixir. `Error::RaiseTerm` needs a
+/// `'static` payload, but the path and value are terms of the calling env,
+/// so the tuple is parked in an `OwnedEnv` and copied back when raised.
+struct RaisedTerm {
+    env: OwnedEnv,
+    term: SavedTerm,
+}
 
-    direct_decode::json_to_term(env, &input, decode_opts)
-        .map_err(|(msg, pos)| Error::RaiseTerm(Box::new((msg.into_owned(), pos))))
+#[cfg(not(fuzzing))]
+impl Encoder for RaisedTerm {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        self.env.run(|owned| self.term.load(owned).in_env(env))
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Convert an encode error into the `{reason, message, path, value}` term
+/// raised to Elixir. The path runs from the root; `value` is `nil` when no
+/// term was at fault.
+#[cold]
+fn raise_encode_error(env: Env, e: direct_json::EncodeError) -> Error {
+    let path: Vec<Term> = e.path().map(|item| item.encode(env)).collect();
+    let owned = OwnedEnv::new();
+    let term = owned.save((e.reason, e.message.as_ref(), path, e.value).encode(env));
+    Error::RaiseTerm(Box::new(RaisedTerm { env: owned, term }))
+}
+
+#[cfg(not(fuzzing))]
+/// Shared decode implementation used by both normal and dirty scheduler NIFs.
+/// With `stats: true` in the opts map, returns `{term, stats}`.
+fn decode_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Term<'a>, Error> {
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    if get_opt_bool(env, opts_map, atoms::stats(), false) {
+        return direct_decode::json_to_term_with_stats(env, &input, decode_opts)
+            .map(|result| result.encode(env))
+            .map_err(raise_decode_failure);
+    }
+    direct_decode::json_to_term(env, &input, decode_opts).map_err(raise_decode_failure)
 }
 
 #[cfg(not(fuzzing))]
@@ -265,6 +575,499 @@ fn decode_dirty<'a>(
     decode_impl(env, input, opts_map)
 }
 
+#[cfg(not(fuzzing))]
+/// Line-level decode errors as returned to Elixir: `{line_number, failure}`,
+/// where `failure` is `{error, path}` or `{error, path, violations}`.
+type LineErrors = Vec<ndjson::LineError>;
+
+#[cfg(not(fuzzing))]
+/// Shared decode_lines implementation used by both normal and dirty scheduler NIFs
+fn decode_lines_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    skip_invalid: bool,
+) -> Result<(Vec<Term<'a>>, LineErrors), Error> {
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    ndjson::lines_to_terms(env, &input, decode_opts, skip_invalid).map_err(raise_decode_failure)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode newline-delimited JSON on normal scheduler
+#[rustler::nif(name = "nif_decode_lines")]
+fn decode_lines<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    skip_invalid: bool,
+) -> Result<(Vec<Term<'a>>, LineErrors), Error> {
+    decode_lines_impl(env, input, opts_map, skip_invalid)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode newline-delimited JSON on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_decode_lines_dirty", schedule = "DirtyCpu")]
+fn decode_lines_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    skip_invalid: bool,
+) -> Result<(Vec<Term<'a>>, LineErrors), Error> {
+    decode_lines_impl(env, input, opts_map, skip_invalid)
+}
+
+#[cfg(not(fuzzing))]
+/// Shared decode_prefix implementation used by both normal and dirty
+/// scheduler NIFs. Returns `{term, rest_offset, rest}`, where `rest` is a
+/// sub-binary of the input.
+fn decode_prefix_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<(Term<'a>, usize, rustler::Binary<'a>), Error> {
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    let (term, offset) =
+        concat::prefix_to_term(env, &input, decode_opts).map_err(raise_decode_failure)?;
+    let rest = input.make_subbinary(offset, input.len() - offset)?;
+    Ok((term, offset, rest))
+}
+
+#[cfg(not(fuzzing))]
+/// Decode the first JSON document of the input on normal scheduler
+#[rustler::nif(name = "nif_decode_prefix")]
+fn decode_prefix<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<(Term<'a>, usize, rustler::Binary<'a>), Error> {
+    decode_prefix_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode the first JSON document of the input on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_decode_prefix_dirty", schedule = "DirtyCpu")]
+fn decode_prefix_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<(Term<'a>, usize, rustler::Binary<'a>), Error> {
+    decode_prefix_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Shared decode_all implementation used by both normal and dirty scheduler NIFs
+fn decode_all_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    concat::all_to_terms(env, &input, decode_opts).map_err(raise_decode_failure)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode concatenated JSON documents on normal scheduler
+#[rustler::nif(name = "nif_decode_all")]
+fn decode_all<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decode_all_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode concatenated JSON documents on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_decode_all_dirty", schedule = "DirtyCpu")]
+fn decode_all_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decode_all_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Encode a list of terms as newline-delimited JSON on normal scheduler
+#[rustler::nif(name = "nif_encode_lines")]
+fn encode_lines<'a>(
+    env: Env<'a>,
+    terms: Vec<Term<'a>>,
+    opts_map: Term<'a>,
+) -> Result<rustler::Binary<'a>, Error> {
+    encode_lines_impl(env, terms, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Encode a list of terms as newline-delimited JSON on dirty CPU scheduler
+#[rustler::nif(name = "nif_encode_lines_dirty", schedule = "DirtyCpu")]
+fn encode_lines_dirty<'a>(
+    env: Env<'a>,
+    terms: Vec<Term<'a>>,
+    opts_map: Term<'a>,
+) -> Result<rustler::Binary<'a>, Error> {
+    encode_lines_impl(env, terms, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Create an incremental decoder resource
+#[rustler::nif(name = "nif_decoder_new")]
+fn decoder_new<'a>(
+    env: Env<'a>,
+    opts_map: Term<'a>,
+) -> Result<ResourceArc<stream_decode::StreamDecoderResource>, Error> {
+    Ok(ResourceArc::new(stream_decode::StreamDecoderResource::new(
+        unchecked_decode_opts(env, opts_map)?,
+    )))
+}
+
+#[cfg(not(fuzzing))]
+/// Shared decoder_feed implementation used by both normal and dirty scheduler NIFs
+fn decoder_feed_impl<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+    chunk: rustler::Binary<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    let mut state = decoder.inner.lock().map_err(|_| Error::BadArg)?;
+    state
+        .feed(env, chunk.as_slice())
+        .map_err(raise_decode_error)
+}
+
+#[cfg(not(fuzzing))]
+/// Feed a chunk to an incremental decoder on normal scheduler
+#[rustler::nif(name = "nif_decoder_feed")]
+fn decoder_feed<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+    chunk: rustler::Binary<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decoder_feed_impl(env, decoder, chunk)
+}
+
+#[cfg(not(fuzzing))]
+/// Feed a chunk to an incremental decoder on dirty CPU scheduler
+#[rustler::nif(name = "nif_decoder_feed_dirty", schedule = "DirtyCpu")]
+fn decoder_feed_dirty<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+    chunk: rustler::Binary<'a>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decoder_feed_impl(env, decoder, chunk)
+}
+
+#[cfg(not(fuzzing))]
+/// Shared decoder_finish implementation used by both normal and dirty scheduler NIFs
+fn decoder_finish_impl<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+) -> Result<Vec<Term<'a>>, Error> {
+    let mut state = decoder.inner.lock().map_err(|_| Error::BadArg)?;
+    state.finish(env).map_err(raise_decode_error)
+}
+
+#[cfg(not(fuzzing))]
+/// Signal end of input to an incremental decoder on normal scheduler
+#[rustler::nif(name = "nif_decoder_finish")]
+fn decoder_finish<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decoder_finish_impl(env, decoder)
+}
+
+#[cfg(not(fuzzing))]
+/// Signal end of input to an incremental decoder on dirty CPU scheduler
+#[rustler::nif(name = "nif_decoder_finish_dirty", schedule = "DirtyCpu")]
+fn decoder_finish_dirty<'a>(
+    env: Env<'a>,
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+) -> Result<Vec<Term<'a>>, Error> {
+    decoder_finish_impl(env, decoder)
+}
+
+#[cfg(not(fuzzing))]
+/// Bytes an incremental decoder holds for the value it is still scanning,
+/// which the next feed or finish may have to decode in one call
+#[rustler::nif(name = "nif_decoder_buffered")]
+fn decoder_buffered(
+    decoder: ResourceArc<stream_decode::StreamDecoderResource>,
+) -> Result<usize, Error> {
+    let state = decoder.inner.lock().map_err(|_| Error::BadArg)?;
+    Ok(state.buffered())
+}
+
+#[cfg(not(fuzzing))]
+/// Create a lazy iterator over the array at `path` in `input`
+#[rustler::nif(name = "nif_iterator_new")]
+fn iterator_new<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    path: Vec<rustler::Binary<'a>>,
+    opts_map: Term<'a>,
+) -> Result<ResourceArc<array_iter::ArrayIterResource>, Error> {
+    let path = path.iter().map(|token| token.as_slice().to_vec()).collect();
+    Ok(ResourceArc::new(array_iter::ArrayIterResource::new(
+        env,
+        input,
+        path,
+        unchecked_decode_opts(env, opts_map)?,
+    )))
+}
+
+#[cfg(not(fuzzing))]
+/// Shared iterator_next implementation used by both normal and dirty
+/// scheduler NIFs. Returns `{:cont | :done, elements}`.
+fn iterator_next_impl<'a>(
+    env: Env<'a>,
+    iterator: ResourceArc<array_iter::ArrayIterResource>,
+    count: usize,
+) -> Result<(rustler::Atom, Vec<Term<'a>>), Error> {
+    let mut state = iterator.inner.lock().map_err(|_| Error::BadArg)?;
+    let (elements, done) = state.next(env, count).map_err(raise_decode_error)?;
+    let status = if done { atoms::done() } else { atoms::cont() };
+    Ok((status, elements))
+}
+
+#[cfg(not(fuzzing))]
+/// Decode the next `count` elements on normal scheduler
+#[rustler::nif(name = "nif_iterator_next")]
+fn iterator_next<'a>(
+    env: Env<'a>,
+    iterator: ResourceArc<array_iter::ArrayIterResource>,
+    count: usize,
+) -> Result<(rustler::Atom, Vec<Term<'a>>), Error> {
+    iterator_next_impl(env, iterator, count)
+}
+
+#[cfg(not(fuzzing))]
+/// Decode the next `count` elements on dirty CPU scheduler for large inputs
+#[rustler::nif(name = "nif_iterator_next_dirty", schedule = "DirtyCpu")]
+fn iterator_next_dirty<'a>(
+    env: Env<'a>,
+    iterator: ResourceArc<array_iter::ArrayIterResource>,
+    count: usize,
+) -> Result<(rustler::Atom, Vec<Term<'a>>), Error> {
+    iterator_next_impl(env, iterator, count)
+}
+
+#[cfg(not(fuzzing))]
+/// Compile a `schema` option once, for reuse across decode calls
+#[rustler::nif(name = "nif_schema_compile")]
+fn schema_compile(spec: Term) -> Result<ResourceArc<schema::SchemaResource>, Error> {
+    let schema = schema::Schema::from_term(spec)?;
+    Ok(ResourceArc::new(schema::SchemaResource(
+        std::sync::Arc::new(schema),
+    )))
+}
+
+#[cfg(not(fuzzing))]
+/// Shared JSON Pointer lookup used by both normal and dirty scheduler NIFs.
+/// Returns `{:ok, result}`, or `:error` when the pointer does not resolve.
+fn get_pointer_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    tokens: Vec<rustler::Binary<'a>>,
+    opts_map: Term<'a>,
+    query: pointer::Query,
+) -> Result<Term<'a>, Error> {
+    let tokens: Vec<Vec<u8>> = tokens
+        .iter()
+        .map(|token| token.as_slice().to_vec())
+        .collect();
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    match pointer::get(env, &input, &tokens, decode_opts, query).map_err(raise_decode_error)? {
+        Some(term) => Ok((rustler::types::atom::ok(), term).encode(env)),
+        None => Ok(rustler::types::atom::error().encode(env)),
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// JSON Pointer lookup on normal scheduler
+#[rustler::nif(name = "nif_get_pointer")]
+fn get_pointer<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    tokens: Vec<rustler::Binary<'a>>,
+    opts_map: Term<'a>,
+    query: pointer::Query,
+) -> Result<Term<'a>, Error> {
+    get_pointer_impl(env, input, tokens, opts_map, query)
+}
+
+#[cfg(not(fuzzing))]
+/// JSON Pointer lookup on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_get_pointer_dirty", schedule = "DirtyCpu")]
+fn get_pointer_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    tokens: Vec<rustler::Binary<'a>>,
+    opts_map: Term<'a>,
+    query: pointer::Query,
+) -> Result<Term<'a>, Error> {
+    get_pointer_impl(env, input, tokens, opts_map, query)
+}
+
+#[cfg(not(fuzzing))]
+/// Shared JSONPath query used by both normal and dirty scheduler NIFs.
+/// A malformed path raises `{:invalid_jsonpath, message, position}`.
+fn query_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    path: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    mode: jsonpath::QueryReturn,
+) -> Result<Vec<Term<'a>>, Error> {
+    let path = jsonpath::Path::parse(path.as_slice()).map_err(|(msg, pos)| {
+        Error::RaiseTerm(Box::new((atoms::invalid_jsonpath(), msg.into_owned(), pos)))
+    })?;
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    let bytes = input.as_slice();
+    direct_decode::check_max_bytes(bytes, &decode_opts).map_err(raise_decode_error)?;
+    let parser = direct_decode::DirectParser::new(env, bytes, input, decode_opts);
+    jsonpath::query(env, parser, &path, mode).map_err(raise_decode_error)
+}
+
+#[cfg(not(fuzzing))]
+/// JSONPath query on normal scheduler
+#[rustler::nif(name = "nif_query")]
+fn query<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    path: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    mode: jsonpath::QueryReturn,
+) -> Result<Vec<Term<'a>>, Error> {
+    query_impl(env, input, path, opts_map, mode)
+}
+
+#[cfg(not(fuzzing))]
+/// JSONPath query on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_query_dirty", schedule = "DirtyCpu")]
+fn query_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    path: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+    mode: jsonpath::QueryReturn,
+) -> Result<Vec<Term<'a>>, Error> {
+    query_impl(env, input, path, opts_map, mode)
+}
+
+#[cfg(not(fuzzing))]
+/// Shared validation used by both normal and dirty scheduler NIFs.
+/// Returns `{:ok, summary}` or `{:error, message, position}` instead of raising.
+fn validate_impl<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Term<'a>, Error> {
+    let decode_opts = decode_opts_from_map(env, opts_map)?;
+    match direct_decode::validate(env, &input, decode_opts) {
+        Ok(summary) => {
+            let keys = [
+                atoms::type_().encode(env),
+                atoms::bytes().encode(env),
+                atoms::max_depth().encode(env),
+                atoms::value_count().encode(env),
+            ];
+            let values = [
+                pointer::ValueType::from_first_byte(summary.root).encode(env),
+                input.len().encode(env),
+                summary.max_depth.encode(env),
+                summary.value_count.encode(env),
+            ];
+            let map = Term::map_from_term_arrays(env, &keys, &values)?;
+            Ok((rustler::types::atom::ok(), map).encode(env))
+        }
+        Err(e) => Ok((rustler::types::atom::error(), e.message.as_ref(), e.pos).encode(env)),
+    }
+}
+
+#[cfg(not(fuzzing))]
+/// Validation on normal scheduler
+#[rustler::nif(name = "nif_validate")]
+fn validate<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Term<'a>, Error> {
+    validate_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Validation on dirty CPU scheduler for large payloads
+#[rustler::nif(name = "nif_validate_dirty", schedule = "DirtyCpu")]
+fn validate_dirty<'a>(
+    env: Env<'a>,
+    input: rustler::Binary<'a>,
+    opts_map: Term<'a>,
+) -> Result<Term<'a>, Error> {
+    validate_impl(env, input, opts_map)
+}
+
+#[cfg(not(fuzzing))]
+/// Create a streaming encoder resource
+#[rustler::nif(name = "nif_encoder_new")]
+fn encoder_new<'a>(
+    env: Env<'a>,
+    opts_map: Term<'a>,
+    container: stream_encode::Container,
+    chunk_size: usize,
+) -> ResourceArc<stream_encode::StreamEncoderResource> {
+    ResourceArc::new(stream_encode::StreamEncoderResource::new(
+        encode_settings_from_map(env, opts_map),
+        container,
+        chunk_size,
+    ))
+}
+
+#[cfg(not(fuzzing))]
+/// Shared encoder_push implementation used by both normal and dirty scheduler NIFs
+fn encoder_push_impl<'a>(
+    env: Env<'a>,
+    encoder: ResourceArc<stream_encode::StreamEncoderResource>,
+    items: Vec<Term<'a>>,
+) -> Result<Vec<rustler::Binary<'a>>, Error> {
+    let mut state = encoder.inner.lock().map_err(|_| Error::BadArg)?;
+    state
+        .push(env, &items)
+        .map_err(|e| raise_encode_error(env, e))
+}
+
+#[cfg(not(fuzzing))]
+/// Push items to a streaming encoder on normal scheduler
+#[rustler::nif(name = "nif_encoder_push")]
+fn encoder_push<'a>(
+    env: Env<'a>,
+    encoder: ResourceArc<stream_encode::StreamEncoderResource>,
+    items: Vec<Term<'a>>,
+) -> Result<Vec<rustler::Binary<'a>>, Error> {
+    encoder_push_impl(env, encoder, items)
+}
+
+#[cfg(not(fuzzing))]
+/// Push items to a streaming encoder on dirty CPU scheduler
+#[rustler::nif(name = "nif_encoder_push_dirty", schedule = "DirtyCpu")]
+fn encoder_push_dirty<'a>(
+    env: Env<'a>,
+    encoder: ResourceArc<stream_encode::StreamEncoderResource>,
+    items: Vec<Term<'a>>,
+) -> Result<Vec<rustler::Binary<'a>>, Error> {
+    encoder_push_impl(env, encoder, items)
+}
+
+#[cfg(not(fuzzing))]
+/// Close a streaming encoder, returning the remaining output
+#[rustler::nif(name = "nif_encoder_finish")]
+fn encoder_finish<'a>(
+    env: Env<'a>,
+    encoder: ResourceArc<stream_encode::StreamEncoderResource>,
+) -> Result<Vec<rustler::Binary<'a>>, Error> {
+    let mut state = encoder.inner.lock().map_err(|_| Error::BadArg)?;
+    state.finish(env).map_err(|e| raise_encode_error(env, e))
+}
+
 #[cfg(not(fuzzing))]
 /// Shared encode_fields implementation used by both normal and dirty scheduler NIFs.
 ///
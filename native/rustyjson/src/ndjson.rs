@@ -0,0 +1,160 @@
+//! Newline-delimited JSON (NDJSON / JSON Lines).
+//!
+//! Decoding splits the input on every newline and parses each non-blank line
+//! as one document. All lines are parsed by a single `DirectParser`, so with
+//! `keys: :intern` the key cache is shared across the whole batch — the
+//! common case for logs, where every line has the same shape. Strings are
+//! still zero-copy sub-binaries of the input.
+//!
+//! Encoding writes each term with `term_to_json` followed by `\n` into one
+//! output buffer.
+
+use crate::direct_decode::{check_max_bytes, DecodeFailure, DecodeOptions, DirectParser};
+use crate::direct_json::{term_to_json, EncodeError, FormatOptions};
+use rustler::{Binary, Env, Term};
+use std::io::Write;
+use std::ops::Range;
+
+/// A line that failed to decode: 1-based line number and the failure, with
+/// its path and any schema violations.
+pub type LineError = (usize, DecodeFailure);
+
+/// Find the byte range of every non-blank line, paired with its 1-based line
+/// number. Every `\n` ends a line, even one that falls inside a string: raw
+/// newlines are not valid inside JSON strings, so such a line is malformed,
+/// and splitting there keeps one bad line from swallowing the lines after it.
+/// No string state is tracked, so the split is a plain newline search.
+pub fn split_lines(input: &[u8]) -> Vec<(usize, Range<usize>)> {
+    let mut lines = Vec::new();
+    let mut line_number = 1;
+    let mut start = 0;
+
+    for pos in memchr::memchr_iter(b'\n', input) {
+        push_line(&mut lines, input, line_number, start..pos);
+        start = pos + 1;
+        line_number += 1;
+    }
+    push_line(&mut lines, input, line_number, start..input.len());
+    lines
+}
+
+#[inline]
+fn push_line(
+    lines: &mut Vec<(usize, Range<usize>)>,
+    input: &[u8],
+    line_number: usize,
+    range: Range<usize>,
+) {
+    let blank = input[range.clone()]
+        .iter()
+        .all(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n'));
+    if !blank {
+        lines.push((line_number, range));
+    }
+}
+
+/// Decode every line of `input_binary`.
+///
+/// With `skip_invalid`, malformed lines are collected as `LineError`s and
+/// decoding continues; otherwise the first malformed line fails the batch.
+/// Error positions are byte offsets into the whole input.
+pub fn lines_to_terms<'a>(
+    env: Env<'a>,
+    input_binary: &Binary<'a>,
+    opts: DecodeOptions,
+    skip_invalid: bool,
+) -> Result<(Vec<Term<'a>>, Vec<LineError>), DecodeFailure> {
+    let input = input_binary.as_slice();
+    check_max_bytes(input, &opts)?;
+
+    let lines = split_lines(input);
+    let mut parser = DirectParser::resume(env, input, *input_binary, opts, 0, 0);
+    let mut terms = Vec::with_capacity(lines.len());
+    let mut errors = Vec::new();
+    for (line_number, range) in lines {
+        match parser.parse_range(range.start, range.end) {
+            Ok(term) => terms.push(term),
+            Err(error) => {
+                let failure = parser.failure(error, range.start);
+                if !skip_invalid {
+                    return Err(failure);
+                }
+                errors.push((line_number, failure));
+            }
+        }
+    }
+    Ok((terms, errors))
+}
+
+/// Write each term as one compact JSON document followed by `\n`. Error
+/// paths start with the index of the failing term.
+pub fn terms_to_lines<'a, W: Write>(
+    terms: &[Term<'a>],
+    writer: &mut W,
+    opts: FormatOptions,
+) -> Result<(), EncodeError<'a>> {
+    for (i, term) in terms.iter().enumerate() {
+        term_to_json(*term, writer, opts).map_err(|e| e.at_index(i))?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<(usize, &str)> {
+        split_lines(input.as_bytes())
+            .into_iter()
+            .map(|(n, r)| (n, &input[r]))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(
+            lines("{\"a\":1}\n[2]\n3"),
+            vec![(1, "{\"a\":1}"), (2, "[2]"), (3, "3")]
+        );
+    }
+
+    #[test]
+    fn test_split_lines_skips_blank_lines_and_keeps_numbering() {
+        assert_eq!(lines("\n1\r\n  \n\t2\n\n"), vec![(2, "1\r"), (4, "\t2")]);
+        assert!(lines("").is_empty());
+        assert!(lines(" \n\r\n").is_empty());
+    }
+
+    #[test]
+    fn test_split_lines_ends_lines_inside_strings() {
+        let input = "\"a\\\"\nb\"\n\"c\\\\\"\n\"d\\\ne\"\n4";
+        assert_eq!(
+            lines(input),
+            vec![
+                (1, "\"a\\\""),
+                (2, "b\""),
+                (3, "\"c\\\\\""),
+                (4, "\"d\\"),
+                (5, "e\""),
+                (6, "4")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_lines_unterminated_string_does_not_merge_lines() {
+        assert_eq!(
+            lines("\"abc\n{\"b\":1}\n{\"c\":2}"),
+            vec![(1, "\"abc"), (2, "{\"b\":1}"), (3, "{\"c\":2}")]
+        );
+    }
+
+    #[test]
+    fn test_split_lines_long_lines() {
+        let long = format!("{{\"k\":\"{}\"}}", "x".repeat(100));
+        let input = format!("{long}\n{long}\n");
+        let expected = vec![(1, long.as_str()), (2, long.as_str())];
+        assert_eq!(lines(&input), expected);
+    }
+}
@@ -0,0 +1,104 @@
+//! RFC 6901 JSON Pointer lookup without decoding the whole document.
+//!
+//! The parser walks from the root to the target with `DirectParser::seek`,
+//! skipping sibling values without building terms: strings are skipped with
+//! the SIMD `skip_plain_string_bytes` scan, and inputs large enough to get a
+//! `StructuralIndex` jump between structural characters. Only the target value
+//! is materialized — or nothing at all for the type, length and key-count
+//! queries.
+//!
+//! Bytes after the target are never read, so a document whose syntax error
+//! lies past the target still resolves. Pointer strings are split into
+//! unescaped tokens on the Elixir side.
+
+use crate::direct_decode::{check_max_bytes, DecodeError, DecodeOptions, DirectParser, Syntax};
+use rustler::{Binary, Encoder, Env, NifUnitEnum, Term};
+
+/// What to return for the value at the pointer.
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum Query {
+    /// The decoded value.
+    Value,
+    /// The JSON type as an atom; only the value's first byte is inspected.
+    Type,
+    /// The element count of an array.
+    Length,
+    /// The member count of an object.
+    KeyCount,
+}
+
+/// JSON value type, returned to Elixir as an atom.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValueType {
+    Object,
+    Array,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+impl ValueType {
+    /// Classify a value by its first byte.
+    pub(crate) fn from_first_byte(b: u8) -> Option<Self> {
+        match b {
+            b'{' => Some(ValueType::Object),
+            b'[' => Some(ValueType::Array),
+            b'"' => Some(ValueType::String),
+            b'-' | b'0'..=b'9' => Some(ValueType::Number),
+            b't' | b'f' => Some(ValueType::Boolean),
+            b'n' => Some(ValueType::Null),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve `tokens` against `input` and answer `query` for the target.
+/// Returns `Ok(None)` when the pointer does not resolve.
+pub fn get<'a>(
+    env: Env<'a>,
+    input: &Binary<'a>,
+    tokens: &[Vec<u8>],
+    opts: DecodeOptions,
+    query: Query,
+) -> Result<Option<Term<'a>>, DecodeError> {
+    let bytes = input.as_slice();
+    check_max_bytes(bytes, &opts)?;
+
+    let duplicates = opts.duplicate_keys;
+    let mut parser = DirectParser::new(env, bytes, *input, opts);
+    if !parser.seek(tokens, duplicates)? {
+        return Ok(None);
+    }
+
+    let term = match query {
+        Query::Value => parser.parse_current()?,
+        Query::Type => match parser.current_byte() {
+            Some(b) => match ValueType::from_first_byte(b) {
+                Some(value_type) => value_type.encode(env),
+                None => return Err(Syntax::UnexpectedCharacter.at(parser.position())),
+            },
+            None => return Err(Syntax::UnexpectedEnd.at(parser.position())),
+        },
+        Query::Length => parser.count_array_elements()?.encode(env),
+        Query::KeyCount => parser.count_object_members()?.encode(env),
+    };
+    Ok(Some(term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_type_from_first_byte() {
+        assert_eq!(ValueType::from_first_byte(b'{'), Some(ValueType::Object));
+        assert_eq!(ValueType::from_first_byte(b'['), Some(ValueType::Array));
+        assert_eq!(ValueType::from_first_byte(b'"'), Some(ValueType::String));
+        assert_eq!(ValueType::from_first_byte(b'-'), Some(ValueType::Number));
+        assert_eq!(ValueType::from_first_byte(b'7'), Some(ValueType::Number));
+        assert_eq!(ValueType::from_first_byte(b'f'), Some(ValueType::Boolean));
+        assert_eq!(ValueType::from_first_byte(b'n'), Some(ValueType::Null));
+        assert_eq!(ValueType::from_first_byte(b'x'), None);
+    }
+}
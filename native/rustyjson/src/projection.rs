@@ -0,0 +1,252 @@
+//! Key projections for selective decoding (`only:` / `except:`).
+//!
+//! A projection is a set of key paths. A path step is an object key, or `*`,
+//! which matches any key and any array element. The paths are compiled into
+//! a small automaton: each node stands for the set of path prefixes a value
+//! can be reached by, with `*` branches already merged into the named ones,
+//! so looking up a member is a single step from one node to the next.
+//!
+//! The parser carries the current node while it decodes a restricted value.
+//! For every object member (or array element) the projection answers with an
+//! `Action`: skip it at the byte level, decode it as usual, or decode it under
+//! a child node.
+
+use rustler::NifUnitEnum;
+use std::collections::HashMap;
+
+/// Whether the paths select what to keep or what to drop.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// Keep only members on a path; a path's target is kept whole.
+    Only,
+    /// Drop the targets of the paths and keep everything else.
+    Except,
+}
+
+/// What to do with one object member or array element.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Skip the value without building terms.
+    Skip,
+    /// Decode the value, restricted by the given node (`None` = unrestricted).
+    Keep(Option<u32>),
+}
+
+#[derive(Default, Debug)]
+struct Node {
+    keys: Vec<(Vec<u8>, u32)>,
+    wildcard: Option<u32>,
+    /// A path ends here.
+    terminal: bool,
+}
+
+/// A compiled `only:` or `except:` projection.
+#[derive(Debug)]
+pub struct Projection {
+    mode: Mode,
+    nodes: Vec<Node>,
+}
+
+/// Path step that matches any key or array element.
+const WILDCARD: &[u8] = b"*";
+
+impl Projection {
+    /// Node for the value being decoded.
+    pub const ROOT: u32 = 0;
+
+    /// Compile `paths`. Each path must have at least one step.
+    pub fn new(mode: Mode, paths: &[Vec<Vec<u8>>]) -> Self {
+        // Plain trie first; index 0 is the root.
+        let mut trie = vec![Node::default()];
+        for path in paths {
+            let mut node = 0usize;
+            for step in path {
+                let existing = if step.as_slice() == WILDCARD {
+                    trie[node].wildcard
+                } else {
+                    trie[node]
+                        .keys
+                        .iter()
+                        .find(|(key, _)| key == step)
+                        .map(|&(_, child)| child)
+                };
+                node = match existing {
+                    Some(child) => child as usize,
+                    None => {
+                        let child = trie.len() as u32;
+                        trie.push(Node::default());
+                        if step.as_slice() == WILDCARD {
+                            trie[node].wildcard = Some(child);
+                        } else {
+                            trie[node].keys.push((step.clone(), child));
+                        }
+                        child as usize
+                    }
+                };
+            }
+            trie[node].terminal = true;
+        }
+
+        let mut compiler = Compiler {
+            trie: &trie,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+        };
+        compiler.node_for(vec![0]);
+        Self {
+            mode,
+            nodes: compiler.nodes,
+        }
+    }
+
+    /// Action for the member `key` of an object restricted by `node`.
+    #[inline]
+    pub fn member_action(&self, node: u32, key: &[u8]) -> Action {
+        let node = &self.nodes[node as usize];
+        let child = node
+            .keys
+            .iter()
+            .find(|(k, _)| k.as_slice() == key)
+            .map(|&(_, child)| child)
+            .or(node.wildcard);
+        self.action(child)
+    }
+
+    /// Action for the elements of an array restricted by `node`.
+    #[inline]
+    pub fn element_action(&self, node: u32) -> Action {
+        self.action(self.nodes[node as usize].wildcard)
+    }
+
+    fn action(&self, child: Option<u32>) -> Action {
+        match (self.mode, child) {
+            (Mode::Only, None) => Action::Skip,
+            (Mode::Except, None) => Action::Keep(None),
+            (Mode::Only, Some(c)) if self.nodes[c as usize].terminal => Action::Keep(None),
+            (Mode::Except, Some(c)) if self.nodes[c as usize].terminal => Action::Skip,
+            (_, Some(c)) => Action::Keep(Some(c)),
+        }
+    }
+}
+
+/// Builds the merged automaton: one node per set of trie nodes reachable by
+/// the same key sequence.
+struct Compiler<'t> {
+    trie: &'t [Node],
+    nodes: Vec<Node>,
+    ids: HashMap<Vec<u32>, u32>,
+}
+
+impl Compiler<'_> {
+    fn node_for(&mut self, mut set: Vec<u32>) -> u32 {
+        set.sort_unstable();
+        set.dedup();
+        if let Some(&id) = self.ids.get(&set) {
+            return id;
+        }
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node::default());
+        self.ids.insert(set.clone(), id);
+
+        let terminal = set.iter().any(|&n| self.trie[n as usize].terminal);
+        // A terminal node's members are never looked up.
+        if !terminal {
+            let wildcards: Vec<u32> = set
+                .iter()
+                .filter_map(|&n| self.trie[n as usize].wildcard)
+                .collect();
+
+            let mut names: Vec<&[u8]> = Vec::new();
+            for &n in &set {
+                for (key, _) in &self.trie[n as usize].keys {
+                    if !names.contains(&key.as_slice()) {
+                        names.push(key);
+                    }
+                }
+            }
+
+            let mut keys = Vec::with_capacity(names.len());
+            for name in names {
+                let mut child_set = wildcards.clone();
+                for &n in &set {
+                    for (key, child) in &self.trie[n as usize].keys {
+                        if key.as_slice() == name {
+                            child_set.push(*child);
+                        }
+                    }
+                }
+                keys.push((name.to_vec(), self.node_for(child_set)));
+            }
+            let wildcard = (!wildcards.is_empty()).then(|| self.node_for(wildcards));
+            let node = &mut self.nodes[id as usize];
+            node.keys = keys;
+            node.wildcard = wildcard;
+        }
+        self.nodes[id as usize].terminal = terminal;
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&[&str]]) -> Vec<Vec<Vec<u8>>> {
+        paths
+            .iter()
+            .map(|p| p.iter().map(|s| s.as_bytes().to_vec()).collect())
+            .collect()
+    }
+
+    /// Follow `keys` from the root, returning the final action.
+    fn walk(projection: &Projection, keys: &[&str]) -> Action {
+        let mut action = Action::Keep(Some(Projection::ROOT));
+        for key in keys {
+            let Action::Keep(Some(node)) = action else {
+                return action;
+            };
+            action = if *key == "[]" {
+                projection.element_action(node)
+            } else {
+                projection.member_action(node, key.as_bytes())
+            };
+        }
+        action
+    }
+
+    #[test]
+    fn test_only_keeps_paths_and_their_prefixes() {
+        let p = Projection::new(Mode::Only, &paths(&[&["data", "*", "id"], &["meta"]]));
+        assert!(matches!(walk(&p, &["data"]), Action::Keep(Some(_))));
+        assert!(matches!(walk(&p, &["data", "[]"]), Action::Keep(Some(_))));
+        assert_eq!(walk(&p, &["data", "[]", "id"]), Action::Keep(None));
+        assert_eq!(walk(&p, &["data", "[]", "name"]), Action::Skip);
+        assert_eq!(walk(&p, &["meta"]), Action::Keep(None));
+        assert_eq!(walk(&p, &["other"]), Action::Skip);
+    }
+
+    #[test]
+    fn test_except_drops_path_targets() {
+        let p = Projection::new(Mode::Except, &paths(&[&["*", "debug"]]));
+        assert_eq!(walk(&p, &["a", "debug"]), Action::Skip);
+        assert_eq!(walk(&p, &["a", "value"]), Action::Keep(None));
+        assert_eq!(walk(&p, &["debug"]), Action::Keep(Some(1)));
+        assert_eq!(walk(&p, &["[]", "debug"]), Action::Skip);
+    }
+
+    #[test]
+    fn test_wildcard_merges_with_named_branches() {
+        let p = Projection::new(Mode::Only, &paths(&[&["*", "id"], &["user", "name"]]));
+        // "user" matches both the named branch and the wildcard.
+        assert_eq!(walk(&p, &["user", "name"]), Action::Keep(None));
+        assert_eq!(walk(&p, &["user", "id"]), Action::Keep(None));
+        assert_eq!(walk(&p, &["user", "email"]), Action::Skip);
+        assert_eq!(walk(&p, &["other", "name"]), Action::Skip);
+    }
+
+    #[test]
+    fn test_shorter_path_wins() {
+        let p = Projection::new(Mode::Only, &paths(&[&["a"], &["a", "b"]]));
+        assert_eq!(walk(&p, &["a"]), Action::Keep(None));
+    }
+}
@@ -0,0 +1,695 @@
+//! Struct schemas for decoding objects straight into structs (`schema:`).
+//!
+//! A schema is a tree of struct nodes, flattened into a vector and addressed
+//! by index like projection nodes. Each node holds the struct's keys,
+//! `__struct__` included, its default values, and the JSON keys it reads.
+//! A JSON key maps to a slot of the struct and to the `Target` its value is
+//! decoded under: a nested struct, a list of structs, or none. Keys outside
+//! the node are skipped without building terms.
+//!
+//! A union node picks the struct node from the string value of a
+//! discriminator key, such as `"type"`, which may appear anywhere in the
+//! object.
+//!
+//! Typed fields and list items carry a `Check`: a JSON type, constraints and
+//! coercions applied to the value as it is decoded. A value that breaks its
+//! check is a violation, reported with the others once the document ends.
+//!
+//! The schema holds no terms, so it can be shared between calls, and
+//! `RustyJson.Schema.compile!/1` keeps one in a `SchemaResource`. Default
+//! values are kept in the external term format and decoded once per call.
+
+use rustler::{Atom, Decoder, NifResult, Term};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// What the value being decoded should become.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// An object decoded to the struct of this node.
+    Struct(u32),
+    /// An array whose objects are decoded to the struct of this node.
+    List(u32),
+    /// An object decoded to the struct its discriminator selects from this
+    /// union node.
+    Union(u32),
+    /// An array whose objects are decoded as `Union` of this union node.
+    UnionList(u32),
+    /// An array whose elements are each decoded under this check.
+    Items(u32),
+}
+
+/// Where an object member goes in the struct being built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Index into the node's `keys` and defaults.
+    pub slot: usize,
+    /// How the member's value is decoded; `None` decodes it as usual.
+    /// Unset for typed fields, whose check holds the target.
+    pub target: Option<Target>,
+    /// The check the member's value must pass, for typed fields.
+    pub check: Option<u32>,
+}
+
+/// One struct module and the JSON keys that fill it.
+#[derive(Debug)]
+pub struct StructNode {
+    /// Struct keys in slot order, `__struct__` among them.
+    pub keys: Vec<Atom>,
+    /// The default value of each slot as one list, in the external term
+    /// format. The `__struct__` slot holds the module.
+    pub defaults: Vec<u8>,
+    /// Slots that some member must fill, with the JSON key named in the
+    /// violation when none does.
+    pub required: Vec<(usize, Vec<u8>)>,
+    fields: HashMap<Vec<u8>, Field>,
+}
+
+/// Struct nodes selected by the value of a discriminator key.
+#[derive(Debug)]
+pub struct UnionNode {
+    key: Vec<u8>,
+    variants: HashMap<Vec<u8>, u32>,
+    /// Whether an unknown or missing discriminator is an error rather than
+    /// a plain map.
+    pub reject_unknown: bool,
+}
+
+/// The JSON type a checked value must have. `Any` takes every type,
+/// `null` included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Any,
+    String,
+    Integer,
+    Float,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+/// A value listed in an `in:` constraint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Literal {
+    String(Vec<u8>),
+    Integer(i64),
+    Bool(bool),
+}
+
+/// The type and constraints of a typed field or list item.
+#[derive(Debug)]
+pub struct Check {
+    pub kind: Kind,
+    /// How the value is decoded: as a struct, a list of structs, a union,
+    /// or an array of checked items.
+    pub target: Option<Target>,
+    /// Whether `null` passes.
+    pub nullable: bool,
+    /// Whether a value of another type is converted when it can be.
+    pub coerce: bool,
+    /// The allowed values; empty allows any.
+    pub allowed: Vec<Literal>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Bounds on the code points of a string or the elements of an array.
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+/// A decoded JSON value as a check sees it. Strings are unescaped and
+/// numbers are their JSON text.
+#[derive(Clone, Copy, Debug)]
+pub enum Json<'s> {
+    Null,
+    Bool(bool),
+    Number(&'s [u8]),
+    String(&'s [u8]),
+    Object,
+    /// An array and its number of elements.
+    Array(usize),
+}
+
+/// What a coerced value is decoded to instead.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Coerced {
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    String(Vec<u8>),
+}
+
+/// A compiled `schema:` option.
+#[derive(Debug)]
+pub struct Schema {
+    root: Target,
+    nodes: Vec<StructNode>,
+    unions: Vec<UnionNode>,
+    checks: Vec<Check>,
+}
+
+/// NIF resource holding a schema compiled by `RustyJson.Schema.compile!/1`,
+/// so that it is built once and shared by every call that passes it.
+#[cfg(not(fuzzing))]
+pub struct SchemaResource(pub Arc<Schema>);
+
+#[cfg(not(fuzzing))]
+#[rustler::resource_impl]
+impl rustler::Resource for SchemaResource {}
+
+impl Schema {
+    /// Compile the NIF form of the option, built by
+    /// `RustyJson.Schema.nif_spec!/1`. A target is `{:struct | :list, node}`,
+    /// `{:union | :union_list, {key, [{value, node}], unknown}}` or
+    /// `{:items, check}`, where a node is
+    /// `{keys, defaults, [{json_key, slot, required, child}]}`, a child is
+    /// `nil`, a target or `{:check, check}`, and a check is
+    /// `{kind, target | nil, constraints}`.
+    pub fn from_term(term: Term<'_>) -> NifResult<Self> {
+        let mut schema = Self {
+            root: Target::Struct(0),
+            nodes: Vec::new(),
+            unions: Vec::new(),
+            checks: Vec::new(),
+        };
+        schema.root = schema.target_from_term(term)?;
+        Ok(schema)
+    }
+
+    /// Target of each decoded document.
+    pub fn root(&self) -> Target {
+        self.root
+    }
+
+    pub fn node(&self, node: u32) -> &StructNode {
+        &self.nodes[node as usize]
+    }
+
+    pub fn union(&self, node: u32) -> &UnionNode {
+        &self.unions[node as usize]
+    }
+
+    pub fn check(&self, check: u32) -> &Check {
+        &self.checks[check as usize]
+    }
+
+    /// Number of struct nodes; node indices are below it.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl StructNode {
+    /// The field the unescaped JSON `key` fills, if any.
+    #[inline]
+    pub fn field(&self, key: &[u8]) -> Option<Field> {
+        self.fields.get(key).copied()
+    }
+}
+
+/// An object member in the NIF form: `{json_key, slot, required, child}`.
+type Member<'a> = (rustler::Binary<'a>, usize, bool, Term<'a>);
+
+fn kind_from_atom(kind: Atom) -> NifResult<Kind> {
+    use crate::atoms;
+    let kinds = [
+        (atoms::any(), Kind::Any),
+        (atoms::string(), Kind::String),
+        (atoms::integer(), Kind::Integer),
+        (atoms::float(), Kind::Float),
+        (atoms::number(), Kind::Number),
+        (atoms::boolean(), Kind::Boolean),
+        (atoms::map(), Kind::Object),
+        (atoms::list(), Kind::Array),
+    ];
+    kinds
+        .into_iter()
+        .find_map(|(atom, found)| (atom == kind).then_some(found))
+        .ok_or(rustler::Error::BadArg)
+}
+
+fn literal_from_term(term: Term<'_>) -> NifResult<Literal> {
+    if let Ok(value) = term.decode::<rustler::Binary>() {
+        Ok(Literal::String(value.as_slice().to_vec()))
+    } else if let Ok(value) = term.decode::<bool>() {
+        Ok(Literal::Bool(value))
+    } else {
+        Ok(Literal::Integer(term.decode()?))
+    }
+}
+
+impl Check {
+    /// Check `value`. Returns what it is coerced to, if anything, or the
+    /// violation message.
+    pub fn apply(&self, value: Json<'_>) -> Result<Option<Coerced>, String> {
+        let coerced = match (self.kind, value) {
+            (Kind::Any, _) => None,
+            (_, Json::Null) if self.nullable => return Ok(None),
+            (Kind::String, Json::String(_))
+            | (Kind::Number, Json::Number(_))
+            | (Kind::Boolean, Json::Bool(_))
+            | (Kind::Object, Json::Object)
+            | (Kind::Array, Json::Array(_)) => None,
+            (Kind::Integer, Json::Number(text)) if is_integral(text) => None,
+            (Kind::Float, Json::Number(text)) if !is_integral(text) => None,
+            (kind, value) => match coerce(kind, value).filter(|_| self.coerce) {
+                Some(coerced) => Some(coerced),
+                None => {
+                    return Err(format!(
+                        "expected {}, got {}",
+                        kind_name(kind),
+                        json_name(value)
+                    ))
+                }
+            },
+        };
+        match &coerced {
+            Some(Coerced::Integer(n)) => self.constrain_number(*n as f64, Some(*n))?,
+            Some(Coerced::Float(n)) => self.constrain_number(*n, None)?,
+            Some(Coerced::Bool(b)) => self.constrain_allowed(|l| *l == Literal::Bool(*b))?,
+            Some(Coerced::String(s)) => self.constrain_string(s)?,
+            None => match value {
+                Json::Number(text) => {
+                    let integer = std::str::from_utf8(text).ok().and_then(|s| s.parse().ok());
+                    self.constrain_number(parse_float(text).unwrap_or(f64::NAN), integer)?
+                }
+                Json::String(s) => self.constrain_string(s)?,
+                Json::Bool(b) => self.constrain_allowed(|l| *l == Literal::Bool(b))?,
+                Json::Array(len) => self.constrain_length(len)?,
+                Json::Null | Json::Object => {}
+            },
+        }
+        Ok(coerced)
+    }
+
+    fn constrain_number(&self, value: f64, integer: Option<i64>) -> Result<(), String> {
+        self.constrain_allowed(|l| integer.is_some_and(|n| *l == Literal::Integer(n)))?;
+        if let Some(min) = self.min {
+            if compare(value, integer, min).is_lt() {
+                return Err(format!("must be at least {}", min));
+            }
+        }
+        if let Some(max) = self.max {
+            if compare(value, integer, max).is_gt() {
+                return Err(format!("must be at most {}", max));
+            }
+        }
+        Ok(())
+    }
+
+    fn constrain_string(&self, s: &[u8]) -> Result<(), String> {
+        self.constrain_allowed(|l| matches!(l, Literal::String(allowed) if allowed == s))?;
+        // Count code points: every byte but UTF-8 continuation bytes.
+        self.constrain_length(s.iter().filter(|&&b| (b as i8) >= -0x40).count())
+    }
+
+    fn constrain_allowed(&self, matches: impl Fn(&Literal) -> bool) -> Result<(), String> {
+        if self.allowed.is_empty() || self.allowed.iter().any(matches) {
+            Ok(())
+        } else {
+            Err(self.not_allowed())
+        }
+    }
+
+    fn constrain_length(&self, len: usize) -> Result<(), String> {
+        match (self.min_length, self.max_length) {
+            (Some(min), _) if len < min => Err(format!("length must be at least {}", min)),
+            (_, Some(max)) if len > max => Err(format!("length must be at most {}", max)),
+            _ => Ok(()),
+        }
+    }
+
+    #[cold]
+    fn not_allowed(&self) -> String {
+        let allowed: Vec<String> = self
+            .allowed
+            .iter()
+            .map(|literal| match literal {
+                Literal::String(s) => format!("{:?}", String::from_utf8_lossy(s)),
+                Literal::Integer(n) => n.to_string(),
+                Literal::Bool(b) => b.to_string(),
+            })
+            .collect();
+        format!("must be one of {}", allowed.join(", "))
+    }
+}
+
+/// The conversion of `value` to `kind`, if there is one.
+fn coerce(kind: Kind, value: Json<'_>) -> Option<Coerced> {
+    match (kind, value) {
+        (Kind::Integer | Kind::Number, Json::String(s)) if is_number(s) && is_integral(s) => {
+            std::str::from_utf8(s)
+                .ok()?
+                .parse()
+                .ok()
+                .map(Coerced::Integer)
+        }
+        (Kind::Float | Kind::Number, Json::String(s)) if is_number(s) => {
+            parse_float(s).map(Coerced::Float)
+        }
+        (Kind::Float, Json::Number(text)) => parse_float(text).map(Coerced::Float),
+        (Kind::Boolean, Json::String(b"true")) => Some(Coerced::Bool(true)),
+        (Kind::Boolean, Json::String(b"false")) => Some(Coerced::Bool(false)),
+        (Kind::String, Json::Number(text)) => Some(Coerced::String(text.to_vec())),
+        (Kind::String, Json::Bool(b)) => Some(Coerced::String(b.to_string().into_bytes())),
+        _ => None,
+    }
+}
+
+/// Order of a number against a bound, exact for integers that fit an
+/// `i64` and integral bounds.
+fn compare(value: f64, integer: Option<i64>, bound: f64) -> std::cmp::Ordering {
+    match integer {
+        Some(n) if bound.fract() == 0.0 && bound.abs() < 9.2e18 => n.cmp(&(bound as i64)),
+        _ => value.total_cmp(&bound),
+    }
+}
+
+/// Whether `s` is a JSON number.
+fn is_number(s: &[u8]) -> bool {
+    fn digits(s: &[u8]) -> usize {
+        s.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let mut i = usize::from(s.first() == Some(&b'-'));
+    match digits(&s[i..]) {
+        0 => return false,
+        n if n > 1 && s[i] == b'0' => return false,
+        n => i += n,
+    }
+    if s.get(i) == Some(&b'.') {
+        match digits(&s[i + 1..]) {
+            0 => return false,
+            n => i += 1 + n,
+        }
+    }
+    if matches!(s.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(s.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(&s[i..]) {
+            0 => return false,
+            n => i += n,
+        }
+    }
+    i == s.len()
+}
+
+/// Whether the JSON number `text` has no fraction or exponent.
+fn is_integral(text: &[u8]) -> bool {
+    !text.iter().any(|&b| matches!(b, b'.' | b'e' | b'E'))
+}
+
+fn parse_float(text: &[u8]) -> Option<f64> {
+    std::str::from_utf8(text)
+        .ok()?
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite())
+}
+
+fn kind_name(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Any => "any",
+        Kind::String => "string",
+        Kind::Integer => "integer",
+        Kind::Float => "float",
+        Kind::Number => "number",
+        Kind::Boolean => "boolean",
+        Kind::Object => "object",
+        Kind::Array => "array",
+    }
+}
+
+fn json_name(value: Json<'_>) -> &'static str {
+    match value {
+        Json::Null => "null",
+        Json::Bool(_) => "boolean",
+        Json::Number(text) if is_integral(text) => "integer",
+        Json::Number(_) => "float",
+        Json::String(_) => "string",
+        Json::Object => "object",
+        Json::Array(_) => "array",
+    }
+}
+
+impl UnionNode {
+    /// The unescaped discriminator key.
+    #[inline]
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The struct node the unescaped discriminator `value` selects, if any.
+    #[inline]
+    pub fn variant(&self, value: &[u8]) -> Option<u32> {
+        self.variants.get(value).copied()
+    }
+}
+
+impl Schema {
+    fn target_from_term(&mut self, term: Term<'_>) -> NifResult<Target> {
+        let (kind, node): (Atom, Term) = term.decode()?;
+        if kind == crate::atoms::union() {
+            Ok(Target::Union(self.union_from_term(node)?))
+        } else if kind == crate::atoms::union_list() {
+            Ok(Target::UnionList(self.union_from_term(node)?))
+        } else if kind == crate::atoms::items() {
+            Ok(Target::Items(self.check_from_term(node)?))
+        } else if kind == crate::atoms::list() {
+            Ok(Target::List(self.node_from_term(node)?))
+        } else {
+            Ok(Target::Struct(self.node_from_term(node)?))
+        }
+    }
+
+    fn node_from_term(&mut self, term: Term<'_>) -> NifResult<u32> {
+        let (keys, defaults, members): (Vec<Atom>, Term, Vec<Member>) = term.decode()?;
+        if defaults.list_length()? != keys.len() {
+            return Err(rustler::Error::BadArg);
+        }
+        // Reserve the index first so children come after their parent.
+        let index = self.nodes.len() as u32;
+        self.nodes.push(StructNode {
+            keys,
+            defaults: defaults.to_binary().as_slice().to_vec(),
+            required: Vec::new(),
+            fields: HashMap::new(),
+        });
+        let mut fields = HashMap::with_capacity(members.len());
+        let mut required = Vec::new();
+        for (key, slot, is_required, child) in members {
+            if slot >= self.nodes[index as usize].keys.len() {
+                return Err(rustler::Error::BadArg);
+            }
+            let (target, check) = match Option::<(Atom, Term)>::decode(child)? {
+                Some((kind, check)) if kind == crate::atoms::check() => {
+                    (None, Some(self.check_from_term(check)?))
+                }
+                Some(_) => (Some(self.target_from_term(child)?), None),
+                None => (None, None),
+            };
+            let key = key.as_slice().to_vec();
+            if is_required {
+                required.push((slot, key.clone()));
+            }
+            fields.insert(
+                key,
+                Field {
+                    slot,
+                    target,
+                    check,
+                },
+            );
+        }
+        self.nodes[index as usize].fields = fields;
+        self.nodes[index as usize].required = required;
+        Ok(index)
+    }
+
+    fn check_from_term(&mut self, term: Term<'_>) -> NifResult<u32> {
+        let (kind, target, constraints): (Atom, Term, Term) = term.decode()?;
+        let target = match Option::<Term>::decode(target)? {
+            Some(target) => Some(self.target_from_term(target)?),
+            None => None,
+        };
+        let get = |key: Atom| constraints.map_get(key.to_term(term.get_env()));
+        let allowed = match Option::<Vec<Term>>::decode(get(crate::atoms::in_())?)? {
+            Some(values) => values
+                .into_iter()
+                .map(literal_from_term)
+                .collect::<NifResult<_>>()?,
+            None => Vec::new(),
+        };
+        self.checks.push(Check {
+            kind: kind_from_atom(kind)?,
+            target,
+            nullable: get(crate::atoms::nullable())?.decode()?,
+            coerce: get(crate::atoms::coerce())?.decode()?,
+            allowed,
+            min: get(crate::atoms::min())?.decode()?,
+            max: get(crate::atoms::max())?.decode()?,
+            min_length: get(crate::atoms::min_length())?.decode()?,
+            max_length: get(crate::atoms::max_length())?.decode()?,
+        });
+        Ok(self.checks.len() as u32 - 1)
+    }
+
+    fn union_from_term(&mut self, term: Term<'_>) -> NifResult<u32> {
+        let (key, variants, unknown): (rustler::Binary, Vec<(rustler::Binary, Term)>, Atom) =
+            term.decode()?;
+        let mut nodes = HashMap::with_capacity(variants.len());
+        for (value, node) in variants {
+            nodes.insert(value.as_slice().to_vec(), self.node_from_term(node)?);
+        }
+        self.unions.push(UnionNode {
+            key: key.as_slice().to_vec(),
+            variants: nodes,
+            reject_unknown: unknown == rustler::types::atom::error(),
+        });
+        Ok(self.unions.len() as u32 - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(kind: Kind) -> Check {
+        Check {
+            kind,
+            target: None,
+            nullable: false,
+            coerce: false,
+            allowed: Vec::new(),
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+        }
+    }
+
+    #[test]
+    fn checks_json_types() {
+        assert_eq!(check(Kind::Integer).apply(Json::Number(b"-12")), Ok(None));
+        assert_eq!(
+            check(Kind::Integer).apply(Json::Number(b"1.5")),
+            Err("expected integer, got float".to_string())
+        );
+        assert_eq!(
+            check(Kind::Float).apply(Json::Number(b"1")),
+            Err("expected float, got integer".to_string())
+        );
+        assert_eq!(check(Kind::Number).apply(Json::Number(b"1e3")), Ok(None));
+        assert_eq!(
+            check(Kind::String).apply(Json::Null),
+            Err("expected string, got null".to_string())
+        );
+        assert_eq!(check(Kind::Any).apply(Json::Null), Ok(None));
+        assert_eq!(check(Kind::Array).apply(Json::Array(0)), Ok(None));
+        assert_eq!(
+            check(Kind::Object).apply(Json::Array(0)),
+            Err("expected object, got array".to_string())
+        );
+        let nullable = Check {
+            nullable: true,
+            ..check(Kind::Boolean)
+        };
+        assert_eq!(nullable.apply(Json::Null), Ok(None));
+    }
+
+    #[test]
+    fn coerces_only_when_asked() {
+        let coerce = |kind| Check {
+            coerce: true,
+            ..check(kind)
+        };
+        assert_eq!(
+            coerce(Kind::Integer).apply(Json::String(b"-42")),
+            Ok(Some(Coerced::Integer(-42)))
+        );
+        assert_eq!(
+            coerce(Kind::Number).apply(Json::String(b"2.5")),
+            Ok(Some(Coerced::Float(2.5)))
+        );
+        assert_eq!(
+            coerce(Kind::Float).apply(Json::Number(b"3")),
+            Ok(Some(Coerced::Float(3.0)))
+        );
+        assert_eq!(
+            coerce(Kind::Boolean).apply(Json::String(b"false")),
+            Ok(Some(Coerced::Bool(false)))
+        );
+        assert_eq!(
+            coerce(Kind::String).apply(Json::Number(b"1e2")),
+            Ok(Some(Coerced::String(b"1e2".to_vec())))
+        );
+        for input in [
+            &b"42x"[..],
+            b"",
+            b"01",
+            b"1.",
+            b" 1",
+            b"99999999999999999999",
+        ] {
+            assert!(coerce(Kind::Integer).apply(Json::String(input)).is_err());
+        }
+        assert!(coerce(Kind::Integer).apply(Json::Number(b"1.5")).is_err());
+        assert!(check(Kind::Integer).apply(Json::String(b"42")).is_err());
+    }
+
+    #[test]
+    fn applies_constraints() {
+        let range = Check {
+            min: Some(0.0),
+            max: Some(10.5),
+            ..check(Kind::Number)
+        };
+        assert_eq!(range.apply(Json::Number(b"10")), Ok(None));
+        assert_eq!(
+            range.apply(Json::Number(b"-1")),
+            Err("must be at least 0".to_string())
+        );
+        assert_eq!(
+            range.apply(Json::Number(b"10.6")),
+            Err("must be at most 10.5".to_string())
+        );
+
+        let big = Check {
+            max: Some(9_007_199_254_740_992.0),
+            ..check(Kind::Integer)
+        };
+        assert!(big.apply(Json::Number(b"9007199254740993")).is_err());
+
+        let length = Check {
+            min_length: Some(2),
+            max_length: Some(3),
+            ..check(Kind::String)
+        };
+        assert_eq!(length.apply(Json::String("héé".as_bytes())), Ok(None));
+        assert_eq!(
+            length.apply(Json::String(b"abcd")),
+            Err("length must be at most 3".to_string())
+        );
+        let items = Check {
+            min_length: Some(1),
+            ..check(Kind::Array)
+        };
+        assert_eq!(
+            items.apply(Json::Array(0)),
+            Err("length must be at least 1".to_string())
+        );
+
+        let allowed = Check {
+            allowed: vec![Literal::String(b"a".to_vec()), Literal::Integer(1)],
+            coerce: true,
+            ..check(Kind::Any)
+        };
+        assert_eq!(allowed.apply(Json::String(b"a")), Ok(None));
+        assert_eq!(allowed.apply(Json::Number(b"1")), Ok(None));
+        assert_eq!(
+            allowed.apply(Json::Number(b"1.0")),
+            Err("must be one of \"a\", 1".to_string())
+        );
+    }
+}
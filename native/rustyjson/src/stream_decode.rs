@@ -0,0 +1,554 @@
+//! Incremental (chunked) JSON decoding.
+//!
+//! A `StreamDecoder` accepts input in arbitrarily-sized chunks and yields
+//! decoded values as soon as they are complete. The root container decides
+//! what a "value" is:
+//!
+//! - **Array root** (`[...]`): each top-level element is yielded as soon as
+//!   its closing byte arrives. Consumed bytes are dropped from the buffer, so
+//!   memory stays proportional to the largest single element rather than the
+//!   whole document.
+//! - **Any other root**: the single value is yielded once it is complete.
+//!
+//! Work per `feed` is bounded by the chunk size plus the size of the elements
+//! it completes. A non-array root is one element, decoded whole by the call
+//! that completes it, so the Elixir side sends that call to a dirty scheduler
+//! once `buffered` plus the chunk reaches `dirty_threshold`. The scanner never rescans bytes: its state (container depth,
+//! whether we are inside a string, a pending escape, a partial scalar token,
+//! and the array separator state) is carried across chunks. Completed element
+//! ranges are then decoded by `DirectParser`, which builds its own structural
+//! index per element, so the hot decode path is shared with `json_to_term`.
+
+use crate::direct_decode::{
+    check_input_size, DecodeError, DecodeErrorKind, DecodeOptions, DirectParser, Syntax,
+};
+use crate::simd_utils::{chunk_has_structural, skip_plain_string_bytes, CHUNK};
+use rustler::{Binary, Env, OwnedBinary, Term};
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// What the scanner knows about the document root.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Root {
+    /// No non-whitespace byte seen yet.
+    Pending,
+    /// Root is an array; elements are yielded individually.
+    Array,
+    /// Root is a scalar, string, or object; yielded as a single value.
+    Value,
+    /// Root value is complete; only whitespace may follow.
+    Done,
+}
+
+/// Separator state between top-level array elements.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Slot {
+    /// Just after `[`: an element or `]` is allowed.
+    First,
+    /// Just after `,`: an element is required.
+    AfterComma,
+    /// Just after an element: `,` or `]` is required.
+    AfterValue,
+}
+
+/// Byte-level incremental scanner. Holds the unconsumed tail of the input
+/// and finds the boundaries of complete top-level values without building
+/// any terms. Independent of `Env` so it can be tested without a BEAM.
+pub struct StreamScanner {
+    buf: Vec<u8>,
+    /// Absolute input offset of `buf[0]`, for error positions.
+    offset: usize,
+    /// Next byte in `buf` to scan.
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    in_scalar: bool,
+    /// Start (in `buf`) of the value currently being scanned.
+    item_start: Option<usize>,
+    root: Root,
+    slot: Slot,
+    total_bytes: usize,
+    max_bytes: usize,
+    max_depth: usize,
+}
+
+#[inline(always)]
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Bytes that terminate a bare scalar token (number, `true`, `false`, `null`).
+#[inline(always)]
+fn ends_scalar(b: u8) -> bool {
+    matches!(
+        b,
+        b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b'[' | b']' | b'{' | b'}' | b'"'
+    )
+}
+
+impl StreamScanner {
+    pub fn new(max_bytes: usize, max_depth: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            offset: 0,
+            pos: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            in_scalar: false,
+            item_start: None,
+            root: Root::Pending,
+            slot: Slot::First,
+            total_bytes: 0,
+            max_bytes,
+            max_depth,
+        }
+    }
+
+    /// Buffered bytes. Ranges returned by `feed`/`finish` index into this.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Absolute input offset of `bytes()[0]`.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Depth at which top-level values live: 1 inside a root array, else 0.
+    #[inline(always)]
+    fn base_depth(&self) -> usize {
+        usize::from(self.root == Root::Array)
+    }
+
+    #[inline(always)]
+    fn err(&self, error: Syntax, pos: usize) -> DecodeError {
+        error.at(self.offset + pos)
+    }
+
+    /// Append a chunk and return the `bytes()` ranges of every value it
+    /// completed. Call `compact` once the ranges have been decoded.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Range<usize>>, DecodeError> {
+        self.total_bytes += chunk.len();
+        check_input_size(self.total_bytes, self.max_bytes)?;
+        self.buf.extend_from_slice(chunk);
+        let mut done = Vec::new();
+        self.scan(&mut done)?;
+        Ok(done)
+    }
+
+    /// Signal end of input. Returns the range of a trailing bare scalar root
+    /// (e.g. `123`), which has no terminator byte, or errors if the document
+    /// is incomplete.
+    pub fn finish(&mut self) -> Result<Vec<Range<usize>>, DecodeError> {
+        let mut done = Vec::new();
+        if self.in_scalar {
+            self.in_scalar = false;
+            self.complete_item(self.buf.len(), &mut done);
+        }
+        match self.root {
+            Root::Done => Ok(done),
+            _ => Err(self.err(Syntax::UnexpectedEnd, self.buf.len())),
+        }
+    }
+
+    /// Drop every byte that no pending value can still reference.
+    pub fn compact(&mut self) {
+        let keep_from = self.item_start.unwrap_or(self.pos);
+        if keep_from == 0 {
+            return;
+        }
+        self.buf.drain(..keep_from);
+        self.offset += keep_from;
+        self.pos -= keep_from;
+        if let Some(start) = self.item_start.as_mut() {
+            *start -= keep_from;
+        }
+    }
+
+    #[inline]
+    fn complete_item(&mut self, end: usize, done: &mut Vec<Range<usize>>) {
+        if let Some(start) = self.item_start.take() {
+            done.push(start..end);
+        }
+        if self.root == Root::Array {
+            self.slot = Slot::AfterValue;
+        } else {
+            self.root = Root::Done;
+        }
+    }
+
+    #[inline]
+    fn open_container(&mut self, pos: usize) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            let msg = format!("Nesting depth exceeds maximum of {}", self.max_depth);
+            let pos = self.offset + pos;
+            return Err(DecodeError::new(DecodeErrorKind::DepthExceeded, msg, pos));
+        }
+        Ok(())
+    }
+
+    fn scan(&mut self, done: &mut Vec<Range<usize>>) -> Result<(), DecodeError> {
+        let len = self.buf.len();
+        let mut pos = self.pos;
+
+        while pos < len {
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                    pos += 1;
+                    continue;
+                }
+                skip_plain_string_bytes(&self.buf, &mut pos);
+                while pos < len && !matches!(self.buf[pos], b'"' | b'\\') {
+                    pos += 1;
+                }
+                if pos == len {
+                    break;
+                }
+                if self.buf[pos] == b'\\' {
+                    self.escaped = true;
+                    pos += 1;
+                    continue;
+                }
+                self.in_string = false;
+                pos += 1;
+                if self.depth == self.base_depth() {
+                    self.complete_item(pos, done);
+                }
+                continue;
+            }
+
+            if self.in_scalar {
+                while pos < len && !ends_scalar(self.buf[pos]) {
+                    pos += 1;
+                }
+                if pos == len {
+                    break;
+                }
+                // The terminator belongs to the enclosing context: re-examine it.
+                self.in_scalar = false;
+                self.complete_item(pos, done);
+                continue;
+            }
+
+            if self.depth > self.base_depth() {
+                // Inside a value's containers: only structural bytes matter,
+                // DirectParser validates everything else once it is complete.
+                while pos + CHUNK <= len && !chunk_has_structural(&self.buf, pos) {
+                    pos += CHUNK;
+                }
+                if pos == len {
+                    break;
+                }
+                match self.buf[pos] {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.open_container(pos)?,
+                    b'}' | b']' => {
+                        self.depth -= 1;
+                        if self.depth == self.base_depth() {
+                            pos += 1;
+                            self.complete_item(pos, done);
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+                continue;
+            }
+
+            let b = self.buf[pos];
+            if is_whitespace(b) {
+                pos += 1;
+                continue;
+            }
+
+            match self.root {
+                Root::Pending => {
+                    if b == b'[' {
+                        self.root = Root::Array;
+                        self.slot = Slot::First;
+                        self.open_container(pos)?;
+                        pos += 1;
+                        continue;
+                    }
+                    self.root = Root::Value;
+                }
+                Root::Done => return Err(self.err(Syntax::TrailingCharacters, pos)),
+                Root::Value => {}
+                Root::Array => match (b, self.slot) {
+                    (b',', Slot::AfterValue) => {
+                        self.slot = Slot::AfterComma;
+                        pos += 1;
+                        continue;
+                    }
+                    (b']', Slot::First | Slot::AfterValue) => {
+                        self.depth = 0;
+                        self.root = Root::Done;
+                        pos += 1;
+                        continue;
+                    }
+                    (_, Slot::AfterValue) => {
+                        return Err(self.err(Syntax::ExpectedCommaOrBracket, pos))
+                    }
+                    _ => {}
+                },
+            }
+
+            // Start of a top-level value.
+            self.item_start = Some(pos);
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.open_container(pos)?,
+                b'}' | b']' | b',' | b':' => return Err(self.err(Syntax::UnexpectedCharacter, pos)),
+                _ => self.in_scalar = true,
+            }
+            pos += 1;
+        }
+
+        self.pos = pos;
+        Ok(())
+    }
+}
+
+/// Scanner plus the options used to decode each completed value.
+pub struct StreamDecoder {
+    scanner: StreamScanner,
+    opts: DecodeOptions,
+    /// First error encountered; the decoder is unusable afterwards.
+    failed: Option<DecodeError>,
+    finished: bool,
+}
+
+/// NIF resource wrapping a `StreamDecoder`. The mutex serializes concurrent
+/// `feed` calls from different processes sharing the same handle.
+pub struct StreamDecoderResource {
+    pub inner: Mutex<StreamDecoder>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for StreamDecoderResource {}
+
+impl StreamDecoderResource {
+    pub fn new(opts: DecodeOptions) -> Self {
+        Self {
+            inner: Mutex::new(StreamDecoder {
+                scanner: StreamScanner::new(opts.max_bytes, opts.max_depth),
+                opts,
+                failed: None,
+                finished: false,
+            }),
+        }
+    }
+}
+
+impl StreamDecoder {
+    /// Feed a chunk, returning every value it completed.
+    pub fn feed<'a>(&mut self, env: Env<'a>, chunk: &[u8]) -> Result<Vec<Term<'a>>, DecodeError> {
+        self.guard()?;
+        let result = self
+            .scanner
+            .feed(chunk)
+            .and_then(|ranges| self.decode_ranges(env, &ranges));
+        self.settle(result)
+    }
+
+    /// End the input, returning any value that was only terminated by EOF.
+    pub fn finish<'a>(&mut self, env: Env<'a>) -> Result<Vec<Term<'a>>, DecodeError> {
+        self.guard()?;
+        self.finished = true;
+        let result = self
+            .scanner
+            .finish()
+            .and_then(|ranges| self.decode_ranges(env, &ranges));
+        self.settle(result)
+    }
+
+    /// Bytes held for the value still being scanned. A non-array root is
+    /// buffered whole until it completes, so this bounds the decode work of
+    /// the call that completes it.
+    pub fn buffered(&self) -> usize {
+        self.scanner.bytes().len()
+    }
+
+    fn guard(&self) -> Result<(), DecodeError> {
+        if let Some(e) = &self.failed {
+            return Err(e.clone());
+        }
+        if self.finished {
+            return Err(DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Decoder already finished",
+                self.scanner.offset() + self.scanner.bytes().len(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn settle<'a>(
+        &mut self,
+        result: Result<Vec<Term<'a>>, DecodeError>,
+    ) -> Result<Vec<Term<'a>>, DecodeError> {
+        match result {
+            Ok(terms) => {
+                self.scanner.compact();
+                Ok(terms)
+            }
+            Err(e) => {
+                self.failed = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Decode completed ranges. The covering span is copied once into a
+    /// fresh binary so each value can keep zero-copy sub-binary references
+    /// after the scanner's buffer is compacted.
+    fn decode_ranges<'a>(
+        &self,
+        env: Env<'a>,
+        ranges: &[Range<usize>],
+    ) -> Result<Vec<Term<'a>>, DecodeError> {
+        let (Some(first), Some(last)) = (ranges.first(), ranges.last()) else {
+            return Ok(Vec::new());
+        };
+        let span = first.start..last.end;
+        let mut owned = OwnedBinary::new(span.len()).ok_or_else(|| {
+            DecodeError::new(
+                DecodeErrorKind::Unknown,
+                "Failed to allocate binary",
+                self.scanner.offset(),
+            )
+        })?;
+        owned
+            .as_mut_slice()
+            .copy_from_slice(&self.scanner.bytes()[span.clone()]);
+        let binary = Binary::from_owned(owned, env);
+
+        let mut terms = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let rel = range.start - span.start;
+            let sub = binary.make_subbinary(rel, range.len()).map_err(|_| {
+                DecodeError::new(
+                    DecodeErrorKind::Unknown,
+                    "Failed to slice binary",
+                    self.scanner.offset(),
+                )
+            })?;
+            let base = self.scanner.offset() + range.start;
+            let term = DirectParser::new(env, sub.as_slice(), sub, self.opts.clone())
+                .parse()
+                .map_err(|e| {
+                    let pos = base + e.pos;
+                    e.at(pos)
+                })?;
+            terms.push(term);
+        }
+        Ok(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direct_decode::DEFAULT_MAX_DEPTH;
+
+    /// Feed `input` in `chunk`-sized pieces, returning completed values as strings.
+    fn scan_all(input: &[u8], chunk: usize) -> Result<Vec<String>, DecodeError> {
+        let mut s = StreamScanner::new(0, DEFAULT_MAX_DEPTH);
+        let mut out = Vec::new();
+        for piece in input.chunks(chunk.max(1)) {
+            for r in s.feed(piece)? {
+                out.push(String::from_utf8_lossy(&s.bytes()[r]).into_owned());
+            }
+            s.compact();
+        }
+        for r in s.finish()? {
+            out.push(String::from_utf8_lossy(&s.bytes()[r]).into_owned());
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn test_array_elements_any_chunking() {
+        let input = br#" [1, "a,]\"b", {"k":[1,{"x":"}"}]}, -2.5e3 ,true,null,[]] "#;
+        let expected = vec![
+            "1",
+            r#""a,]\"b""#,
+            r#"{"k":[1,{"x":"}"}]}"#,
+            "-2.5e3",
+            "true",
+            "null",
+            "[]",
+        ];
+        for chunk in 1..=input.len() {
+            assert_eq!(scan_all(input, chunk).unwrap(), expected, "chunk={chunk}");
+        }
+    }
+
+    #[test]
+    fn test_non_array_roots() {
+        assert_eq!(scan_all(b"12345", 2).unwrap(), vec!["12345"]);
+        assert_eq!(scan_all(b" {\"a\":[1]} ", 3).unwrap(), vec!["{\"a\":[1]}"]);
+        assert_eq!(scan_all(b"\"x\\\"y\"", 1).unwrap(), vec!["\"x\\\"y\""]);
+        assert!(scan_all(b"[]", 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors_report_absolute_positions() {
+        assert_eq!(scan_all(b"[1 2]", 2).unwrap_err().pos, 3);
+        assert_eq!(scan_all(b"[1,]", 1).unwrap_err().pos, 3);
+        assert_eq!(
+            scan_all(b"[1,2", 1).unwrap_err().kind,
+            DecodeErrorKind::UnexpectedEnd
+        );
+        assert_eq!(scan_all(b"{} x", 1).unwrap_err().pos, 3);
+        let deep = "[".repeat(DEFAULT_MAX_DEPTH + 1);
+        assert!(scan_all(deep.as_bytes(), 7).is_err());
+    }
+
+    #[test]
+    fn test_compact_bounds_buffer() {
+        let mut s = StreamScanner::new(0, DEFAULT_MAX_DEPTH);
+        s.feed(b"[1,2,").unwrap();
+        s.compact();
+        assert!(s.bytes().is_empty());
+        s.feed(b"{\"a\":").unwrap();
+        s.compact();
+        assert_eq!(s.bytes(), b"{\"a\":");
+        assert_eq!(s.offset(), 5);
+    }
+
+    #[test]
+    fn test_object_root_stays_buffered_until_complete() {
+        let mut s = StreamScanner::new(0, DEFAULT_MAX_DEPTH);
+        for chunk in [&b"{\"a\":"[..], b"[1,2]", b",\"b\":3"] {
+            assert!(s.feed(chunk).unwrap().is_empty());
+            s.compact();
+        }
+        assert_eq!(s.bytes(), b"{\"a\":[1,2],\"b\":3");
+        assert_eq!(s.feed(b"}").unwrap(), vec![0..17]);
+    }
+
+    #[test]
+    fn test_max_bytes_counts_whole_stream() {
+        let mut s = StreamScanner::new(4, DEFAULT_MAX_DEPTH);
+        s.feed(b"[1,").unwrap();
+        assert!(s.feed(b"2]").is_err());
+    }
+
+    #[test]
+    fn test_max_depth_reports_limit() {
+        let mut s = StreamScanner::new(0, 2);
+        s.feed(b"[[1],").unwrap();
+        let error = s.feed(b"[[2]]]").unwrap_err();
+        assert_eq!(error.kind, DecodeErrorKind::DepthExceeded);
+        assert_eq!(error.message, "Nesting depth exceeds maximum of 2");
+        assert_eq!(error.pos, 6);
+    }
+}
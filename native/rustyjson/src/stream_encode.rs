@@ -0,0 +1,314 @@
+//! Streaming JSON encoding.
+//!
+//! A `StreamEncoder` writes one JSON array — or one object built from
+//! `{key, value}` pairs — across many NIF calls. Each `push` encodes one or
+//! more items and returns the bytes produced so far, split into chunks of at
+//! most `chunk_size` bytes; `finish` writes the closing bracket. The
+//! concatenated chunks are exactly what `encode!/2` produces for the whole
+//! list (or map, in push order), so they can be written straight to a socket
+//! or `Plug.Conn.chunk/2` as they arrive.
+//!
+//! With gzip, one `GzEncoder` lives for the whole stream: the chunks together
+//! form a single gzip member, not one member per push. Deflate buffers
+//! internally, so small pushes may return no chunks until enough input has
+//! accumulated; `finish` flushes the rest and the gzip trailer.
+//!
+//! A push is atomic. Items are encoded into a fresh buffer and the encoder
+//! state (separator, seen keys, gzip stream) only advances once every item
+//! has encoded, so a failed push emits nothing and the stream stays valid.
+
+use crate::compression::{self, Algs};
+use crate::direct_json::{
+    key_to_string, term_to_json, write_json_string_escaped_pub, EncodeError, EncodeReason,
+};
+use crate::nif_binary_writer::NifBinaryWriter;
+use crate::EncodeSettings;
+use flate2::write::GzEncoder;
+use rustler::{Binary, Env, NewBinary, NifUnitEnum, Term};
+use std::collections::HashSet;
+use std::io::Write;
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// JSON container the stream writes.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Container {
+    /// `[item, ...]` — each pushed term is one element.
+    Array,
+    /// `{"key": value, ...}` — each pushed term is a `{key, value}` tuple.
+    Object,
+}
+
+impl Container {
+    fn open(self) -> &'static [u8] {
+        match self {
+            Container::Array => b"[",
+            Container::Object => b"{",
+        }
+    }
+
+    fn close(self) -> &'static [u8] {
+        match self {
+            Container::Array => b"]",
+            Container::Object => b"}",
+        }
+    }
+}
+
+pub struct StreamEncoder {
+    settings: EncodeSettings,
+    container: Container,
+    /// Items written so far. Array error paths count from the whole stream,
+    /// not from the current push.
+    written: usize,
+    finished: bool,
+    /// Present for the whole stream when gzip was requested.
+    gzip: Option<GzEncoder<Vec<u8>>>,
+    /// Keys written so far in object mode; only tracked with `strict_keys`.
+    seen_keys: Option<HashSet<String>>,
+    chunk_size: usize,
+}
+
+/// NIF resource wrapping a `StreamEncoder`.
+pub struct StreamEncoderResource {
+    pub inner: Mutex<StreamEncoder>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for StreamEncoderResource {}
+
+impl StreamEncoderResource {
+    pub fn new(mut settings: EncodeSettings, container: Container, chunk_size: usize) -> Self {
+        let gzip = match settings.compression.take() {
+            Some((Algs::Gzip, level)) => Some(compression::gzip_encoder(level)),
+            _ => None,
+        };
+        let strict_object = settings.ctx.strict_keys && container == Container::Object;
+        Self {
+            inner: Mutex::new(StreamEncoder {
+                settings,
+                container,
+                written: 0,
+                finished: false,
+                gzip,
+                seen_keys: strict_object.then(HashSet::new),
+                chunk_size: chunk_size.max(1),
+            }),
+        }
+    }
+}
+
+impl StreamEncoder {
+    /// Encode `items` and return the output produced so far.
+    pub fn push<'a>(
+        &mut self,
+        env: Env<'a>,
+        items: &[Term<'a>],
+    ) -> Result<Vec<Binary<'a>>, EncodeError<'a>> {
+        self.check_open()?;
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if self.gzip.is_none() {
+            // Plain output is returned as sub-binaries of one NIF binary.
+            let mut writer = NifBinaryWriter::new(128 * items.len());
+            let batch_keys = self.write_items(items, &mut writer)?;
+            self.commit(items.len(), batch_keys);
+            let binary = writer.into_binary(env);
+            return Ok(chunk_ranges(binary.as_slice(), self.chunk_size, true)
+                .into_iter()
+                .filter_map(|r| binary.make_subbinary(r.start, r.len()).ok())
+                .collect());
+        }
+
+        let mut staging = Vec::with_capacity(128 * items.len());
+        let batch_keys = self.write_items(items, &mut staging)?;
+        self.commit(items.len(), batch_keys);
+        let mut compressed = Vec::new();
+        if let Some(gz) = self.gzip.as_mut() {
+            gz.write_all(&staging)?;
+            compressed = std::mem::take(gz.get_mut());
+        }
+        Ok(copy_chunks(env, &compressed, self.chunk_size))
+    }
+
+    /// Advance the stream state once a whole batch has encoded.
+    fn commit(&mut self, count: usize, batch_keys: Option<HashSet<String>>) {
+        self.written += count;
+        if let (Some(seen), Some(batch)) = (self.seen_keys.as_mut(), batch_keys) {
+            seen.extend(batch);
+        }
+    }
+
+    /// Write the closing bracket (and the gzip trailer) and return the
+    /// remaining output. The encoder cannot be used afterwards.
+    pub fn finish<'a>(&mut self, env: Env<'a>) -> Result<Vec<Binary<'a>>, EncodeError<'a>> {
+        self.check_open()?;
+        self.finished = true;
+
+        let container = self.container;
+        let mut tail = Vec::new();
+        if self.written == 0 {
+            tail.extend_from_slice(container.open());
+        } else {
+            self.settings.format_options().write_newline(&mut tail)?;
+        }
+        tail.extend_from_slice(container.close());
+
+        match self.gzip.take() {
+            Some(mut gz) => {
+                gz.write_all(&tail)?;
+                Ok(copy_chunks(env, &gz.finish()?, self.chunk_size))
+            }
+            None => Ok(copy_chunks(env, &tail, self.chunk_size)),
+        }
+    }
+
+    fn check_open<'a>(&self) -> Result<(), EncodeError<'a>> {
+        if self.finished {
+            return Err(EncodeError::without_value(
+                EncodeReason::Finished,
+                "Encoder already finished",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write `items` with their separators without touching encoder state.
+    /// In strict object mode, returns the keys written by this batch.
+    fn write_items<'a, W: Write>(
+        &self,
+        items: &[Term<'a>],
+        writer: &mut W,
+    ) -> Result<Option<HashSet<String>>, EncodeError<'a>> {
+        let nested = self.settings.format_options().nested();
+        let mut batch_keys = self.seen_keys.as_ref().map(|_| HashSet::new());
+
+        for (i, item) in items.iter().enumerate() {
+            if i == 0 && self.written == 0 {
+                writer.write_all(self.container.open())?;
+            } else {
+                writer.write_all(b",")?;
+            }
+            nested.write_newline(writer)?;
+
+            match self.container {
+                Container::Array => {
+                    term_to_json(*item, writer, nested).map_err(|e| e.at_index(self.written + i))?
+                }
+                Container::Object => {
+                    let (key, key_term, value) = object_entry(*item)?;
+                    if let (Some(seen), Some(batch)) = (&self.seen_keys, batch_keys.as_mut()) {
+                        if seen.contains(&key) || !batch.insert(key.clone()) {
+                            return Err(EncodeError::new(
+                                EncodeReason::DuplicateKey,
+                                format!("duplicate key: {:?}", key),
+                                key_term,
+                            ));
+                        }
+                    }
+                    write_json_string_escaped_pub(&key, writer, self.settings.escape_mode)?;
+                    writer.write_all(b":")?;
+                    nested.write_space(writer)?;
+                    term_to_json(value, writer, nested).map_err(|e| e.at_key(key_term))?;
+                }
+            }
+        }
+        Ok(batch_keys)
+    }
+}
+
+/// Split a `{key, value}` tuple pushed in object mode into the JSON key,
+/// the key term and the value.
+fn object_entry(term: Term) -> Result<(String, Term, Term), EncodeError> {
+    let invalid = || {
+        EncodeError::new(
+            EncodeReason::InvalidValue,
+            "Object stream items must be {key, value} tuples",
+            term,
+        )
+    };
+    let items = rustler::types::tuple::get_tuple(term).map_err(|_| invalid())?;
+    let [key, value] = items[..] else {
+        return Err(invalid());
+    };
+    match key_to_string(&key)? {
+        Some(key_str) => Ok((key_str, key, value)),
+        None => Err(EncodeError::new(
+            EncodeReason::InvalidKey,
+            "Invalid object key: __struct__",
+            key,
+        )),
+    }
+}
+
+/// Copy `bytes` into binaries of at most `max` bytes each.
+fn copy_chunks<'a>(env: Env<'a>, bytes: &[u8], max: usize) -> Vec<Binary<'a>> {
+    chunk_ranges(bytes, max, false)
+        .into_iter()
+        .map(|r| {
+            let mut bin = NewBinary::new(env, r.len());
+            bin.as_mut_slice().copy_from_slice(&bytes[r]);
+            bin.into()
+        })
+        .collect()
+}
+
+/// Split `bytes` into ranges of at most `max` bytes. With `utf8`, cuts are
+/// moved back so no multi-byte character is split across chunks (unless a
+/// chunk could not otherwise make progress).
+fn chunk_ranges(bytes: &[u8], max: usize, utf8: bool) -> Vec<Range<usize>> {
+    let mut ranges = Vec::with_capacity(bytes.len() / max + 1);
+    let mut start = 0;
+    while start < bytes.len() {
+        let mut end = (start + max).min(bytes.len());
+        if utf8 {
+            let mut cut = end;
+            while cut > start && cut < bytes.len() && bytes[cut] & 0xC0 == 0x80 {
+                cut -= 1;
+            }
+            if cut > start {
+                end = cut;
+            }
+        }
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(s: &str, max: usize) -> Vec<&str> {
+        chunk_ranges(s.as_bytes(), max, true)
+            .into_iter()
+            .map(|r| &s[r])
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_ranges_bounded() {
+        assert_eq!(split("[1,2,3]", 3), vec!["[1,", "2,3", "]"]);
+        assert_eq!(split("[1]", 10), vec!["[1]"]);
+        assert!(split("", 4).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_ranges_respect_utf8() {
+        // "é" is two bytes, "😀" four.
+        assert_eq!(split("aé😀b", 4), vec!["aé", "😀", "b"]);
+        assert_eq!(split("aé😀b", 5), vec!["aé", "😀b"]);
+        // A character wider than `max` is split rather than looping forever.
+        assert_eq!(chunk_ranges("😀".as_bytes(), 2, true), vec![0..2, 2..4]);
+    }
+
+    #[test]
+    fn test_chunk_ranges_binary_mode_ignores_utf8() {
+        let bytes = "é".repeat(3);
+        let ranges = chunk_ranges(bytes.as_bytes(), 4, false);
+        assert_eq!(ranges, vec![0..4, 4..6]);
+    }
+}
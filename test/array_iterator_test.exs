@@ -0,0 +1,134 @@
+defmodule ArrayIteratorTest do
+  use ExUnit.Case
+
+  # Drain an iterator in batches of `count`, returning every batch.
+  defp batches(iterator, count) do
+    case RustyJson.iterator_next(iterator, count) do
+      {:cont, values} -> [values | batches(iterator, count)]
+      {:done, values} -> [values]
+    end
+  end
+
+  describe "iterator_new/iterator_next" do
+    test "returns batches of the requested size" do
+      iterator = RustyJson.iterator_new("[1, 2, 3, 4, 5]")
+      assert batches(iterator, 2) == [[1, 2], [3, 4], [5]]
+    end
+
+    test "empty array" do
+      iterator = RustyJson.iterator_new(" [ ] ")
+      assert {:done, []} = RustyJson.iterator_next(iterator, 10)
+      assert {:done, []} = RustyJson.iterator_next(iterator, 10)
+    end
+
+    test "every batch size matches decode!/1" do
+      json =
+        ~s([{"a":1,"b":"x"},{"a":2,"b":"y"},{"b":3,"a":4},[1,[2]],"s",null,) <>
+          ~s({"a":5,"b":{"c":6}},{"a":7,"b":8},1.5e3])
+
+      expected = RustyJson.decode!(json)
+
+      for size <- 1..12 do
+        iterator = RustyJson.iterator_new(json)
+        assert batches(iterator, size) |> Enum.concat() == expected, "batch size #{size}"
+      end
+    end
+
+    test "homogeneous rows decode identically across batch boundaries" do
+      rows =
+        for i <- 1..500, do: %{"id" => i, "name" => "user#{i}", "active" => rem(i, 2) == 0}
+      json = RustyJson.encode!(rows)
+
+      for opts <- [[], [keys: :intern], [objects: :ordered_objects]] do
+        expected = RustyJson.decode!(json, opts)
+        iterator = RustyJson.iterator_new(json, opts)
+        assert batches(iterator, 7) |> Enum.concat() == expected, inspect(opts)
+      end
+    end
+
+    test "honors decode options" do
+      json = ~s([{"price":1.25},{"price":2.5}])
+      iterator = RustyJson.iterator_new(json, floats: :decimals, keys: :atoms)
+
+      assert {:cont, [%{price: %Decimal{} = d}]} = RustyJson.iterator_next(iterator, 1)
+      assert Decimal.equal?(d, Decimal.new("1.25"))
+    end
+
+    test "iterates the array at a path" do
+      json = ~s({"meta":{"n":[9]},"data":{"items":[{"id":1},{"id":2}],"x":1}})
+      iterator = RustyJson.iterator_new(json, path: ["data", "items"])
+      assert batches(iterator, 10) == [[%{"id" => 1}, %{"id" => 2}]]
+    end
+
+    test "path may index into arrays" do
+      json = ~s([[1,2],[3,[4,5]]])
+      iterator = RustyJson.iterator_new(json, path: [1, 1])
+      assert batches(iterator, 10) == [[4, 5]]
+    end
+
+    test "path keys with escapes are matched after unescaping" do
+      iterator = RustyJson.iterator_new(~s({"a\\/b":[1]}), path: ["a/b"])
+      assert {:done, [1]} = RustyJson.iterator_next(iterator, 10)
+    end
+
+    test "a repeated path key resolves to its first occurrence" do
+      json = ~s({"rows":[1,2],"rows":[3]})
+
+      for duplicate_keys <- [:last, :error] do
+        iterator = RustyJson.iterator_new(json, path: ["rows"], duplicate_keys: duplicate_keys)
+        assert batches(iterator, 10) == [[1, 2]]
+      end
+    end
+
+    test "missing path is an error" do
+      iterator = RustyJson.iterator_new(~s({"a":[1]}), path: ["b"])
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.iterator_next(iterator, 1)
+
+      assert msg =~ "Path not found"
+    end
+
+    test "non-array target is an error" do
+      iterator = RustyJson.iterator_new(~s({"a":1}))
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.iterator_next(iterator, 1)
+
+      assert msg =~ "Expected array"
+    end
+
+    test "errors are reported by the batch that reaches them" do
+      json = "[1, 2, x]"
+      iterator = RustyJson.iterator_new(json)
+      assert {:cont, [1, 2]} = RustyJson.iterator_next(iterator, 2)
+
+      assert {:error, %RustyJson.DecodeError{position: 7, data: ^json}} =
+               RustyJson.iterator_next(iterator, 2)
+    end
+
+    test "rejects trailing characters after a root array" do
+      iterator = RustyJson.iterator_new("[1] 2")
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.iterator_next(iterator, 5)
+    end
+
+    test "rejects trailing commas" do
+      iterator = RustyJson.iterator_new("[1,]")
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.iterator_next(iterator, 5)
+    end
+  end
+
+  describe "stream_array/2" do
+    test "streams elements lazily" do
+      json = RustyJson.encode!(Enum.to_list(1..10_000))
+      assert json |> RustyJson.stream_array(batch_size: 333) |> Enum.take(3) == [1, 2, 3]
+      assert json |> RustyJson.stream_array() |> Enum.sum() == 50_005_000
+    end
+
+    test "raises DecodeError on invalid input" do
+      assert_raise RustyJson.DecodeError, fn ->
+        "[1, }" |> RustyJson.stream_array() |> Enum.to_list()
+      end
+    end
+  end
+end
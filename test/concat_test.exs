@@ -0,0 +1,104 @@
+defmodule ConcatTest do
+  use ExUnit.Case
+
+  describe "decode_prefix/2" do
+    test "returns the first document and the rest of the input" do
+      assert RustyJson.decode_prefix(~s({"a":1}\n{"b":2}\n)) ==
+               {:ok, %{"a" => 1}, 8, ~s({"b":2}\n)}
+
+      assert RustyJson.decode_prefix!("  [1]") == {[1], 5, ""}
+      assert RustyJson.decode_prefix!(~s("x"true)) == {"x", 3, "true"}
+    end
+
+    test "the rest offset indexes the input" do
+      input = ~s([1, 2]   {"id": 7} tail)
+      {_term, offset, rest} = RustyJson.decode_prefix!(input)
+      assert binary_part(input, offset, byte_size(input) - offset) == rest
+      assert {%{"id" => 7}, _, "tail"} = RustyJson.decode_prefix!(rest)
+    end
+
+    test "frames a buffer until an incomplete document remains" do
+      buffer = ~s({"seq":1} {"seq":2} {"seq":)
+      {first, _, rest} = RustyJson.decode_prefix!(buffer)
+      {second, _, rest} = RustyJson.decode_prefix!(rest)
+
+      assert [first, second] == [%{"seq" => 1}, %{"seq" => 2}]
+      assert rest == ~s({"seq":)
+      assert {:error, %RustyJson.DecodeError{code: :unexpected_end}} =
+               RustyJson.decode_prefix(rest)
+
+      assert {:error, %RustyJson.DecodeError{code: :unterminated_string}} =
+               RustyJson.decode_prefix(~s({"se))
+    end
+
+    test "skips leading record separators" do
+      assert RustyJson.decode_prefix!("\x1E[1]\n\x1E[2]\n") == {[1], 5, "\x1E[2]\n"}
+    end
+
+    test "fails on input without a document" do
+      for input <- ["", "  \n", "\x1E"] do
+        assert {:error, %RustyJson.DecodeError{code: :unexpected_end}} =
+                 RustyJson.decode_prefix(input)
+      end
+    end
+
+    test "honors decode options" do
+      assert {%{id: 1}, _, "[]"} = RustyJson.decode_prefix!(~s({"id":1}[]), keys: :atoms)
+
+      assert_raise ArgumentError, ~r/not supported by decode_prefix/, fn ->
+        RustyJson.decode_prefix!("{a: 1}", relaxed: true)
+      end
+    end
+  end
+
+  describe "decode_all/2" do
+    test "decodes back-to-back and whitespace-separated documents" do
+      assert RustyJson.decode_all(~s({"a":1}{"b":2}[3]"s"null)) ==
+               {:ok, [%{"a" => 1}, %{"b" => 2}, [3], "s", nil]}
+
+      assert RustyJson.decode_all!("1 2\n3\t-4.5") == [1, 2, 3, -4.5]
+    end
+
+    test "decodes RFC 7464 JSON text sequences" do
+      input = "\x1E{\"a\":1}\n\x1E[true]\n\x1E\x1E\"x\"\n"
+      assert RustyJson.decode_all!(input) == [%{"a" => 1}, [true], "x"]
+    end
+
+    test "returns an empty list for empty input" do
+      assert RustyJson.decode_all!("") == []
+      assert RustyJson.decode_all!(" \n\x1E ") == []
+    end
+
+    test "accepts iodata" do
+      assert RustyJson.decode_all!([~s({"a":), "1}", ["[2]"]]) == [%{"a" => 1}, [2]]
+    end
+
+    test "every document matches decode!/1" do
+      docs = [
+        ~s({"id":1,"tags":["a","b"],"nested":{"x":[true,false]}}),
+        ~s({"id":2,"esc":"q\\"\\u00e9"}),
+        ~s([123456789012345678901234567890, -1.5e3]),
+        ~s({"long":"#{String.duplicate("x", 300)}"})
+      ]
+
+      assert RustyJson.decode_all!(Enum.join(docs)) == Enum.map(docs, &RustyJson.decode!/1)
+    end
+
+    test "interned keys are shared across documents" do
+      input = Enum.map_join(1..100, &RustyJson.encode!(%{"id" => &1, "name" => "n#{&1}"}))
+      assert RustyJson.decode_all!(input, keys: :intern) == RustyJson.decode_all!(input)
+    end
+
+    test "reports errors with positions in the whole input and paths in the document" do
+      input = ~s([1] {"a": [1, })
+
+      assert {:error, %RustyJson.DecodeError{position: 14, path: "$.a[1]", data: ^input}} =
+               RustyJson.decode_all(input)
+    end
+
+    test "honors max_bytes" do
+      assert {:error, %RustyJson.DecodeError{message: "input size 7 exceeds max_bytes" <> _}} =
+               RustyJson.decode_all("[1] [2]", max_bytes: 3)
+    end
+  end
+end
@@ -541,12 +541,44 @@ defmodule DecoderTest do
       result = RustyJson.decode!(json, keys: :atoms)
       assert result == %{user: %{name: "Alice"}}
     end
+
+    test ":atoms handles escaped and non-ASCII keys" do
+      json = ~s({"caf\\u00e9": 1, "日本": 2, "a\\"b": 3})
+      assert RustyJson.decode!(json, keys: :atoms) == %{café: 1, 日本: 2, "a\"b": 3}
+    end
+
+    test ":atoms reuses keys across arrays of objects" do
+      rows = for i <- 1..100, do: %{"id" => i, "name" => "row"}
+      decoded = RustyJson.decode!(RustyJson.encode!(rows), keys: :atoms)
+      assert decoded == for(i <- 1..100, do: %{id: i, name: "row"})
+    end
+
+    test "max_new_atoms caps the atoms one decode creates" do
+      [a, b] = for _ <- 1..2, do: "new_atom_key_#{System.unique_integer([:positive])}"
+      json = ~s({"name":1,"#{a}":2,"#{b}":3})
+
+      assert_raise RustyJson.DecodeError, ~r/keys exceed 1 new atom limit at position/, fn ->
+        RustyJson.decode!(json, keys: :atoms, max_new_atoms: 1)
+      end
+
+      assert %{name: 1} = RustyJson.decode!(json, keys: :atoms, max_new_atoms: 2)
+      assert map_size(RustyJson.decode!(json, keys: :atoms, max_new_atoms: 1)) == 3
+    end
+
+    test ":atoms rejects keys longer than 255 characters" do
+      json = ~s({"#{String.duplicate("é", 256)}": 1})
+
+      assert {:error, %RustyJson.DecodeError{message: message}} =
+               RustyJson.decode(json, keys: :atoms)
+
+      assert message == "Key cannot be converted to an atom at position 1"
+    end
   end
 
   describe "decode with keys: :atoms! (strict, existing atoms only)" do
-    test ":atoms! raises for non-existing atoms" do
-      assert_raise ArgumentError, fn ->
-        RustyJson.decode!(~s({"nonexistent_atom_zzzzzz_12345": 1}), keys: :atoms!)
+    test ":atoms! fails at the position of a non-existing atom" do
+      assert_raise RustyJson.DecodeError, ~r/not an existing atom at position 10/, fn ->
+        RustyJson.decode!(~s({"name":1,"nonexistent_atom_zzzzzz_12345": 1}), keys: :atoms!)
       end
     end
 
@@ -564,6 +596,79 @@ defmodule DecoderTest do
     end
   end
 
+  describe "decode with key_case:" do
+    @json ~s({"userId": 1, "home_address": {"zip-code": "x"}, "HTTPServer": [{"isUp": true}]})
+
+    test "converts keys at every level" do
+      assert RustyJson.decode!(@json, key_case: :snake_case) == %{
+               "user_id" => 1,
+               "home_address" => %{"zip_code" => "x"},
+               "http_server" => [%{"is_up" => true}]
+             }
+
+      assert %{"userId" => 1, "homeAddress" => %{"zipCode" => "x"}, "httpServer" => _} =
+               RustyJson.decode!(@json, key_case: :camel_case)
+
+      assert %{"user-id" => 1, "home-address" => %{"zip-code" => "x"}} =
+               RustyJson.decode!(@json, key_case: :kebab_case)
+    end
+
+    test "keeps leading and trailing separators" do
+      assert RustyJson.decode!(~s({"_id": 1, "__typeName": 2}), key_case: :snake_case) ==
+               %{"_id" => 1, "__type_name" => 2}
+    end
+
+    test "combines with atom keys, ordered objects and arrays of objects" do
+      rows = RustyJson.encode!(for i <- 1..50, do: %{"rowId" => i})
+
+      assert RustyJson.decode!(rows, key_case: :snake_case, keys: :atoms) ==
+               for(i <- 1..50, do: %{row_id: i})
+
+      assert %RustyJson.OrderedObject{values: [{"first_name", 1}, {"last_name", 2}]} =
+               RustyJson.decode!(~s({"firstName":1,"lastName":2}),
+                 key_case: :snake_case,
+                 objects: :ordered_objects
+               )
+    end
+
+    test "rejects unknown styles" do
+      assert_raise ArgumentError, ~r/invalid :key_case option :pascal_case/, fn ->
+        RustyJson.decode!("{}", key_case: :pascal_case)
+      end
+    end
+  end
+
+  describe "decode with integer_keys:" do
+    test "decodes canonical integer keys to integers" do
+      json = ~s({"1": "a", "-2": "b", "007": "c", "x": "d", "18446744073709551616": "e"})
+
+      assert RustyJson.decode!(json, integer_keys: true) == %{
+               1 => "a",
+               -2 => "b",
+               "007" => "c",
+               "x" => "d",
+               18_446_744_073_709_551_616 => "e"
+             }
+    end
+
+    test "integer keys are held to the integer digit limit" do
+      json = ~s({"a": 1, "-123456": 2})
+
+      assert {:error, %RustyJson.DecodeError{code: :limit_exceeded, position: 9} = error} =
+               RustyJson.decode(json, integer_keys: true, decoding_integer_digit_limit: 5)
+
+      assert error.message == "integer key exceeds 5 digit limit at position 9"
+
+      assert RustyJson.decode!(json, integer_keys: true, decoding_integer_digit_limit: 6) ==
+               %{"a" => 1, -123_456 => 2}
+    end
+
+    test "takes precedence over atom keys" do
+      assert RustyJson.decode!(~s({"1": 1, "name": 2}), integer_keys: true, keys: :atoms) ==
+               %{1 => 1, name: 2}
+    end
+  end
+
   describe "decode with keys: :copy" do
     test "keys: :copy is accepted and decodes correctly" do
       # :copy is a Jason-compatible alias for :strings. Both produce string keys.
@@ -576,7 +681,7 @@ defmodule DecoderTest do
 
   describe "decode with strings: option (Gap 2)" do
     test "strings: :copy and :reference are both accepted and decode correctly" do
-      # RustyJson always copies from the NIF, so both options behave identically.
+      # The modes differ only in whether strings share the input's memory.
       # This test guards against either option being accidentally rejected.
       json = ~s({"key":"value","nested":{"a":"b"}})
       copy = RustyJson.decode!(json, strings: :copy)
@@ -585,6 +690,50 @@ defmodule DecoderTest do
       assert copy == %{"key" => "value", "nested" => %{"a" => "b"}}
     end
 
+    test ":reference shares long strings with the input and :copy does not" do
+      long = String.duplicate("x", 100)
+      json = RustyJson.encode!(%{"long" => long, "pad" => Enum.to_list(1..100)})
+
+      %{"long" => ref} = RustyJson.decode!(json)
+      assert :binary.referenced_byte_size(ref) == byte_size(json)
+
+      %{"long" => copy} = RustyJson.decode!(json, strings: :copy)
+      assert copy == long
+      assert :binary.referenced_byte_size(copy) == byte_size(long)
+
+      digits = String.duplicate("1", 100)
+      [text] = RustyJson.decode!("[0.#{digits}, 0]", numbers: :strings, strings: :copy)
+      assert :binary.referenced_byte_size(text) == byte_size(text)
+    end
+
+    test "subbinary_threshold sets the shortest shared string" do
+      pad = List.duplicate(0, 50)
+      json = RustyJson.encode!(%{"s" => String.duplicate("y", 100), "pad" => pad})
+
+      %{"s" => s} = RustyJson.decode!(json, subbinary_threshold: 101)
+      assert :binary.referenced_byte_size(s) == 100
+
+      %{"s" => s} = RustyJson.decode!(json, subbinary_threshold: 100)
+      assert :binary.referenced_byte_size(s) == byte_size(json)
+
+      assert_raise ArgumentError, fn -> RustyJson.decode!("1", subbinary_threshold: -1) end
+    end
+
+    test ":auto copies strings that are a small part of the input" do
+      id = String.duplicate("i", 100)
+      json = RustyJson.encode!(%{"id" => id, "rows" => Enum.to_list(1..10_000)})
+      %{"id" => copy} = RustyJson.decode!(json, strings: :auto)
+      assert :binary.referenced_byte_size(copy) == byte_size(id)
+
+      # Once the shareable strings reach a quarter of the input, they share it
+      strings = for i <- 1..100, do: String.duplicate("s#{i}", 50)
+      json = RustyJson.encode!(strings)
+      decoded = RustyJson.decode!(json, strings: :auto)
+      assert decoded == strings
+      assert :binary.referenced_byte_size(List.first(decoded)) < byte_size(json)
+      assert :binary.referenced_byte_size(List.last(decoded)) == byte_size(json)
+    end
+
     test "invalid strings option raises" do
       assert_raise ArgumentError, fn ->
         RustyJson.decode!("1", strings: :invalid)
@@ -632,6 +781,169 @@ defmodule DecoderTest do
         RustyJson.decode!("1", floats: :invalid)
       end
     end
+
+    test "exponents are exact or fail when they do not fit" do
+      assert %Decimal{exp: 1024} = RustyJson.decode!("1e1024", floats: :decimals)
+      assert %Decimal{exp: -3} = RustyJson.decode!("0.125", floats: :decimals)
+
+      assert {:error, %RustyJson.DecodeError{code: :limit_exceeded, position: 0} = error} =
+               RustyJson.decode("1e999999999", floats: :decimals)
+
+      assert error.message == "decimal exponent 999999999 exceeds limit of 1024 at position 0"
+
+      # The fraction counts towards the exponent
+      assert {:error, %RustyJson.DecodeError{message: "decimal exponent -1025 exceeds" <> _}} =
+               RustyJson.decode("1.5e-1024", numbers: :decimals)
+
+      assert {:error, %RustyJson.DecodeError{code: :limit_exceeded, position: 1} = error} =
+               RustyJson.decode("[1e99999999999999999999]", floats: :decimals)
+
+      assert error.message == "decimal exponent out of range at position 1"
+
+      # Native floats are unaffected
+      assert RustyJson.decode!("1e-999999999") == 0.0
+    end
+
+    test "the digit quotas bound decimals" do
+      assert {:error, %RustyJson.DecodeError{code: :limit_exceeded} = error} =
+               RustyJson.decode("[1e999999999]", floats: :decimals, max_exponent_digits: 3)
+
+      assert error.message == "exponent exceeds 3 digit limit at position 1"
+
+      fraction = "0." <> String.duplicate("1", 20)
+
+      assert {:error, %{message: "fraction exceeds 10 digit limit" <> _}} =
+               RustyJson.decode(fraction, numbers: :decimals, max_fraction_digits: 10)
+
+      assert %Decimal{exp: -20} = RustyJson.decode!(fraction, floats: :decimals)
+    end
+
+    test "decimals within the limits round-trip through encode" do
+      for text <- ["1.10", "-0.001", "123456789012345678901234567890123456789.5", "1.5e-1000"] do
+        decimal = RustyJson.decode!(text, floats: :decimals)
+        assert Decimal.equal?(decimal, Decimal.new(text))
+        # Decimals encode as strings
+        encoded = RustyJson.encode!(decimal)
+        assert Decimal.equal?(Decimal.new(RustyJson.decode!(encoded)), decimal)
+      end
+    end
+  end
+
+  describe "decode with numbers:" do
+    @numbers_json ~s({"a":1.10,"b":[1e2,-0,12345678901234567890123,7],"c":{"d":1E-7}})
+
+    test ":raw returns fragments that encode to the original text" do
+      result = RustyJson.decode!(@numbers_json, numbers: :raw)
+      assert %{"a" => %RustyJson.Fragment{encode: "1.10"}} = result
+      assert RustyJson.encode!(result) == @numbers_json
+      assert RustyJson.encode!(result, protocol: false) == @numbers_json
+    end
+
+    test ":strings returns the text of every number" do
+      assert RustyJson.decode!(@numbers_json, numbers: :strings) == %{
+               "a" => "1.10",
+               "b" => ["1e2", "-0", "12345678901234567890123", "7"],
+               "c" => %{"d" => "1E-7"}
+             }
+
+      assert RustyJson.decode!("[1,2,3]", numbers: :strings) == ["1", "2", "3"]
+      long = "0." <> String.duplicate("1", 100)
+      assert RustyJson.decode!("[#{long}]", numbers: :strings) == [long]
+    end
+
+    test ":decimals covers integers" do
+      assert [%Decimal{} = int, %Decimal{} = float] =
+               RustyJson.decode!("[42, 1.10]", numbers: :decimals)
+
+      assert Decimal.equal?(int, Decimal.new(42))
+      assert Decimal.to_string(float) == "1.10"
+    end
+
+    test "overrides floats:" do
+      assert RustyJson.decode!("[1.5]", numbers: :strings, floats: :decimals) == ["1.5"]
+    end
+
+    test "leaves number structs alone when transforming keys" do
+      assert %{"a" => %Decimal{}} =
+               RustyJson.decode!(~s({"A":1}), numbers: :decimals, keys: &String.downcase/1)
+    end
+
+    test "invalid numbers option raises" do
+      assert_raise ArgumentError, fn -> RustyJson.decode!("1", numbers: :floats) end
+    end
+  end
+
+  describe "decode with datetimes:" do
+    test "true decodes every ISO 8601 string value" do
+      json = ~s(["2024-01-15", "10:30:00.5", "2024-01-15T10:30:00", "2024-01-15T10:30:00Z"])
+
+      assert RustyJson.decode!(json, datetimes: true) == [
+               ~D[2024-01-15],
+               ~T[10:30:00.5],
+               ~N[2024-01-15 10:30:00],
+               ~U[2024-01-15 10:30:00Z]
+             ]
+    end
+
+    test "decodes what the encoder writes" do
+      values = [
+        ~D[1999-12-31],
+        ~T[23:59:59.123456],
+        ~N[2024-02-29 00:00:00.001],
+        ~U[2024-01-15 10:30:00Z]
+      ]
+
+      assert RustyJson.decode!(RustyJson.encode!(values), datetimes: true) == values
+    end
+
+    test "shifts offsets to UTC like DateTime.from_iso8601/1" do
+      for text <- ["2024-01-01T01:30:00+02:00", "2023-12-31T18:00:00.25-05:30"] do
+        {:ok, expected, _offset} = DateTime.from_iso8601(text)
+        assert RustyJson.decode!(~s(["#{text}"]), datetimes: true) == [expected]
+      end
+    end
+
+    test "leaves other strings alone" do
+      strings = [
+        "2024-02-30",
+        "2024-01-15 10:30:00",
+        "2024-01-15T10:30",
+        "24:00:00",
+        "2024-01-15T10:30:00-00:00",
+        "2024-01-15T10:30:00Z trailing",
+        "hello"
+      ]
+
+      assert RustyJson.decode!(RustyJson.encode!(strings), datetimes: true) == strings
+    end
+
+    test "a list of keys limits decoding to those members' values" do
+      json = ~s({"at":"2024-01-15","note":"2024-01-15","list":["2024-01-15"],"n":{"at":"x"}})
+
+      assert RustyJson.decode!(json, datetimes: ["at", :list]) == %{
+               "at" => ~D[2024-01-15],
+               "note" => "2024-01-15",
+               "list" => ["2024-01-15"],
+               "n" => %{"at" => "x"}
+             }
+    end
+
+    test "keys match the JSON before key conversion" do
+      rows = Enum.map_join(1..3, ",", &~s({"createdAt":"2024-01-0#{&1}","id":#{&1}}))
+
+      opts = [datetimes: ["createdAt"], key_case: :snake_case, keys: :atoms]
+      assert [%{created_at: ~D[2024-01-01]} | _] = RustyJson.decode!("[#{rows}]", opts)
+    end
+
+    test "keys are the only strings left undecoded" do
+      assert RustyJson.decode!(~s({"2024-01-15":"2024-01-15"}), datetimes: true) ==
+               %{"2024-01-15" => ~D[2024-01-15]}
+    end
+
+    test "invalid datetimes option raises" do
+      assert_raise ArgumentError, fn -> RustyJson.decode!("1", datetimes: :all) end
+      assert_raise ArgumentError, fn -> RustyJson.decode!("1", datetimes: [1]) end
+    end
   end
 
   describe "DecodeError struct fields (Gap 8)" do
@@ -673,6 +985,82 @@ defmodule DecoderTest do
     end
   end
 
+  describe "DecodeError details" do
+    test "locate the error by line, column, path and snippet" do
+      json = """
+      {
+        "orders": [
+          {"id": 1},
+          {"items": [1, {"price": x}]}
+        ]
+      }
+      """
+
+      error = assert_raise RustyJson.DecodeError, fn -> RustyJson.decode!(json) end
+
+      assert error.position == 59
+      assert {error.line, error.column} == {4, 29}
+      assert error.path == "$.orders[1].items[1].price"
+      caret = String.duplicate(" ", 28) <> "^"
+      assert error.snippet == ~s(    {"items": [1, {"price": x}]}\n) <> caret
+      assert error.code == :unexpected_token
+      assert "null" in error.expected
+    end
+
+    test "report a code and the expected tokens" do
+      assert {:error, %{code: :unexpected_token, expected: [",", "]"]}} =
+               RustyJson.decode("[1 2]")
+
+      assert {:error, %{code: :unterminated_string, path: "$.a"}} =
+               RustyJson.decode(~s({"a": "abc))
+
+      assert {:error, %{code: :depth_exceeded, path: "$[0][0]", expected: []}} =
+               RustyJson.decode("[[[1]]]", max_depth: 2)
+
+      assert {:error, %{code: :trailing_characters, path: "$"}} = RustyJson.decode("[] x")
+      assert {:error, %{code: :limit_exceeded}} = RustyJson.decode("[1,2]", max_array_elements: 1)
+    end
+
+    test "codes come from the parser for every error family" do
+      assert {:error, %{code: :invalid_escape}} = RustyJson.decode(~S(["\q"]))
+      assert {:error, %{code: :lone_surrogate}} = RustyJson.decode(~S(["\ud800"]))
+      assert {:error, %{code: :invalid_number}} = RustyJson.decode("[-]")
+      assert {:error, %{code: :control_character}} = RustyJson.decode(~s(["a\tb"]))
+      assert {:error, %{code: :duplicate_key}} =
+               RustyJson.decode(~s({"a":1,"a":2}), duplicate_keys: :error)
+
+      assert {:error, %{code: :limit_exceeded, expected: []}} =
+               RustyJson.decode("12345", decoding_integer_digit_limit: 2)
+    end
+
+    test "paths skip containers that have closed" do
+      assert {:error, %{code: :unexpected_token, path: "$.c"}} =
+               RustyJson.decode(~s({"a": {"b": 1}, "c": {x}}))
+
+      assert {:error, %{code: :unexpected_end, path: "$.b[0]"}} =
+               RustyJson.decode(~s({"a": [1, [2]], "b": [))
+    end
+
+    test "paths quote keys that are not identifiers" do
+      assert {:error, %{path: ~s($["a b"][0].c)}} = RustyJson.decode(~s({"a b": [{"c": x}]}))
+      assert {:error, %{path: ~s($["\u00e9"])}} = RustyJson.decode(~s({"\\u00e9": x}))
+    end
+
+    test "columns count characters" do
+      assert {:error, %{position: 8, line: 1, column: 7, path: ~s($["é"])}} =
+               RustyJson.decode(~s({"é": x}))
+    end
+
+    test "relaxed paths read unquoted and single-quoted keys" do
+      assert {:error, %{path: "$.a.b"}} = RustyJson.decode("{a: {'b': x}}", relaxed: true)
+    end
+
+    test "errors at the root" do
+      assert {:error, error} = RustyJson.decode("x")
+      assert {error.path, error.line, error.column, error.snippet} == {"$", 1, 1, "x\n^"}
+    end
+  end
+
   describe "large integer precision" do
     test "20-digit integer preserves precision" do
       big = String.duplicate("9", 20)
@@ -850,6 +850,80 @@ defmodule EncoderTest do
     end
   end
 
+  # =====================================================================
+  # EncodeError reason, path and value
+  # =====================================================================
+
+  describe "EncodeError details" do
+    test "points to the offending value" do
+      data = %{users: [%{name: "ok"}, %{name: "ok"}, %{id: 3, name: <<0xFF>>}]}
+      assert {:error, error} = RustyJson.encode(data)
+
+      assert %RustyJson.EncodeError{
+               message: "Failed to decode binary",
+               reason: :invalid_utf8,
+               path: [:users, 2, :name],
+               value: "<<255>>"
+             } = error
+    end
+
+    test "keeps map keys as written" do
+      data = %{"a" => %{1 => [nil, self()]}}
+      assert {:error, error} = RustyJson.encode(data, protocol: false)
+      assert {:unsupported_type, ["a", 1, 1]} = {error.reason, error.path}
+      assert error.value =~ "#PID<"
+    end
+
+    test "indexes tuples and follows sorted keys" do
+      data = %{b: {:ok, [1, <<0xFF>>]}, a: 1}
+      assert {:error, error} = RustyJson.encode(data, protocol: false, sort_keys: true)
+      assert error.path == [:b, 1, 1]
+    end
+
+    test "a bad key ends the path at its map" do
+      assert {:error, error} = RustyJson.encode(%{data: %{{1, 2} => 3}}, protocol: false)
+      assert {:invalid_key, [:data], "{1, 2}"} = {error.reason, error.path, error.value}
+    end
+
+    test "strict duplicate keys" do
+      data = [%{:a => 1, "a" => 2}]
+      assert {:error, error} = RustyJson.encode(data, maps: :strict)
+      assert {:duplicate_key, [0]} = {error.reason, error.path}
+      assert error.value in [":a", ~s("a")]
+    end
+
+    test "ordered objects" do
+      obj = RustyJson.OrderedObject.new([{"x", 1}, {"y", [<<0xFF>>]}])
+      assert {:error, %{path: [0, "y", 0], reason: :invalid_utf8}} =
+               RustyJson.encode([obj], protocol: false)
+    end
+
+    test "depth errors" do
+      deep_list = Enum.reduce(1..10, 1, fn _, acc -> [acc] end)
+      assert {:error, error} = RustyJson.encode(deep_list, max_depth: 9)
+      assert error.reason == :depth_exceeded
+      assert error.path == List.duplicate(0, 10)
+    end
+
+    test "truncates long values" do
+      assert {:error, error} = RustyJson.encode([String.duplicate("a", 200) <> <<0xFF>>])
+      assert error.value == "<<" <> String.duplicate("97, ", 10) <> "...>>"
+
+      assert {:error, error} = RustyJson.encode([{:ok, self()}], protocol: false)
+      assert error.path == [0, 1]
+    end
+
+    test "encode! raises the same error" do
+      error = assert_raise RustyJson.EncodeError, fn -> RustyJson.encode!(%{a: [<<0xFF>>]}) end
+      assert error.path == [:a, 0]
+    end
+
+    test "errors from Elixir encoders have no path" do
+      assert %RustyJson.EncodeError{reason: nil, path: [], value: nil} =
+               RustyJson.EncodeError.new({:duplicate_key, "name"})
+    end
+  end
+
   # =====================================================================
   # EncodeError.new/1
   # =====================================================================
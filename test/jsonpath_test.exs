@@ -0,0 +1,161 @@
+defmodule JsonPathTest do
+  use ExUnit.Case
+
+  @store ~s({
+    "store": {
+      "book": [
+        {"category": "reference", "author": "Rees", "title": "Sayings", "price": 8.95},
+        {"category": "fiction", "author": "Waugh", "title": "Sword", "price": 12.99},
+        {"category": "fiction", "author": "Melville", "title": "Moby Dick",
+         "isbn": "0-553-21311-3", "price": 8.99},
+        {"category": "fiction", "author": "Tolkien", "title": "The Lord",
+         "isbn": "0-395-19395-8", "price": 22.99}
+      ],
+      "bicycle": {"color": "red", "price": 399}
+    }
+  })
+
+  defp q(path, opts \\ []), do: RustyJson.query!(@store, path, opts)
+
+  describe "query/3 selectors" do
+    test "child and wildcard" do
+      assert q("$.store.bicycle.color") == ["red"]
+      assert q("$['store']['bicycle']['price']") == [399]
+      assert q("$.store.book[*].author") == ["Rees", "Waugh", "Melville", "Tolkien"]
+      assert q("$.store.bicycle.*") == ["red", 399]
+    end
+
+    test "the root" do
+      assert q("$") == [RustyJson.decode!(@store)]
+    end
+
+    test "recursive descent" do
+      assert q("$..author") == ["Rees", "Waugh", "Melville", "Tolkien"]
+      assert q("$.store..price") == [8.95, 12.99, 8.99, 22.99, 399]
+      assert q("$..book[2].title") == ["Moby Dick"]
+    end
+
+    test "recursive descent reports nested matches once each, outer first" do
+      json = ~s({"a":{"a":{"a":1}}})
+      assert RustyJson.query!(json, "$..a") == [%{"a" => %{"a" => 1}}, %{"a" => 1}, 1]
+      assert RustyJson.query!(json, "$..*", return: :paths) ==
+               ["$['a']", "$['a']['a']", "$['a']['a']['a']"]
+    end
+
+    test "indices and slices" do
+      assert q("$.store.book[0].title") == ["Sayings"]
+      assert q("$.store.book[-1].title") == ["The Lord"]
+      assert q("$.store.book[4]") == []
+      assert q("$.store.book[:2].author") == ["Rees", "Waugh"]
+      assert q("$.store.book[1:3].author") == ["Waugh", "Melville"]
+      assert q("$.store.book[-2:].author") == ["Melville", "Tolkien"]
+      assert q("$.store.book[::2].author") == ["Rees", "Melville"]
+    end
+
+    test "selectors that do not apply select nothing" do
+      assert q("$.store.bicycle[0]") == []
+      assert q("$.store.book.title") == []
+      assert q("$.nope.deeper") == []
+    end
+  end
+
+  describe "query/3 filters" do
+    test "comparisons" do
+      assert q("$.store.book[?(@.price < 10)].title") == ["Sayings", "Moby Dick"]
+      assert q("$..book[?(@.author == 'Waugh')].price") == [12.99]
+      assert q(~s($..book[?@.category != "fiction"].title)) == ["Sayings"]
+      assert q("$..book[?(20 <= @.price)].title") == ["The Lord"]
+    end
+
+    test "existence and negation" do
+      assert q("$..book[?(@.isbn)].title") == ["Moby Dick", "The Lord"]
+      assert q("$..book[?(!@.isbn)].title") == ["Sayings", "Sword"]
+    end
+
+    test "boolean combinations" do
+      assert q("$..book[?(@.isbn && @.price < 10 || @.author == 'Rees')].title") ==
+               ["Sayings", "Moby Dick"]
+
+      assert q("$..book[?(@.category == 'fiction' && (@.price < 10 || @.price > 20))].author") ==
+               ["Melville", "Tolkien"]
+    end
+
+    test "filters over object members and the current node" do
+      json = ~s({"a":{"x":1},"b":{"x":2},"c":[1,2,3]})
+      assert RustyJson.query!(json, "$[?(@.x > 1)]") == [%{"x" => 2}]
+      assert RustyJson.query!(json, "$.c[?(@ >= 2)]") == [2, 3]
+    end
+
+    test "mismatched types never compare equal" do
+      json = ~s([{"v":"1"},{"v":1},{"v":true},{"v":null},{"v":[1]}])
+      assert RustyJson.query!(json, "$[?(@.v == 1)]") == [%{"v" => 1}]
+      assert RustyJson.query!(json, "$[?(@.v == null)]") == [%{"v" => nil}]
+      assert length(RustyJson.query!(json, "$[?(@.v != 1)]")) == 4
+      assert RustyJson.query!(json, "$[?(@.v < '2')]") == [%{"v" => "1"}]
+    end
+
+    test "nested relative paths and escaped keys" do
+      json = ~s([{"m":{"k\\u00e9":[5,6]}},{"m":{"ké":[7]}}])
+      assert RustyJson.query!(json, "$[?(@.m['ké'][1] == 6)]") == [%{"m" => %{"ké" => [5, 6]}}]
+    end
+  end
+
+  describe "query/3 options" do
+    test "return: :paths" do
+      assert q("$..book[?(@.price > 20)]", return: :paths) == ["$['store']['book'][3]"]
+
+      assert RustyJson.query!(~s({"it's":{"a\\\\b":1}}), "$..*", return: :paths) ==
+               ["$['it\\'s']", "$['it\\'s']['a\\\\b']"]
+    end
+
+    test "applies decode options to matches" do
+      assert q("$.store.bicycle", keys: :atoms) == [%{color: "red", price: 399}]
+      assert [%Decimal{}] = q("$.store.book[0].price", floats: :decimals)
+    end
+
+    test "works on inputs large enough for the structural index" do
+      rows = for i <- 1..5_000, do: %{"id" => i, "active" => rem(i, 1000) == 0}
+      json = RustyJson.encode!(%{"rows" => rows})
+
+      assert RustyJson.query!(json, "$.rows[?(@.active == true)].id") ==
+               [1000, 2000, 3000, 4000, 5000]
+
+      assert RustyJson.query!(json, "$.rows[-1:]") == [%{"id" => 5000, "active" => false}]
+    end
+
+    test "honors max_bytes" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.query(@store, "$", max_bytes: 10)
+
+      assert msg =~ "max_bytes"
+    end
+  end
+
+  describe "query/3 errors" do
+    test "invalid JSON anywhere in the document" do
+      assert {:error, %RustyJson.DecodeError{position: 14}} =
+               RustyJson.query(~s({"a":1,"b":[1,}), "$.a")
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} = RustyJson.query("[1] x", "$[0]")
+      assert msg =~ "Unexpected trailing characters"
+    end
+
+    test "invalid paths" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} = RustyJson.query("[]", "a")
+      assert msg == "invalid JSONPath: Path must start with '$' at position 0"
+
+      assert_raise ArgumentError, ~r/Slice step must be a positive integer/, fn ->
+        RustyJson.query!("[]", "$[::-1]")
+      end
+
+      assert_raise ArgumentError, ~r/Expected '\]'/, fn -> RustyJson.query!("[]", "$[0") end
+    end
+
+    test "rejects an invalid :return option" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.query("[]", "$", return: :both)
+
+      assert msg =~ "invalid :return option"
+    end
+  end
+end
@@ -0,0 +1,150 @@
+defmodule NdjsonTest do
+  use ExUnit.Case
+
+  describe "decode_lines/2" do
+    test "decodes one document per line" do
+      input = ~s({"a":1}\n[1,2]\n"s"\nnull\n12.5\n)
+      assert RustyJson.decode_lines(input) == {:ok, [%{"a" => 1}, [1, 2], "s", nil, 12.5]}
+    end
+
+    test "ignores blank lines and CRLF line endings" do
+      assert RustyJson.decode_lines!("\n  \n1\r\n\r\n2\r\n\n") == [1, 2]
+      assert RustyJson.decode_lines!("") == []
+    end
+
+    test "accepts iodata" do
+      assert RustyJson.decode_lines!([~s({"a":), "1}\n", ["2\n"]]) == [%{"a" => 1}, 2]
+    end
+
+    test "every line matches decode!/1" do
+      docs = [
+        ~s({"id":1,"tags":["a","b"],"nested":{"x":[true,false]}}),
+        ~s({"id":2,"esc":"q\\"\\u00e9"}),
+        ~s([123456789012345678901234567890, -1.5e3]),
+        ~s({"long":"#{String.duplicate("x", 300)}"})
+      ]
+
+      assert RustyJson.decode_lines!(Enum.join(docs, "\n")) ==
+               Enum.map(docs, &RustyJson.decode!/1)
+    end
+
+    test "honors decode options" do
+      input = ~s({"price":1.25}\n{"price":2}\n)
+
+      assert [%{price: %Decimal{}}, %{price: 2}] =
+               RustyJson.decode_lines!(input, keys: :atoms, floats: :decimals)
+
+      assert RustyJson.decode_lines!(input, keys: &String.upcase/1) ==
+               [%{"PRICE" => 1.25}, %{"PRICE" => 2}]
+    end
+
+    test "interned keys are shared across lines" do
+      lines = for i <- 1..100, do: RustyJson.encode!(%{"id" => i, "name" => "n#{i}"})
+      input = Enum.join(lines, "\n")
+      assert RustyJson.decode_lines!(input, keys: :intern) == RustyJson.decode_lines!(input)
+    end
+
+    test "a malformed line fails the batch by default" do
+      input = "1\n[2,\n3"
+
+      assert {:error, %RustyJson.DecodeError{message: msg, position: 5, data: ^input}} =
+               RustyJson.decode_lines(input)
+
+      assert msg =~ "at position 5"
+    end
+
+    test "a document may not span lines" do
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.decode_lines("{\"a\":\n1}")
+    end
+
+    test "rejects trailing characters on a line" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} = RustyJson.decode_lines("1 2\n")
+      assert msg =~ "Unexpected trailing characters"
+    end
+
+    test "invalid_lines: :skip reports malformed lines by line number" do
+      input = ~s({"ok":1}\n{bad}\n\n[1,\n{"ok":2}\n)
+
+      assert {:ok, {[%{"ok" => 1}, %{"ok" => 2}], errors}} =
+               RustyJson.decode_lines(input, invalid_lines: :skip)
+
+      assert [{2, msg2}, {4, msg4}] = errors
+      assert msg2 =~ "at position 10"
+      assert msg4 =~ "at position 19"
+    end
+
+    test "raw newline inside a string ends the line" do
+      input = ~s(1\n"a\nb"\n2)
+
+      assert {:ok, {[1, 2], [{2, msg2}, {3, msg3}]}} =
+               RustyJson.decode_lines(input, invalid_lines: :skip)
+
+      assert msg2 =~ "Unterminated string"
+      assert msg3 =~ "Unexpected character"
+    end
+
+    test "unterminated string does not swallow the following lines" do
+      input = ~s("abc\n{"b":1}\n{"c":2})
+
+      assert {:ok, {[%{"b" => 1}, %{"c" => 2}], [{1, msg}]}} =
+               RustyJson.decode_lines(input, invalid_lines: :skip)
+
+      assert msg =~ "Unterminated string"
+    end
+
+    test "rejects an invalid :invalid_lines option" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decode_lines("1", invalid_lines: :ignore)
+
+      assert msg =~ "invalid :invalid_lines option"
+    end
+
+    test "max_bytes limits the whole input" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decode_lines("1\n2\n3\n", max_bytes: 4)
+
+      assert msg =~ "max_bytes"
+    end
+  end
+
+  describe "encode_lines/2" do
+    test "writes one compact document per line" do
+      assert RustyJson.encode_lines!([%{a: [1, 2]}, "x", nil, 1.5]) ==
+               ~s({"a":[1,2]}\n"x"\nnull\n1.5\n)
+    end
+
+    test "empty list encodes to an empty binary" do
+      assert RustyJson.encode_lines!([]) == ""
+    end
+
+    test "round-trips through decode_lines" do
+      rows = for i <- 1..50, do: %{"id" => i, "msg" => "line\nbreak #{i}"}
+      assert rows |> RustyJson.encode_lines!() |> RustyJson.decode_lines!() == rows
+    end
+
+    test "uses the Encoder protocol and encode options" do
+      assert RustyJson.encode_lines!([~D[2024-01-15], %{b: "<a>"}], escape: :html_safe) ==
+               ~s("2024-01-15"\n{"b":"\\u003ca\\u003e"}\n)
+
+      assert RustyJson.encode_lines!([%{b: 1, a: 2}], sort_keys: true) == ~s({"a":2,"b":1}\n)
+    end
+
+    test "supports gzip compression" do
+      output = RustyJson.encode_lines!([1, %{"a" => 2}], compress: :gzip)
+      assert :zlib.gunzip(output) == ~s(1\n{"a":2}\n)
+    end
+
+    test "rejects :pretty" do
+      assert {:error, %ArgumentError{}} = RustyJson.encode_lines([1], pretty: true)
+    end
+
+    test "returns an error for unencodable terms" do
+      assert {:error, _} = RustyJson.encode_lines([1, <<0xFF>>])
+    end
+
+    test "error paths start with the index of the term" do
+      assert {:error, %{path: [1, :a], reason: :invalid_utf8}} =
+               RustyJson.encode_lines([%{a: 1}, %{a: <<0xFF>>}])
+    end
+  end
+end
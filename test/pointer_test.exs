@@ -0,0 +1,172 @@
+defmodule PointerTest do
+  use ExUnit.Case
+
+  @json ~s({
+    "data": {
+      "items": [
+        {"id": 1, "tags": ["a", "b"]},
+        {"id": 2, "tags": []}
+      ],
+      "total": 2,
+      "a/b": {"m~n": "escaped"},
+      "": "empty key",
+      "dup": 1,
+      "dup": 2
+    },
+    "meta": null
+  })
+
+  describe "get_pointer/3" do
+    test "extracts nested values" do
+      assert RustyJson.get_pointer(@json, "/data/items/0/id") == {:ok, 1}
+      assert RustyJson.get_pointer(@json, "/data/items/1") == {:ok, %{"id" => 2, "tags" => []}}
+      assert RustyJson.get_pointer(@json, "/meta") == {:ok, nil}
+    end
+
+    test "the empty pointer is the whole document" do
+      assert RustyJson.get_pointer(@json, "") == RustyJson.decode(@json)
+    end
+
+    test "unescapes ~1 and ~0" do
+      assert RustyJson.get_pointer(@json, "/data/a~1b/m~0n") == {:ok, "escaped"}
+      assert RustyJson.get_pointer(~s({"~1":1,"/":2}), "/~01") == {:ok, 1}
+    end
+
+    test "matches escaped keys in the input" do
+      assert RustyJson.get_pointer(~s({"caf\\u00e9":{"x":1}}), "/café/x") == {:ok, 1}
+    end
+
+    test "supports the empty key" do
+      assert RustyJson.get_pointer(@json, "/data/") == {:ok, "empty key"}
+    end
+
+    test "accepts a segment list" do
+      assert RustyJson.get_pointer(@json, [:data, "items", 1, "id"]) == {:ok, 2}
+    end
+
+    test "returns :not_found for missing targets" do
+      for pointer <- ["/nope", "/data/items/2", "/data/items/-", "/data/items/01", "/meta/x"] do
+        assert RustyJson.get_pointer(@json, pointer) == {:error, :not_found}
+      end
+    end
+
+    test "repeated keys follow duplicate_keys:" do
+      assert RustyJson.get_pointer(@json, "/data/dup") == {:ok, 2}
+      assert RustyJson.get_pointer(@json, "/data/dup", duplicate_keys: :first) == {:ok, 1}
+
+      assert {:error, %RustyJson.DecodeError{code: :duplicate_key}} =
+               RustyJson.get_pointer(@json, "/data/dup", duplicate_keys: :error)
+
+      assert RustyJson.get_pointer(~s({"a":{"b":1},"a":{"c":2}}), "/a/b") ==
+               {:error, :not_found}
+    end
+
+    test "applies decode options to the target" do
+      assert RustyJson.get_pointer(@json, "/data/items/0", keys: :atoms) ==
+               {:ok, %{id: 1, tags: ["a", "b"]}}
+
+      assert {:ok, %Decimal{}} =
+               RustyJson.get_pointer(~s({"p":1.5}), "/p", floats: :decimals)
+    end
+
+    test "does not read past the target with duplicate_keys: :first" do
+      assert RustyJson.get_pointer(~s({"a":1,"b":}), "/a", duplicate_keys: :first) == {:ok, 1}
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.get_pointer(~s({"a":1,"b":}), "/a")
+    end
+
+    test "reports syntax errors before the target" do
+      assert {:error, %RustyJson.DecodeError{message: msg, position: 8}} =
+               RustyJson.get_pointer(~s({"a":[1,}],"b":2}), "/b")
+
+      assert msg =~ "Unexpected character"
+    end
+
+    test "rejects invalid pointers" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer(@json, "data")
+
+      assert msg =~ "invalid JSON Pointer"
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer(@json, "/a~2")
+
+      assert msg =~ "invalid escape"
+    end
+
+    test "honors max_bytes" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer(@json, "/meta", max_bytes: 10)
+
+      assert msg =~ "max_bytes"
+    end
+
+    test "works on inputs large enough for the structural index" do
+      rows = for i <- 1..5_000, do: %{"id" => i, "name" => "row #{i}", "tags" => ["x", "y"]}
+      json = RustyJson.encode!(%{"rows" => rows, "after" => true})
+
+      assert RustyJson.get_pointer(json, "/rows/4321/name") == {:ok, "row 4322"}
+      assert RustyJson.get_pointer(json, "/after") == {:ok, true}
+      assert RustyJson.get_pointer(json, "/rows", return: :length) == {:ok, 5_000}
+    end
+  end
+
+  describe "get_pointer/3 queries" do
+    test "return: :type" do
+      types =
+        for pointer <- ["", "/data/items", "/data/total", "/data/a~1b/m~0n", "/meta"] do
+          RustyJson.get_pointer!(@json, pointer, return: :type)
+        end
+
+      assert types == [:object, :array, :number, :string, :null]
+      assert RustyJson.get_pointer!("[true]", "/0", return: :type) == :boolean
+    end
+
+    test "return: :length" do
+      assert RustyJson.get_pointer(@json, "/data/items", return: :length) == {:ok, 2}
+      assert RustyJson.get_pointer(@json, "/data/items/1/tags", return: :length) == {:ok, 0}
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer(@json, "/data", return: :length)
+
+      assert msg =~ "Expected array"
+    end
+
+    test "return: :key_count counts duplicate keys" do
+      assert RustyJson.get_pointer(@json, "/data", return: :key_count) == {:ok, 6}
+      assert RustyJson.get_pointer("{}", "", return: :key_count) == {:ok, 0}
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer(@json, "/data/items", return: :key_count)
+
+      assert msg =~ "Expected object"
+    end
+
+    test "queries validate the skipped container" do
+      assert {:error, %RustyJson.DecodeError{}} =
+               RustyJson.get_pointer(~s({"a":[1,,2]}), "/a", return: :length)
+    end
+
+    test "rejects an invalid :return option" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.get_pointer("[]", "", return: :size)
+
+      assert msg =~ "invalid :return option"
+    end
+  end
+
+  describe "get_pointer!/3" do
+    test "returns the value directly" do
+      assert RustyJson.get_pointer!(@json, "/data/total") == 2
+    end
+
+    test "raises when the pointer does not resolve" do
+      assert_raise RustyJson.DecodeError, ~s(JSON Pointer "/missing" not found), fn ->
+        RustyJson.get_pointer!(@json, "/missing")
+      end
+    end
+
+    test "raises on invalid input" do
+      assert_raise RustyJson.DecodeError, fn -> RustyJson.get_pointer!("[1,", "/1") end
+    end
+  end
+end
@@ -0,0 +1,114 @@
+defmodule ProjectionTest do
+  use ExUnit.Case
+
+  @json ~s({
+    "data": [
+      {"id": 1, "name": "a", "debug": {"trace": [1, 2]}, "tags": ["x"]},
+      {"id": 2, "name": "b", "debug": null, "tags": []}
+    ],
+    "meta": {"page": 1, "debug": true},
+    "debug": "top"
+  })
+
+  describe "only:" do
+    test "keeps the listed paths through wildcards" do
+      assert RustyJson.decode!(@json, only: [["data", "*", "id"], ["data", "*", "name"]]) ==
+               %{"data" => [%{"id" => 1, "name" => "a"}, %{"id" => 2, "name" => "b"}]}
+    end
+
+    test "keeps the value at the end of a path whole" do
+      assert RustyJson.decode!(@json, only: [["meta"]]) ==
+               %{"meta" => %{"page" => 1, "debug" => true}}
+    end
+
+    test "keeps scalars found where a path expects a container" do
+      assert RustyJson.decode!(~s({"a":1,"b":2}), only: [["a", "x"]]) == %{"a" => 1}
+    end
+
+    test "elements off every path leave an empty array" do
+      assert RustyJson.decode!(~s([{"a":1},{"a":2}]), only: [["b"]]) == []
+      assert RustyJson.decode!(~s({"a":[1,2]}), only: [["a", "x"]]) == %{"a" => []}
+    end
+
+    test "accepts atom steps and matches escaped keys" do
+      assert RustyJson.decode!(~s({"caf\\u00e9":1,"other":2}), only: [[:café]]) ==
+               %{"café" => 1}
+    end
+
+    test "works across arrays of differently shaped objects" do
+      rows =
+        for i <- 1..2_000 do
+          if rem(i, 3) == 0,
+            do: %{"id" => i, "extra" => [i]},
+            else: %{"id" => i, "name" => "row #{i}", "blob" => String.duplicate("x", 50)}
+        end
+
+      json = RustyJson.encode!(%{"rows" => rows})
+      %{"rows" => decoded} = RustyJson.decode!(json, only: [["rows", "*", "id"]])
+
+      assert decoded == for(i <- 1..2_000, do: %{"id" => i})
+    end
+  end
+
+  describe "except:" do
+    test "drops the listed paths at any depth the wildcard reaches" do
+      assert RustyJson.decode!(@json, except: [["*", "debug"], ["data", "*", "debug"]]) ==
+               %{
+                 "data" => [
+                   %{"id" => 1, "name" => "a", "tags" => ["x"]},
+                   %{"id" => 2, "name" => "b", "tags" => []}
+                 ],
+                 "meta" => %{"page" => 1},
+                 "debug" => "top"
+               }
+    end
+
+    test "dropping array elements leaves an empty array" do
+      assert RustyJson.decode!(~s({"a":[1,2],"b":3}), except: [["a", "*"]]) ==
+               %{"a" => [], "b" => 3}
+    end
+  end
+
+  describe "with other options" do
+    test "applies to each value returned by the lookup APIs" do
+      assert RustyJson.get_pointer(@json, "/meta", only: [["page"]]) == {:ok, %{"page" => 1}}
+      assert RustyJson.query!(@json, "$.data[*]", only: [["id"]]) == [%{"id" => 1}, %{"id" => 2}]
+    end
+
+    test "combines with key and object options" do
+      assert RustyJson.decode!(@json, only: [["meta", "page"]], keys: :atoms) ==
+               %{meta: %{page: 1}}
+
+      assert %RustyJson.OrderedObject{values: [{"meta", _}]} =
+               RustyJson.decode!(@json, only: [["meta"]], objects: :ordered_objects)
+    end
+
+    test "skipped members are still checked for syntax and duplicates" do
+      assert {:error, %RustyJson.DecodeError{}} =
+               RustyJson.decode(~s({"a":1,"b":[1,}), only: [["a"]])
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decode(~s({"a":1,"b":2,"b":3}), only: [["a"]], duplicate_keys: :error)
+
+      assert msg =~ "Duplicate key"
+    end
+  end
+
+  describe "invalid options" do
+    test "rejects malformed paths" do
+      assert_raise ArgumentError, ~r/expected a non-empty list of keys/, fn ->
+        RustyJson.decode!("{}", only: [[]])
+      end
+
+      assert_raise ArgumentError, ~r/invalid :except path step 1/, fn ->
+        RustyJson.decode!("{}", except: [["a", 1]])
+      end
+    end
+
+    test "rejects :only together with :except" do
+      assert_raise ArgumentError, ~r/cannot be combined/, fn ->
+        RustyJson.decode!("{}", only: [["a"]], except: [["b"]])
+      end
+    end
+  end
+end
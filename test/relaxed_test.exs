@@ -0,0 +1,105 @@
+defmodule RelaxedTest do
+  use ExUnit.Case
+
+  @config ~s"""
+  // service config
+  {
+    name: 'api',  /* single quotes */
+    port: 0x1F90,
+    weights: [+1, -0x10, 2.5,],
+    'it\\'s': "ok",
+    limits: {max: Infinity, min: -Infinity, avg: NaN,},
+  }
+  """
+
+  describe "relaxed: true" do
+    test "accepts every extension" do
+      assert RustyJson.decode!(@config, relaxed: true) == %{
+               "name" => "api",
+               "port" => 8080,
+               "weights" => [1, -16, 2.5],
+               "it's" => "ok",
+               "limits" => %{"max" => :infinity, "min" => :neg_infinity, "avg" => :nan}
+             }
+    end
+
+    test "still decodes strict JSON" do
+      json = ~s({"a": [1, "two", {"b": null}], "c": true})
+      assert RustyJson.decode!(json, relaxed: true) == RustyJson.decode!(json)
+    end
+
+    test "comments may appear anywhere whitespace can" do
+      assert RustyJson.decode!("/* a */ [1, // b\n 2 /* c */] // d", relaxed: true) == [1, 2]
+    end
+
+    test "hex numbers beyond 64 bits become big integers" do
+      assert RustyJson.decode!("[0xFFFFFFFFFFFFFFFF, 0x10000000000000000]", relaxed: true) ==
+               [18_446_744_073_709_551_615, 18_446_744_073_709_551_616]
+    end
+
+    test "keeps strict errors for malformed input" do
+      for json <- ["[1,,]", "{a 1}", "[0x]", "/* open", "'open", "[+]"] do
+        assert {:error, %RustyJson.DecodeError{}} = RustyJson.decode(json, relaxed: true)
+      end
+    end
+  end
+
+  describe "relaxed: extensions" do
+    test "only the listed extensions are accepted" do
+      assert RustyJson.decode!("[1,]", relaxed: [:trailing_commas]) == [1]
+
+      assert {:error, %RustyJson.DecodeError{}} =
+               RustyJson.decode("[1,] // x", relaxed: [:trailing_commas])
+
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.decode("{a: 1}", relaxed: [:comments])
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.decode("NaN", relaxed: [:hex_numbers])
+    end
+
+    test "rejects unknown extensions" do
+      assert_raise ArgumentError, ~r/invalid :relaxed/, fn ->
+        RustyJson.decode!("{}", relaxed: [:octal])
+      end
+    end
+  end
+
+  describe "non_finite:" do
+    test "maps to nil" do
+      assert RustyJson.decode!("[NaN, -Infinity]", relaxed: true, non_finite: nil) == [nil, nil]
+    end
+
+    test "overrides individual atoms" do
+      opts = [relaxed: true, non_finite: [nan: :undefined]]
+      assert RustyJson.decode!("[NaN, +Infinity]", opts) == [:undefined, :infinity]
+    end
+
+    test "rejects invalid values" do
+      assert_raise ArgumentError, ~r/invalid :non_finite/, fn ->
+        RustyJson.decode!("NaN", relaxed: true, non_finite: [nan: "NaN"])
+      end
+    end
+  end
+
+  describe "with other options" do
+    test "combines with projections and key options" do
+      assert RustyJson.decode!(@config, relaxed: true, only: [["limits", "max"]], keys: :atoms) ==
+               %{limits: %{max: :infinity}}
+    end
+
+    test "rejects duplicate keys regardless of quoting" do
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decode(~s({a: 1, 'a': 2}), relaxed: true, duplicate_keys: :error)
+
+      assert msg =~ "Duplicate key"
+    end
+
+    test "is rejected by the other decode APIs" do
+      assert_raise ArgumentError, ~r/not supported by get_pointer/, fn ->
+        RustyJson.get_pointer("{a: 1}", "/a", relaxed: true)
+      end
+
+      assert_raise ArgumentError, ~r/not supported by validate/, fn ->
+        RustyJson.validate("{a: 1}", relaxed: true)
+      end
+    end
+  end
+end
@@ -12,6 +12,125 @@ defmodule SafetyTest do
       deep_list = Enum.reduce(1..128, 1, fn _, acc -> [acc] end)
       assert {:ok, _} = RustyJson.encode(deep_list)
     end
+
+    test "256 levels encode on either scheduler and 257 fail" do
+      for wrap <- [&[&1], &%{"a" => &1}],
+          scheduler <- [:normal, :dirty],
+          sort_keys <- [false, true] do
+        opts = [max_depth: 256, scheduler: scheduler, sort_keys: sort_keys]
+        deep = Enum.reduce(1..256, 1, fn _, acc -> wrap.(acc) end)
+        assert {:ok, _} = RustyJson.encode(deep, opts)
+
+        assert {:error, %RustyJson.EncodeError{reason: :depth_exceeded}} =
+                 RustyJson.encode(wrap.(deep), opts)
+      end
+    end
+
+    test "256 levels decode on either scheduler and 257 fail" do
+      for open <- ["[", ~s({"a":)], threshold <- [0, 1] do
+        close = if open == "[", do: "]", else: "}"
+        json = fn n -> String.duplicate(open, n) <> "1" <> String.duplicate(close, n) end
+        opts = [max_depth: 256, dirty_threshold: threshold]
+
+        assert {:ok, _} = RustyJson.decode(json.(256), opts)
+
+        assert {:error, %RustyJson.DecodeError{code: :depth_exceeded}} =
+                 RustyJson.decode(json.(257), opts)
+      end
+    end
+
+    test "encoder max_depth lowers the limit" do
+      deep_list = Enum.reduce(1..10, 1, fn _, acc -> [acc] end)
+      assert {:ok, _} = RustyJson.encode(deep_list, max_depth: 10)
+
+      assert {:error, %RustyJson.EncodeError{message: "Nesting depth exceeds maximum of 9"}} =
+               RustyJson.encode(deep_list, max_depth: 9)
+    end
+
+    test "decoder max_depth raises or lowers the limit" do
+      json = String.duplicate("[", 200) <> String.duplicate("]", 200)
+      assert {:ok, _} = RustyJson.decode(json, max_depth: 200)
+
+      assert {:error, %RustyJson.DecodeError{message: msg, position: 36}} =
+               RustyJson.decode(~s({"a":) <> json <> "}", max_depth: 32)
+
+      assert msg == "Nesting depth exceeds maximum of 32 at position 36"
+    end
+
+    test "max_depth applies to the streaming and lookup APIs" do
+      json = ~s({"a":[[[1]]]})
+
+      assert {:error, %RustyJson.DecodeError{}} =
+               RustyJson.get_pointer(json, "/a/0/0/0", max_depth: 3)
+
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.validate(json, max_depth: 3)
+      assert {:ok, _} = RustyJson.validate(json, max_depth: 4)
+
+      assert_raise RustyJson.DecodeError, ~r/maximum of 1/, fn ->
+        ["[[1]]"] |> RustyJson.decode_stream(max_depth: 1) |> Enum.to_list()
+      end
+    end
+
+    test "max_depth is bounded by the native stack budget" do
+      assert_raise ArgumentError, ~r/expected an integer from 1 to 256/, fn ->
+        RustyJson.decode!("[]", max_depth: 257)
+      end
+
+      assert_raise ArgumentError, ~r/expected an integer from 1 to 256/, fn ->
+        RustyJson.encode!([], max_depth: 257)
+      end
+
+      assert_raise ArgumentError, ~r/invalid :max_depth/, fn ->
+        RustyJson.decode!("[]", max_depth: 0)
+      end
+    end
+  end
+
+  describe "decode quotas" do
+    test "each quota fails with its own message and position" do
+      for {json, opts, message, position} <- [
+            {~s(["abcdef"]), [max_string_bytes: 5], "string exceeds 5 byte limit", 1},
+            {"[1,2,3,4]", [max_array_elements: 3], "array exceeds 3 element limit", 7},
+            {~s({"a":1,"b":2}), [max_object_keys: 1], "object exceeds 1 key limit", 7},
+            {"[[1,2],[3]]", [max_total_values: 4], "document exceeds 4 value limit", 7},
+            {"[1.2345]", [max_fraction_digits: 3], "fraction exceeds 3 digit limit", 1},
+            {"1e1000", [max_exponent_digits: 3], "exponent exceeds 3 digit limit", 0}
+          ] do
+        assert {:error, %RustyJson.DecodeError{position: ^position} = error} =
+                 RustyJson.decode(json, opts)
+
+        assert error.message == "#{message} at position #{position}"
+        assert {:error, ^message, ^position} = RustyJson.validate(json, opts)
+      end
+    end
+
+    test "inputs at the limits decode" do
+      opts = [
+        max_string_bytes: 5,
+        max_array_elements: 3,
+        max_object_keys: 1,
+        max_total_values: 6,
+        max_fraction_digits: 2,
+        max_exponent_digits: 2
+      ]
+
+      assert RustyJson.decode!(~s([{"abcde":1.25e10}, 2, 3]), opts) ==
+               [%{"abcde" => 1.25e10}, 2, 3]
+    end
+
+    test "applies to same-shaped objects and skipped members" do
+      assert {:error, %RustyJson.DecodeError{position: 20}} =
+               RustyJson.decode(~s([{"id":1},{"id":2},{"id":3}]), max_total_values: 6)
+
+      assert {:error, %RustyJson.DecodeError{position: 16}} =
+               RustyJson.decode(~s({"a":1,"b":[1,2,3]}), only: [["a"]], max_array_elements: 2)
+    end
+
+    test "rejects invalid quotas" do
+      assert_raise ArgumentError, ~r/invalid :max_object_keys option -1/, fn ->
+        RustyJson.decode!("{}", max_object_keys: -1)
+      end
+    end
   end
 
   describe "large integers" do
@@ -110,6 +229,60 @@ defmodule SafetyTest do
       assert RustyJson.decode(~s([{"a":1},{"a":2}]), duplicate_keys: :error) ==
                {:ok, [%{"a" => 1}, %{"a" => 2}]}
     end
+
+    test "duplicate_keys: :error names the key at its second occurrence" do
+      assert {:error, %RustyJson.DecodeError{message: msg, position: 14}} =
+               RustyJson.decode(~s({"id":1,"x":2,"id":3}), duplicate_keys: :error)
+
+      assert msg == ~s(Duplicate key "id" in object at position 14)
+    end
+
+    test "duplicate_keys: :error catches keys that only differ in escapes" do
+      assert {:error, %RustyJson.DecodeError{message: msg, position: 0}} =
+               RustyJson.decode(~s({"a":1,"\\u0061":2}), duplicate_keys: :error)
+
+      assert msg =~ ~s(Duplicate key "a")
+    end
+
+    test "duplicate_keys: :first keeps the first value" do
+      assert RustyJson.decode!(~s({"a":1,"b":2,"a":3}), duplicate_keys: :first) ==
+               %{"a" => 1, "b" => 2}
+    end
+
+    test "duplicate_keys: :collect merges repeated values into a list" do
+      json = ~s({"tag":"x","id":1,"tag":"y","tag":["z"]})
+
+      assert RustyJson.decode!(json, duplicate_keys: :collect) ==
+               %{"tag" => ["x", "y", ["z"]], "id" => 1}
+    end
+
+    test "policies apply to arrays of same-shaped objects and to atom keys" do
+      json = ~s([{"a":1,"a":2},{"a":3,"a":4}])
+
+      assert RustyJson.decode!(json, duplicate_keys: :first, keys: :atoms) ==
+               [%{a: 1}, %{a: 3}]
+
+      assert RustyJson.decode!(json, duplicate_keys: :collect, keys: :intern) ==
+               [%{"a" => [1, 2]}, %{"a" => [3, 4]}]
+    end
+
+    test "policies apply to ordered objects" do
+      json = ~s({"b":1,"a":2,"b":3})
+      decode = &RustyJson.decode!(json, objects: :ordered_objects, duplicate_keys: &1)
+
+      assert decode.(:last).values == [{"b", 1}, {"a", 2}, {"b", 3}]
+      assert decode.(:first).values == [{"b", 1}, {"a", 2}]
+      assert decode.(:collect).values == [{"b", [1, 3]}, {"a", 2}]
+
+      assert {:error, %RustyJson.DecodeError{position: 13}} =
+               RustyJson.decode(json, objects: :ordered_objects, duplicate_keys: :error)
+    end
+
+    test "rejects unknown policies" do
+      assert_raise ArgumentError, fn ->
+        RustyJson.decode!("{}", duplicate_keys: :merge)
+      end
+    end
   end
 
   describe "UTF-8 string validation" do
@@ -165,6 +338,64 @@ defmodule SafetyTest do
     end
   end
 
+  describe "invalid_unicode" do
+    test ":error rejects lone surrogates" do
+      for json <- [~S(["\ud800"]), ~S(["\udc00x"]), ~S(["\ud800A"])] do
+        assert {:error, %RustyJson.DecodeError{message: msg}} = RustyJson.decode(json)
+        assert msg =~ "Lone surrogate"
+      end
+    end
+
+    test ":replace writes U+FFFD for lone surrogates and invalid bytes" do
+      json = <<~S({"a\ud800": ["x\udc00y", "), 0xFF, 0xFE, "ok", 0xC3, 34, "]}">>
+
+      assert RustyJson.decode!(json, invalid_unicode: :replace) ==
+               %{"a�" => ["x�y", "��ok�"]}
+    end
+
+    test ":replace keeps valid surrogate pairs" do
+      assert RustyJson.decode!(~S(["😀"]), invalid_unicode: :replace) == ["😀"]
+    end
+
+    test ":keep leaves invalid bytes and writes surrogates as WTF-8" do
+      json = <<~S(["\ud800", "), 0xFF, 34, "]">>
+
+      assert RustyJson.decode!(json, invalid_unicode: :keep) ==
+               [<<0xED, 0xA0, 0x80>>, <<0xFF>>]
+    end
+
+    test "decode_with_stats/2 counts replacements" do
+      json = <<~S([{"k": "\ud800\udbff"}, "), 34, 0xFF, 34, "]">>
+
+      assert {:ok, [%{"k" => "��"}, "�"], %{replacements: 3}} =
+               RustyJson.decode_with_stats(json, invalid_unicode: :replace)
+
+      assert {:ok, %{"a" => 1}, %{replacements: 0}} =
+               RustyJson.decode_with_stats(~s({"a": 1}), keys: :strings)
+    end
+
+    test "decode_with_stats/2 returns decode errors" do
+      assert {:error, %RustyJson.DecodeError{position: 1}} =
+               RustyJson.decode_with_stats(~S(["\ud800"]))
+    end
+
+    test "applies to relaxed strings, validation and streaming" do
+      assert RustyJson.decode!(~S(['\ud800']), relaxed: true, invalid_unicode: :replace) ==
+               ["�"]
+
+      assert {:ok, _} = RustyJson.validate(~S(["\ud800"]), invalid_unicode: :replace)
+
+      assert RustyJson.decode_lines!(<<34, 0xFF, 34, ?\n>>, invalid_unicode: :replace) ==
+               ["�"]
+    end
+
+    test "rejects unknown modes" do
+      assert_raise ArgumentError, ~r/invalid :invalid_unicode/, fn ->
+        RustyJson.decode!("[]", invalid_unicode: :drop)
+      end
+    end
+  end
+
   describe "scheduler dispatch" do
     # Functional correctness only — no timing/scheduler assertions.
     # These verify the dirty NIF stubs load and produce correct results.
@@ -177,6 +408,12 @@ defmodule SafetyTest do
       assert {:ok, %{"a" => 1}} = RustyJson.decode(~s({"a":1}), dirty_threshold: 0)
     end
 
+    test "iterator_next works with dirty_threshold forcing dirty NIF" do
+      iterator = RustyJson.iterator_new(~s([1,2,3]), dirty_threshold: 1)
+      assert {:cont, [1, 2]} = RustyJson.iterator_next(iterator, 2)
+      assert {:done, [3]} = RustyJson.iterator_next(iterator, 2)
+    end
+
     test "encode works with scheduler: :dirty" do
       assert {:ok, _} = RustyJson.encode(%{a: 1}, scheduler: :dirty)
     end
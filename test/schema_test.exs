@@ -0,0 +1,394 @@
+defmodule SchemaTest do
+  use ExUnit.Case, async: true
+
+  defmodule Address do
+    defstruct city: nil, zip: "00000"
+  end
+
+  defmodule Post do
+    defstruct title: nil, tags: []
+  end
+
+  defmodule User do
+    defstruct id: nil, name: "anonymous", address: nil, posts: [], role: :member
+  end
+
+  defmodule OrderCreated do
+    defstruct [:id, :total]
+  end
+
+  defmodule OrderShipped do
+    defstruct [:order_id, carrier: "post"]
+  end
+
+  defmodule Account do
+    defstruct [:id, :name, :role, :age, :tags, :score]
+  end
+
+  @post {Post, %{"title" => :title, "tags" => :tags}}
+
+  @account {Account,
+            %{
+              "id" => {:id, :integer, required: true, coerce: true},
+              "name" => {:name, :string, min_length: 1, max_length: 5},
+              "role" => {:role, :string, in: ["admin", "member"]},
+              "age" => {:age, :integer, min: 0, max: 150, nullable: true},
+              "tags" => {:tags, [{:string, max_length: 3}], max_length: 2},
+              "score" => {:score, :float, coerce: true}
+            }}
+
+  @events {:one_of, "type",
+           %{
+             "order.created" => OrderCreated,
+             "order.shipped" => {OrderShipped, %{"id" => :order_id, "carrier" => :carrier}}
+           }}
+
+  @user {User,
+         %{
+           "id" => :id,
+           "name" => :name,
+           "address" => {:address, {Address, %{"city" => :city, "zip" => :zip}}},
+           "posts" => {:posts, [@post]}
+         }}
+
+  describe "decode with schema:" do
+    test "builds nested structs, drops unknown keys and keeps defaults" do
+      json = ~s({"id":1,"extra":{"deep":[1,2]},"address":{"city":"Paris"},
+                 "posts":[{"title":"a","tags":["x"]},{"title":"b","draft":true}]})
+
+      assert RustyJson.decode!(json, schema: @user) == %User{
+               id: 1,
+               name: "anonymous",
+               address: %Address{city: "Paris", zip: "00000"},
+               posts: [%Post{title: "a", tags: ["x"]}, %Post{title: "b", tags: []}],
+               role: :member
+             }
+    end
+
+    test "a list schema decodes a root array of structs in any key order" do
+      json = ~s([{"title":"a","tags":[]},{"tags":["t"],"title":"b"},{"x":1},{"title":"c"}])
+
+      assert RustyJson.decode!(json, schema: [@post]) == [
+               %Post{title: "a", tags: []},
+               %Post{title: "b", tags: ["t"]},
+               %Post{},
+               %Post{title: "c"}
+             ]
+    end
+
+    test "supplied defaults fill missing fields" do
+      schema = {User, %{"id" => :id}, role: :guest, name: "guest"}
+
+      assert RustyJson.decode!(~s({"id":7}), schema: schema) ==
+               %User{id: 7, name: "guest", role: :guest}
+    end
+
+    test "values of other JSON types decode as usual" do
+      json = ~s({"address":"unknown","posts":null,"name":{"first":"Ada"}})
+
+      assert %User{address: "unknown", posts: nil, name: %{"first" => "Ada"}} =
+               RustyJson.decode!(json, schema: @user)
+
+      assert RustyJson.decode!(~s([1, "x"]), schema: @user) == [1, "x"]
+      assert RustyJson.decode!(~s({"id":1}), schema: [@post]) == %{"id" => 1}
+    end
+
+    test "keys are matched as written, escapes decoded" do
+      json = ~s({"na\\u006de":"Ada","Name":"ignored"})
+      assert %User{name: "Ada"} = RustyJson.decode!(json, schema: @user)
+
+      assert %User{name: "Ada"} =
+               RustyJson.decode!(~s({"name":"Ada"}), schema: @user, key_case: :camel_case)
+    end
+
+    test "repeated keys follow duplicate_keys:" do
+      json = ~s({"id":1,"id":2})
+      assert %User{id: 2} = RustyJson.decode!(json, schema: @user)
+      assert %User{id: 1} = RustyJson.decode!(json, schema: @user, duplicate_keys: :first)
+
+      assert {:error, %RustyJson.DecodeError{message: message}} =
+               RustyJson.decode(json, schema: @user, duplicate_keys: :error)
+
+      assert message =~ "id"
+    end
+
+    test "other decode options apply to field values" do
+      json = ~s({"id":"2024-01-15","name":{"firstName":"Ada"}})
+      opts = [schema: @user, datetimes: ["id"], keys: :atoms, key_case: :snake_case]
+
+      assert %User{id: ~D[2024-01-15], name: %{first_name: "Ada"}} =
+               RustyJson.decode!(json, opts)
+    end
+
+    test "decode_all/2 builds one struct per document" do
+      assert RustyJson.decode_all!(~s({"title":"a"} {"title":"b"}), schema: @post) ==
+               [%Post{title: "a"}, %Post{title: "b"}]
+    end
+
+    test "errors report the path inside the struct" do
+      assert {:error, %RustyJson.DecodeError{path: "$.posts[1].title"}} =
+               RustyJson.decode(~s({"posts":[{"title":"a"},{"title":]}), schema: @user)
+    end
+
+    test "invalid schemas raise" do
+      for schema <- [
+            {String, %{}},
+            {User, %{"id" => :missing}},
+            {User, %{"id" => :__struct__}},
+            {User, %{"id" => :id}, missing: 1},
+            {User, %{1 => :id}},
+            {User, [:id]}
+          ] do
+        assert_raise ArgumentError, fn -> RustyJson.decode!("{}", schema: schema) end
+      end
+    end
+
+    test "a schema the NIF cannot compile raises instead of decoding unchecked" do
+      for resource <- [:bogus, {:struct, :bogus}] do
+        assert_raise ArgumentError, fn ->
+          RustyJson.decode!("{}", schema: %RustyJson.Schema{resource: resource})
+        end
+      end
+
+      assert {:error, %RustyJson.DecodeError{}} =
+               RustyJson.decode("{}", schema: %RustyJson.Schema{resource: :bogus})
+    end
+
+    test "a bare module reads each field from the key of the same name" do
+      assert RustyJson.decode!(~s({"title":"a","extra":1}), schema: Post) == %Post{title: "a"}
+    end
+
+    test "invalid union specs raise" do
+      for schema <- [
+            {:one_of, "type", [{"a", Post}]},
+            {:one_of, 1, %{"a" => Post}},
+            {:one_of, "type", %{nil => Post}},
+            {:one_of, "type", %{"a" => :missing}},
+            {:one_of, "type", %{"a" => Post}, unknown: :skip}
+          ] do
+        assert_raise ArgumentError, fn -> RustyJson.decode!("{}", schema: schema) end
+      end
+    end
+
+    test "cannot be combined with projections, relaxed syntax or partial decoding" do
+      for opts <- [
+            [only: [["id"]]],
+            [except: [["id"]]],
+            [relaxed: true],
+            [relaxed: [:comments]],
+            [duplicate_keys: :collect]
+          ] do
+        assert_raise ArgumentError, ~r/cannot be combined/, fn ->
+          RustyJson.decode!("{}", [schema: @user] ++ opts)
+        end
+      end
+
+      assert_raise ArgumentError, ~r/not supported by get_pointer/, fn ->
+        RustyJson.get_pointer("{}", "/id", schema: @user)
+      end
+    end
+  end
+
+  describe "decode with a schema: union" do
+    test "the discriminator selects the struct wherever it appears" do
+      json = ~s([{"type":"order.created","id":1,"total":9.5},
+                 {"id":2,"carrier":"ups","type":"order.shipped"},
+                 {"id":3,"type":"order.created"}])
+
+      assert RustyJson.decode!(json, schema: [@events]) == [
+               %OrderCreated{id: 1, total: 9.5},
+               %OrderShipped{order_id: 2, carrier: "ups"},
+               %OrderCreated{id: 3}
+             ]
+    end
+
+    test "the discriminator key and value are matched after decoding escapes" do
+      json = ~s({"id":1,"t\\u0079pe":"order\\u002ecreated"})
+      assert RustyJson.decode!(json, schema: @events) == %OrderCreated{id: 1}
+    end
+
+    test "a repeated discriminator follows duplicate_keys:" do
+      json = ~s({"type":"order.created","id":1,"type":"order.shipped"})
+      assert RustyJson.decode!(json, schema: @events) == %OrderShipped{order_id: 1}
+
+      assert RustyJson.decode!(json, schema: @events, duplicate_keys: :first) ==
+               %OrderCreated{id: 1}
+
+      assert {:error, %RustyJson.DecodeError{code: :duplicate_key, message: message}} =
+               RustyJson.decode(json, schema: @events, duplicate_keys: :error)
+
+      assert message =~ ~s(Duplicate key "type")
+    end
+
+    test "nested unions each find their own discriminator" do
+      inner = {:one_of, "type", %{"post" => @post}}
+      outer = {:one_of, "type", %{"user" => {User, %{"posts" => {:posts, [inner]}}}}}
+
+      json = ~s({"posts":[{"title":"a","type":"post"},{"type":"note","title":"b"}],
+                 "note":{"type":"post"},"type":"user"})
+
+      assert RustyJson.decode!(json, schema: outer) ==
+               %User{posts: [%Post{title: "a"}, %{"type" => "note", "title" => "b"}]}
+    end
+
+    test "unknown, missing and non-string discriminators decode as maps" do
+      for json <- [~s({"type":"order.paid","id":1}), ~s({"id":1}), ~s({"type":1,"id":1})] do
+        assert %{"id" => 1} = RustyJson.decode!(json, schema: @events)
+      end
+    end
+
+    test "unknown: :error rejects them" do
+      {:one_of, key, variants} = @events
+      schema = {:one_of, key, variants, unknown: :error}
+
+      assert {:error, %RustyJson.DecodeError{code: :unknown_discriminator} = error} =
+               RustyJson.decode(~s({"items":[{"id":1,"type":"order.paid"}]}),
+                 schema: {Post, %{"items" => {:tags, [schema]}}}
+               )
+
+      assert error.message =~ ~s(Unknown discriminator "order.paid" for key "type")
+      assert error.path == "$.items[0]"
+
+      assert {:error, %RustyJson.DecodeError{code: :unknown_discriminator} = error} =
+               RustyJson.decode(~s({"id":1}), schema: schema)
+
+      assert error.message =~ ~s(Missing discriminator key "type")
+    end
+
+    test "unions nest inside structs" do
+      schema = {Post, %{"title" => :title, "tags" => {:tags, [@events]}}}
+      json = ~s({"title":"log","tags":[{"type":"order.shipped","id":7}, "note"]})
+
+      assert RustyJson.decode!(json, schema: schema) ==
+               %Post{title: "log", tags: [%OrderShipped{order_id: 7}, "note"]}
+    end
+
+    test "syntax errors inside the object keep their path" do
+      assert {:error, %RustyJson.DecodeError{path: "$[0].id"}} =
+               RustyJson.decode(~s([{"type":"order.created","id":]), schema: [@events])
+    end
+  end
+
+  describe "decode with typed schema fields" do
+    test "valid values decode, coerced where asked" do
+      json = ~s({"id":"42","name":"Ada","role":"admin","age":null,"tags":["a","bc"],"score":3})
+
+      assert RustyJson.decode!(json, schema: @account) ==
+               %Account{id: 42, name: "Ada", role: "admin", tags: ["a", "bc"], score: 3.0}
+    end
+
+    test "every violation is reported with its path" do
+      json = ~s({"name":"","role":"root","age":-1,"tags":["abcd",1,"x"],"score":"x"})
+
+      assert {:error, %RustyJson.DecodeError{code: :schema_violation} = error} =
+               RustyJson.decode(json, schema: @account)
+
+      assert Enum.map(error.violations, &{&1.path, &1.message}) == [
+               {"$.name", "length must be at least 1"},
+               {"$.role", ~s(must be one of "admin", "member")},
+               {"$.age", "must be at least 0"},
+               {"$.tags[0]", "length must be at most 3"},
+               {"$.tags[1]", "expected string, got integer"},
+               {"$.tags", "length must be at most 2"},
+               {"$.score", "expected float, got string"},
+               {"$", ~s(missing required key "id")}
+             ]
+
+      assert error.path == "$.name"
+      assert error.position == 8
+      assert error.message =~ "$.tags[1]: expected string, got integer"
+    end
+
+    test "nested structs report their own paths" do
+      schema =
+        {Post, %{"tags" => {:tags, [{Post, %{"title" => {:title, :string, required: true}}}]}}}
+
+      assert {:error, %RustyJson.DecodeError{violations: violations}} =
+               RustyJson.decode(~s({"tags":[{"title":"a"},{},{"title":1}]}), schema: schema)
+
+      assert Enum.map(violations, &{&1.path, &1.message}) == [
+               {"$.tags[1]", ~s(missing required key "title")},
+               {"$.tags[2].title", "expected string, got integer"}
+             ]
+    end
+
+    test "struct specs with constraints require an object" do
+      schema = {User, %{"address" => {:address, {Address, %{"city" => :city}}, required: true}}}
+
+      assert %User{address: %Address{city: "Paris"}} =
+               RustyJson.decode!(~s({"address":{"city":"Paris"}}), schema: schema)
+
+      for {json, message} <- [
+            {~s({"address":"Paris"}), "expected object, got string"},
+            {~s({"address":null}), "expected object, got null"},
+            {~s({}), ~s(missing required key "address")}
+          ] do
+        assert {:error, %RustyJson.DecodeError{violations: [%{message: ^message}]}} =
+                 RustyJson.decode(json, schema: schema)
+      end
+    end
+
+    test "a list of types checks a root array" do
+      assert RustyJson.decode!(~s([1, "2"]), schema: [{:integer, coerce: true}]) == [1, 2]
+
+      assert {:error, %RustyJson.DecodeError{violations: [%{path: "$[1]"}]}} =
+               RustyJson.decode(~s([1, "2"]), schema: [:integer])
+    end
+
+    test "decode_all/2 checks each document" do
+      error =
+        assert_raise RustyJson.DecodeError, fn ->
+          RustyJson.decode_all!(~s({"id":1} {"id":"x"}), schema: @account)
+        end
+
+      assert [%{path: "$.id", message: "expected integer, got string"}] = error.violations
+    end
+
+    test "decode_lines/2 checks each line" do
+      input = ~s({"id":1}\n{"id":"x"}\n{"id":2}\n{"id":[]}\n)
+
+      error =
+        assert_raise RustyJson.DecodeError, fn ->
+          RustyJson.decode_lines!(input, schema: @account)
+        end
+
+      assert [%{path: "$.id", message: "expected integer, got string"}] = error.violations
+
+      assert {[%Account{id: 1}, %Account{id: 2}], [{2, msg2}, {4, msg4}]} =
+               RustyJson.decode_lines!(input, schema: @account, invalid_lines: :skip)
+
+      assert msg2 == "Schema validation failed: $.id: expected integer, got string"
+      assert msg4 == "Schema validation failed: $.id: expected integer, got array"
+    end
+
+    test "compile!/1 builds a schema reused across calls" do
+      schema = RustyJson.Schema.compile!(@account)
+      assert %RustyJson.Schema{resource: resource} = schema
+      assert is_reference(resource)
+      assert RustyJson.Schema.compile!(schema) == schema
+
+      for id <- 1..3 do
+        assert RustyJson.decode!(~s({"id":#{id}}), schema: schema) == %Account{id: id}
+      end
+
+      assert {:error, %RustyJson.DecodeError{code: :schema_violation}} =
+               RustyJson.decode("{}", schema: schema)
+    end
+
+    test "invalid typed fields raise" do
+      for field <- [
+            {:id, :text},
+            {:id, :integer, min: "0"},
+            {:id, :integer, in: [1.5]},
+            {:id, :integer, required: 1},
+            {:id, :string, min_length: -1},
+            {:id, :integer, unknown: true},
+            {:id, [{:string, required: true}]}
+          ] do
+        assert_raise ArgumentError, fn ->
+          RustyJson.Schema.compile!({Account, %{"id" => field}})
+        end
+      end
+    end
+  end
+end
@@ -0,0 +1,170 @@
+defmodule StreamDecodeTest do
+  use ExUnit.Case
+
+  defp split(json, size) when byte_size(json) > size do
+    <<head::binary-size(size), rest::binary>> = json
+    [head | split(rest, size)]
+  end
+
+  defp split(json, _size), do: [json]
+
+  # Feed `json` in `size`-byte chunks and collect every emitted value.
+  defp feed_all(json, size, opts \\ []) do
+    decoder = RustyJson.decoder_new(opts)
+
+    values =
+      Enum.flat_map(split(json, size), fn chunk ->
+        {:ok, values} = RustyJson.decoder_feed(decoder, chunk)
+        values
+      end)
+
+    {:ok, rest} = RustyJson.decoder_finish(decoder)
+    values ++ rest
+  end
+
+  describe "decoder_new/feed/finish" do
+    test "yields array elements as they complete" do
+      decoder = RustyJson.decoder_new()
+      assert {:ok, [1]} = RustyJson.decoder_feed(decoder, ~s([1, {"a"))
+      assert {:ok, []} = RustyJson.decoder_feed(decoder, ~s(:[2,))
+      assert {:ok, [%{"a" => [2, 3]}, "x"]} = RustyJson.decoder_feed(decoder, ~s(3]}, "x"))
+      assert {:ok, []} = RustyJson.decoder_feed(decoder, "]")
+      assert {:ok, []} = RustyJson.decoder_finish(decoder)
+    end
+
+    test "every chunk size matches decode!/1" do
+      json =
+        ~s([{"id":1,"tags":["a,b","c]"],"esc":"q\\"\\\\u00e9\\u00e9"}, -12.5e2, true, null, ) <>
+          ~s(false, [], {}, "\\uD83D\\uDE00", 123456789012345678901234567890])
+
+      expected = RustyJson.decode!(json)
+
+      for size <- 1..byte_size(json) do
+        assert feed_all(json, size) == expected, "chunk size #{size}"
+      end
+    end
+
+    test "non-array roots are returned as a single value" do
+      assert feed_all(~s({"a":{"b":[1,2]}}), 3) == [%{"a" => %{"b" => [1, 2]}}]
+      assert feed_all(~s("hello"), 2) == ["hello"]
+      assert feed_all("12345", 2) == [12345]
+      assert feed_all(" true ", 1) == [true]
+    end
+
+    test "empty array yields nothing" do
+      assert feed_all("[ ]", 1) == []
+    end
+
+    test "decode options apply to every element" do
+      json = ~s([{"a":1.5},{"b":2}])
+
+      assert feed_all(json, 4, keys: :atoms, floats: :decimals) ==
+               [%{a: Decimal.new("1.5")}, %{b: 2}]
+
+      assert feed_all(json, 4, keys: &String.upcase/1) == [%{"A" => 1.5}, %{"B" => 2}]
+    end
+
+    test "incomplete document errors on finish" do
+      decoder = RustyJson.decoder_new()
+      assert {:ok, [1]} = RustyJson.decoder_feed(decoder, "[1, 2")
+      assert {:error, %RustyJson.DecodeError{message: msg}} = RustyJson.decoder_finish(decoder)
+      assert msg =~ "Unexpected end of input"
+    end
+
+    test "error positions are absolute across chunks" do
+      decoder = RustyJson.decoder_new()
+      assert {:ok, [1]} = RustyJson.decoder_feed(decoder, "[1,")
+
+      assert {:error, %RustyJson.DecodeError{position: 5}} =
+               RustyJson.decoder_feed(decoder, ~s( {x}]))
+    end
+
+    test "decoder is unusable after an error" do
+      decoder = RustyJson.decoder_new()
+      assert {:error, err} = RustyJson.decoder_feed(decoder, "[1 2]")
+      assert {:error, ^err} = RustyJson.decoder_feed(decoder, "]")
+      assert {:error, ^err} = RustyJson.decoder_finish(decoder)
+    end
+
+    test "rejects trailing characters after the root" do
+      decoder = RustyJson.decoder_new()
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decoder_feed(decoder, "[] x")
+
+      assert msg =~ "Unexpected trailing characters"
+    end
+
+    test "rejects nesting beyond 128 levels" do
+      decoder = RustyJson.decoder_new()
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decoder_feed(decoder, String.duplicate("[", 130))
+
+      assert msg =~ "Nesting depth"
+    end
+
+    test "max_bytes limits the whole stream" do
+      decoder = RustyJson.decoder_new(max_bytes: 8)
+      assert {:ok, [1]} = RustyJson.decoder_feed(decoder, "[1,2")
+
+      assert {:error, %RustyJson.DecodeError{message: msg}} =
+               RustyJson.decoder_feed(decoder, ",3,4]")
+
+      assert msg =~ "max_bytes"
+    end
+
+    test "feed after finish is an error" do
+      decoder = RustyJson.decoder_new()
+      assert {:ok, [[]]} = RustyJson.decoder_feed(decoder, "[[]]")
+      assert {:ok, []} = RustyJson.decoder_finish(decoder)
+      assert {:error, %RustyJson.DecodeError{}} = RustyJson.decoder_feed(decoder, " ")
+    end
+
+    test "dirty scheduler feed produces identical results" do
+      decoder = RustyJson.decoder_new(dirty_threshold: 1)
+      assert {:ok, [1, 2]} = RustyJson.decoder_feed(decoder, "[1,2,")
+      assert {:ok, [3]} = RustyJson.decoder_feed(decoder, "3]")
+    end
+
+    test "a large object root completes on a dirty scheduler" do
+      object = Map.new(1..20_000, &{"key#{&1}", [&1, "value #{&1}"]})
+      json = RustyJson.encode!(object)
+
+      # Each chunk is far below the threshold; the buffered root is not.
+      for opts <- [[dirty_threshold: 65_536], []] do
+        assert feed_all(json, 4096, opts) == [object]
+      end
+
+      decoder = RustyJson.decoder_new(dirty_threshold: 1)
+      assert {:ok, []} = RustyJson.decoder_feed(decoder, "12345")
+      assert {:ok, [12345]} = RustyJson.decoder_finish(decoder)
+    end
+  end
+
+  describe "decode_stream/2" do
+    test "decodes an enumerable of chunks lazily" do
+      chunks = [~s([{"a"), ~s(:1},{"b":), ~s(2},), ~s(3])]
+
+      assert chunks |> RustyJson.decode_stream() |> Enum.to_list() ==
+               [%{"a" => 1}, %{"b" => 2}, 3]
+    end
+
+    test "accepts a single binary and splits it by chunk_size" do
+      json = RustyJson.encode!(Enum.to_list(1..1000))
+
+      assert json |> RustyJson.decode_stream(chunk_size: 7) |> Enum.to_list() ==
+               Enum.to_list(1..1000)
+    end
+
+    test "can be consumed partially" do
+      assert ["[1,2,", "3,4]"] |> RustyJson.decode_stream() |> Enum.take(2) == [1, 2]
+    end
+
+    test "raises DecodeError on invalid input" do
+      assert_raise RustyJson.DecodeError, ~r/Unexpected end of input/, fn ->
+        ["[1,", "2"] |> RustyJson.decode_stream() |> Enum.to_list()
+      end
+    end
+  end
+end
@@ -0,0 +1,174 @@
+defmodule StreamEncodeTest do
+  use ExUnit.Case
+
+  # Push `items` in batches of `size` and return the concatenated output.
+  defp encode_all(items, size, opts \\ []) do
+    encoder = RustyJson.encoder_new(opts)
+
+    chunks =
+      items
+      |> Enum.chunk_every(size)
+      |> Enum.flat_map(fn batch ->
+        {:ok, chunks} = RustyJson.encoder_push_many(encoder, batch)
+        chunks
+      end)
+
+    {:ok, rest} = RustyJson.encoder_finish(encoder)
+    IO.iodata_to_binary(chunks ++ rest)
+  end
+
+  @items [%{"id" => 1, "tags" => ["a", "b"]}, "sé", nil, 1.5, [], %{}, [1, [2, 3]]]
+
+  describe "encoder_new/push/finish" do
+    test "output matches encode!/2 for every batch size" do
+      for size <- 1..length(@items) do
+        assert encode_all(@items, size) == RustyJson.encode!(@items), "batch size #{size}"
+      end
+    end
+
+    test "output matches encode!/2 with pretty printing" do
+      for pretty <- [true, 4, [indent: "\t", line_separator: "\r\n"]] do
+        assert encode_all(@items, 2, pretty: pretty) == RustyJson.encode!(@items, pretty: pretty)
+      end
+    end
+
+    test "empty stream encodes to an empty container" do
+      assert encode_all([], 1) == "[]"
+      assert encode_all([], 1, container: :object) == "{}"
+      assert encode_all([], 1, pretty: true) == "[]"
+    end
+
+    test "each push returns the bytes produced so far" do
+      encoder = RustyJson.encoder_new()
+      assert {:ok, ["[1"]} = RustyJson.encoder_push(encoder, 1)
+      assert {:ok, [",2,3"]} = RustyJson.encoder_push_many(encoder, [2, 3])
+      assert {:ok, []} = RustyJson.encoder_push_many(encoder, [])
+      assert {:ok, ["]"]} = RustyJson.encoder_finish(encoder)
+    end
+
+    test "object container writes key/value pairs" do
+      entries = [{"a", 1}, {:b, [true]}, {3, %{"c" => nil}}]
+      assert encode_all(entries, 2, container: :object) == ~s({"a":1,"b":[true],"3":{"c":null}})
+
+      assert encode_all([{"a", 1}, {"b", 2}], 1, container: :object, pretty: true) ==
+               RustyJson.encode!(%{"a" => 1, "b" => 2}, pretty: true)
+    end
+
+    test "object container rejects non-tuple items" do
+      encoder = RustyJson.encoder_new(container: :object)
+      assert {:error, %RustyJson.EncodeError{message: msg}} = RustyJson.encoder_push(encoder, 1)
+      assert msg =~ "{key, value}"
+    end
+
+    test "strict maps reject duplicate keys across pushes" do
+      encoder = RustyJson.encoder_new(container: :object, maps: :strict)
+      assert {:ok, _} = RustyJson.encoder_push(encoder, {"a", 1})
+      assert {:error, %RustyJson.EncodeError{}} = RustyJson.encoder_push(encoder, {:a, 2})
+      assert {:error, %RustyJson.EncodeError{}} =
+               RustyJson.encoder_push_many(encoder, [{"b", 1}, {"b", 2}])
+
+      assert {:ok, [",\"b\":3"]} = RustyJson.encoder_push(encoder, {"b", 3})
+    end
+
+    test "chunks are bounded by chunk_size and keep UTF-8 intact" do
+      value = String.duplicate("é", 100)
+      encoder = RustyJson.encoder_new(chunk_size: 15)
+      {:ok, chunks} = RustyJson.encoder_push(encoder, value)
+
+      assert length(chunks) > 1
+      assert Enum.all?(chunks, &(byte_size(&1) <= 15))
+      assert Enum.all?(chunks, &String.valid?/1)
+      assert IO.iodata_to_binary(chunks) == "[" <> RustyJson.encode!(value)
+    end
+
+    test "a failed push writes nothing and the encoder stays usable" do
+      encoder = RustyJson.encoder_new()
+      assert {:ok, ["[1"]} = RustyJson.encoder_push(encoder, 1)
+      assert {:error, %RustyJson.EncodeError{}} =
+               RustyJson.encoder_push_many(encoder, [2, <<0xFF>>])
+
+      assert {:ok, [",3"]} = RustyJson.encoder_push(encoder, 3)
+      assert {:ok, ["]"]} = RustyJson.encoder_finish(encoder)
+    end
+
+    test "error paths count items across pushes" do
+      encoder = RustyJson.encoder_new()
+      assert {:ok, _} = RustyJson.encoder_push_many(encoder, [1, 2])
+      assert {:error, error} = RustyJson.encoder_push(encoder, %{a: <<0xFF>>})
+      assert {:invalid_utf8, [2, :a]} = {error.reason, error.path}
+
+      encoder = RustyJson.encoder_new(container: :object)
+      assert {:error, %{path: ["k", 0]}} = RustyJson.encoder_push(encoder, {"k", [<<0xFF>>]})
+    end
+
+    test "push after finish is an error" do
+      encoder = RustyJson.encoder_new()
+      assert {:ok, ["[]"]} = RustyJson.encoder_finish(encoder)
+      assert {:error, %RustyJson.EncodeError{message: msg}} = RustyJson.encoder_push(encoder, 1)
+      assert msg =~ "already finished"
+      assert {:error, %RustyJson.EncodeError{reason: :finished}} =
+               RustyJson.encoder_finish(encoder)
+    end
+
+    test "uses the Encoder protocol and encode options" do
+      items = [~D[2024-01-15], %{html: "<b>"}, Decimal.new("1.50")]
+      opts = [escape: :html_safe]
+      assert encode_all(items, 2, opts) == RustyJson.encode!(items, opts)
+    end
+
+    test "gzip output is a single stream across all chunks" do
+      rows = for i <- 1..2_000, do: %{"id" => i, "name" => "row #{i}"}
+      encoder = RustyJson.encoder_new(compress: :gzip, chunk_size: 1024)
+
+      chunks =
+        Enum.flat_map(rows, fn row ->
+          {:ok, chunks} = RustyJson.encoder_push(encoder, row)
+          chunks
+        end)
+
+      {:ok, rest} = RustyJson.encoder_finish(encoder)
+      all = chunks ++ rest
+
+      assert Enum.all?(all, &(byte_size(&1) <= 1024))
+      # Only a single gzip member: gunzip of the whole is the full document.
+      assert :zlib.gunzip(IO.iodata_to_binary(all)) == RustyJson.encode!(rows)
+    end
+
+    test "rejects invalid options" do
+      assert_raise ArgumentError, fn -> RustyJson.encoder_new(container: :list) end
+      assert_raise ArgumentError, fn -> RustyJson.encoder_new(chunk_size: 0) end
+    end
+  end
+
+  describe "encode_stream/2" do
+    test "encodes a lazy enumerable" do
+      stream = Stream.map(1..1000, &%{"n" => &1})
+      expected = RustyJson.encode!(Enum.to_list(stream))
+
+      assert stream |> RustyJson.encode_stream() |> Enum.join() == expected
+      assert stream |> RustyJson.encode_stream(batch_size: 64) |> Enum.join() == expected
+    end
+
+    test "emits the opening bracket with the first item" do
+      assert [%{id: 1}, %{id: 2}] |> RustyJson.encode_stream() |> Enum.to_list() ==
+               [~s([{"id":1}), ~s(,{"id":2}), "]"]
+    end
+
+    test "encodes a map as a streamed object" do
+      map = Map.new(1..50, &{"k#{&1}", &1})
+      json = map |> RustyJson.encode_stream(container: :object) |> Enum.join()
+      assert RustyJson.decode!(json) == map
+    end
+
+    test "supports gzip" do
+      output = 1..100 |> RustyJson.encode_stream(compress: :gzip) |> Enum.join()
+      assert :zlib.gunzip(output) == RustyJson.encode!(Enum.to_list(1..100))
+    end
+
+    test "raises EncodeError on unencodable items" do
+      assert_raise RustyJson.EncodeError, fn ->
+        [1, <<0xFF>>] |> RustyJson.encode_stream() |> Enum.to_list()
+      end
+    end
+  end
+end
@@ -0,0 +1,68 @@
+defmodule ValidateTest do
+  use ExUnit.Case
+
+  describe "validate/2 summary" do
+    test "reports the root type, size, depth and value count" do
+      json = ~s({"a": [1, "two", {"b": null}], "c": true})
+
+      assert RustyJson.validate(json) ==
+               {:ok, %{type: :object, bytes: byte_size(json), max_depth: 3, value_count: 7}}
+    end
+
+    test "scalar roots have depth 0" do
+      assert {:ok, %{type: :string, max_depth: 0, value_count: 1}} = RustyJson.validate(~s("x"))
+      assert {:ok, %{type: :number}} = RustyJson.validate(" -1.5e3 ")
+      assert {:ok, %{type: :boolean}} = RustyJson.validate("false")
+      assert {:ok, %{type: :null}} = RustyJson.validate("null")
+      assert {:ok, %{type: :array, max_depth: 1, value_count: 1}} = RustyJson.validate("[]")
+    end
+
+    test "accepts iodata" do
+      assert {:ok, %{type: :array, bytes: 5}} = RustyJson.validate(["[1,", ["2]"]])
+    end
+
+    test "works on inputs large enough for the structural index" do
+      rows = for i <- 1..1_000, do: %{"id" => i, "tags" => ["x"]}
+      json = RustyJson.encode!(rows)
+
+      assert {:ok, %{max_depth: 3, value_count: 4_001}} = RustyJson.validate(json)
+    end
+  end
+
+  describe "validate/2 errors" do
+    test "match decode/2 messages and positions" do
+      for json <- [~s({"a":1,}), "[1 2]", ~s(["\\x"]), "[01]", "[1] x", "", ~s({"a")] do
+        assert {:error, message, position} = RustyJson.validate(json)
+        assert {:error, %RustyJson.DecodeError{position: ^position} = error} =
+                 RustyJson.decode(json)
+
+        assert error.message == "#{message} at position #{position}"
+      end
+    end
+
+    test "checks strings" do
+      assert {:error, "Invalid UTF-8 in string", 1} = RustyJson.validate(<<"[\"", 0xFF, "\"]">>)
+      assert {:ok, _} = RustyJson.validate(<<"[\"", 0xFF, "\"]">>, validate_strings: false)
+      assert {:error, "Lone surrogate in string", 1} = RustyJson.validate(~s(["\\ud800"]))
+      assert {:error, "Unescaped control character", _} = RustyJson.validate(<<"\"a\nb\"">>)
+    end
+
+    test "enforces the depth limit" do
+      assert {:error, "Nesting depth exceeds maximum of 128", _} =
+               RustyJson.validate(String.duplicate("[", 200) <> String.duplicate("]", 200))
+    end
+
+    test "honors decode limits" do
+      assert {:error, "integer exceeds 3 digit limit", 1} =
+               RustyJson.validate("[1234]", decoding_integer_digit_limit: 3)
+
+      assert {:error, "input size 6 exceeds max_bytes limit of 5", 0} =
+               RustyJson.validate("[1234]", max_bytes: 5)
+
+      assert {:error, ~s(Duplicate key "a" in object), 7} =
+               RustyJson.validate(~s({"a":1,"a":2}), duplicate_keys: :error)
+
+      assert {:ok, _} = RustyJson.validate(~s({"a":1,"a":2}))
+    end
+  end
+end